@@ -47,7 +47,7 @@ function fill_program_id() {
 cd programs/agnostic-orderbook
 git checkout e37a307
 cd ../..
-cargo fmt -p dex instruments dummy-oracle noop-risk-engine dex-macros constant-fees alpha-risk-engine
+cargo fmt -p dex instruments dummy-oracle noop-risk-engine dex-macros constant-fees token-discount-fees alpha-risk-engine liquidity-rewards
 # Input keypair begins here
 
 if [[ $REPLACE == 1 ]]
@@ -58,6 +58,7 @@ then
     alpha_pid=`solana-keygen pubkey target/deploy/alpha_risk_engine-keypair.json`
     dummy_pid=`solana-keygen pubkey target/deploy/dummy_oracle-keypair.json`
     fees_pid=`solana-keygen pubkey target/deploy/constant_fees-keypair.json`
+    rewards_pid=`solana-keygen pubkey target/deploy/liquidity_rewards-keypair.json`
 
     ## Dex
     fill_program_id $dex_pid programs/dex/src/lib.rs
@@ -76,6 +77,9 @@ then
 
     ## Constant Fees
     fill_program_id $fees_pid programs/fees/constant-fees/src/lib.rs
+
+    ## Liquidity Rewards
+    fill_program_id $rewards_pid programs/rewards/liquidity-rewards/src/lib.rs
 else
     dex_pid=`cat master_program_config.json | jq .programs.dex | tr -d '"'`
     inst_pid=`cat master_program_config.json | jq .programs.instruments| tr -d '"'`
@@ -83,6 +87,7 @@ else
     alpha_pid=`cat master_program_config.json | jq .programs.alpha_risk_engine| tr -d '"'`
     dummy_pid=`cat master_program_config.json | jq .programs.dummy_oracle| tr -d '"'`
     fees_pid=`cat master_program_config.json | jq .programs.constant_fees| tr -d '"'`
+    rewards_pid=`cat master_program_config.json | jq .programs.liquidity_rewards| tr -d '"'`
     ## Dex
     fill_program_id $dex_pid programs/dex/src/lib.rs
     ## Instruments
@@ -95,6 +100,8 @@ else
     fill_program_id $dummy_pid programs/dummy-oracle/src/lib.rs
     ## Constant Fees
     fill_program_id $fees_pid programs/fees/constant-fees/src/lib.rs
+    ## Liquidity Rewards
+    fill_program_id $rewards_pid programs/rewards/liquidity-rewards/src/lib.rs
 fi
 
 mkdir -p target/idl && mkdir -p target/types
@@ -104,6 +111,7 @@ anchor idl parse -f programs/dex/src/lib.rs -o target/idl/dex.json -t target/typ
 anchor idl parse -f programs/instruments/src/lib.rs -o target/idl/instruments.json -t target/types/instruments.ts
 anchor idl parse -f programs/risk/noop-risk-engine/src/lib.rs -o target/idl/noop_risk_engine.json -t target/types/noop_risk_engine.ts
 anchor idl parse -f programs/risk/alpha-risk-engine/src/lib.rs -o target/idl/alpha_risk_engine.json -t target/types/alpha_risk_engine.ts
+anchor idl parse -f programs/rewards/liquidity-rewards/src/lib.rs -o target/idl/liquidity_rewards.json -t target/types/liquidity_rewards.ts
 
 # Update on chain IDL
 if [[ $NETWORK == "devnet" || $NETWORK == "mainnet-beta" ]]
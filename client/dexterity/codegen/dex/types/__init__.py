@@ -5,10 +5,12 @@ from .bitset import Bitset
 from .call_back_info import CallBackInfo
 from .cancel_order_params import CancelOrderParams
 from .clear_expired_orderbook_params import ClearExpiredOrderbookParams
+from .collateral import Collateral
 from .combo import Combo
 from .consume_orderbook_events_params import ConsumeOrderbookEventsParams
 from .deposit_funds_params import DepositFundsParams
 from .dex_error import DexError
+from .fee_model_config import FeeModelConfig
 from .fractional import Fractional
 from .health_info import HealthInfo
 from .health_result import HealthResult
@@ -28,13 +30,16 @@ from .order_info import OrderInfo
 from .order_type import OrderType
 from .outright import Outright
 from .params import Params
+from .price_band import PriceBand
 from .price_ewma import PriceEwma
 from .product import Product
 from .product_array import ProductArray
 from .product_metadata import ProductMetadata
 from .product_status import ProductStatus
+from .risk_engine_config import RiskEngineConfig
 from .risk_output_register import RiskOutputRegister
 from .social_loss import SocialLoss
+from .spot_market import SpotMarket
 from .trader_fee_params import TraderFeeParams
 from .trader_fees import TraderFees
 from .trader_position import TraderPosition
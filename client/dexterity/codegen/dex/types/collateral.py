@@ -0,0 +1,30 @@
+# LOCK-BEGIN[imports]: DON'T MODIFY
+from podite import (
+    U64,
+    pod,
+)
+from solana.publickey import PublicKey
+
+# LOCK-END
+
+
+# LOCK-BEGIN[class(Collateral)]: DON'T MODIFY
+@pod
+class Collateral:
+    mint: PublicKey
+    oracle: PublicKey
+    decimals: U64
+    vault_bump: U64
+    haircut_bps: U64
+    oracle_type: U64
+    max_staleness_slots: U64
+    max_confidence_bps: U64
+    # LOCK-END
+
+    @classmethod
+    def to_bytes(cls, obj, **kwargs):
+        return cls.pack(obj, converter="bytes", **kwargs)
+
+    @classmethod
+    def from_bytes(cls, raw, **kwargs):
+        return cls.unpack(raw, converter="bytes", **kwargs)
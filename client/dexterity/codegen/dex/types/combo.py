@@ -15,7 +15,7 @@ from solmate.dtypes import Usize
 class Combo:
     metadata: ProductMetadata
     num_legs: Usize
-    legs: FixedLenArray["Leg", 4]
+    legs: FixedLenArray["Leg", 8]
     # LOCK-END
 
     @classmethod
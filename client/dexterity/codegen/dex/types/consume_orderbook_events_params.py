@@ -11,6 +11,7 @@ from podite import (
 @pod
 class ConsumeOrderbookEventsParams:
     max_iterations: U64
+    num_maker_fee_groups: U64
     # LOCK-END
 
     @classmethod
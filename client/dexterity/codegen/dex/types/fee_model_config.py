@@ -0,0 +1,34 @@
+# LOCK-BEGIN[imports]: DON'T MODIFY
+from podite import (
+    FixedLenArray,
+    U64,
+    U8,
+    pod,
+)
+from solana.publickey import PublicKey
+
+# LOCK-END
+
+
+# LOCK-BEGIN[class(FeeModelConfig)]: DON'T MODIFY
+@pod
+class FeeModelConfig:
+    program_id: PublicKey
+    configuration_acct: PublicKey
+    output_register: PublicKey
+    find_fees_discriminant_len: U64
+    find_fees_discriminant: FixedLenArray[U8, 8]
+    on_post_discriminant_len: U64
+    on_cancel_discriminant_len: U64
+    on_post_discriminant: FixedLenArray[U8, 8]
+    on_cancel_discriminant: FixedLenArray[U8, 8]
+    fee_accounts_len: U64
+    # LOCK-END
+
+    @classmethod
+    def to_bytes(cls, obj, **kwargs):
+        return cls.pack(obj, converter="bytes", **kwargs)
+
+    @classmethod
+    def from_bytes(cls, raw, **kwargs):
+        return cls.unpack(raw, converter="bytes", **kwargs)
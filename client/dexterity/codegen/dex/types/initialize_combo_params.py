@@ -2,7 +2,7 @@
 from dexterity.codegen.dex.types.fractional import Fractional
 from podite import (
     FixedLenArray,
-    I8,
+    I32,
     U64,
     U8,
     Vec,
@@ -19,7 +19,7 @@ class InitializeComboParams:
     tick_size: Fractional
     price_offset: Fractional
     base_decimals: U64
-    ratios: Vec[I8]
+    ratios: Vec[I32]
     # LOCK-END
 
     @classmethod
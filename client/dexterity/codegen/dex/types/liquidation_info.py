@@ -18,7 +18,7 @@ class LiquidationInfo:
     action: "ActionStatus"
     total_social_loss: "Fractional"
     liquidation_price: "Fractional"
-    social_losses: FixedLenArray["SocialLoss", 16]
+    social_losses: FixedLenArray["SocialLoss", 128]
     # LOCK-END
 
     @classmethod
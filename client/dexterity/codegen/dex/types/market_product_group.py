@@ -1,11 +1,17 @@
 # LOCK-BEGIN[imports]: DON'T MODIFY
 from dexterity.codegen.dex.types.account_tag import AccountTag
 from dexterity.codegen.dex.types.bitset import Bitset
+from dexterity.codegen.dex.types.collateral import Collateral
+from dexterity.codegen.dex.types.fee_model_config import FeeModelConfig
 from dexterity.codegen.dex.types.fractional import Fractional
+from dexterity.codegen.dex.types.price_band import PriceBand
 from dexterity.codegen.dex.types.product_array import ProductArray
+from dexterity.codegen.dex.types.risk_engine_config import RiskEngineConfig
+from dexterity.codegen.dex.types.spot_market import SpotMarket
 from podite import (
     FixedLenArray,
     I16,
+    I64,
     U128,
     U16,
     U64,
@@ -53,6 +59,23 @@ class MarketProductGroup:
     fee_output_register: PublicKey
     risk_output_register: PublicKey
     sequence_number: U128
+    on_post_discriminant_len: U16
+    on_cancel_discriminant_len: U16
+    on_post_discriminant: FixedLenArray[U8, 8]
+    on_cancel_discriminant: FixedLenArray[U8, 8]
+    fee_accounts_len: U16
+    rebates_paid: Fractional
+    product_lister: PublicKey
+    min_base_order_sizes: FixedLenArray[U64, 256]
+    price_bands: FixedLenArray["PriceBand", 256]
+    spot_markets: FixedLenArray["SpotMarket", 256]
+    collaterals: FixedLenArray["Collateral", 8]
+    engine_update_timelock: U64
+    pending_risk_engine: "RiskEngineConfig"
+    pending_risk_engine_effective_at: I64
+    pending_fee_model: "FeeModelConfig"
+    pending_fee_model_effective_at: I64
+    product_epochs: FixedLenArray[U64, 256]
     # LOCK-END
 
     @classmethod
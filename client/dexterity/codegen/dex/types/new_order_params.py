@@ -22,6 +22,7 @@ class NewOrderParams:
     self_trade_behavior: SelfTradeBehavior
     match_limit: U64
     limit_price: Fractional
+    implied_out: bool
     # LOCK-END
 
     @classmethod
@@ -23,7 +23,11 @@ class Outright:
     cum_social_loss_per_share: "Fractional"
     open_long_interest: "Fractional"
     open_short_interest: "Fractional"
-    padding: FixedLenArray[U64, 14]
+    max_open_interest: "Fractional"
+    max_position: "Fractional"
+    index_price: "Fractional"
+    index_price_slot: U64
+    padding: FixedLenArray[U64, 7]
     # LOCK-END
 
     @classmethod
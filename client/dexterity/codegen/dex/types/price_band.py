@@ -0,0 +1,25 @@
+# LOCK-BEGIN[imports]: DON'T MODIFY
+from dexterity.codegen.dex.types.fractional import Fractional
+from podite import (
+    U64,
+    pod,
+)
+
+# LOCK-END
+
+
+# LOCK-BEGIN[class(PriceBand)]: DON'T MODIFY
+@pod
+class PriceBand:
+    bps: U64
+    width: "Fractional"
+    ewma_window: U64
+    # LOCK-END
+
+    @classmethod
+    def to_bytes(cls, obj, **kwargs):
+        return cls.pack(obj, converter="bytes", **kwargs)
+
+    @classmethod
+    def from_bytes(cls, raw, **kwargs):
+        return cls.unpack(raw, converter="bytes", **kwargs)
@@ -0,0 +1,31 @@
+# LOCK-BEGIN[imports]: DON'T MODIFY
+from podite import (
+    FixedLenArray,
+    U64,
+    U8,
+    pod,
+)
+from solana.publickey import PublicKey
+
+# LOCK-END
+
+
+# LOCK-BEGIN[class(RiskEngineConfig)]: DON'T MODIFY
+@pod
+class RiskEngineConfig:
+    program_id: PublicKey
+    configuration_acct: PublicKey
+    output_register: PublicKey
+    validate_account_discriminant_len: U64
+    validate_account_health_discriminant: FixedLenArray[U8, 8]
+    validate_account_liquidation_discriminant: FixedLenArray[U8, 8]
+    create_risk_state_account_discriminant: FixedLenArray[U8, 8]
+    # LOCK-END
+
+    @classmethod
+    def to_bytes(cls, obj, **kwargs):
+        return cls.pack(obj, converter="bytes", **kwargs)
+
+    @classmethod
+    def from_bytes(cls, raw, **kwargs):
+        return cls.unpack(raw, converter="bytes", **kwargs)
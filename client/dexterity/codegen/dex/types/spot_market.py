@@ -0,0 +1,25 @@
+# LOCK-BEGIN[imports]: DON'T MODIFY
+from podite import (
+    U64,
+    pod,
+)
+from solana.publickey import PublicKey
+
+# LOCK-END
+
+
+# LOCK-BEGIN[class(SpotMarket)]: DON'T MODIFY
+@pod
+class SpotMarket:
+    base_mint: PublicKey
+    base_decimals: U64
+    base_vault_bump: U64
+    # LOCK-END
+
+    @classmethod
+    def to_bytes(cls, obj, **kwargs):
+        return cls.pack(obj, converter="bytes", **kwargs)
+
+    @classmethod
+    def from_bytes(cls, raw, **kwargs):
+        return cls.unpack(raw, converter="bytes", **kwargs)
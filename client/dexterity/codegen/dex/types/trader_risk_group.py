@@ -31,7 +31,7 @@ class TraderRiskGroup:
     valid_until: UnixTimestamp
     maker_fee_bps: I32
     taker_fee_bps: I32
-    trader_positions: FixedLenArray[TraderPosition, 16]
+    trader_positions: FixedLenArray[TraderPosition, 128]
     risk_state_account: PublicKey
     fee_state_account: PublicKey
     client_order_id: U128
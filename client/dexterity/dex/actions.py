@@ -2,7 +2,7 @@ from typing import Any, Optional, Tuple, List
 
 import solana.system_program as sp
 import solana.sysvar
-from podite import pod, U64, U128, Option, I8, Static
+from podite import pod, U64, U128, Option, I8, I32, Static
 from solana.keypair import Keypair
 from solana.publickey import PublicKey
 from solana.rpc import types
@@ -548,8 +548,10 @@ def get_combo_product_key(product_keys, ratios):
     sort_idx = [i[0] for i in sorted(enumerate(product_keys), key=lambda x: str(x[1]))]
     product_keys = [product_keys[idx] for idx in sort_idx]
     ratios = [ratios[idx] for idx in sort_idx]
+    # ratios are encoded as i8s whenever they all fit, to keep the keys of existing combos
+    ratio_type = I8 if all(-128 <= r <= 127 for r in ratios) else I32
     seeds = [bytes(product_key) for product_key in product_keys] + [
-        I8.to_bytes(r) for r in ratios
+        ratio_type.to_bytes(r) for r in ratios
     ]
     return PublicKey.find_program_address(seeds, DEX_PROGRAM_ID)[0]
 
@@ -828,7 +830,9 @@ def new_order(
         decimals: Optional[U64] = None,
         authority: Optional[PublicKey] = None,
         risk_engine_program: Optional[PublicKey] = None,
+        implied_out: bool = False,
 ):
+    group_obj: Optional[MarketProductGroup] = None
     if (
             authority is None
             or decimals is None
@@ -837,7 +841,7 @@ def new_order(
             or fee_model_program is None
     ):
         group_details = fetch_account_details(market_product_group)
-        group_obj: MarketProductGroup = group_details.data_obj
+        group_obj = group_details.data_obj
         authority = group_obj.authority
         decimals = group_obj.decimals
         product = group_obj.get_product_by_key(
@@ -892,6 +896,35 @@ def new_order(
     limit_price = Fractional.to_decimal(limit_price)
     max_base_qty = Fractional.to_decimal(max_base_qty)
 
+    # the orderbook, market signer, event queue, bids and asks of each leg of a combo
+    # matched against the implied price of its outright books
+    remaining_accounts = []
+    if implied_out:
+        if group_obj is None:
+            group_obj = fetch_account_details(market_product_group).data_obj
+        combo = next(
+            p.field.combo for p in group_obj.market_products.array
+            if getattr(p.field, "combo", None) is not None
+            and p.field.combo.metadata.product_key == product_key
+        )
+        for leg in combo.legs[:combo.num_legs]:
+            (leg_orderbook, leg_event_queue, leg_bids, leg_asks) = get_orderbook_addrs(
+                authority,
+                market_product_group,
+                leg.product_key,
+            )
+            remaining_accounts += [
+                AccountMeta(pubkey=leg_orderbook, is_signer=False, is_writable=True),
+                AccountMeta(
+                    pubkey=get_agnostic_orderbook_authority(leg.product_key),
+                    is_signer=False,
+                    is_writable=False,
+                ),
+                AccountMeta(pubkey=leg_event_queue, is_signer=False, is_writable=True),
+                AccountMeta(pubkey=leg_bids, is_signer=False, is_writable=True),
+                AccountMeta(pubkey=leg_asks, is_signer=False, is_writable=True),
+            ]
+
     return Transaction(fee_payer=trader).add(
         ixs.new_order(
             user=trader,
@@ -909,6 +942,7 @@ def new_order(
                 order_type=order_type,
                 self_trade_behavior=self_trade_behavior,
                 match_limit=match_limit,
+                implied_out=implied_out,
             ),
             risk_engine_program=risk_engine_program,
             risk_model_configuration_acct=risk_model_configuration_acct,
@@ -918,6 +952,7 @@ def new_order(
             fee_model_program=fee_model_program,
             risk_output_register=out_register_acct,
             risk_state_account_info=risk_state_account,
+            remaining_accounts=remaining_accounts,
         )
     )
 
@@ -1070,6 +1105,7 @@ def consume_orderbook_events(
             market_signer=authority,
             params=dex_types.ConsumeOrderbookEventsParams(
                 max_iterations=max_iterations,
+                num_maker_fee_groups=0,
             ),
             remaining_accounts=user_accounts
         )
@@ -11,4 +11,6 @@ deploy dummy_oracle $1
 deploy noop_risk_engine $1
 deploy alpha_risk_engine $1
 deploy constant_fees $1
+deploy token_discount_fees $1
+deploy liquidity_rewards $1
 deploy agnostic_orderbook $1
@@ -4,15 +4,32 @@ use agnostic_orderbook::state::Side;
 use solana_program::pubkey::Pubkey;
 use solana_sdk::{signature::Keypair, signer::Signer};
 
-use crate::processor::remove_market_product::remove_market_product_ixs;
-use dex::{state::constants::NAME_LEN, utils::numeric::Fractional};
+use crate::processor::remove_market_product::{
+    remove_all_dependent_combos_ixs, remove_market_product_ixs,
+};
+use dex::{
+    state::{constants::NAME_LEN, market_product_group::MarketProductGroup},
+    utils::numeric::Fractional,
+};
 
 use crate::{
     common::utils::SDKError,
     initialize_market_product_ixs,
     instrument::initialize_derivative,
-    processor::{combo::initialize_combo_ixs, new_order::new_order_ixs},
-    KeypairD, SDKClient, SDKContext, SDKResult, SDKTrader,
+    processor::{
+        collateral::{add_collateral_ixs, update_collateral_ixs},
+        combo::{initialize_combo_ixs, update_combo_ixs},
+        market_product::{
+            update_market_product_ixs, update_position_limits_ixs, update_price_band_ixs,
+        },
+        market_product_group::{
+            apply_market_product_group_update_ix, choose_product_lister_ix,
+            migrate_market_product_group_ix, update_market_product_group_ix,
+        },
+        new_order::new_order_ixs,
+        spot::initialize_spot_product_ixs,
+    },
+    KeypairD, SDKClient, SDKCombo, SDKContext, SDKProduct, SDKResult, SDKTrader,
 };
 
 pub struct DexAdmin {
@@ -61,6 +78,19 @@ impl DexAdmin {
             .await
     }
 
+    /// Makes a product that hasn't traded yet physically settled in `base_mint`
+    pub async fn initialize_spot_product(&self, product: Pubkey, base_mint: Pubkey) -> SDKResult {
+        let ixs = initialize_spot_product_ixs(
+            self.authority.pubkey(),
+            self.market_product_group,
+            product,
+            base_mint,
+        );
+        self.client
+            .sign_send_instructions(ixs, vec![&self.authority])
+            .await
+    }
+
     pub async fn remove_market_product(
         &self,
         market_product_group: Pubkey,
@@ -88,6 +118,43 @@ impl DexAdmin {
             .await
     }
 
+    /// Closes and deactivates every combo that has the expired `product` as a leg.
+    /// The combos' books must already be cleared and their event queues cranked.
+    pub async fn remove_all_dependent_combos(
+        &self,
+        product: Pubkey,
+        combos: &[SDKCombo],
+    ) -> SDKResult {
+        let market_product_group = self.get_market_product_group().await;
+        let mut combo_accounts = vec![];
+        for (_, combo) in market_product_group.active_combos() {
+            if !combo.has_leg(product) {
+                continue;
+            }
+            let sdk_combo = combos
+                .iter()
+                .find(|c| c.key == combo.product_key)
+                .ok_or(SDKError::PublicKeyMismatch)?;
+            combo_accounts.extend([
+                sdk_combo.orderbook,
+                sdk_combo.market_signer,
+                sdk_combo.event_queue,
+                sdk_combo.bids,
+                sdk_combo.asks,
+            ]);
+        }
+        let ixs = remove_all_dependent_combos_ixs(
+            self.authority.pubkey(),
+            self.market_product_group,
+            product,
+            self.aaob_program_id,
+            &combo_accounts,
+        );
+        self.client
+            .sign_send_instructions(ixs, vec![&self.authority])
+            .await
+    }
+
     pub async fn initialize_combo(
         &self,
         orderbook: Pubkey,
@@ -96,7 +163,7 @@ impl DexAdmin {
         tick_size: Fractional,
         price_offset: Fractional,
         base_decimals: u64,
-        ratios: Vec<i8>,
+        ratios: Vec<i32>,
     ) -> SDKResult {
         let ixs = initialize_combo_ixs(
             self.authority.pubkey(),
@@ -113,6 +180,196 @@ impl DexAdmin {
             .sign_send_instructions(ixs, vec![&self.authority])
             .await
     }
+
+    /// The tick size can only change while the event queue is empty. Resting orders are moved to a
+    /// new tick size that divides the old one, `traders` must hold every trader with an order
+    pub async fn update_market_product(
+        &self,
+        product: &SDKProduct,
+        traders: &[&SDKTrader],
+        name: [u8; NAME_LEN],
+        tick_size: impl Into<Fractional>,
+        min_base_order_size: u64,
+    ) -> SDKResult {
+        let trader_risk_groups = traders.iter().map(|t| t.account).collect::<Vec<_>>();
+        let ixs = update_market_product_ixs(
+            self.authority.pubkey(),
+            self.market_product_group,
+            product.key,
+            self.aaob_program_id,
+            product.orderbook,
+            product.market_signer,
+            product.event_queue,
+            product.bids,
+            product.asks,
+            &trader_risk_groups,
+            name,
+            tick_size.into(),
+            min_base_order_size,
+        );
+        self.client
+            .sign_send_instructions(ixs, vec![&self.authority])
+            .await
+    }
+
+    /// Pass 0 to remove a limit
+    pub async fn update_position_limits(
+        &self,
+        product: Pubkey,
+        max_open_interest: impl Into<Fractional>,
+        max_position: impl Into<Fractional>,
+    ) -> SDKResult {
+        let ixs = update_position_limits_ixs(
+            self.authority.pubkey(),
+            self.market_product_group,
+            product,
+            max_open_interest.into(),
+            max_position.into(),
+        );
+        self.client
+            .sign_send_instructions(ixs, vec![&self.authority])
+            .await
+    }
+
+    /// Pass 0 for both bps and width to remove the band
+    pub async fn update_price_band(
+        &self,
+        product: Pubkey,
+        bps: u64,
+        width: impl Into<Fractional>,
+        ewma_window: u64,
+    ) -> SDKResult {
+        let ixs = update_price_band_ixs(
+            self.authority.pubkey(),
+            self.market_product_group,
+            product,
+            bps,
+            width.into(),
+            ewma_window,
+        );
+        self.client
+            .sign_send_instructions(ixs, vec![&self.authority])
+            .await
+    }
+
+    pub async fn update_combo(
+        &self,
+        combo: &SDKCombo,
+        traders: &[&SDKTrader],
+        name: [u8; NAME_LEN],
+        tick_size: impl Into<Fractional>,
+        min_base_order_size: u64,
+    ) -> SDKResult {
+        let trader_risk_groups = traders.iter().map(|t| t.account).collect::<Vec<_>>();
+        let ixs = update_combo_ixs(
+            self.authority.pubkey(),
+            self.market_product_group,
+            combo.key,
+            self.aaob_program_id,
+            combo.orderbook,
+            combo.market_signer,
+            combo.event_queue,
+            combo.bids,
+            combo.asks,
+            &trader_risk_groups,
+            name,
+            tick_size.into(),
+            min_base_order_size,
+        );
+        self.client
+            .sign_send_instructions(ixs, vec![&self.authority])
+            .await
+    }
+
+    /// Accepts `mint` as collateral that is valued at the `oracle` price less the haircut
+    pub async fn add_collateral(
+        &self,
+        mint: Pubkey,
+        oracle: Pubkey,
+        params: dex::AddCollateralParams,
+    ) -> SDKResult {
+        let ixs = add_collateral_ixs(
+            self.authority.pubkey(),
+            self.market_product_group,
+            mint,
+            oracle,
+            params,
+        );
+        self.client
+            .sign_send_instructions(ixs, vec![&self.authority])
+            .await
+    }
+
+    pub async fn update_collateral(
+        &self,
+        mint: Pubkey,
+        oracle: Pubkey,
+        params: dex::UpdateCollateralParams,
+    ) -> SDKResult {
+        let ixs = update_collateral_ixs(
+            self.authority.pubkey(),
+            self.market_product_group,
+            mint,
+            oracle,
+            params,
+        );
+        self.client
+            .sign_send_instructions(ixs, vec![&self.authority])
+            .await
+    }
+
+    /// Allows `product_lister` to list products in the group, pass the default pubkey to disable
+    pub async fn choose_product_lister(&self, product_lister: Pubkey) -> SDKResult {
+        let ix = choose_product_lister_ix(
+            self.authority.pubkey(),
+            self.market_product_group,
+            product_lister,
+        );
+        self.client
+            .sign_send_instructions(vec![ix], vec![&self.authority])
+            .await
+    }
+
+    pub async fn update_market_product_group(
+        &self,
+        params: dex::UpdateMarketProductGroupParams,
+    ) -> SDKResult {
+        let ix = update_market_product_group_ix(
+            self.authority.pubkey(),
+            self.market_product_group,
+            params,
+        );
+        self.client
+            .sign_send_instructions(vec![ix], vec![&self.authority])
+            .await
+    }
+
+    /// Applies risk engine and fee model swaps whose timelock has passed, anyone can call this
+    pub async fn apply_market_product_group_update(&self) -> SDKResult {
+        let ix = apply_market_product_group_update_ix(self.market_product_group);
+        self.client.sign_send_instructions(vec![ix], vec![]).await
+    }
+
+    /// Grows a market product group with the legacy layout to the current one, the account can
+    /// only grow by 10KB per instruction so this sends one transaction per step
+    pub async fn migrate_market_product_group(&self) -> SDKResult {
+        let new_len = 8 + std::mem::size_of::<MarketProductGroup>();
+        loop {
+            let account = self.client.get_account(self.market_product_group).await?;
+            if account.data.len() >= new_len {
+                break;
+            }
+            let ix = migrate_market_product_group_ix(
+                self.authority.pubkey(),
+                self.client.payer.pubkey(),
+                self.market_product_group,
+            );
+            self.client
+                .sign_send_instructions(vec![ix], vec![&self.authority])
+                .await?;
+        }
+        Ok(())
+    }
 }
 
 impl Deref for DexAdmin {
@@ -28,7 +28,7 @@ use std::str::{FromStr, Utf8Error};
 use crate::{
     admin::DexAdmin,
     common::{utils::*, KeypairD},
-    instrument::{initialize_derivative, InstrumentAdmin},
+    instrument::{initialize_derivative, series, InstrumentAdmin},
     oracle::{create_clock::*, create_oracle::*, update_clock::*, update_oracle::*},
     processor::{
         combo::initialize_combo_ixs, market_product::*, market_product_group::*, orderbook::*,
@@ -37,12 +37,14 @@ use crate::{
     sdk_client::{ClientSubset, SDKClient},
     state::*,
     trader::SDKTrader,
-    BootstrapConfig, OptionalBootstrapFields, RiskEngines, SDKContext, FIND_FEES_DISCRIMINANT,
-    MINT_DECIMALS,
+    BootstrapConfig, OptionalBootstrapFields, RiskEngines, SDKContext, SeriesConfig,
+    FIND_FEES_DISCRIMINANT, MINT_DECIMALS,
 };
 use constant_fees::initialize_trader_fee_acct_ix;
 use dex::{
-    state::{constants::*, enums::*, market_product_group::*, trader_risk_group::*},
+    state::{
+        constants::*, enums::*, market_product_group::*, products::Combo, trader_risk_group::*,
+    },
     utils::numeric::{Fractional, ZERO_FRAC},
 };
 use instruments::state::enums::{InstrumentType, OracleType};
@@ -136,13 +138,28 @@ pub async fn bootstrap_mpg(config: &mut BootstrapConfig, client: SDKClient) -> S
         .await
         .unwrap();
 
-    create_fee_program_config_acct(
-        &client,
-        &fee_model_config_acct,
-        &config.fee_model_program_id,
-        market_product_group_keypair.pubkey(),
-    )
-    .await?;
+    match &config.fee_discount_tiers {
+        Some(tiers) => {
+            create_discount_fee_program_config_acct(
+                &client,
+                &fee_model_config_acct,
+                &config.fee_model_program_id,
+                &market_product_group_keypair,
+                mint.pubkey(),
+                tiers,
+            )
+            .await?
+        }
+        None => {
+            create_fee_program_config_acct(
+                &client,
+                &fee_model_config_acct,
+                &config.fee_model_program_id,
+                market_product_group_keypair.pubkey(),
+            )
+            .await?
+        }
+    }
 
     let mut product_group_name: [u8; NAME_LEN] = [0_u8; NAME_LEN];
     product_group_name
@@ -169,6 +186,9 @@ pub async fn bootstrap_mpg(config: &mut BootstrapConfig, client: SDKClient) -> S
         config.liq_disc.to_le_bytes(),
         config.create_risk_state_disc.to_le_bytes(),
         config.fees_disc.to_le_bytes(),
+        config.on_post_disc.map(u64::to_le_bytes),
+        config.on_cancel_disc.map(u64::to_le_bytes),
+        config.fee_accounts_len,
     )
     .await
     .unwrap();
@@ -236,6 +256,7 @@ pub async fn bootstrap_mpg(config: &mut BootstrapConfig, client: SDKClient) -> S
             additional_risk_accts: ArrayVec::new(),
             risk_model_config_acct: risk_model_config_acct.pubkey(),
             trader_risk_state_account_len,
+            on_cancel_hook: config.on_cancel_disc.is_some(),
         },
         config.payer,
         fee_collector,
@@ -348,6 +369,12 @@ pub async fn bootstrap_full(
         })
     }
 
+    if let Some(series_config) = &config.series {
+        let (_, series_products) =
+            bootstrap_series(&ctx, oracle, clock, series_config, config.aaob_program_id).await?;
+        products.extend(series_products);
+    }
+
     let mut traders: Vec<SDKTrader> = Vec::with_capacity(n_traders as usize);
     for trader in (0..n_traders).map(|_| KeypairD::new()) {
         // trader account (not trader risk group)
@@ -400,28 +427,29 @@ pub async fn setup_combo(
     products: &[Pubkey],
     product_index: u8,
 ) -> SDKResult<SDKCombo> {
-    let mut seeds: Vec<u8> = Vec::new();
-
-    let ratios: Vec<i8> = vec![1, -1];
-    let mut combo_legs = ratios
-        .into_iter()
-        .zip(products)
-        .collect::<Vec<(i8, &Pubkey)>>();
-    combo_legs.sort_by(|(_, a), (_, b)| a.as_ref().cmp(b.as_ref()));
-
-    for (i, (ratio, &product)) in combo_legs.iter().enumerate() {
-        // legs[i] = Leg {
-        //     product_index: i,
-        //     product_key: product,
-        //     ratio: *ratio as i64,
-        // };
-        seeds.extend(product.to_bytes().iter());
-    }
-    for (ratio, _) in combo_legs.iter() {
-        seeds.extend(ratio.to_le_bytes().iter());
-    }
+    setup_combo_with_ratios(admin_ctx, products, &[1, -1], product_index).await
+}
 
-    // Format of the seeds is [product_key_1, ..., product_key_N, [ratio_1, ..., ratio_N]]
+/// Lists a combo with one leg per product, `ratios[i]` being the ratio of `products[i]`
+pub async fn setup_combo_with_ratios(
+    admin_ctx: &DexAdmin,
+    products: &[Pubkey],
+    ratios: &[i32],
+    product_index: u8,
+) -> SDKResult<SDKCombo> {
+    let mut combo_legs = products
+        .iter()
+        .copied()
+        .zip(ratios.iter().copied())
+        .collect::<Vec<(Pubkey, i32)>>();
+    combo_legs.sort_by(|(a, _), (b, _)| a.as_ref().cmp(b.as_ref()));
+    let products = combo_legs.iter().map(|(p, _)| *p).collect::<Vec<Pubkey>>();
+    let ratios = combo_legs.iter().map(|(_, r)| *r).collect::<Vec<i32>>();
+
+    let seeds = Combo::get_seeds(
+        &products,
+        &ratios.iter().map(|r| *r as i64).collect::<Vec<i64>>(),
+    );
     let (product_key, _bump) = Pubkey::find_program_address(
         &seeds.chunks(32).collect::<Vec<&[u8]>>(),
         &admin_ctx.dex_program_id,
@@ -434,13 +462,6 @@ pub async fn setup_combo(
             .await
             .unwrap();
 
-    let ratios = combo_legs.iter().map(|(i, _)| *i).collect::<Vec<i8>>();
-    let products = combo_legs
-        .iter()
-        .map(|(_, p)| *p)
-        .copied()
-        .collect::<Vec<Pubkey>>();
-
     let name_str = format!("combo{:width$}", product_index, width = NAME_LEN - 5);
     let mut name: [u8; NAME_LEN] = Default::default();
     name.clone_from_slice(name_str.as_bytes());
@@ -464,9 +485,74 @@ pub async fn setup_combo(
         asks: asks_key,
         market_signer,
         event_queue: eq_key,
+        legs: combo_legs,
     })
 }
 
+/// Creates a series of expiring options starting at the current time of `clock` and
+/// lists its first expiry
+pub async fn bootstrap_series(
+    ctx: &DexAdmin,
+    oracle: Pubkey,
+    clock: Pubkey,
+    series_config: &SeriesConfig,
+    aaob_program_id: Pubkey,
+) -> SDKResult<(Pubkey, Vec<SDKProduct>)> {
+    let client = &ctx.client;
+    ctx.choose_product_lister(series::get_product_lister_key(ctx.market_product_group))
+        .await?;
+
+    let clock_account = client.get_account(clock).await?;
+    let start_time = bincode::deserialize::<Clock>(&clock_account.data)
+        .map_err(|e| SDKError::from(anyhow::anyhow!(e)))?
+        .unix_timestamp;
+    let oracle_type = if clock == sysvar::clock::id() {
+        OracleType::Pyth
+    } else {
+        OracleType::Dummy
+    };
+    let name_str = format!("series{:width$}", 0, width = NAME_LEN - 6);
+    let mut name: [u8; NAME_LEN] = Default::default();
+    name.clone_from_slice(name_str.as_bytes());
+    let params = instruments::InitializeSeriesParams {
+        instrument_type: if series_config.puts {
+            InstrumentType::ExpiringPut
+        } else {
+            InstrumentType::ExpiringCall
+        },
+        oracle_type,
+        strikes: series_config
+            .strikes
+            .iter()
+            .map(|strike| Fractional::from(*strike))
+            .collect(),
+        start_time,
+        expiry_interval: series_config.expiry_interval,
+        close_authority: ctx.authority.pubkey(),
+        name,
+        tick_size: Fractional::new(100, 4),
+        base_decimals: 7,
+        price_offset: ZERO_FRAC,
+        max_staleness_slots: 0,
+        max_confidence_bps: 0,
+        cap: ZERO_FRAC,
+        digital_payout: ZERO_FRAC,
+        settlement_window: 0,
+        min_settlement_samples: 0,
+    };
+    let series_key = series::initialize_series(
+        client,
+        &ctx.authority,
+        oracle,
+        ctx.market_product_group,
+        clock,
+        params,
+    )
+    .await?;
+    let products = series::roll_series(client, aaob_program_id, series_key).await?;
+    Ok((series_key, products))
+}
+
 pub async fn default_clock_and_oracle(
     client: &SDKClient,
     ctx: &DexAdmin,
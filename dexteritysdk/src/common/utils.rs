@@ -158,6 +158,42 @@ pub async fn create_fee_program_config_acct(
         .await
 }
 
+pub async fn create_discount_fee_program_config_acct(
+    client: &SDKClient,
+    fee_acct_global_config: &Pubkey,
+    fee_acct_program_id: &Pubkey,
+    market_product_group: &KeypairD,
+    discount_mint: Pubkey,
+    tiers: &[(u64, i32)],
+) -> std::result::Result<(), SDKError> {
+    client
+        .sign_send_instructions(
+            vec![token_discount_fees::update_fees_ix_for_new_group(
+                *fee_acct_program_id,
+                client.payer.pubkey(),
+                *fee_acct_global_config,
+                market_product_group.pubkey(),
+                solana_program::system_program::id(),
+                token_discount_fees::UpdateFeesParams {
+                    maker_fee_bps: 0,
+                    taker_fee_bps: 0,
+                    discount_mint,
+                    tiers: tiers
+                        .iter()
+                        .map(
+                            |&(min_balance, discount_bps)| token_discount_fees::DiscountTier {
+                                min_balance,
+                                discount_bps,
+                            },
+                        )
+                        .collect(),
+                },
+            )],
+            vec![market_product_group],
+        )
+        .await
+}
+
 pub async fn create_mint2(
     client: &SDKClient,
     pool_mint: &KeypairD,
@@ -25,6 +25,7 @@ impl SDKContext {
         event_queue: Pubkey,
         reward_target: &KeypairD,
         trader_and_risk_accounts: &mut [Pubkey],
+        maker_fee_groups: &[(Pubkey, &[Pubkey])],
         max_iterations: u64,
     ) -> SDKResult {
         trader_and_risk_accounts.sort();
@@ -44,6 +45,7 @@ impl SDKContext {
             self.risk_engine_program_id,
             self.out_register_risk_info,
             trader_and_risk_accounts,
+            maker_fee_groups,
             max_iterations,
         );
         self.client
@@ -0,0 +1,159 @@
+use agnostic_orderbook::{critbit::Slab, state::Side};
+use solana_program::{clock::UnixTimestamp, program_error::ProgramError};
+
+use dex::{
+    processor::new_order::{get_limit_price_aob, process_from_aob},
+    state::{
+        fee_model::TraderFees, market_product_group::MarketProductGroup, products::Product,
+        trader_risk_group::TraderRiskGroup,
+    },
+    utils::numeric::{fp32_mul, Fractional, ZERO_FRAC},
+};
+
+use crate::{
+    bootstrap::get_curr_time, common::utils::SDKResult, load_order_book, state::SDKProduct,
+    trader::SDKTrader, SDKContext,
+};
+
+/// Expected outcome of sending an order, computed off-chain against the current book
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderEstimate {
+    pub filled_base_qty: Fractional,
+    pub filled_quote_qty: Fractional,
+    // None if nothing would be filled
+    pub average_price: Option<Fractional>,
+    // Fee rates after applying the group clamp bounds
+    pub maker_fee_bps: Fractional,
+    pub taker_fee_bps: Fractional,
+    pub taker_fee: Fractional,
+    // Change in cash balance once the fill is settled, including fees
+    pub net_cash_impact: Fractional,
+    // If false, the fee model will be called again and the fee rates may change
+    pub fees_are_cached: bool,
+    pub fees_valid_until: UnixTimestamp,
+}
+
+/// Walks the opposite side of the book the same way the AAOB does and converts the
+/// result back into DEX space. Self trades are treated as regular fills.
+pub fn estimate_order(
+    market_product_group: &MarketProductGroup,
+    trader_risk_group: &TraderRiskGroup,
+    product: &Product,
+    bids: Slab,
+    asks: Slab,
+    side: Side,
+    max_base_qty: Fractional,
+    limit_price: Fractional,
+    now: UnixTimestamp,
+) -> SDKResult<OrderEstimate> {
+    let max_base_qty_aob = max_base_qty
+        .round(product.base_decimals as u32)
+        .map_err(ProgramError::from)?
+        .m as u64;
+    let limit_price_aob =
+        get_limit_price_aob(limit_price, product.price_offset, product.tick_size)?;
+
+    let mut remaining_base_qty_aob = max_base_qty_aob;
+    let mut matched_quote_qty_aob = 0;
+    let opposite = match side {
+        Side::Bid => asks,
+        Side::Ask => bids,
+    };
+    for leaf in opposite.into_iter(side == Side::Bid) {
+        let crosses = match side {
+            Side::Bid => leaf.price() <= limit_price_aob,
+            Side::Ask => leaf.price() >= limit_price_aob,
+        };
+        if remaining_base_qty_aob == 0 || !crosses {
+            break;
+        }
+        let fill_qty = remaining_base_qty_aob.min(leaf.base_quantity);
+        matched_quote_qty_aob += fp32_mul(fill_qty, leaf.price());
+        remaining_base_qty_aob -= fill_qty;
+    }
+
+    let [_, filled_base_qty, filled_quote_qty] = process_from_aob(
+        max_base_qty_aob,
+        remaining_base_qty_aob,
+        matched_quote_qty_aob + fp32_mul(remaining_base_qty_aob, limit_price_aob),
+        limit_price_aob,
+        product.price_offset,
+        product.tick_size,
+        product.base_decimals,
+    )?;
+
+    let fees = TraderFees::new(
+        trader_risk_group.maker_fee_bps,
+        trader_risk_group.taker_fee_bps,
+        trader_risk_group.valid_until,
+    );
+    let taker_fee_bps = fees.taker_fee_bps(Some(market_product_group));
+    let taker_fee = taker_fee_bps
+        .checked_mul(filled_quote_qty)
+        .map_err(ProgramError::from)?;
+    let net_cash_impact = match side {
+        Side::Bid => -filled_quote_qty,
+        Side::Ask => filled_quote_qty,
+    }
+    .checked_sub(taker_fee)?;
+    let average_price = if filled_base_qty == ZERO_FRAC {
+        None
+    } else {
+        Some(
+            filled_quote_qty
+                .checked_div(filled_base_qty)
+                .map_err(ProgramError::from)?,
+        )
+    };
+
+    Ok(OrderEstimate {
+        filled_base_qty,
+        filled_quote_qty,
+        average_price,
+        maker_fee_bps: fees.maker_fee_bps(Some(market_product_group)),
+        taker_fee_bps,
+        taker_fee,
+        net_cash_impact,
+        fees_are_cached: trader_risk_group.valid_until > now,
+        fees_valid_until: trader_risk_group.valid_until,
+    })
+}
+
+impl SDKTrader {
+    /// Estimates the fill, average price and fees of a limit order without sending it
+    pub async fn estimate_order(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        side: Side,
+        size: impl Into<Fractional>,
+        price: impl Into<Fractional>,
+    ) -> SDKResult<OrderEstimate> {
+        let market_product_group = ctx.get_market_product_group().await;
+        let trader_risk_group = self.get_trader_risk_group(&ctx.client).await;
+        let (_, dex_product) = market_product_group
+            .find_product_index(&product.key)
+            .map_err(ProgramError::from)?;
+        let market_state = load_order_book(product.orderbook, &ctx.client).await?;
+        let callback_info_len = market_state.callback_info_len as usize;
+
+        let bids_acct = &mut (product.bids, ctx.client.get_account(product.bids).await?);
+        let asks_acct = &mut (product.asks, ctx.client.get_account(product.asks).await?);
+        let bids_info = solana_sdk::account_info::IntoAccountInfo::into_account_info(bids_acct);
+        let asks_info = solana_sdk::account_info::IntoAccountInfo::into_account_info(asks_acct);
+        let bids = Slab::new_from_acc_info(&bids_info, callback_info_len);
+        let asks = Slab::new_from_acc_info(&asks_info, callback_info_len);
+
+        estimate_order(
+            &market_product_group,
+            &trader_risk_group,
+            dex_product,
+            bids,
+            asks,
+            side,
+            size.into(),
+            price.into(),
+            get_curr_time(&ctx.client).await,
+        )
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{common::utils::*, sdk_client::SDKClient};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use dex::utils::numeric::Fractional;
+use instruments::{
+    state::enums::{FixedIncomeType, OracleType},
+    InitializeFixedIncomeParams,
+};
+use rand::Rng;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_sdk::signature::{Keypair, Signer};
+
+pub fn get_fixed_income_key(
+    rate_oracle: Pubkey,
+    market_product_group: Pubkey,
+    params: &InitializeFixedIncomeParams,
+) -> Pubkey {
+    let seeds: &[&[u8]] = &[
+        b"fixed_income",
+        &rate_oracle.to_bytes(),
+        &market_product_group.to_bytes(),
+        &(params.fixed_income_type as u64).to_le_bytes(),
+        &params.notional.m.to_le_bytes(),
+        &params.notional.exp.to_le_bytes(),
+        &params.fixed_rate.m.to_le_bytes(),
+        &params.fixed_rate.exp.to_le_bytes(),
+        &params.tenor.to_le_bytes(),
+        &params.initialization_time.to_le_bytes(),
+        &params.maturity_time.to_le_bytes(),
+        &params.coupon_period.to_le_bytes(),
+    ];
+    let (fixed_income_key, _) = Pubkey::find_program_address(seeds, &instruments::ID);
+    fixed_income_key
+}
+
+pub fn initialize_fixed_income_ixs(
+    rate_oracle: Pubkey,
+    market_product_group: Pubkey,
+    payer: Pubkey,
+    clock: Pubkey,
+    fixed_income_metadata: Pubkey,
+    params: InitializeFixedIncomeParams,
+) -> Vec<Instruction> {
+    let account_metas = instruments::accounts::InitializeFixedIncome {
+        fixed_income_metadata,
+        rate_oracle,
+        market_product_group,
+        payer,
+        system_program: system_program::id(),
+        clock,
+    }
+    .to_account_metas(Some(true));
+
+    vec![Instruction {
+        program_id: instruments::ID,
+        data: instruments::instruction::InitializeFixedIncome { params }.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub fn settle_fixed_income_ixs(
+    rate_oracle: Pubkey,
+    market_product_group: Pubkey,
+    clock: Pubkey,
+    fixed_income_metadata: Pubkey,
+    oracle_accounts: &[Pubkey],
+) -> Vec<Instruction> {
+    let mut account_metas = instruments::accounts::SettleFixedIncome {
+        market_product_group,
+        fixed_income_metadata,
+        rate_oracle,
+        dex_program: dex::id(),
+        clock,
+    }
+    .to_account_metas(Some(true));
+    // Underlying oracles of a composite rate oracle
+    account_metas.extend(
+        oracle_accounts
+            .iter()
+            .map(|oracle| AccountMeta::new_readonly(*oracle, false)),
+    );
+
+    let mut data = instruments::instruction::SettleFixedIncome.data();
+    // Hack to get back test runtime dedupe
+    let mut rng = rand::prelude::thread_rng();
+    let out = rng.gen_range(0..255);
+    data.push(out as u8);
+    vec![Instruction {
+        program_id: instruments::ID,
+        data,
+        accounts: account_metas,
+    }]
+}
+
+pub fn close_fixed_income_account_ixs(
+    fixed_income_metadata: Pubkey,
+    close_authority: Pubkey,
+    destination: Pubkey,
+) -> Vec<Instruction> {
+    let account_metas = instruments::accounts::CloseFixedIncomeAccount {
+        fixed_income_metadata,
+        close_authority,
+        destination,
+    }
+    .to_account_metas(Some(true));
+
+    vec![Instruction {
+        program_id: instruments::ID,
+        data: instruments::instruction::CloseFixedIncomeAccount.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub struct InitializeFixedIncomeOptionalArgs {
+    pub initialization_time: i64,
+    pub oracle_type: OracleType,
+    pub max_staleness_slots: u64,
+    pub max_confidence_bps: u64,
+}
+
+impl Default for InitializeFixedIncomeOptionalArgs {
+    fn default() -> Self {
+        InitializeFixedIncomeOptionalArgs {
+            initialization_time: 100,
+            oracle_type: OracleType::Dummy,
+            max_staleness_slots: 0,
+            max_confidence_bps: 0,
+        }
+    }
+}
+
+pub async fn initialize_fixed_income(
+    client: &SDKClient,
+    close_authority: Pubkey,
+    market_product_group: Pubkey,
+    rate_oracle: Pubkey,
+    clock: Pubkey,
+    fixed_income_type: FixedIncomeType,
+    notional: impl Into<Fractional>,
+    fixed_rate: impl Into<Fractional>,
+    tenor: i64,
+    maturity_time: i64,
+    coupon_period: i64,
+    optional_args: InitializeFixedIncomeOptionalArgs,
+) -> std::result::Result<Pubkey, SDKError> {
+    let params = InitializeFixedIncomeParams {
+        fixed_income_type,
+        notional: notional.into(),
+        fixed_rate: fixed_rate.into(),
+        tenor,
+        initialization_time: optional_args.initialization_time,
+        maturity_time,
+        coupon_period,
+        close_authority,
+        oracle_type: optional_args.oracle_type,
+        max_staleness_slots: optional_args.max_staleness_slots,
+        max_confidence_bps: optional_args.max_confidence_bps,
+    };
+    let fixed_income_metadata = get_fixed_income_key(rate_oracle, market_product_group, &params);
+    let ixs = initialize_fixed_income_ixs(
+        rate_oracle,
+        market_product_group,
+        client.payer.pubkey(),
+        clock,
+        fixed_income_metadata,
+        params,
+    );
+    client
+        .sign_send_instructions(ixs, vec![&client.payer])
+        .await?;
+    Ok(fixed_income_metadata)
+}
+
+pub async fn settle_fixed_income(
+    client: &SDKClient,
+    market_product_group: Pubkey,
+    rate_oracle: Pubkey,
+    clock: Pubkey,
+    fixed_income_metadata: Pubkey,
+    oracle_accounts: &[Pubkey],
+) -> std::result::Result<(), SDKError> {
+    let ixs = settle_fixed_income_ixs(
+        rate_oracle,
+        market_product_group,
+        clock,
+        fixed_income_metadata,
+        oracle_accounts,
+    );
+    client.sign_send_instructions(ixs, vec![]).await
+}
+
+pub async fn close_fixed_income_account(
+    client: &SDKClient,
+    fixed_income_metadata: Pubkey,
+    close_authority: &Keypair,
+    destination: Pubkey,
+) -> std::result::Result<(), SDKError> {
+    let ixs = close_fixed_income_account_ixs(
+        fixed_income_metadata,
+        close_authority.pubkey(),
+        destination,
+    );
+    client
+        .sign_send_instructions(ixs, vec![close_authority])
+        .await
+}
@@ -0,0 +1,61 @@
+use crate::{common::utils::*, sdk_client::SDKClient};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use instruments::state::enums::OracleType;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use solana_sdk::signature::{Keypair, Signer};
+
+pub fn initialize_composite_oracle_ixs(
+    composite_oracle: Pubkey,
+    payer: Pubkey,
+    oracles: &[(OracleType, Pubkey)],
+    min_valid_oracles: u64,
+) -> Vec<Instruction> {
+    let mut account_metas = instruments::accounts::InitializeCompositeOracle {
+        composite_oracle,
+        payer,
+        system_program: solana_program::system_program::id(),
+    }
+    .to_account_metas(Some(true));
+    account_metas.extend(
+        oracles
+            .iter()
+            .map(|(_, oracle)| AccountMeta::new_readonly(*oracle, false)),
+    );
+    let data = instruments::instruction::InitializeCompositeOracle {
+        params: instruments::InitializeCompositeOracleParams {
+            oracle_types: oracles
+                .iter()
+                .map(|(oracle_type, _)| *oracle_type)
+                .collect(),
+            min_valid_oracles,
+        },
+    }
+    .data();
+    vec![Instruction {
+        program_id: instruments::ID,
+        data,
+        accounts: account_metas,
+    }]
+}
+
+pub async fn initialize_composite_oracle(
+    client: &SDKClient,
+    payer: &Keypair,
+    oracles: &[(OracleType, Pubkey)],
+    min_valid_oracles: u64,
+) -> std::result::Result<Pubkey, SDKError> {
+    let composite_oracle = Keypair::new();
+    let ixs = initialize_composite_oracle_ixs(
+        composite_oracle.pubkey(),
+        payer.pubkey(),
+        oracles,
+        min_valid_oracles,
+    );
+    client
+        .sign_send_instructions(ixs, vec![payer, &composite_oracle])
+        .await?;
+    Ok(composite_oracle.pubkey())
+}
@@ -1,6 +1,6 @@
 use crate::{common::utils::*, sdk_client::SDKClient};
 use anchor_lang::{InstructionData, ToAccountMetas};
-use dex::utils::numeric::Fractional;
+use dex::utils::numeric::{Fractional, ZERO_FRAC};
 use instruments::{
     accounts,
     state::enums::{InstrumentType, OracleType},
@@ -52,6 +52,15 @@ pub fn initialize_derivative_ixs(
     minimum_funding_period: UnixTimestamp,
     initialization_time: UnixTimestamp,
     oracle_type: OracleType,
+    max_staleness_slots: u64,
+    max_confidence_bps: u64,
+    max_funding_rate_bps: u64,
+    funding_dampening_bps: u64,
+    cap: Fractional,
+    digital_payout: Fractional,
+    mark_price_ewma_window: u64,
+    settlement_window: UnixTimestamp,
+    min_settlement_samples: u64,
 ) -> Vec<Instruction> {
     let params = instruments::InitializeDerivativeParams {
         instrument_type,
@@ -61,6 +70,15 @@ pub fn initialize_derivative_ixs(
         close_authority,
         initialization_time,
         oracle_type,
+        max_staleness_slots,
+        max_confidence_bps,
+        max_funding_rate_bps,
+        funding_dampening_bps,
+        cap,
+        digital_payout,
+        mark_price_ewma_window,
+        settlement_window,
+        min_settlement_samples,
     };
 
     let account_metas = instruments::accounts::InitializeDerivative {
@@ -87,6 +105,15 @@ pub struct InitializeDerivativeOptionalArgs {
     pub full_funding_period: i64,
     pub minimum_funding_period: i64,
     pub oracle_type: OracleType,
+    pub max_staleness_slots: u64,
+    pub max_confidence_bps: u64,
+    pub max_funding_rate_bps: u64,
+    pub funding_dampening_bps: u64,
+    pub cap: Fractional,
+    pub digital_payout: Fractional,
+    pub mark_price_ewma_window: u64,
+    pub settlement_window: UnixTimestamp,
+    pub min_settlement_samples: u64,
 }
 
 impl Default for InitializeDerivativeOptionalArgs {
@@ -97,6 +124,15 @@ impl Default for InitializeDerivativeOptionalArgs {
             full_funding_period: 101,
             minimum_funding_period: 10,
             oracle_type: OracleType::Dummy,
+            max_staleness_slots: 0,
+            max_confidence_bps: 0,
+            max_funding_rate_bps: 0,
+            funding_dampening_bps: 0,
+            cap: ZERO_FRAC,
+            digital_payout: ZERO_FRAC,
+            mark_price_ewma_window: 0,
+            settlement_window: 0,
+            min_settlement_samples: 0,
         }
     }
 }
@@ -115,6 +151,15 @@ impl InitializeDerivativeOptionalArgs {
             full_funding_period,
             minimum_funding_period,
             oracle_type,
+            max_staleness_slots: 0,
+            max_confidence_bps: 0,
+            max_funding_rate_bps: 0,
+            funding_dampening_bps: 0,
+            cap: ZERO_FRAC,
+            digital_payout: ZERO_FRAC,
+            mark_price_ewma_window: 0,
+            settlement_window: 0,
+            min_settlement_samples: 0,
         }
     }
 }
@@ -133,6 +178,15 @@ pub async fn initialize_derivative(
     let full_funding_period = optional_args.full_funding_period;
     let minimum_funding_period = optional_args.minimum_funding_period;
     let oracle_type = optional_args.oracle_type;
+    let max_staleness_slots = optional_args.max_staleness_slots;
+    let max_confidence_bps = optional_args.max_confidence_bps;
+    let max_funding_rate_bps = optional_args.max_funding_rate_bps;
+    let funding_dampening_bps = optional_args.funding_dampening_bps;
+    let cap = optional_args.cap;
+    let digital_payout = optional_args.digital_payout;
+    let mark_price_ewma_window = optional_args.mark_price_ewma_window;
+    let settlement_window = optional_args.settlement_window;
+    let min_settlement_samples = optional_args.min_settlement_samples;
     let strike = strike.into();
 
     let derivative_metadata = get_derivative_key(
@@ -158,6 +212,15 @@ pub async fn initialize_derivative(
         minimum_funding_period,
         initialization_time,
         oracle_type,
+        max_staleness_slots,
+        max_confidence_bps,
+        max_funding_rate_bps,
+        funding_dampening_bps,
+        cap,
+        digital_payout,
+        mark_price_ewma_window,
+        settlement_window,
+        min_settlement_samples,
     );
     client
         .sign_send_instructions(ixs, vec![&client.payer])
@@ -5,7 +5,10 @@ use dex::utils::numeric::Fractional;
 
 use crate::{common::utils::SDKError, KeypairD, SDKClient};
 
+pub mod fixed_income;
+pub mod initialize_composite_oracle;
 pub mod initialize_derivative;
+pub mod series;
 pub mod settle_derivative;
 
 pub struct InstrumentAdmin {
@@ -42,6 +45,15 @@ impl InstrumentAdmin {
         let full_funding_period = optional_args.full_funding_period;
         let minimum_funding_period = optional_args.minimum_funding_period;
         let oracle_type = optional_args.oracle_type;
+        let max_staleness_slots = optional_args.max_staleness_slots;
+        let max_confidence_bps = optional_args.max_confidence_bps;
+        let max_funding_rate_bps = optional_args.max_funding_rate_bps;
+        let funding_dampening_bps = optional_args.funding_dampening_bps;
+        let cap = optional_args.cap;
+        let digital_payout = optional_args.digital_payout;
+        let mark_price_ewma_window = optional_args.mark_price_ewma_window;
+        let settlement_window = optional_args.settlement_window;
+        let min_settlement_samples = optional_args.min_settlement_samples;
         let strike = strike.into();
 
         let derivative_metadata = initialize_derivative::get_derivative_key(
@@ -67,6 +79,15 @@ impl InstrumentAdmin {
             minimum_funding_period,
             initialization_time,
             oracle_type,
+            max_staleness_slots,
+            max_confidence_bps,
+            max_funding_rate_bps,
+            funding_dampening_bps,
+            cap,
+            digital_payout,
+            mark_price_ewma_window,
+            settlement_window,
+            min_settlement_samples,
         );
         self.client
             .sign_send_instructions(ixs, vec![&self.payer])
@@ -0,0 +1,167 @@
+use crate::{
+    common::utils::*, instrument::initialize_derivative::get_derivative_key,
+    processor::orderbook::create_orderbook, sdk_client::SDKClient, state::SDKProduct,
+};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use instruments::{state::series::Series, InitializeSeriesParams};
+use solana_program::{
+    clock::{Clock, UnixTimestamp},
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// PDA of the instruments program that lists series products in a market product group
+pub fn get_product_lister_key(market_product_group: Pubkey) -> Pubkey {
+    let (product_lister, _) = Pubkey::find_program_address(
+        &[b"product_lister", market_product_group.as_ref()],
+        &instruments::ID,
+    );
+    product_lister
+}
+
+/// Keys of the derivatives listed by rolling the series at `now`, in strike order
+pub fn get_next_series_products(series: &Series, now: UnixTimestamp) -> Vec<Pubkey> {
+    let initialization_time = series.initialization_time(series.next_roll(now));
+    series
+        .strikes()
+        .iter()
+        .map(|strike| {
+            get_derivative_key(
+                series.price_oracle,
+                series.market_product_group,
+                series.instrument_type,
+                *strike,
+                series.expiry_interval as u64,
+                series.expiry_interval,
+                initialization_time,
+            )
+        })
+        .collect()
+}
+
+pub fn initialize_series_ixs(
+    series: Pubkey,
+    price_oracle: Pubkey,
+    market_product_group: Pubkey,
+    authority: Pubkey,
+    payer: Pubkey,
+    clock: Pubkey,
+    params: InitializeSeriesParams,
+) -> Vec<Instruction> {
+    let account_metas = instruments::accounts::InitializeSeries {
+        series,
+        price_oracle,
+        market_product_group,
+        authority,
+        payer,
+        system_program: system_program::id(),
+        clock,
+    }
+    .to_account_metas(Some(true));
+
+    vec![Instruction {
+        program_id: instruments::ID,
+        data: instruments::instruction::InitializeSeries { params }.data(),
+        accounts: account_metas,
+    }]
+}
+
+/// `products` holds the (derivative metadata, orderbook) pair of every strike
+pub fn roll_series_ixs(
+    series: Pubkey,
+    market_product_group: Pubkey,
+    payer: Pubkey,
+    clock: Pubkey,
+    products: &[(Pubkey, Pubkey)],
+) -> Vec<Instruction> {
+    let mut account_metas = instruments::accounts::RollSeries {
+        series,
+        market_product_group,
+        product_lister: get_product_lister_key(market_product_group),
+        payer,
+        system_program: system_program::id(),
+        dex_program: dex::ID,
+        clock,
+    }
+    .to_account_metas(Some(true));
+    for (derivative_metadata, orderbook) in products {
+        account_metas.push(AccountMeta::new(*derivative_metadata, false));
+        account_metas.push(AccountMeta::new_readonly(*orderbook, false));
+    }
+
+    vec![Instruction {
+        program_id: instruments::ID,
+        data: instruments::instruction::RollSeries {}.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub async fn initialize_series(
+    client: &SDKClient,
+    authority: &Keypair,
+    price_oracle: Pubkey,
+    market_product_group: Pubkey,
+    clock: Pubkey,
+    params: InitializeSeriesParams,
+) -> std::result::Result<Pubkey, SDKError> {
+    let series = Keypair::new();
+    let ixs = initialize_series_ixs(
+        series.pubkey(),
+        price_oracle,
+        market_product_group,
+        authority.pubkey(),
+        client.payer.pubkey(),
+        clock,
+        params,
+    );
+    client
+        .sign_send_instructions(ixs, vec![authority, &series])
+        .await?;
+    Ok(series.pubkey())
+}
+
+/// Creates the orderbooks of the next expiry and lists its products
+pub async fn roll_series(
+    client: &SDKClient,
+    aaob_program_id: Pubkey,
+    series_key: Pubkey,
+) -> std::result::Result<Vec<SDKProduct>, SDKError> {
+    let series = client.get_anchor_account::<Series>(series_key).await;
+    let clock_account = client.get_account(series.clock).await?;
+    let now = bincode::deserialize::<Clock>(&clock_account.data)
+        .map_err(|e| SDKError::from(anyhow::anyhow!(e)))?
+        .unix_timestamp;
+    let roll = series.next_roll(now);
+    let mut products = vec![];
+    for (i, product_key) in get_next_series_products(&series, now)
+        .into_iter()
+        .enumerate()
+    {
+        let (market_signer, _) = Pubkey::find_program_address(&[product_key.as_ref()], &dex::ID);
+        let (orderbook, bids, asks, event_queue) =
+            create_orderbook(client, aaob_program_id, market_signer).await?;
+        products.push(SDKProduct {
+            key: product_key,
+            name: series.product_name(roll, i),
+            orderbook,
+            bids,
+            asks,
+            market_signer,
+            event_queue,
+        });
+    }
+    let ixs = roll_series_ixs(
+        series_key,
+        series.market_product_group,
+        client.payer.pubkey(),
+        series.clock,
+        &products
+            .iter()
+            .map(|p| (p.key, p.orderbook))
+            .collect::<Vec<_>>(),
+    );
+    client.sign_send_instructions(ixs, vec![]).await?;
+    Ok(products)
+}
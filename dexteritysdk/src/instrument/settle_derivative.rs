@@ -1,14 +1,15 @@
 use crate::{common::utils::*, sdk_client::SDKClient};
-use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_lang::{AnchorDeserialize, InstructionData, ToAccountMetas};
 use dex::utils::numeric::Fractional;
 use instruments::{
     accounts,
     state::enums::{InstrumentType, OracleType},
+    SettlementOutcome,
 };
 use rand::Rng;
 use solana_program::{
     clock::UnixTimestamp,
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
     sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
@@ -20,8 +21,9 @@ pub fn settle_derivative_ixs(
     market_product_group: Pubkey,
     clock: Pubkey,
     derivative_metadata: Pubkey,
+    oracle_accounts: &[Pubkey],
 ) -> Vec<Instruction> {
-    let account_metas = instruments::accounts::SettleDerivative {
+    let mut account_metas = instruments::accounts::SettleDerivative {
         market_product_group,
         derivative_metadata,
         price_oracle,
@@ -29,6 +31,12 @@ pub fn settle_derivative_ixs(
         clock,
     }
     .to_account_metas(Some(true));
+    // Underlying oracles of a composite price oracle
+    account_metas.extend(
+        oracle_accounts
+            .iter()
+            .map(|oracle| AccountMeta::new_readonly(*oracle, false)),
+    );
 
     let mut data = instruments::instruction::SettleDerivative.data();
     // Hack to get back test runtime dedupe
@@ -50,13 +58,176 @@ pub async fn settle_derivative(
     price_oracle: Pubkey,
     clock: Pubkey,
     derivative_metadata: Pubkey,
+    oracle_accounts: &[Pubkey],
 ) -> std::result::Result<Pubkey, SDKError> {
     let ixs = settle_derivative_ixs(
         price_oracle,
         market_product_group,
         clock,
         derivative_metadata,
+        oracle_accounts,
     );
     client.sign_send_instructions(ixs, vec![]).await?;
     Ok(derivative_metadata)
 }
+
+/// Settles every due derivative of `derivatives`, given as (derivative_metadata, price_oracle)
+/// pairs, skipping the ones that are not due
+pub fn settle_derivatives_ixs(
+    market_product_group: Pubkey,
+    clock: Pubkey,
+    derivatives: &[(Pubkey, Pubkey)],
+    oracle_accounts: &[Pubkey],
+) -> Vec<Instruction> {
+    let mut account_metas = instruments::accounts::SettleDerivatives {
+        market_product_group,
+        dex_program: dex::id(),
+        clock,
+    }
+    .to_account_metas(Some(true));
+    for (derivative_metadata, price_oracle) in derivatives {
+        account_metas.push(AccountMeta::new(*derivative_metadata, false));
+        account_metas.push(AccountMeta::new_readonly(*price_oracle, false));
+    }
+    account_metas.extend(
+        oracle_accounts
+            .iter()
+            .map(|oracle| AccountMeta::new_readonly(*oracle, false)),
+    );
+
+    let mut data = instruments::instruction::SettleDerivatives {
+        params: instruments::SettleDerivativesParams {
+            num_derivatives: derivatives.len() as u64,
+        },
+    }
+    .data();
+    // Hack to get back test runtime dedupe
+    let mut rng = rand::prelude::thread_rng();
+    let out = rng.gen_range(0..255);
+    data.push(out as u8);
+    vec![Instruction {
+        program_id: instruments::ID,
+        data,
+        accounts: account_metas,
+    }]
+}
+
+pub async fn settle_derivatives(
+    client: &SDKClient,
+    market_product_group: Pubkey,
+    clock: Pubkey,
+    derivatives: &[(Pubkey, Pubkey)],
+    oracle_accounts: &[Pubkey],
+) -> std::result::Result<(), SDKError> {
+    let ixs = settle_derivatives_ixs(market_product_group, clock, derivatives, oracle_accounts);
+    client.sign_send_instructions(ixs, vec![]).await
+}
+
+/// Simulates a batch settlement and returns the outcome of each derivative
+pub async fn simulate_settle_derivatives(
+    client: &SDKClient,
+    market_product_group: Pubkey,
+    clock: Pubkey,
+    derivatives: &[(Pubkey, Pubkey)],
+    oracle_accounts: &[Pubkey],
+) -> std::result::Result<Vec<SettlementOutcome>, SDKError> {
+    let ixs = settle_derivatives_ixs(market_product_group, clock, derivatives, oracle_accounts);
+    let data = client
+        .simulate_return_data(ixs, vec![], instruments::ID)
+        .await?
+        .ok_or(anyhow::anyhow!("No settlement outcomes returned"))?;
+    Ok(Vec::<SettlementOutcome>::try_from_slice(&data)?)
+}
+
+pub fn update_premium_index_ixs(
+    price_oracle: Pubkey,
+    market_product_group: Pubkey,
+    clock: Pubkey,
+    derivative_metadata: Pubkey,
+    oracle_accounts: &[Pubkey],
+) -> Vec<Instruction> {
+    let mut account_metas = instruments::accounts::UpdatePremiumIndex {
+        market_product_group,
+        derivative_metadata,
+        price_oracle,
+        dex_program: dex::id(),
+        clock,
+    }
+    .to_account_metas(Some(true));
+    account_metas.extend(
+        oracle_accounts
+            .iter()
+            .map(|oracle| AccountMeta::new_readonly(*oracle, false)),
+    );
+
+    let mut data = instruments::instruction::UpdatePremiumIndex.data();
+    // Hack to get back test runtime dedupe
+    let mut rng = rand::prelude::thread_rng();
+    let out = rng.gen_range(0..255);
+    data.push(out as u8);
+    vec![Instruction {
+        program_id: instruments::ID,
+        data,
+        accounts: account_metas,
+    }]
+}
+
+pub async fn update_premium_index(
+    client: &SDKClient,
+    market_product_group: Pubkey,
+    price_oracle: Pubkey,
+    clock: Pubkey,
+    derivative_metadata: Pubkey,
+    oracle_accounts: &[Pubkey],
+) -> std::result::Result<(), SDKError> {
+    let ixs = update_premium_index_ixs(
+        price_oracle,
+        market_product_group,
+        clock,
+        derivative_metadata,
+        oracle_accounts,
+    );
+    client.sign_send_instructions(ixs, vec![]).await
+}
+
+pub fn record_settlement_sample_ixs(
+    price_oracle: Pubkey,
+    clock: Pubkey,
+    derivative_metadata: Pubkey,
+    oracle_accounts: &[Pubkey],
+) -> Vec<Instruction> {
+    let mut account_metas = instruments::accounts::RecordSettlementSample {
+        derivative_metadata,
+        price_oracle,
+        clock,
+    }
+    .to_account_metas(Some(true));
+    account_metas.extend(
+        oracle_accounts
+            .iter()
+            .map(|oracle| AccountMeta::new_readonly(*oracle, false)),
+    );
+
+    let mut data = instruments::instruction::RecordSettlementSample.data();
+    // Hack to get back test runtime dedupe
+    let mut rng = rand::prelude::thread_rng();
+    let out = rng.gen_range(0..255);
+    data.push(out as u8);
+    vec![Instruction {
+        program_id: instruments::ID,
+        data,
+        accounts: account_metas,
+    }]
+}
+
+pub async fn record_settlement_sample(
+    client: &SDKClient,
+    price_oracle: Pubkey,
+    clock: Pubkey,
+    derivative_metadata: Pubkey,
+    oracle_accounts: &[Pubkey],
+) -> std::result::Result<(), SDKError> {
+    let ixs =
+        record_settlement_sample_ixs(price_oracle, clock, derivative_metadata, oracle_accounts);
+    client.sign_send_instructions(ixs, vec![]).await
+}
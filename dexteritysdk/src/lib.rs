@@ -44,6 +44,7 @@ pub mod admin;
 pub mod bootstrap;
 pub mod common;
 pub mod context;
+pub mod estimate;
 pub mod instrument;
 pub mod oracle;
 pub mod processor;
@@ -59,6 +60,8 @@ pub const ANCHOR_CREATE_RISK_STATE_ACCOUNT_DISCRIMINANT: u64 = 56505690607425760
 pub const VALIDATE_ACCOUNT_HEALTH_DISCRIMINANT: u64 = 0;
 pub const VALIDATE_ACCOUNT_LIQUIDATION_DISCRIMINANT: u64 = 1;
 pub const FIND_FEES_DISCRIMINANT: u8 = 0;
+pub const ON_POST_DISCRIMINANT: u8 = 3;
+pub const ON_CANCEL_DISCRIMINANT: u8 = 4;
 pub const MINT_DECIMALS: u8 = 6;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -95,10 +98,34 @@ pub struct BootstrapConfig {
     pub create_risk_state_disc: u64,
     pub liq_disc: u64,
     pub fees_disc: u64,
+    // optional fee model hooks, these share `fees_disc_len`
+    #[serde(default)]
+    pub on_post_disc: Option<u64>,
+    #[serde(default)]
+    pub on_cancel_disc: Option<u64>,
+    // number of remaining accounts in new_order forwarded to the fee model
+    #[serde(default)]
+    pub fee_accounts_len: u64,
+    // (min_balance, discount_bps) tiers of the vault mint when the fee model is token-discount-fees
+    #[serde(default)]
+    pub fee_discount_tiers: Option<Vec<(u64, i32)>>,
+    // expiring options that are relisted by roll_series instead of listed one by one
+    #[serde(default)]
+    pub series: Option<SeriesConfig>,
 
     pub optional: OptionalBootstrapFields,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SeriesConfig {
+    pub strikes: Vec<i64>,
+    // seconds between expiries
+    pub expiry_interval: i64,
+    // lists puts instead of calls
+    #[serde(default)]
+    pub puts: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct OptionalBootstrapFields {
     pub mint_authority: Option<KeypairD>,
@@ -131,6 +158,8 @@ pub struct SDKContext {
     pub fee_output_register: Pubkey,
     pub fee_collector: Pubkey,
     pub additional_risk_accts: ArrayVec<Pubkey, 4>, // todo clean this up and integrate w/ dex
+    // cancels pass the fee model accounts when the group has an on_cancel hook
+    pub on_cancel_hook: bool,
 }
 
 impl SDKContext {
@@ -186,6 +215,7 @@ impl SDKContext {
             additional_risk_accts: ArrayVec::new(),
             risk_model_config_acct: mpg.risk_model_configuration_acct,
             trader_risk_state_account_len,
+            on_cancel_hook: mpg.on_cancel_discriminant_len > 0,
         };
         ctx.load_products().await?;
         Ok(ctx)
@@ -224,6 +254,11 @@ impl SDKContext {
                 asks: Pubkey::new_from_array(market_state.asks),
                 market_signer,
                 event_queue: Pubkey::new_from_array(market_state.event_queue),
+                legs: product
+                    .legs()
+                    .iter()
+                    .map(|leg| (leg.product_key, leg.ratio as i32))
+                    .collect(),
             });
         }
         self.products = products;
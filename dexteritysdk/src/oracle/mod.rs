@@ -1,4 +1,5 @@
 pub mod create_clock;
 pub mod create_oracle;
+pub mod set_price_schedule;
 pub mod update_clock;
 pub mod update_oracle;
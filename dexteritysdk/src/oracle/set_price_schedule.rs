@@ -0,0 +1,33 @@
+use crate::{common::utils::SDKError, sdk_client::SDKClient, KeypairD};
+use dummy_oracle::state::{PricePoint, ScheduleClock};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+/// Loads a schedule of (time, price) points into the dummy oracle. Reads return the price
+/// of the latest point at or before the slot or unix timestamp of the clock they use.
+pub async fn set_oracle_price_schedule(
+    client: &SDKClient,
+    dummy_oracle_program_id: Pubkey,
+    authority: &KeypairD,
+    schedule_clock: ScheduleClock,
+    schedule: Vec<(i64, i64)>,
+) -> std::result::Result<Pubkey, SDKError> {
+    let (oracle_metadata_key, _) =
+        Pubkey::find_program_address(&[b"oracle"], &dummy_oracle_program_id);
+    let set_price_schedule_ix = dummy_oracle::processor::set_price_schedule_ix(
+        dummy_oracle_program_id,
+        oracle_metadata_key,
+        authority.pubkey(),
+        dummy_oracle::processor::set_price_schedule::Params {
+            schedule_clock,
+            schedule: schedule
+                .into_iter()
+                .map(|(time, price)| PricePoint { time, price })
+                .collect(),
+        },
+    );
+    client
+        .sign_send_instructions(vec![set_price_schedule_ix], vec![authority])
+        .await?;
+    Ok(oracle_metadata_key)
+}
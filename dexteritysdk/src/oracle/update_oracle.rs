@@ -39,6 +39,7 @@ pub async fn update_oracle_price_account(
         oracle_metadata_key,
         authority.pubkey(),
         system_program_id,
+        None,
         dummy_oracle::processor::update_price::Params {
             price: price,
             decimals: decimals,
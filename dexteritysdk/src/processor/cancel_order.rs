@@ -28,6 +28,10 @@ pub fn cancel_order_ixs(
     risk_output_register: Pubkey,
     trader_risk_state_acct: Pubkey,
     risk_model_configuration_acct: Pubkey,
+    fee_model_program: Pubkey,
+    fee_model_configuration_acct: Pubkey,
+    trader_fee_state_acct: Pubkey,
+    on_cancel_hook: bool,
 ) -> Vec<Instruction> {
     let (risk_signer, _) = Pubkey::find_program_address(&[market_product_group.as_ref()], &dex::ID);
     let mut account_metas = accounts::CancelOrder {
@@ -49,6 +53,15 @@ pub fn cancel_order_ixs(
         risk_signer,
     }
     .to_account_metas(None);
+    // The fee model accounts are only needed by the on_cancel hook
+    if on_cancel_hook {
+        account_metas.push(AccountMeta::new_readonly(fee_model_program, false));
+        account_metas.push(AccountMeta::new_readonly(
+            fee_model_configuration_acct,
+            false,
+        ));
+        account_metas.push(AccountMeta::new(trader_fee_state_acct, false));
+    }
     for key in risk_engine_accounts.into_iter() {
         account_metas.push(AccountMeta::new_readonly(key, false));
     }
@@ -0,0 +1,130 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use dex::{accounts, instruction, utils::numeric::Fractional};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+pub fn get_collateral_vault(market_product_group: Pubkey, mint: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"collateral_vault",
+            market_product_group.as_ref(),
+            mint.as_ref(),
+        ],
+        &dex::ID,
+    )
+    .0
+}
+
+pub fn add_collateral_ixs(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    mint: Pubkey,
+    oracle: Pubkey,
+    params: dex::AddCollateralParams,
+) -> Vec<Instruction> {
+    let account_metas = accounts::AddCollateral {
+        authority,
+        market_product_group,
+        mint,
+        oracle,
+        collateral_vault: get_collateral_vault(market_product_group, mint),
+        sysvar_rent: sysvar::rent::id(),
+        system_program: system_program::id(),
+        token_program: spl_token::ID,
+    }
+    .to_account_metas(None);
+    vec![Instruction {
+        program_id: dex::ID,
+        data: instruction::AddCollateral { params }.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub fn update_collateral_ixs(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    mint: Pubkey,
+    oracle: Pubkey,
+    params: dex::UpdateCollateralParams,
+) -> Vec<Instruction> {
+    let account_metas = accounts::UpdateCollateral {
+        authority,
+        market_product_group,
+        mint,
+        oracle,
+    }
+    .to_account_metas(None);
+    vec![Instruction {
+        program_id: dex::ID,
+        data: instruction::UpdateCollateral { params }.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub fn deposit_collateral_ixs(
+    user: Pubkey,
+    user_token_account: Pubkey,
+    trader_risk_group: Pubkey,
+    market_product_group: Pubkey,
+    mint: Pubkey,
+    quantity: Fractional,
+) -> Vec<Instruction> {
+    let params = dex::DepositCollateralParams { quantity };
+    let account_metas = accounts::DepositCollateral {
+        token_program: spl_token::ID,
+        user,
+        user_token_account,
+        trader_risk_group,
+        market_product_group,
+        collateral_vault: get_collateral_vault(market_product_group, mint),
+    }
+    .to_account_metas(None);
+    vec![Instruction {
+        program_id: dex::ID,
+        data: instruction::DepositCollateral { params }.data(),
+        accounts: account_metas,
+    }]
+}
+
+/// `risk_engine_accounts` should include the oracles of the trader's collaterals
+pub fn withdraw_collateral_ixs(
+    user: Pubkey,
+    user_token_account: Pubkey,
+    trader_risk_group: Pubkey,
+    market_product_group: Pubkey,
+    mint: Pubkey,
+    risk_engine_program: Pubkey,
+    risk_model_configuration_acct: Pubkey,
+    risk_output_register: Pubkey,
+    trader_risk_state_acct: Pubkey,
+    risk_engine_accounts: &[Pubkey],
+    quantity: Fractional,
+) -> Vec<Instruction> {
+    let params = dex::WithdrawCollateralParams { quantity };
+    let (risk_signer, _) = Pubkey::find_program_address(&[market_product_group.as_ref()], &dex::ID);
+    let mut account_metas = accounts::WithdrawCollateral {
+        token_program: spl_token::ID,
+        user,
+        user_token_account,
+        trader_risk_group,
+        market_product_group,
+        collateral_vault: get_collateral_vault(market_product_group, mint),
+        risk_engine_program,
+        risk_model_configuration_acct,
+        risk_output_register,
+        trader_risk_state_acct,
+        risk_signer,
+    }
+    .to_account_metas(None);
+    for key in risk_engine_accounts.iter() {
+        account_metas.push(AccountMeta::new_readonly(*key, false));
+    }
+    vec![Instruction {
+        program_id: dex::ID,
+        data: instruction::WithdrawCollateral { params }.data(),
+        accounts: account_metas,
+    }]
+}
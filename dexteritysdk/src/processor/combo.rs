@@ -18,7 +18,7 @@ pub fn initialize_combo_ixs(
     tick_size: Fractional,
     price_offset: Fractional,
     base_decimals: u64,
-    ratios: Vec<i8>,
+    ratios: Vec<i32>,
 ) -> Vec<Instruction> {
     let params = dex::InitializeComboParams {
         name,
@@ -42,3 +42,50 @@ pub fn initialize_combo_ixs(
         accounts: account_metas,
     }]
 }
+
+/// `trader_risk_groups` must hold every trader with resting orders when a finer tick size is
+/// set on a non-empty book, their orders are rescaled to the new tick size
+pub fn update_combo_ixs(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    product: Pubkey,
+    aaob_program: Pubkey,
+    orderbook: Pubkey,
+    market_signer: Pubkey,
+    event_queue: Pubkey,
+    bids: Pubkey,
+    asks: Pubkey,
+    trader_risk_groups: &[Pubkey],
+    name: [u8; NAME_LEN],
+    tick_size: Fractional,
+    min_base_order_size: u64,
+) -> Vec<Instruction> {
+    let params = dex::UpdateMarketProductParams {
+        name,
+        tick_size,
+        min_base_order_size,
+    };
+    let mut account_metas = accounts::UpdateCombo {
+        authority,
+        market_product_group,
+        product,
+        aaob_program,
+        orderbook,
+        market_signer,
+        event_queue,
+        bids,
+        asks,
+        system_program: solana_program::system_program::id(),
+    }
+    .to_account_metas(None);
+    account_metas.extend(
+        trader_risk_groups
+            .iter()
+            .map(|key| AccountMeta::new(*key, false)),
+    );
+    vec![Instruction {
+        program_id: dex::ID,
+        data: dex::instruction::UpdateCombo { params }.data(),
+        accounts: account_metas,
+    }]
+}
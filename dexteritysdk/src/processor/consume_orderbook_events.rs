@@ -25,11 +25,15 @@ pub fn consume_orderbook_events_ixs(
     risk_engine_program: Pubkey,
     risk_output_register: Pubkey,
     user_accounts: &[Pubkey],
+    maker_fee_groups: &[(Pubkey, &[Pubkey])],
     max_iterations: u64,
 ) -> Vec<Instruction> {
     let (risk_and_fee_signer, _) =
         Pubkey::find_program_address(&[market_product_group.as_ref()], &dex::ID);
-    let params = dex::ConsumeOrderbookEventsParams { max_iterations };
+    let params = dex::ConsumeOrderbookEventsParams {
+        max_iterations,
+        num_maker_fee_groups: maker_fee_groups.len() as u64,
+    };
     let mut account_metas = accounts::ConsumeOrderbookEvents {
         aaob_program,
         market_product_group,
@@ -47,6 +51,10 @@ pub fn consume_orderbook_events_ixs(
     for key in user_accounts {
         account_metas.push(AccountMeta::new(*key, false));
     }
+    for (maker, fee_accounts) in maker_fee_groups {
+        account_metas.push(AccountMeta::new(*maker, false));
+        account_metas.extend(fee_accounts.iter().map(|k| AccountMeta::new(*k, false)));
+    }
     vec![Instruction {
         program_id: dex::ID,
         data: instruction::ConsumeOrderbookEvents { params }.data(),
@@ -1,7 +1,10 @@
 use crate::{common::utils::*, sdk_client::SDKClient};
 use anchor_lang::{InstructionData, ToAccountMetas};
 use dex::{accounts, state::constants::NAME_LEN, utils::numeric::Fractional};
-use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
 use solana_program_test::ProgramTestContext;
 use solana_sdk::signature::{Keypair, Signer};
 
@@ -34,3 +37,100 @@ pub fn initialize_market_product_ixs(
         accounts: account_metas,
     }]
 }
+
+/// `trader_risk_groups` must hold every trader with resting orders when a finer tick size is
+/// set on a non-empty book, their orders are rescaled to the new tick size
+pub fn update_market_product_ixs(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    product: Pubkey,
+    aaob_program: Pubkey,
+    orderbook: Pubkey,
+    market_signer: Pubkey,
+    event_queue: Pubkey,
+    bids: Pubkey,
+    asks: Pubkey,
+    trader_risk_groups: &[Pubkey],
+    name: [u8; NAME_LEN],
+    tick_size: Fractional,
+    min_base_order_size: u64,
+) -> Vec<Instruction> {
+    let params = dex::UpdateMarketProductParams {
+        name,
+        tick_size,
+        min_base_order_size,
+    };
+    let mut account_metas = accounts::UpdateMarketProduct {
+        authority,
+        market_product_group,
+        product,
+        aaob_program,
+        orderbook,
+        market_signer,
+        event_queue,
+        bids,
+        asks,
+        system_program: solana_program::system_program::id(),
+    }
+    .to_account_metas(None);
+    account_metas.extend(
+        trader_risk_groups
+            .iter()
+            .map(|key| AccountMeta::new(*key, false)),
+    );
+    vec![Instruction {
+        program_id: dex::ID,
+        data: dex::instruction::UpdateMarketProduct { params }.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub fn update_position_limits_ixs(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    product: Pubkey,
+    max_open_interest: Fractional,
+    max_position: Fractional,
+) -> Vec<Instruction> {
+    let params = dex::UpdatePositionLimitsParams {
+        max_open_interest,
+        max_position,
+    };
+    let account_metas = accounts::UpdatePositionLimits {
+        authority,
+        market_product_group,
+        product,
+    }
+    .to_account_metas(None);
+    vec![Instruction {
+        program_id: dex::ID,
+        data: dex::instruction::UpdatePositionLimits { params }.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub fn update_price_band_ixs(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    product: Pubkey,
+    bps: u64,
+    width: Fractional,
+    ewma_window: u64,
+) -> Vec<Instruction> {
+    let params = dex::UpdatePriceBandParams {
+        bps,
+        width,
+        ewma_window,
+    };
+    let account_metas = accounts::UpdatePriceBand {
+        authority,
+        market_product_group,
+        product,
+    }
+    .to_account_metas(None);
+    vec![Instruction {
+        program_id: dex::ID,
+        data: dex::instruction::UpdatePriceBand { params }.data(),
+        accounts: account_metas,
+    }]
+}
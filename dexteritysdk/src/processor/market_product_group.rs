@@ -1,7 +1,10 @@
 use anchor_lang::{InstructionData, ToAccountMetas};
 use solana_program::{
-    instruction::Instruction, program_error::ProgramError, pubkey::Pubkey,
-    system_instruction::create_account, system_program, sysvar,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction::create_account,
+    system_program, sysvar,
 };
 use solana_program_test::ProgramTestContext;
 use solana_sdk::signature::{Keypair, Signer};
@@ -37,6 +40,9 @@ pub fn initialize_market_product_group_ixs(
     validate_account_liquidation_discriminant: [u8; 8],
     create_risk_state_account_discriminant: [u8; 8],
     find_fees_discriminant: [u8; 8],
+    on_post_discriminant: Option<[u8; 8]>,
+    on_cancel_discriminant: Option<[u8; 8]>,
+    fee_accounts_len: u64,
 ) -> Vec<Instruction> {
     let size = std::mem::size_of::<MarketProductGroup>() + 8;
     let lamports = client.rent_exempt(size).max(1);
@@ -63,6 +69,13 @@ pub fn initialize_market_product_group_ixs(
                 min_maker_fee_bps: -100,
                 max_taker_fee_bps: 1000,
                 min_taker_fee_bps: -100,
+                on_post_discriminant_len: on_post_discriminant
+                    .map_or(0, |_| find_fees_discriminant_len),
+                on_cancel_discriminant_len: on_cancel_discriminant
+                    .map_or(0, |_| find_fees_discriminant_len),
+                on_post_discriminant: on_post_discriminant.unwrap_or_default(),
+                on_cancel_discriminant: on_cancel_discriminant.unwrap_or_default(),
+                fee_accounts_len,
             },
         }
         .data(),
@@ -133,6 +146,9 @@ pub async fn initialize_market_product_group(
     validate_account_liquidation_discriminant: [u8; 8],
     create_risk_state_account_discriminant: [u8; 8],
     find_fees_discriminant: [u8; 8],
+    on_post_discriminant: Option<[u8; 8]>,
+    on_cancel_discriminant: Option<[u8; 8]>,
+    fee_accounts_len: u64,
 ) -> std::result::Result<Pubkey, SDKError> {
     let ixs = initialize_market_product_group_ixs(
         client,
@@ -155,6 +171,9 @@ pub async fn initialize_market_product_group(
         validate_account_liquidation_discriminant,
         create_risk_state_account_discriminant,
         find_fees_discriminant,
+        on_post_discriminant,
+        on_cancel_discriminant,
+        fee_accounts_len,
     );
     client
         .sign_send_instructions(
@@ -169,3 +188,75 @@ pub async fn initialize_market_product_group(
         .await?;
     Ok(market_product_group.pubkey())
 }
+
+pub fn choose_product_lister_ix(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    product_lister: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: dex::ID,
+        data: dex::instruction::ChooseProductLister {}.data(),
+        accounts: dex::accounts::ChooseProductLister {
+            market_product_group,
+            authority,
+            product_lister,
+        }
+        .to_account_metas(None),
+    }
+}
+
+pub fn migrate_market_product_group_ix(
+    authority: Pubkey,
+    payer: Pubkey,
+    market_product_group: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: dex::ID,
+        data: dex::instruction::MigrateMarketProductGroup {}.data(),
+        accounts: dex::accounts::MigrateMarketProductGroup {
+            authority,
+            payer,
+            market_product_group,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+    }
+}
+
+/// Passes the output registers of a new risk engine or fee model, which the dex validates
+pub fn update_market_product_group_ix(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    params: dex::UpdateMarketProductGroupParams,
+) -> Instruction {
+    let mut accounts = dex::accounts::UpdateMarketProductGroup {
+        authority,
+        market_product_group,
+    }
+    .to_account_metas(None);
+    accounts.extend(
+        params
+            .risk_engine
+            .map(|c| c.output_register)
+            .into_iter()
+            .chain(params.fee_model.map(|c| c.output_register))
+            .map(|register| AccountMeta::new_readonly(register, false)),
+    );
+    Instruction {
+        program_id: dex::ID,
+        data: dex::instruction::UpdateMarketProductGroup { params }.data(),
+        accounts,
+    }
+}
+
+pub fn apply_market_product_group_update_ix(market_product_group: Pubkey) -> Instruction {
+    Instruction {
+        program_id: dex::ID,
+        data: dex::instruction::ApplyMarketProductGroupUpdate {}.data(),
+        accounts: dex::accounts::ApplyMarketProductGroupUpdate {
+            market_product_group,
+        }
+        .to_account_metas(None),
+    }
+}
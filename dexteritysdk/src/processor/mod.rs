@@ -1,5 +1,6 @@
 pub mod cancel_order;
 pub mod clear_expired_orderbook;
+pub mod collateral;
 pub mod combo;
 pub mod consume_orderbook_events;
 pub mod deposit_funds;
@@ -9,6 +10,7 @@ pub mod market_product_group;
 pub mod new_order;
 pub mod orderbook;
 pub mod remove_market_product;
+pub mod spot;
 pub mod trader_risk_group;
 pub mod transfer_full_position;
 pub mod update_product_funding;
@@ -27,6 +27,8 @@ pub fn new_order_ixs(
     fee_output_register: Pubkey,
     risk_engine_program: Pubkey,
     risk_model_configuration_acct: Pubkey,
+    fee_accounts: &[Pubkey],
+    implied_leg_accounts: &[Pubkey],
     risk_engine_accounts: &[Pubkey],
     side: Side,
     max_base_qty: Fractional,
@@ -44,6 +46,7 @@ pub fn new_order_ixs(
         self_trade_behavior,
         match_limit,
         limit_price,
+        implied_out: !implied_leg_accounts.is_empty(),
     };
     let (risk_and_fee_signer, _) =
         Pubkey::find_program_address(&[market_product_group.as_ref()], &dex::ID);
@@ -70,6 +73,16 @@ pub fn new_order_ixs(
         risk_and_fee_signer,
     }
     .to_account_metas(Some(true));
+    for key in fee_accounts.iter() {
+        account_metas.push(AccountMeta::new_readonly(*key, false));
+    }
+    for (i, key) in implied_leg_accounts.iter().enumerate() {
+        // the market signer of each leg is the only account that isn't written to
+        account_metas.push(match i % 5 {
+            1 => AccountMeta::new_readonly(*key, false),
+            _ => AccountMeta::new(*key, false),
+        });
+    }
     for key in risk_engine_accounts.iter() {
         account_metas.push(AccountMeta::new(*key, false));
     }
@@ -99,6 +112,8 @@ pub async fn new_order(
     fee_output_register: Pubkey,
     risk_engine_program_id: Pubkey,
     risk_model_configuration_acct: Pubkey,
+    fee_accounts: &[Pubkey],
+    implied_leg_accounts: &[Pubkey],
     risk_engine_accounts: &[Pubkey],
     side: Side,
     max_base_qty: Fractional,
@@ -126,6 +141,8 @@ pub async fn new_order(
         fee_output_register,
         risk_engine_program_id,
         risk_model_configuration_acct,
+        fee_accounts,
+        implied_leg_accounts,
         risk_engine_accounts,
         side,
         max_base_qty,
@@ -39,3 +39,34 @@ pub fn remove_market_product_ixs(
         accounts: account_metas,
     }]
 }
+
+/// `combo_accounts` holds the orderbook, market signer, event queue, bids and asks of every
+/// combo that has `product` as a leg, in the order the combos are listed in the group
+pub fn remove_all_dependent_combos_ixs(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    product: Pubkey,
+    aaob_program_id: Pubkey,
+    combo_accounts: &[Pubkey],
+) -> Vec<Instruction> {
+    let mut account_metas = accounts::RemoveAllDependentCombos {
+        authority,
+        market_product_group,
+        product,
+        aaob_program: aaob_program_id,
+    }
+    .to_account_metas(None);
+    for (i, key) in combo_accounts.iter().enumerate() {
+        // the market signer is the only readonly account of each combo
+        if i % 5 == 1 {
+            account_metas.push(AccountMeta::new_readonly(*key, false));
+        } else {
+            account_metas.push(AccountMeta::new(*key, false));
+        }
+    }
+    vec![Instruction {
+        program_id: dex::ID,
+        data: instruction::RemoveAllDependentCombos.data(),
+        accounts: account_metas,
+    }]
+}
@@ -0,0 +1,109 @@
+use anchor_lang::{InstructionData, ToAccountMetas};
+use dex::{accounts, instruction, utils::numeric::Fractional};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+pub fn get_base_vault(market_product_group: Pubkey, product: Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"base_vault",
+            market_product_group.as_ref(),
+            product.as_ref(),
+        ],
+        &dex::ID,
+    )
+    .0
+}
+
+pub fn initialize_spot_product_ixs(
+    authority: Pubkey,
+    market_product_group: Pubkey,
+    product: Pubkey,
+    base_mint: Pubkey,
+) -> Vec<Instruction> {
+    let account_metas = accounts::InitializeSpotProduct {
+        authority,
+        market_product_group,
+        product,
+        base_mint,
+        base_vault: get_base_vault(market_product_group, product),
+        sysvar_rent: sysvar::rent::id(),
+        system_program: system_program::id(),
+        token_program: spl_token::ID,
+    }
+    .to_account_metas(None);
+    vec![Instruction {
+        program_id: dex::ID,
+        data: instruction::InitializeSpotProduct.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub fn deposit_spot_funds_ixs(
+    user: Pubkey,
+    user_token_account: Pubkey,
+    trader_risk_group: Pubkey,
+    market_product_group: Pubkey,
+    product: Pubkey,
+    quantity: Fractional,
+) -> Vec<Instruction> {
+    let params = dex::DepositSpotFundsParams { quantity };
+    let account_metas = accounts::DepositSpotFunds {
+        token_program: spl_token::ID,
+        user,
+        user_token_account,
+        trader_risk_group,
+        market_product_group,
+        product,
+        base_vault: get_base_vault(market_product_group, product),
+    }
+    .to_account_metas(None);
+    vec![Instruction {
+        program_id: dex::ID,
+        data: instruction::DepositSpotFunds { params }.data(),
+        accounts: account_metas,
+    }]
+}
+
+pub fn withdraw_spot_funds_ixs(
+    user: Pubkey,
+    user_token_account: Pubkey,
+    trader_risk_group: Pubkey,
+    market_product_group: Pubkey,
+    product: Pubkey,
+    risk_engine_program: Pubkey,
+    risk_model_configuration_acct: Pubkey,
+    risk_output_register: Pubkey,
+    trader_risk_state_acct: Pubkey,
+    risk_engine_accounts: &[Pubkey],
+    quantity: Fractional,
+) -> Vec<Instruction> {
+    let params = dex::WithdrawSpotFundsParams { quantity };
+    let (risk_signer, _) = Pubkey::find_program_address(&[market_product_group.as_ref()], &dex::ID);
+    let mut account_metas = accounts::WithdrawSpotFunds {
+        token_program: spl_token::ID,
+        user,
+        user_token_account,
+        trader_risk_group,
+        market_product_group,
+        product,
+        base_vault: get_base_vault(market_product_group, product),
+        risk_engine_program,
+        risk_model_configuration_acct,
+        risk_output_register,
+        trader_risk_state_acct,
+        risk_signer,
+    }
+    .to_account_metas(None);
+    for key in risk_engine_accounts.iter() {
+        account_metas.push(AccountMeta::new(*key, false));
+    }
+    vec![Instruction {
+        program_id: dex::ID,
+        data: instruction::WithdrawSpotFunds { params }.data(),
+        accounts: account_metas,
+    }]
+}
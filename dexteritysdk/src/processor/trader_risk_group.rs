@@ -103,3 +103,60 @@ pub async fn initialize_trader_risk_group_pda(
         .sign_send_instructions(ixs, vec![&trader_risk_group, owner])
         .await
 }
+
+pub fn migrate_trader_risk_group_ix(
+    owner: Pubkey,
+    payer: Pubkey,
+    trader_risk_group: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: dex::ID,
+        data: instruction::MigrateTraderRiskGroup {}.data(),
+        accounts: accounts::MigrateTraderRiskGroup {
+            owner,
+            payer,
+            trader_risk_group,
+            system_program: solana_program::system_program::id(),
+        }
+        .to_account_metas(None),
+    }
+}
+
+pub fn update_trader_engine_accounts_ix(
+    owner: Pubkey,
+    trader_risk_group: Pubkey,
+    market_product_group: Pubkey,
+    risk_signer: Pubkey,
+    old_trader_risk_state_acct: Pubkey,
+    old_trader_fee_state_acct: Pubkey,
+    trader_risk_state_acct: Pubkey,
+    trader_fee_state_acct: Pubkey,
+    risk_engine_program: Pubkey,
+) -> Instruction {
+    let mut accounts = accounts::UpdateTraderEngineAccounts {
+        owner,
+        trader_risk_group,
+        market_product_group,
+        risk_signer,
+        old_trader_risk_state_acct,
+        old_trader_fee_state_acct,
+        trader_risk_state_acct,
+        trader_fee_state_acct,
+        risk_engine_program,
+        system_program: solana_program::system_program::id(),
+    }
+    .to_account_metas(None);
+    // a new risk state account is created by the risk engine and has to sign
+    if trader_risk_state_acct != old_trader_risk_state_acct {
+        for meta in accounts.iter_mut() {
+            if meta.pubkey == trader_risk_state_acct {
+                meta.is_signer = true;
+            }
+        }
+    }
+    Instruction {
+        program_id: dex::ID,
+        data: instruction::UpdateTraderEngineAccounts {}.data(),
+        accounts,
+    }
+}
@@ -1,7 +1,10 @@
 use crate::{common::utils::*, sdk_client::SDKClient};
 use anchor_lang::{InstructionData, ToAccountMetas};
 use dex::{accounts, instruction};
-use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
 use solana_program_test::ProgramTestContext;
 use solana_sdk::signature::{Keypair, Signer};
 
@@ -15,9 +18,10 @@ pub fn transfer_full_position_ixs(
     liquidator_risk_state_account_info: Pubkey,
     liquidatee_risk_state_account_info: Pubkey,
     risk_model_configuration_acct: Pubkey,
+    risk_accounts: &[Pubkey],
 ) -> Vec<Instruction> {
     let (risk_signer, _) = Pubkey::find_program_address(&[market_product_group.as_ref()], &dex::ID);
-    let account_metas = accounts::TransferFullPosition {
+    let mut account_metas = accounts::TransferFullPosition {
         liquidator: user,
         market_product_group,
         liquidatee_risk_group,
@@ -30,6 +34,11 @@ pub fn transfer_full_position_ixs(
         risk_signer,
     }
     .to_account_metas(Some(true));
+    account_metas.extend(
+        risk_accounts
+            .iter()
+            .map(|k| AccountMeta::new_readonly(*k, false)),
+    );
 
     vec![Instruction {
         program_id: dex::ID,
@@ -37,12 +37,23 @@ pub trait ClientSubset {
     async fn process_transaction(&self, mut tx: Transaction, signers: &Vec<&Keypair>) -> SDKResult;
     async fn fetch_latest_blockhash(&self) -> std::result::Result<Hash, SDKError>;
     async fn fetch_account(&self, key: Pubkey) -> std::result::Result<Account, SDKError>;
+    /// Runs the transaction without committing it and returns its logs
+    async fn simulate_transaction(
+        &self,
+        mut tx: Transaction,
+        signers: &Vec<&Keypair>,
+    ) -> SDKResult<Vec<String>>;
 }
 
 pub trait ClientSubsetSync {
     fn process_transaction(&self, tx: Transaction, signers: &Vec<&Keypair>) -> SDKResult;
     fn fetch_latest_blockhash(&self) -> std::result::Result<Hash, SDKError>;
     fn fetch_account(&self, key: Pubkey) -> std::result::Result<Account, SDKError>;
+    fn simulate_transaction(
+        &self,
+        tx: Transaction,
+        signers: &Vec<&Keypair>,
+    ) -> SDKResult<Vec<String>>;
 }
 
 #[derive(Clone)]
@@ -96,6 +107,54 @@ impl SDKClient {
             .await
     }
 
+    /// Simulates the instructions and returns their logs
+    pub async fn simulate_instructions(
+        &self,
+        instructions: Vec<Instruction>,
+        mut signers: Vec<&Keypair>,
+    ) -> std::result::Result<Vec<String>, SDKError> {
+        signers.insert(0, &self.payer);
+        self.client
+            .simulate_transaction(
+                Transaction::new_with_payer(&instructions, Some(&self.payer.pubkey())),
+                &signers,
+            )
+            .await
+    }
+
+    /// Simulates the instructions and returns the data set by `program_id` with `set_return_data`
+    pub async fn simulate_return_data(
+        &self,
+        instructions: Vec<Instruction>,
+        signers: Vec<&Keypair>,
+        program_id: Pubkey,
+    ) -> std::result::Result<Option<Vec<u8>>, SDKError> {
+        let logs = self.simulate_instructions(instructions, signers).await?;
+        let prefix = format!("Program return: {} ", program_id);
+        logs.iter()
+            .rev()
+            .find_map(|log| log.strip_prefix(&prefix))
+            .map(|data| base64::decode(data).map_err(|e| anyhow::Error::from(e).into()))
+            .transpose()
+    }
+
+    /// Simulates the instructions and returns the compute units consumed by `program_id`
+    pub async fn simulate_compute_units(
+        &self,
+        instructions: Vec<Instruction>,
+        signers: Vec<&Keypair>,
+        program_id: Pubkey,
+    ) -> std::result::Result<u64, SDKError> {
+        let logs = self.simulate_instructions(instructions, signers).await?;
+        let prefix = format!("Program {} consumed ", program_id);
+        logs.iter()
+            .rev()
+            .find_map(|log| log.strip_prefix(&prefix))
+            .and_then(|units| units.split(' ').next())
+            .and_then(|units| units.parse().ok())
+            .ok_or_else(|| anyhow!("No compute units logged by {}", program_id).into())
+    }
+
     pub async fn get_latest_blockhash(&self) -> std::result::Result<Hash, SDKError> {
         self.client.fetch_latest_blockhash().await
     }
@@ -142,6 +201,26 @@ impl ClientSubset for Arc<RpcClient> {
             .map_err(|e| SDKError::Other(anyhow::Error::msg(e.to_string())))
             .and_then(|e| e)
     }
+
+    async fn simulate_transaction(
+        &self,
+        tx: Transaction,
+        signers: &Vec<&Keypair>,
+    ) -> SDKResult<Vec<String>> {
+        let client = self.clone();
+        let signers_owned = signers
+            .into_iter()
+            .map(|&i| KeypairD::from(i).0)
+            .collect_vec();
+
+        tokio::task::spawn_blocking(move || {
+            let signers = signers_owned.iter().collect();
+            ClientSubsetSync::simulate_transaction(&*client, tx, &signers)
+        })
+        .await
+        .map_err(|e| SDKError::Other(anyhow::Error::msg(e.to_string())))
+        .and_then(|e| e)
+    }
 }
 
 impl ClientSubsetSync for RpcClient {
@@ -172,6 +251,19 @@ impl ClientSubsetSync for RpcClient {
             .value
             .ok_or(anyhow!("Failed to get account"))?)
     }
+
+    fn simulate_transaction(
+        &self,
+        mut tx: Transaction,
+        signers: &Vec<&Keypair>,
+    ) -> SDKResult<Vec<String>> {
+        tx.partial_sign(signers, self.get_latest_blockhash()?);
+        let result = RpcClient::simulate_transaction(self, &tx)?.value;
+        if let Some(err) = result.err {
+            return Err(anyhow!("Simulation failed: {}", err).into());
+        }
+        Ok(result.logs.unwrap_or_default())
+    }
 }
 
 #[async_trait]
@@ -200,6 +292,22 @@ impl ClientSubset for RwLock<BanksClient> {
             .await?
             .ok_or(anyhow!("Failed to get account").into())
     }
+
+    async fn simulate_transaction(
+        &self,
+        mut tx: Transaction,
+        signers: &Vec<&Keypair>,
+    ) -> SDKResult<Vec<String>> {
+        tx.partial_sign(signers, self.fetch_latest_blockhash().await?);
+        let simulation = self.write().await.simulate_transaction(tx).await?;
+        if let Some(Err(err)) = simulation.result {
+            return Err(anyhow!("Simulation failed: {}", err).into());
+        }
+        Ok(simulation
+            .simulation_details
+            .map(|details| details.logs)
+            .unwrap_or_default())
+    }
 }
 
 ///////////// Non-interesting impls  ////////////////////
@@ -67,6 +67,8 @@ pub struct SDKCombo {
     pub asks: Pubkey,
     pub market_signer: Pubkey,
     pub event_queue: Pubkey,
+    // outright and ratio of each leg, sorted by outright key
+    pub legs: Vec<(Pubkey, i32)>,
 }
 
 impl Key for SDKProduct {
@@ -1,6 +1,7 @@
 use agnostic_orderbook::state::{SelfTradeBehavior, Side};
 use anchor_lang::Key;
 use anyhow::anyhow;
+use constant_fees::initialize_trader_fee_acct_ix;
 use dex::{
     state::{constants::SENTINEL, enums::OrderType, trader_risk_group::TraderRiskGroup},
     utils::numeric::Fractional,
@@ -16,9 +17,12 @@ use crate::{
     common::{utils::SDKResult, KeypairD},
     processor::{
         cancel_order::cancel_order_ixs,
+        collateral::{deposit_collateral_ixs, withdraw_collateral_ixs},
         consume_orderbook_events::consume_orderbook_events_ixs,
         deposit_funds::{deposit_funds, deposit_funds_ixs},
         new_order::{new_order, new_order_ixs},
+        spot::{deposit_spot_funds_ixs, withdraw_spot_funds_ixs},
+        trader_risk_group::{migrate_trader_risk_group_ix, update_trader_engine_accounts_ix},
         transfer_full_position::transfer_full_position_ixs,
         update_trader_funding::update_trader_funding,
     },
@@ -94,6 +98,183 @@ impl SDKTrader {
             .await
     }
 
+    /// Deposits base tokens of a spot product from `token_account` into its base vault
+    pub async fn deposit_spot(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        token_account: Pubkey,
+        qty: impl Into<Fractional>,
+    ) -> SDKResult {
+        let ixs = deposit_spot_funds_ixs(
+            self.keypair.pubkey(),
+            token_account,
+            self.account,
+            ctx.market_product_group,
+            product.key,
+            qty.into(),
+        );
+        ctx.client
+            .sign_send_instructions(ixs, vec![&self.keypair])
+            .await
+    }
+
+    pub async fn withdraw_spot(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        token_account: Pubkey,
+        qty: impl Into<Fractional>,
+    ) -> SDKResult {
+        let ixs = withdraw_spot_funds_ixs(
+            self.keypair.pubkey(),
+            token_account,
+            self.account,
+            ctx.market_product_group,
+            product.key,
+            ctx.risk_engine_program_id,
+            ctx.risk_model_config_acct,
+            ctx.out_register_risk_info,
+            self.risk_state_account,
+            &[],
+            qty.into(),
+        );
+        ctx.client
+            .sign_send_instructions(ixs, vec![&self.keypair])
+            .await
+    }
+
+    /// Deposits a non-cash collateral of the group from `token_account`
+    pub async fn deposit_collateral(
+        &self,
+        ctx: &SDKContext,
+        mint: Pubkey,
+        token_account: Pubkey,
+        qty: impl Into<Fractional>,
+    ) -> SDKResult {
+        let ixs = deposit_collateral_ixs(
+            self.keypair.pubkey(),
+            token_account,
+            self.account,
+            ctx.market_product_group,
+            mint,
+            qty.into(),
+        );
+        ctx.client
+            .sign_send_instructions(ixs, vec![&self.keypair])
+            .await
+    }
+
+    /// `oracles` are passed to the risk engine to value the trader's remaining collateral
+    pub async fn withdraw_collateral(
+        &self,
+        ctx: &SDKContext,
+        mint: Pubkey,
+        token_account: Pubkey,
+        qty: impl Into<Fractional>,
+        oracles: &[Pubkey],
+    ) -> SDKResult {
+        let ixs = withdraw_collateral_ixs(
+            self.keypair.pubkey(),
+            token_account,
+            self.account,
+            ctx.market_product_group,
+            mint,
+            ctx.risk_engine_program_id,
+            ctx.risk_model_config_acct,
+            ctx.out_register_risk_info,
+            self.risk_state_account,
+            oracles,
+            qty.into(),
+        );
+        ctx.client
+            .sign_send_instructions(ixs, vec![&self.keypair])
+            .await
+    }
+
+    /// Grows a trader risk group created with fewer than `MAX_TRADER_POSITIONS` positions, the
+    /// account can only grow by 10KB per instruction so this sends one transaction per step
+    pub async fn migrate_trader_risk_group(&self, ctx: &SDKContext) -> SDKResult {
+        let new_len = 8 + std::mem::size_of::<TraderRiskGroup>();
+        loop {
+            let account = ctx.client.get_account(self.account).await?;
+            if account.data.len() >= new_len {
+                break;
+            }
+            let ix = migrate_trader_risk_group_ix(
+                self.keypair.pubkey(),
+                ctx.client.payer.pubkey(),
+                self.account,
+            );
+            ctx.client
+                .sign_send_instructions(vec![ix], vec![&self.keypair])
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Moves the trader's risk and fee state accounts to the group's risk engine and fee model
+    /// after either was swapped, creating the new accounts
+    pub async fn update_engine_accounts(&mut self, ctx: &SDKContext) -> SDKResult {
+        let market_product_group = ctx.get_market_product_group().await;
+        let owner_of = |account: std::result::Result<solana_sdk::account::Account, SDKError>| {
+            account.map_or(Pubkey::default(), |a| a.owner)
+        };
+        let risk_state_owner = owner_of(ctx.client.get_account(self.risk_state_account).await);
+        let fee_state_owner = owner_of(ctx.client.get_account(self.fee_acct).await);
+        let new_risk_state_account = KeypairD::new();
+        let mut signers = vec![&*self.keypair];
+        let mut ixs = vec![];
+        let risk_state_account = if risk_state_owner != market_product_group.risk_engine_program_id
+        {
+            signers.push(&new_risk_state_account);
+            new_risk_state_account.pubkey()
+        } else {
+            self.risk_state_account
+        };
+        let (fee_acct, fee_acct_bump) =
+            if fee_state_owner != market_product_group.fee_model_program_id {
+                let (fee_acct, fee_acct_bump) = Pubkey::find_program_address(
+                    &[
+                        b"trader_fee_acct",
+                        &self.account.to_bytes(),
+                        &ctx.market_product_group.to_bytes(),
+                    ],
+                    &market_product_group.fee_model_program_id,
+                );
+                ixs.push(initialize_trader_fee_acct_ix(
+                    market_product_group.fee_model_program_id,
+                    ctx.payer.pubkey(),
+                    market_product_group.fee_model_configuration_acct,
+                    fee_acct,
+                    ctx.market_product_group,
+                    self.account,
+                    solana_program::system_program::id(),
+                ));
+                (fee_acct, fee_acct_bump)
+            } else {
+                (self.fee_acct, self.fee_acct_bump)
+            };
+        let (risk_signer, _) =
+            Pubkey::find_program_address(&[ctx.market_product_group.as_ref()], &ctx.dex_program_id);
+        ixs.push(update_trader_engine_accounts_ix(
+            self.keypair.pubkey(),
+            self.account,
+            ctx.market_product_group,
+            risk_signer,
+            self.risk_state_account,
+            self.fee_acct,
+            risk_state_account,
+            fee_acct,
+            market_product_group.risk_engine_program_id,
+        ));
+        ctx.client.sign_send_instructions(ixs, signers).await?;
+        self.risk_state_account = risk_state_account;
+        self.fee_acct = fee_acct;
+        self.fee_acct_bump = fee_acct_bump;
+        Ok(())
+    }
+
     pub async fn apply_funding(&self, ctx: &SDKContext, market_product_group: Pubkey) -> SDKResult {
         update_trader_funding(&ctx.client, self.account, market_product_group).await
     }
@@ -127,6 +308,25 @@ impl SDKTrader {
         market_product_group: Pubkey,
         liquidatee_risk_group: Pubkey,
         liquidatee_risk_state_account: Pubkey,
+    ) -> SDKResult {
+        self.transfer_position_with_accts(
+            ctx,
+            market_product_group,
+            liquidatee_risk_group,
+            liquidatee_risk_state_account,
+            &[],
+        )
+        .await
+    }
+
+    /// `risk_accounts` are passed to the risk engine, e.g. the oracles of collaterals
+    pub async fn transfer_position_with_accts(
+        &self,
+        ctx: &SDKContext,
+        market_product_group: Pubkey,
+        liquidatee_risk_group: Pubkey,
+        liquidatee_risk_state_account: Pubkey,
+        risk_accounts: &[Pubkey],
     ) -> SDKResult {
         let ixs = transfer_full_position_ixs(
             self.keypair.pubkey(),
@@ -138,6 +338,7 @@ impl SDKTrader {
             self.risk_state_account,
             liquidatee_risk_state_account,
             ctx.risk_model_config_acct,
+            risk_accounts,
         );
         ctx.client
             .sign_send_instructions(ixs, vec![&self.keypair])
@@ -155,7 +356,87 @@ impl SDKTrader {
         risk_accounts: &[Pubkey],
         order_type: OrderType,
     ) -> SDKResult {
-        let ixs = new_order_ixs(
+        self.place_order_with_accts(
+            ctx,
+            product,
+            side,
+            size,
+            price,
+            self_trade_behavior,
+            &[],
+            risk_accounts,
+            order_type,
+        )
+        .await
+    }
+
+    pub async fn place_order_with_accts(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        side: Side,
+        size: impl Into<Fractional>,
+        price: impl Into<Fractional>,
+        self_trade_behavior: SelfTradeBehavior,
+        fee_accounts: &[Pubkey],
+        risk_accounts: &[Pubkey],
+        order_type: OrderType,
+    ) -> SDKResult {
+        let ixs = self.order_ixs(
+            ctx,
+            product,
+            side,
+            size.into(),
+            price.into(),
+            self_trade_behavior,
+            fee_accounts,
+            risk_accounts,
+            order_type,
+        );
+        ctx.client
+            .sign_send_instructions(ixs, vec![&self.keypair])
+            .await
+    }
+
+    /// Compute units the dex consumes to place a limit order, measured by simulating it
+    pub async fn order_compute_units(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        side: Side,
+        size: impl Into<Fractional>,
+        price: impl Into<Fractional>,
+    ) -> SDKResult<u64> {
+        let ixs = self.order_ixs(
+            ctx,
+            product,
+            side,
+            size.into(),
+            price.into(),
+            SelfTradeBehavior::DecrementTake,
+            &[],
+            &[],
+            OrderType::Limit,
+        );
+        ctx.client
+            .simulate_compute_units(ixs, vec![&self.keypair], ctx.dex_program_id)
+            .await
+    }
+
+    /// Instructions sent by `place_order_with_accts`, e.g. to simulate an order
+    pub fn order_ixs(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        side: Side,
+        size: Fractional,
+        price: Fractional,
+        self_trade_behavior: SelfTradeBehavior,
+        fee_accounts: &[Pubkey],
+        risk_accounts: &[Pubkey],
+        order_type: OrderType,
+    ) -> Vec<Instruction> {
+        new_order_ixs(
             ctx.aaob_program_id,
             self.keypair.pubkey(),
             self.account,
@@ -172,19 +453,18 @@ impl SDKTrader {
             ctx.fee_output_register,
             ctx.risk_engine_program_id,
             ctx.risk_model_config_acct,
+            fee_accounts,
+            &[],
             risk_accounts,
             side,
-            size.into(),
+            size,
             order_type,
             self_trade_behavior,
             50,
-            price.into(),
+            price,
             ctx.out_register_risk_info,
             self.risk_state_account,
-        );
-        ctx.client
-            .sign_send_instructions(ixs, vec![&self.keypair])
-            .await
+        )
     }
 
     pub async fn place_order(
@@ -251,6 +531,29 @@ impl SDKTrader {
         .await
     }
 
+    pub async fn place_order_with_fee_accts(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        side: Side,
+        size: impl Into<Fractional>,
+        price: impl Into<Fractional>,
+        fee_accounts: &[Pubkey],
+    ) -> SDKResult {
+        self.place_order_with_accts(
+            ctx,
+            product,
+            side,
+            size,
+            price,
+            SelfTradeBehavior::DecrementTake,
+            fee_accounts,
+            &[],
+            OrderType::Limit,
+        )
+        .await
+    }
+
     pub async fn place_ioc_order_with_risk_accts(
         &self,
         ctx: &SDKContext,
@@ -280,6 +583,48 @@ impl SDKTrader {
         side: Side,
         size: impl Into<Fractional>,
         price: impl Into<Fractional>,
+    ) -> SDKResult {
+        self.place_combo_order_with_leg_accts(ctx, combo, &[], side, size, price)
+            .await
+    }
+
+    /// Places a combo order that first fills against the outright books of its legs whenever
+    /// their implied price crosses the order and beats the combo book
+    pub async fn place_implied_combo_order(
+        &self,
+        ctx: &SDKContext,
+        combo: &SDKCombo,
+        side: Side,
+        size: impl Into<Fractional>,
+        price: impl Into<Fractional>,
+    ) -> SDKResult {
+        let mut leg_accounts = Vec::with_capacity(5 * combo.legs.len());
+        for (product_key, _) in combo.legs.iter() {
+            let leg = ctx
+                .products
+                .iter()
+                .find(|p| p.key == *product_key)
+                .ok_or(SDKError::PublicKeyMismatch)?;
+            leg_accounts.extend([
+                leg.orderbook,
+                leg.market_signer,
+                leg.event_queue,
+                leg.bids,
+                leg.asks,
+            ]);
+        }
+        self.place_combo_order_with_leg_accts(ctx, combo, &leg_accounts, side, size, price)
+            .await
+    }
+
+    async fn place_combo_order_with_leg_accts(
+        &self,
+        ctx: &SDKContext,
+        combo: &SDKCombo,
+        leg_accounts: &[Pubkey],
+        side: Side,
+        size: impl Into<Fractional>,
+        price: impl Into<Fractional>,
     ) -> SDKResult {
         new_order(
             &ctx.client,
@@ -301,6 +646,8 @@ impl SDKTrader {
             ctx.risk_engine_program_id,
             ctx.risk_model_config_acct,
             &[],
+            leg_accounts,
+            &[],
             side,
             size.into(),
             OrderType::Limit,
@@ -339,6 +686,8 @@ impl SDKTrader {
                 ctx.risk_engine_program_id,
                 ctx.risk_model_config_acct,
                 &[],
+                &[],
+                &[],
                 *order.side,
                 order.size,
                 OrderType::Limit,
@@ -359,6 +708,19 @@ impl SDKTrader {
         ctx: &SDKContext,
         product: &SDKProduct,
         other_traders: &[&SDKTrader],
+    ) -> SDKResult {
+        self.crank_with_fee_accts(ctx, product, other_traders, &[])
+            .await
+    }
+
+    /// `maker_fee_groups` pairs each maker's trader risk group with the accounts forwarded to the
+    /// fee model on its fills
+    pub async fn crank_with_fee_accts(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        other_traders: &[&SDKTrader],
+        maker_fee_groups: &[(Pubkey, &[Pubkey])],
     ) -> SDKResult {
         let mut accts = Vec::with_capacity(3 + other_traders.len() * 3);
         accts.extend_from_slice(&[self.account, self.fee_acct, self.risk_state_account]);
@@ -374,6 +736,7 @@ impl SDKTrader {
             product.event_queue,
             &self.keypair,
             accts.as_mut_slice(),
+            maker_fee_groups,
             4,
         )
         .await
@@ -451,6 +814,10 @@ impl SDKTrader {
                     ctx.out_register_risk_info,
                     under_water_trader.risk_state_account,
                     ctx.risk_model_config_acct,
+                    ctx.fee_model_program_id,
+                    ctx.fee_model_config_acct,
+                    under_water_trader.fee_acct,
+                    ctx.on_cancel_hook,
                 ),
                 vec![&self.keypair],
             )
@@ -463,8 +830,20 @@ impl SDKTrader {
         product: &SDKProduct,
         order: u128,
     ) -> SDKResult {
-        let mut ixs: Vec<Instruction> = vec![];
-        ixs.append(&mut cancel_order_ixs(
+        let ixs = self.cancel_order_ixs(ctx, product, order);
+        ctx.client
+            .sign_send_instructions(ixs, vec![&self.keypair])
+            .await
+    }
+
+    /// Instructions sent by `cancel_order`
+    pub fn cancel_order_ixs(
+        &self,
+        ctx: &SDKContext,
+        product: &SDKProduct,
+        order: u128,
+    ) -> Vec<Instruction> {
+        cancel_order_ixs(
             ctx.aaob_program_id,
             self.keypair.pubkey(),
             self.account,
@@ -481,10 +860,11 @@ impl SDKTrader {
             ctx.out_register_risk_info,
             self.risk_state_account,
             ctx.risk_model_config_acct,
-        ));
-        ctx.client
-            .sign_send_instructions(ixs, vec![&self.keypair])
-            .await
+            ctx.fee_model_program_id,
+            ctx.fee_model_config_acct,
+            self.fee_acct,
+            ctx.on_cancel_hook,
+        )
     }
 
     pub async fn cancel_orders(
@@ -512,6 +892,10 @@ impl SDKTrader {
                 ctx.out_register_risk_info,
                 self.risk_state_account,
                 ctx.risk_model_config_acct,
+                ctx.fee_model_program_id,
+                ctx.fee_model_config_acct,
+                self.fee_acct,
+                ctx.on_cancel_hook,
             ));
         }
         ctx.client
@@ -145,6 +145,36 @@ pub enum DexError {
     InvalidOrderID,
     #[error("Invalid bytes for zero-copy deserialization")]
     InvalidBytesForZeroCopyDeserialization,
+    #[error("Discriminant length must be at most 8 bytes")]
+    InvalidDiscriminantLength,
+    #[error("Orderbook and event queue must be empty")]
+    OrderbookNotEmpty,
+    #[error("Order would exceed the product's position limit")]
+    PositionLimitExceeded,
+    #[error("Order would exceed the product's open interest limit")]
+    OpenInterestLimitExceeded,
+    #[error("Limit price is outside of the product's price band")]
+    PriceOutsideBand,
+    #[error("Outright leg of an implied order was not fully filled")]
+    ImpliedOrderNotFilled,
+    #[error("Spot sells and withdrawals must be covered by the trader's base balance")]
+    InsufficientBaseBalance,
+    #[error("Operation is not supported for spot products")]
+    SpotProductNotSupported,
+    #[error("Expected product to be a spot product")]
+    ProductNotSpot,
+    #[error("Mint is not an accepted collateral")]
+    CollateralNotAccepted,
+    #[error("Mint is already an accepted collateral")]
+    DuplicateCollateral,
+    #[error("MarketProductGroup has no free collateral slots")]
+    TooManyCollaterals,
+    #[error("No risk engine or fee model update is pending")]
+    EngineUpdateNotPending,
+    #[error("Pending risk engine or fee model update is still timelocked")]
+    EngineUpdateTimelocked,
+    #[error("Engine update timelock can only be raised")]
+    EngineUpdateTimelockDecreased,
 }
 
 impl From<UtilError> for ProgramError {
@@ -19,8 +19,10 @@ use crate::{
     state::{
         constants::NAME_LEN,
         enums::OrderType,
-        fee_model::TraderFeeParams,
-        market_product_group::MarketProductGroup,
+        fee_model::{OrderEventParams, TraderFeeParams},
+        market_product_group::{
+            FeeBpsBounds, FeeModelConfig, MarketProductGroup, RiskEngineConfig,
+        },
         risk_engine_register::{OperationType, OrderInfo, RiskOutputRegister},
         trader_risk_group::TraderRiskGroup,
     },
@@ -56,10 +58,20 @@ pub mod dex {
         processor::initialize_market_product::process(ctx, params).map_err(log_errors)
     }
 
+    pub fn initialize_spot_product(ctx: Context<InitializeSpotProduct>) -> ProgramResult {
+        processor::initialize_spot_product::process(ctx).map_err(log_errors)
+    }
+
     pub fn remove_market_product(ctx: Context<RemoveMarketProduct>) -> ProgramResult {
         processor::remove_market_product::process(ctx).map_err(log_errors)
     }
 
+    pub fn remove_all_dependent_combos<'info>(
+        ctx: Context<'_, '_, '_, 'info, RemoveAllDependentCombos<'info>>,
+    ) -> ProgramResult {
+        processor::remove_all_dependent_combos::process(ctx).map_err(log_errors)
+    }
+
     pub fn initialize_trader_risk_group<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, InitializeTraderRiskGroup<'info>>,
     ) -> ProgramResult {
@@ -98,6 +110,48 @@ pub mod dex {
         processor::withdraw_funds::process(ctx, params).map_err(log_errors)
     }
 
+    pub fn deposit_spot_funds(
+        ctx: Context<DepositSpotFunds>,
+        params: DepositSpotFundsParams,
+    ) -> ProgramResult {
+        processor::deposit_spot_funds::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn withdraw_spot_funds<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawSpotFunds<'info>>,
+        params: WithdrawSpotFundsParams,
+    ) -> ProgramResult {
+        processor::withdraw_spot_funds::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn add_collateral(
+        ctx: Context<AddCollateral>,
+        params: AddCollateralParams,
+    ) -> ProgramResult {
+        processor::add_collateral::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn update_collateral(
+        ctx: Context<UpdateCollateral>,
+        params: UpdateCollateralParams,
+    ) -> ProgramResult {
+        processor::update_collateral::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn deposit_collateral(
+        ctx: Context<DepositCollateral>,
+        params: DepositCollateralParams,
+    ) -> ProgramResult {
+        processor::deposit_collateral::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn withdraw_collateral<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawCollateral<'info>>,
+        params: WithdrawCollateralParams,
+    ) -> ProgramResult {
+        processor::withdraw_collateral::process(ctx, params).map_err(log_errors)
+    }
+
     pub fn update_product_funding(
         ctx: Context<UpdateProductFunding>,
         params: UpdateProductFundingParams,
@@ -105,6 +159,13 @@ pub mod dex {
         processor::update_product_funding::process(ctx, params).map_err(log_errors)
     }
 
+    pub fn update_index_price(
+        ctx: Context<UpdateIndexPrice>,
+        params: UpdateIndexPriceParams,
+    ) -> ProgramResult {
+        processor::update_index_price::process(ctx, params).map_err(log_errors)
+    }
+
     pub fn transfer_full_position<'info>(
         ctx: Context<'_, '_, '_, 'info, TransferFullPosition<'info>>,
     ) -> ProgramResult {
@@ -141,6 +202,65 @@ pub mod dex {
     pub fn claim_authority(ctx: Context<ClaimAuthority>) -> ProgramResult {
         processor::change_authority::claim_authority(ctx).map_err(log_errors)
     }
+
+    pub fn choose_product_lister(ctx: Context<ChooseProductLister>) -> ProgramResult {
+        processor::change_authority::choose_product_lister(ctx).map_err(log_errors)
+    }
+
+    pub fn update_market_product<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, UpdateMarketProduct<'info>>,
+        params: UpdateMarketProductParams,
+    ) -> ProgramResult {
+        processor::update_market_product::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn update_combo<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, UpdateCombo<'info>>,
+        params: UpdateMarketProductParams,
+    ) -> ProgramResult {
+        processor::update_market_product::process_combo(ctx, params).map_err(log_errors)
+    }
+
+    pub fn update_market_product_group(
+        ctx: Context<UpdateMarketProductGroup>,
+        params: UpdateMarketProductGroupParams,
+    ) -> ProgramResult {
+        processor::update_market_product_group::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn apply_market_product_group_update(
+        ctx: Context<ApplyMarketProductGroupUpdate>,
+    ) -> ProgramResult {
+        processor::update_market_product_group::apply(ctx).map_err(log_errors)
+    }
+
+    pub fn update_trader_engine_accounts<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, UpdateTraderEngineAccounts<'info>>,
+    ) -> ProgramResult {
+        processor::update_trader_engine_accounts::process(ctx).map_err(log_errors)
+    }
+
+    pub fn update_position_limits(
+        ctx: Context<UpdatePositionLimits>,
+        params: UpdatePositionLimitsParams,
+    ) -> ProgramResult {
+        processor::update_position_limits::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn update_price_band(
+        ctx: Context<UpdatePriceBand>,
+        params: UpdatePriceBandParams,
+    ) -> ProgramResult {
+        processor::update_price_band::process(ctx, params).map_err(log_errors)
+    }
+
+    pub fn migrate_market_product_group(ctx: Context<MigrateMarketProductGroup>) -> ProgramResult {
+        processor::migrate_market_product_group::process(ctx).map_err(log_errors)
+    }
+
+    pub fn migrate_trader_risk_group(ctx: Context<MigrateTraderRiskGroup>) -> ProgramResult {
+        processor::migrate_trader_risk_group::process(ctx).map_err(log_errors)
+    }
 }
 
 fn log_errors(e: DomainOrProgramError) -> ProgramError {
@@ -162,6 +282,12 @@ pub struct InitializeMarketProductGroupParams {
     pub min_maker_fee_bps: i16,
     pub max_taker_fee_bps: i16,
     pub min_taker_fee_bps: i16,
+    pub on_post_discriminant_len: u64,
+    pub on_cancel_discriminant_len: u64,
+    pub on_post_discriminant: [u8; 8],
+    pub on_cancel_discriminant: [u8; 8],
+    /// Number of remaining accounts in NewOrder that belong to the fee model
+    pub fee_accounts_len: u64,
 }
 
 #[derive(Accounts)]
@@ -204,6 +330,21 @@ pub struct InitializeMarketProduct<'info> {
     orderbook: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeSpotProduct<'info> {
+    #[account(mut)]
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: AccountInfo<'info>,
+    base_mint: Account<'info, Mint>,
+    #[account(mut)]
+    base_vault: AccountInfo<'info>,
+    sysvar_rent: AccountInfo<'info>,
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+}
+
 #[derive(Accounts)]
 pub struct RemoveMarketProduct<'info> {
     authority: Signer<'info>,
@@ -223,6 +364,16 @@ pub struct RemoveMarketProduct<'info> {
     asks: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct RemoveAllDependentCombos<'info> {
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: AccountInfo<'info>,
+    #[account(executable)]
+    aaob_program: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 pub struct InitializeTraderRiskGroup<'info> {
     #[account(mut)]
@@ -254,6 +405,10 @@ pub struct NewOrderParams {
     pub match_limit: u64,
     /// The order's limit price in ticks
     pub limit_price: Fractional,
+    /// Whether a combo order is first matched against the price implied by its legs' outright
+    /// books. The orderbook, market signer, event queue, bids and asks of each leg then follow
+    /// the fee accounts in the remaining accounts.
+    pub implied_out: bool,
 }
 
 #[derive(Accounts)]
@@ -299,6 +454,9 @@ pub struct NewOrder<'info> {
 pub struct ConsumeOrderbookEventsParams {
     /// The maximum number of events to consume
     pub max_iterations: u64,
+    /// The number of maker fee account groups at the end of the remaining accounts, only used
+    /// when the fee model takes extra accounts
+    pub num_maker_fee_groups: u64,
 }
 
 #[derive(Accounts)]
@@ -321,7 +479,10 @@ pub struct ConsumeOrderbookEvents<'info> {
     #[account(mut)]
     fee_output_register: AccountInfo<'info>,
     risk_and_fee_signer: AccountInfo<'info>,
-    // Remaining accounts are for risk engine
+    // Remaining accounts are the trader and fee accounts, sorted by key.
+    // If the fee model takes extra accounts, they are followed by `num_maker_fee_groups` groups of
+    // the maker's trader risk group and its `fee_accounts_len` fee accounts, which are only
+    // forwarded on that maker's fills.
 }
 #[repr(C)]
 #[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Clone)]
@@ -359,7 +520,8 @@ pub struct CancelOrder<'info> {
     #[account(mut)]
     trader_risk_state_acct: AccountInfo<'info>,
     risk_signer: AccountInfo<'info>,
-    // Remaining accounts are for risk engine
+    // Remaining accounts start with fee_model_program, fee_model_configuration_acct and
+    // trader_fee_state_acct (mut) when the on_cancel hook is enabled, the rest are for risk engine
 }
 
 #[repr(C)]
@@ -409,6 +571,150 @@ pub struct WithdrawFunds<'info> {
     // Remaining accounts are for risk engine
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Clone)]
+pub struct DepositSpotFundsParams {
+    pub quantity: Fractional,
+}
+
+#[derive(Accounts)]
+pub struct DepositSpotFunds<'info> {
+    token_program: Program<'info, Token>,
+    user: Signer<'info>,
+    #[account(mut)]
+    user_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    trader_risk_group: AccountLoader<'info, TraderRiskGroup>,
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: AccountInfo<'info>,
+    #[account(mut)]
+    base_vault: Account<'info, TokenAccount>,
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Clone)]
+pub struct WithdrawSpotFundsParams {
+    pub quantity: Fractional,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawSpotFunds<'info> {
+    token_program: Program<'info, Token>,
+    user: Signer<'info>,
+    #[account(mut)]
+    user_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    trader_risk_group: AccountLoader<'info, TraderRiskGroup>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: AccountInfo<'info>,
+    #[account(mut)]
+    base_vault: Account<'info, TokenAccount>,
+    #[account(executable)]
+    risk_engine_program: AccountInfo<'info>,
+    risk_model_configuration_acct: AccountInfo<'info>,
+    #[account(mut)]
+    risk_output_register: AccountInfo<'info>,
+    #[account(mut)]
+    trader_risk_state_acct: AccountInfo<'info>,
+    risk_signer: AccountInfo<'info>,
+    // Remaining accounts are for risk engine
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone)]
+pub struct AddCollateralParams {
+    pub haircut_bps: u64,
+    // instruments OracleType of the oracle account
+    pub oracle_type: u64,
+    // 0 disables the staleness check
+    pub max_staleness_slots: u64,
+    // 0 uses the instruments' default bound on the confidence interval
+    pub max_confidence_bps: u64,
+}
+
+#[derive(Accounts)]
+pub struct AddCollateral<'info> {
+    #[account(mut)]
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    mint: Account<'info, Mint>,
+    oracle: AccountInfo<'info>,
+    #[account(mut)]
+    collateral_vault: AccountInfo<'info>,
+    sysvar_rent: AccountInfo<'info>,
+    system_program: Program<'info, System>,
+    token_program: Program<'info, Token>,
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone)]
+pub struct UpdateCollateralParams {
+    pub haircut_bps: u64,
+    // instruments OracleType of the oracle account
+    pub oracle_type: u64,
+    // 0 disables the staleness check
+    pub max_staleness_slots: u64,
+    // 0 uses the instruments' default bound on the confidence interval
+    pub max_confidence_bps: u64,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCollateral<'info> {
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    mint: AccountInfo<'info>,
+    oracle: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Clone)]
+pub struct DepositCollateralParams {
+    pub quantity: Fractional,
+}
+
+#[derive(Accounts)]
+pub struct DepositCollateral<'info> {
+    token_program: Program<'info, Token>,
+    user: Signer<'info>,
+    #[account(mut)]
+    user_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    trader_risk_group: AccountLoader<'info, TraderRiskGroup>,
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    #[account(mut)]
+    collateral_vault: Account<'info, TokenAccount>,
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Clone)]
+pub struct WithdrawCollateralParams {
+    pub quantity: Fractional,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCollateral<'info> {
+    token_program: Program<'info, Token>,
+    user: Signer<'info>,
+    #[account(mut)]
+    user_token_account: Account<'info, TokenAccount>,
+    #[account(mut)]
+    trader_risk_group: AccountLoader<'info, TraderRiskGroup>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    #[account(mut)]
+    collateral_vault: Account<'info, TokenAccount>,
+    #[account(executable)]
+    risk_engine_program: AccountInfo<'info>,
+    risk_model_configuration_acct: AccountInfo<'info>,
+    #[account(mut)]
+    risk_output_register: AccountInfo<'info>,
+    #[account(mut)]
+    trader_risk_state_acct: AccountInfo<'info>,
+    risk_signer: AccountInfo<'info>,
+    // Remaining accounts are for risk engine
+}
+
 #[repr(C)]
 #[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Clone)]
 pub struct UpdateProductFundingParams {
@@ -423,6 +729,19 @@ pub struct UpdateProductFunding<'info> {
     product: Signer<'info>,
 }
 
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Clone)]
+pub struct UpdateIndexPriceParams {
+    pub price: Fractional,
+}
+
+#[derive(Accounts)]
+pub struct UpdateIndexPrice<'info> {
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct TransferFullPosition<'info> {
     liquidator: Signer<'info>,
@@ -453,7 +772,7 @@ pub struct InitializeComboParams {
     pub tick_size: Fractional,
     pub price_offset: Fractional,
     pub base_decimals: u64,
-    pub ratios: Vec<i8>,
+    pub ratios: Vec<i32>,
 }
 
 #[derive(Accounts)]
@@ -523,6 +842,173 @@ pub struct ClaimAuthority<'info> {
     new_authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct ChooseProductLister<'info> {
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    authority: Signer<'info>,
+    product_lister: AccountInfo<'info>,
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone)]
+pub struct UpdateMarketProductParams {
+    pub name: [u8; NAME_LEN],
+    pub tick_size: Fractional,
+    // Must be 0 or at least the orderbook's min_base_order_size, which is fixed at listing
+    pub min_base_order_size: u64,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketProduct<'info> {
+    // Pays the cranker rewards of the orders that are reposted on a finer tick size
+    #[account(mut)]
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: AccountInfo<'info>,
+    #[account(executable)]
+    aaob_program: AccountInfo<'info>,
+    #[account(mut)]
+    orderbook: AccountInfo<'info>,
+    market_signer: AccountInfo<'info>,
+    #[account(mut)]
+    event_queue: AccountInfo<'info>,
+    #[account(mut)]
+    bids: AccountInfo<'info>,
+    #[account(mut)]
+    asks: AccountInfo<'info>,
+    system_program: Program<'info, System>,
+    // Remaining accounts are the trader risk groups with resting orders, only needed when a
+    // finer tick size is set while orders rest in the book
+}
+
+/// Fields left as None are unchanged
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone, Default)]
+pub struct UpdateMarketProductGroupParams {
+    pub fee_collector: Option<Pubkey>,
+    /// Delayed by engine_update_timelock, also the way to change the discriminants
+    pub risk_engine: Option<RiskEngineConfig>,
+    pub fee_model: Option<FeeModelConfig>,
+    pub ewma_windows: Option<[u64; 4]>,
+    pub fee_bps_bounds: Option<FeeBpsBounds>,
+    /// Seconds that risk engine and fee model swaps are delayed by, can only be raised
+    pub engine_update_timelock: Option<u64>,
+    /// Drops the scheduled swaps before applying the rest of the update
+    pub cancel_pending_updates: bool,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarketProductGroup<'info> {
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+}
+
+#[derive(Accounts)]
+pub struct ApplyMarketProductGroupUpdate<'info> {
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateTraderEngineAccounts<'info> {
+    #[account(mut)]
+    owner: Signer<'info>,
+    #[account(mut)]
+    trader_risk_group: AccountLoader<'info, TraderRiskGroup>,
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    risk_signer: AccountInfo<'info>,
+    old_trader_risk_state_acct: AccountInfo<'info>,
+    old_trader_fee_state_acct: AccountInfo<'info>,
+    // only replaces the old account if that is owned by a swapped out program
+    #[account(mut)]
+    trader_risk_state_acct: AccountInfo<'info>,
+    trader_fee_state_acct: AccountInfo<'info>,
+    risk_engine_program: AccountInfo<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCombo<'info> {
+    // Pays the cranker rewards of the orders that are reposted on a finer tick size
+    #[account(mut)]
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: AccountInfo<'info>,
+    #[account(executable)]
+    aaob_program: AccountInfo<'info>,
+    #[account(mut)]
+    orderbook: AccountInfo<'info>,
+    market_signer: AccountInfo<'info>,
+    #[account(mut)]
+    event_queue: AccountInfo<'info>,
+    #[account(mut)]
+    bids: AccountInfo<'info>,
+    #[account(mut)]
+    asks: AccountInfo<'info>,
+    system_program: Program<'info, System>,
+    // Remaining accounts are the trader risk groups with resting orders, only needed when a
+    // finer tick size is set while orders rest in the book
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone)]
+pub struct UpdatePositionLimitsParams {
+    // 0 removes the limit
+    pub max_open_interest: Fractional,
+    pub max_position: Fractional,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePositionLimits<'info> {
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: AccountInfo<'info>,
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone)]
+pub struct UpdatePriceBandParams {
+    // half width in bps of the reference price, takes precedence over width
+    pub bps: u64,
+    // half width as an absolute price, the band is removed if both are 0
+    pub width: Fractional,
+    pub ewma_window: u64,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePriceBand<'info> {
+    authority: Signer<'info>,
+    #[account(mut)]
+    market_product_group: AccountLoader<'info, MarketProductGroup>,
+    product: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateMarketProductGroup<'info> {
+    authority: Signer<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    // not an AccountLoader, the legacy layout can't be loaded
+    #[account(mut, owner = crate::ID)]
+    market_product_group: AccountInfo<'info>,
+    system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateTraderRiskGroup<'info> {
+    owner: Signer<'info>,
+    #[account(mut)]
+    payer: Signer<'info>,
+    // not an AccountLoader, trader risk groups with fewer positions can't be loaded
+    #[account(mut, owner = crate::ID)]
+    trader_risk_group: AccountInfo<'info>,
+    system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateHealthState<'info> {
     authority: Signer<'info>,
@@ -578,10 +1064,11 @@ pub fn find_fees_ix(
     fee_model_configuration: Pubkey,
     fee_output_register: Pubkey,
     fee_signer: Pubkey,
+    fee_model_accounts: Vec<Pubkey>,
     fee_params: &TraderFeeParams,
     mut discriminant: Vec<u8>,
 ) -> std::result::Result<Instruction, DomainOrProgramError> {
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new_readonly(market_product_group, false),
         AccountMeta::new_readonly(trader_risk_group, false),
         AccountMeta::new(trader_fee_state_acct, false),
@@ -589,6 +1076,9 @@ pub fn find_fees_ix(
         AccountMeta::new(fee_output_register, false),
         AccountMeta::new_readonly(fee_signer, true),
     ];
+    for key in fee_model_accounts.into_iter() {
+        accounts.push(AccountMeta::new_readonly(key, false));
+    }
     BorshSerialize::serialize(fee_params, &mut discriminant)
         .map_err(|_| UtilError::SerializeError)?;
     Ok(Instruction {
@@ -598,6 +1088,31 @@ pub fn find_fees_ix(
     })
 }
 
+pub fn order_event_ix(
+    program_id: Pubkey,
+    market_product_group: Pubkey,
+    trader_risk_group: Pubkey,
+    trader_fee_state_acct: Pubkey,
+    fee_model_configuration: Pubkey,
+    fee_signer: Pubkey,
+    params: &OrderEventParams,
+    mut discriminant: Vec<u8>,
+) -> std::result::Result<Instruction, DomainOrProgramError> {
+    let accounts = vec![
+        AccountMeta::new_readonly(market_product_group, false),
+        AccountMeta::new_readonly(trader_risk_group, false),
+        AccountMeta::new(trader_fee_state_acct, false),
+        AccountMeta::new_readonly(fee_model_configuration, false),
+        AccountMeta::new_readonly(fee_signer, true),
+    ];
+    BorshSerialize::serialize(params, &mut discriminant).map_err(|_| UtilError::SerializeError)?;
+    Ok(Instruction {
+        program_id,
+        accounts,
+        data: discriminant,
+    })
+}
+
 pub fn create_trader_risk_state_acct_ix(
     program_id: Pubkey,
     authority: Pubkey,
@@ -0,0 +1,124 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        msg,
+        program::invoke_signed,
+        program_pack::{IsInitialized, Pack},
+        pubkey::Pubkey,
+        system_instruction,
+        sysvar::{rent::Rent, Sysvar},
+    },
+};
+
+use crate::{
+    error::{DexError, DomainOrProgramResult, UtilError},
+    state::market_product_group::Collateral,
+    utils::validation::{assert, assert_keys_equal, assert_with_msg, get_rent},
+    AddCollateral, AddCollateralParams, DomainOrProgramError,
+};
+
+const TOKEN_ACCOUNT_SIZE: u64 = spl_token::state::Account::LEN as u64;
+
+fn validate(
+    ctx: &Context<AddCollateral>,
+    params: &AddCollateralParams,
+) -> std::result::Result<usize, DomainOrProgramError> {
+    let accts = &ctx.accounts;
+    let market_product_group = accts.market_product_group.load()?;
+    if !market_product_group.is_initialized() {
+        msg!("MarketProductGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+    assert_with_msg(
+        params.haircut_bps <= 10_000,
+        ProgramError::InvalidArgument,
+        "Haircut can be at most 10000 bps",
+    )?;
+    assert_with_msg(
+        params.oracle_type != 0,
+        ProgramError::InvalidArgument,
+        "Collateral oracle type must be set",
+    )?;
+    assert(
+        accts.mint.key() != market_product_group.vault_mint
+            && market_product_group
+                .find_collateral(&accts.mint.key())
+                .is_err(),
+        DexError::DuplicateCollateral,
+    )?;
+    let index = market_product_group
+        .collaterals
+        .iter()
+        .position(|c| !c.is_enabled())
+        .ok_or(DexError::TooManyCollaterals)?;
+    Ok(index)
+}
+
+pub fn process(ctx: Context<AddCollateral>, params: AddCollateralParams) -> DomainOrProgramResult {
+    let index = validate(&ctx, &params)?;
+    let accts = ctx.accounts;
+
+    let vault_seeds_without_bump: &[&[u8]] = &[
+        b"collateral_vault",
+        &accts.market_product_group.key().to_bytes(),
+        &accts.mint.key().to_bytes(),
+    ];
+    let (vault_key, vault_bump) =
+        Pubkey::find_program_address(vault_seeds_without_bump, ctx.program_id);
+    let vault_seeds = &[
+        vault_seeds_without_bump[0],
+        vault_seeds_without_bump[1],
+        vault_seeds_without_bump[2],
+        &[vault_bump],
+    ];
+    assert_keys_equal(vault_key, *accts.collateral_vault.key)?;
+    msg!("Creating the collateral vault");
+    invoke_signed(
+        &system_instruction::create_account(
+            accts.authority.key,
+            accts.collateral_vault.key,
+            get_rent(&Rent::get()?, TOKEN_ACCOUNT_SIZE, &accts.collateral_vault),
+            TOKEN_ACCOUNT_SIZE,
+            accts.token_program.key,
+        ),
+        &[
+            accts.authority.to_account_info(),
+            accts.collateral_vault.clone(),
+            accts.system_program.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+
+    msg!("Initializing the collateral vault");
+    invoke_signed(
+        &spl_token::instruction::initialize_account2(
+            accts.token_program.key,
+            accts.collateral_vault.key,
+            &accts.mint.key(),
+            accts.collateral_vault.key,
+        )?,
+        &[
+            accts.collateral_vault.clone(),
+            accts.mint.to_account_info(),
+            accts.sysvar_rent.clone(),
+        ],
+        &[vault_seeds],
+    )?;
+
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    market_product_group.collaterals[index] = Collateral {
+        mint: accts.mint.key(),
+        oracle: accts.oracle.key(),
+        decimals: accts.mint.decimals as u64,
+        vault_bump: vault_bump as u64,
+        haircut_bps: params.haircut_bps,
+        oracle_type: params.oracle_type,
+        max_staleness_slots: params.max_staleness_slots,
+        max_confidence_bps: params.max_confidence_bps,
+    };
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    accts.market_product_group.key().log();
+    Ok(())
+}
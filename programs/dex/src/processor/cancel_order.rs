@@ -21,9 +21,12 @@ use agnostic_orderbook::{
 
 use crate::{
     error::{DexError, DomainOrProgramResult, UtilError},
-    state::risk_engine_register::*,
+    state::{
+        fee_model::OrderEventParams, market_product_group::MarketProductGroup,
+        risk_engine_register::*, trader_risk_group::TraderRiskGroup,
+    },
     utils::{
-        cpi::risk_check,
+        cpi::{notify_fee_model, risk_check},
         loadable::Loadable,
         numeric::{Fractional, ZERO_FRAC},
         orderbook::{get_bbo, update_prices},
@@ -74,6 +77,35 @@ fn validate(accts: &CancelOrder) -> DomainOrProgramResult {
     Ok(())
 }
 
+/// Fee model accounts that lead the remaining accounts when the on_cancel hook is enabled
+pub const ON_CANCEL_ACCOUNTS_LEN: usize = 3;
+
+/// Splits the remaining accounts into the on_cancel hook's accounts and the risk engine's
+fn split_remaining_accounts<'c, 'info>(
+    market_product_group: &MarketProductGroup,
+    trader_risk_group: &TraderRiskGroup,
+    remaining_accounts: &'c [AccountInfo<'info>],
+) -> DomainOrProgramResult<(&'c [AccountInfo<'info>], &'c [AccountInfo<'info>])> {
+    if market_product_group.on_cancel_discriminant_len == 0 {
+        return Ok((&[], remaining_accounts));
+    }
+    assert(
+        remaining_accounts.len() >= ON_CANCEL_ACCOUNTS_LEN,
+        DexError::MissingUserAccount,
+    )?;
+    let (fee_accounts, risk_accounts) = remaining_accounts.split_at(ON_CANCEL_ACCOUNTS_LEN);
+    assert_keys_equal(
+        fee_accounts[0].key(),
+        market_product_group.fee_model_program_id,
+    )?;
+    assert_keys_equal(
+        fee_accounts[1].key(),
+        market_product_group.fee_model_configuration_acct,
+    )?;
+    assert_keys_equal(fee_accounts[2].key(), trader_risk_group.fee_state_account)?;
+    Ok((fee_accounts, risk_accounts))
+}
+
 pub fn process<'info>(
     ctx: Context<'_, '_, '_, 'info, CancelOrder<'info>>,
     params: CancelOrderParams,
@@ -84,6 +116,11 @@ pub fn process<'info>(
     let CancelOrderParams { order_id } = params;
     let mut trader_risk_group = accts.trader_risk_group.load_mut()?;
     let mut market_product_group = accts.market_product_group.load_mut()?;
+    let (fee_accounts, risk_accounts) = split_remaining_accounts(
+        &market_product_group,
+        &trader_risk_group,
+        ctx.remaining_accounts,
+    )?;
     let voluntary_cancel = trader_risk_group.owner == *accts.user.key;
     if !voluntary_cancel {
         // Apply all unsettled funding prior to calling the risk engine
@@ -98,7 +135,7 @@ pub fn process<'info>(
             &accts.trader_risk_state_acct,
             &accts.risk_model_configuration_acct,
             &accts.risk_signer,
-            ctx.remaining_accounts,
+            risk_accounts,
             &OrderInfo {
                 operation_type: OperationType::CheckHealth,
                 ..Default::default()
@@ -190,6 +227,24 @@ pub fn process<'info>(
     let side = agnostic_orderbook::state::get_side_from_order_id(order_id);
     let order_qty = process_from_aob(order_summary.total_base_qty, product.base_decimals).abs();
     trader_risk_group.decrement_book_size(product_index, side, order_qty)?;
+    if let Some(discriminant) = market_product_group.get_on_cancel_discriminant() {
+        notify_fee_model(
+            &fee_accounts[0],
+            accts.market_product_group.as_ref(),
+            &accts.trader_risk_group,
+            &fee_accounts[2],
+            &fee_accounts[1],
+            &accts.risk_signer,
+            discriminant,
+            &OrderEventParams {
+                side,
+                order_id,
+                base_qty: order_qty,
+                product: accts.product.key(),
+            },
+            market_product_group.risk_and_fee_bump as u8,
+        )?;
+    }
     market_product_group.sequence_number += 1;
     msg!("sequence: {}", market_product_group.sequence_number);
     accts.market_product_group.key().log();
@@ -1,6 +1,6 @@
 use crate::{
-    error::DomainOrProgramResult, utils::validation::assert_keys_equal, ChooseSuccessor,
-    ClaimAuthority,
+    error::DomainOrProgramResult, utils::validation::assert_keys_equal, ChooseProductLister,
+    ChooseSuccessor, ClaimAuthority,
 };
 use anchor_lang::prelude::*;
 
@@ -25,3 +25,15 @@ pub fn claim_authority(ctx: Context<ClaimAuthority>) -> DomainOrProgramResult {
     accts.market_product_group.key().log();
     Ok(())
 }
+
+pub fn choose_product_lister(ctx: Context<ChooseProductLister>) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    assert_keys_equal(market_product_group.authority, *accts.authority.key)?;
+    // The system program (default pubkey) disables the product lister
+    market_product_group.product_lister = *accts.product_lister.key;
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    accts.market_product_group.key().log();
+    Ok(())
+}
@@ -77,7 +77,10 @@ pub fn process<'a, 'b, 'c, 'info>(
         accts.market_product_group.load_mut()?,
     );
 
-    let ConsumeOrderbookEventsParams { max_iterations } = params;
+    let ConsumeOrderbookEventsParams {
+        max_iterations,
+        num_maker_fee_groups,
+    } = params;
 
     let (product_index, product) = market_product_group.find_product_index(&accts.product.key())?;
     let product = *product;
@@ -95,11 +98,18 @@ pub fn process<'a, 'b, 'c, 'info>(
         CALLBACK_INFO_LEN as usize,
     );
 
+    let (user_accounts, maker_fee_groups) = split_maker_fee_groups(
+        ctx.remaining_accounts,
+        market_product_group.fee_accounts_len as usize,
+        num_maker_fee_groups as usize,
+    )?;
+
     let clock = &Clock::get()?;
     let mut total_iterations = 0;
     for event in event_queue.iter().take(max_iterations as usize) {
         let consume_event_result = consume_event(
-            ctx.remaining_accounts,
+            user_accounts,
+            maker_fee_groups,
             &mut market_product_group,
             product_index,
             event,
@@ -181,6 +191,7 @@ pub fn process_out_from_event_queue(base_size_eq: u64, base_decimals: u64) -> Fr
 
 fn consume_event<'c, 'info>(
     accounts: &'c [AccountInfo<'info>],
+    maker_fee_groups: &'c [AccountInfo<'info>],
     market_product_group: &mut WithAcct<'_, 'info, RefMut<'_, MarketProductGroup>>,
     product_index: usize,
     event: Event,
@@ -233,6 +244,11 @@ fn consume_event<'c, 'info>(
                         &fee_model_configuration,
                         &fee_output_register,
                         &fee_and_risk_signer,
+                        find_maker_fee_accounts(
+                            maker_fee_groups,
+                            market_product_group.fee_accounts_len as usize,
+                            &maker.risk_group.key(),
+                        )?,
                         market_product_group.get_find_fees_discriminant(),
                         &fee_params,
                         market_product_group.risk_and_fee_bump as u8,
@@ -380,13 +396,29 @@ fn update_positions_no_self_trade<'info>(
     }
     seller.position = seller.position.checked_sub(base_size.checked_mul(ratio)?)?;
     buyer.position = buyer.position.checked_add(base_size.checked_mul(ratio)?)?;
+    // The fill was already matched so failing here would only block the event queue. new_order
+    // bounds the taker when matching and reserved room for the maker's resting order when it
+    // was posted, so this only happens when the limits were lowered in between
+    if market_product.check_open_interest_limit(ZERO_FRAC).is_err()
+        || market_product
+            .check_position_limit(ZERO_FRAC, buyer.position)
+            .is_err()
+        || market_product
+            .check_position_limit(ZERO_FRAC, seller.position)
+            .is_err()
+    {
+        msg!(
+            "Fill exceeds the limits of product {}",
+            market_product.product_key
+        );
+    }
     Ok(())
 }
 
 /// update_cash_balance:
 /// 1. moves cash between maker and taker
 /// 2. pays maker fees calculated in this Ix
-/// 3. settles previously calculated taker fees
+/// 3. settles the fill's share of previously calculated taker fees
 fn update_cash_balance(
     market_product_group: &mut MarketProductGroup,
     maker: &mut MakerInfo,
@@ -404,38 +436,49 @@ fn update_cash_balance(
         seller.cash_balance = seller.cash_balance.checked_add(quote_size)?;
         buyer.cash_balance = buyer.cash_balance.checked_sub(quote_size)?;
     }
-    // mutate taker
-    let taker_pending_fees = {
+    let mut taker_fee = {
         let mut taker = taker.load_mut()?;
         taker.pending_cash_balance = taker.pending_cash_balance.checked_add(match taker_side {
             Side::Bid => quote_size,
             Side::Ask => -quote_size,
         })?;
-        taker.cash_balance = taker.cash_balance.checked_sub(taker.pending_fees)?;
-        taker.pending_fees
+        taker.take_pending_fill_fees(quote_size.abs())?
     };
-    // mutate maker
-    {
-        let mut maker_risk_group = maker.risk_group.load_mut()?;
-        let maker_fee = TraderFees::new(
+    let mut maker_fee = {
+        let maker_risk_group = maker.risk_group.load()?;
+        TraderFees::new(
             maker_risk_group.maker_fee_bps,
             maker_risk_group.taker_fee_bps,
             maker_risk_group.valid_until,
         )
         .maker_fee_bps(Some(market_product_group))
-        .checked_mul(quote_size)?;
-        maker_risk_group.cash_balance = maker_risk_group.cash_balance.checked_sub(maker_fee)?;
-
-        market_product_group.collected_fees = market_product_group
-            .collected_fees
-            .checked_add(maker_fee)?
-            .checked_add(taker_pending_fees)?;
+        .checked_mul(quote_size)?
+    };
+    // The rebate on a fill can never exceed the fee charged to the other side of it
+    if taker_fee < ZERO_FRAC {
+        taker_fee = taker_fee.max(-maker_fee.max(ZERO_FRAC));
+    }
+    if maker_fee < ZERO_FRAC {
+        maker_fee = maker_fee.max(-taker_fee.max(ZERO_FRAC));
+    }
+    for fee in [maker_fee, taker_fee] {
+        if fee < ZERO_FRAC {
+            market_product_group.rebates_paid =
+                market_product_group.rebates_paid.checked_sub(fee)?;
+        }
     }
-    // mutate taker
     {
         let mut taker = taker.load_mut()?;
-        taker.pending_fees = ZERO_FRAC;
+        taker.cash_balance = taker.cash_balance.checked_sub(taker_fee)?;
     }
+    {
+        let mut maker_risk_group = maker.risk_group.load_mut()?;
+        maker_risk_group.cash_balance = maker_risk_group.cash_balance.checked_sub(maker_fee)?;
+    }
+    market_product_group.collected_fees = market_product_group
+        .collected_fees
+        .checked_add(maker_fee)?
+        .checked_add(taker_fee)?;
     Ok(())
 }
 
@@ -480,6 +523,45 @@ fn find_participants<'c, 'info>(
     ))
 }
 
+/// Splits the remaining accounts into the sorted trader accounts and the trailing maker fee
+/// account groups
+fn split_maker_fee_groups<'c, 'info>(
+    accounts: &'c [AccountInfo<'info>],
+    fee_accounts_len: usize,
+    num_maker_fee_groups: usize,
+) -> std::result::Result<(&'c [AccountInfo<'info>], &'c [AccountInfo<'info>]), DexError> {
+    if fee_accounts_len == 0 {
+        return Ok((accounts, &accounts[..0]));
+    }
+    let groups_len = num_maker_fee_groups.saturating_mul(fee_accounts_len + 1);
+    if accounts.len() < groups_len {
+        msg!("Expected {} maker fee accounts", groups_len);
+        return Err(DexError::MissingUserAccount);
+    }
+    Ok(accounts.split_at(accounts.len() - groups_len))
+}
+
+/// Returns the fee accounts of the group led by the maker's trader risk group
+fn find_maker_fee_accounts<'c, 'info>(
+    maker_fee_groups: &'c [AccountInfo<'info>],
+    fee_accounts_len: usize,
+    maker_key: &Pubkey,
+) -> std::result::Result<&'c [AccountInfo<'info>], DexError> {
+    if fee_accounts_len == 0 {
+        return Ok(&maker_fee_groups[..0]);
+    }
+    match maker_fee_groups
+        .chunks_exact(fee_accounts_len + 1)
+        .find(|group| group[0].key == maker_key)
+    {
+        Some(group) => Ok(&group[1..]),
+        None => {
+            msg!("Could not find the fee accounts of maker {:?}", maker_key);
+            Err(DexError::MissingUserAccount)
+        }
+    }
+}
+
 fn find_acct<'c, 'info>(
     accounts: &'c [AccountInfo<'info>],
     key: &Pubkey,
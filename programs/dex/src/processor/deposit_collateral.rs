@@ -0,0 +1,78 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::invoke_signed, program_error::ProgramError, program_pack::IsInitialized,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    error::{DexError, DomainOrProgramResult, UtilError},
+    utils::{
+        numeric::ZERO_FRAC,
+        validation::{assert, assert_keys_equal},
+    },
+    DepositCollateral, DepositCollateralParams, DomainOrProgramError,
+};
+
+fn validate(accts: &DepositCollateral) -> std::result::Result<usize, DomainOrProgramError> {
+    let trader_risk_group = accts.trader_risk_group.load()?;
+    let market_product_group = accts.market_product_group.load()?;
+    assert_keys_equal(accts.token_program.key(), spl_token::ID)?;
+    assert(
+        trader_risk_group.is_initialized(),
+        UtilError::AccountUninitialized,
+    )?;
+    assert_keys_equal(trader_risk_group.owner, accts.user.key())?;
+    assert_keys_equal(
+        trader_risk_group.market_product_group,
+        accts.market_product_group.key(),
+    )?;
+    market_product_group.find_collateral(&accts.user_token_account.mint)
+}
+
+pub fn process(
+    ctx: Context<DepositCollateral>,
+    params: DepositCollateralParams,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let index = validate(accts)?;
+    let DepositCollateralParams { quantity } = params;
+    let mut trader_risk_group = accts.trader_risk_group.load_mut()?;
+    let market_product_group = accts.market_product_group.load()?;
+    let collateral = market_product_group.collaterals[index];
+    let vault_seeds = &[
+        b"collateral_vault",
+        accts.market_product_group.as_ref().key.as_ref(),
+        collateral.mint.as_ref(),
+        &[collateral.vault_bump as u8],
+    ];
+    let vault_key =
+        Pubkey::create_program_address(vault_seeds, ctx.program_id).map_err(ProgramError::from)?;
+    assert_keys_equal(vault_key, accts.collateral_vault.key())?;
+
+    let token_quantity = quantity.round(collateral.decimals as u32)?;
+    assert(token_quantity > ZERO_FRAC, DexError::FundsError)?;
+
+    let token_transfer_instruction = spl_token::instruction::transfer(
+        accts.token_program.key,
+        &accts.user_token_account.key(),
+        &accts.collateral_vault.key(),
+        accts.user.key,
+        &[],
+        token_quantity.m as u64,
+    )?;
+    invoke_signed(
+        &token_transfer_instruction,
+        &[
+            accts.token_program.to_account_info(),
+            accts.user_token_account.to_account_info(),
+            accts.collateral_vault.to_account_info(),
+            accts.user.to_account_info(),
+        ],
+        &[vault_seeds],
+    )?;
+    trader_risk_group.collateral_balances[index] =
+        trader_risk_group.collateral_balances[index].checked_add(quantity)?;
+    Ok(())
+}
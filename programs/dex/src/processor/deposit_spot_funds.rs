@@ -0,0 +1,94 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        program::invoke_signed, program_error::ProgramError, program_pack::IsInitialized,
+        pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    error::{DexError, DomainOrProgramResult, UtilError},
+    utils::{
+        numeric::ZERO_FRAC,
+        validation::{assert, assert_keys_equal},
+    },
+    DepositSpotFunds, DepositSpotFundsParams, DomainOrProgramError,
+};
+
+fn validate(accts: &DepositSpotFunds) -> std::result::Result<usize, DomainOrProgramError> {
+    let trader_risk_group = accts.trader_risk_group.load()?;
+    let market_product_group = accts.market_product_group.load()?;
+    assert_keys_equal(accts.token_program.key(), spl_token::ID)?;
+    assert(
+        trader_risk_group.is_initialized(),
+        UtilError::AccountUninitialized,
+    )?;
+    assert_keys_equal(trader_risk_group.owner, accts.user.key())?;
+    assert_keys_equal(
+        trader_risk_group.market_product_group,
+        accts.market_product_group.key(),
+    )?;
+    let (product_index, _) = market_product_group.find_outright(&accts.product.key())?;
+    assert(
+        market_product_group.is_spot(product_index),
+        DexError::ProductNotSpot,
+    )?;
+    Ok(product_index)
+}
+
+pub fn process(
+    ctx: Context<DepositSpotFunds>,
+    params: DepositSpotFundsParams,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let product_index = validate(accts)?;
+    let DepositSpotFundsParams { quantity } = params;
+    let mut trader_risk_group = accts.trader_risk_group.load_mut()?;
+    let market_product_group = accts.market_product_group.load()?;
+    let spot_market = market_product_group.spot_markets[product_index];
+    let outright = market_product_group.market_products[product_index].try_to_outright()?;
+    let base_vault_seeds = &[
+        b"base_vault",
+        accts.market_product_group.as_ref().key.as_ref(),
+        accts.product.key.as_ref(),
+        &[spot_market.base_vault_bump as u8],
+    ];
+    let base_vault_key = Pubkey::create_program_address(base_vault_seeds, ctx.program_id)
+        .map_err(ProgramError::from)?;
+    assert_keys_equal(base_vault_key, accts.base_vault.key())?;
+
+    let quantity = quantity.round(outright.base_decimals as u32)?;
+    assert(quantity > ZERO_FRAC, DexError::FundsError)?;
+    let token_quantity = quantity.round(spot_market.base_decimals as u32)?;
+
+    let token_transfer_instruction = spl_token::instruction::transfer(
+        accts.token_program.key,
+        &accts.user_token_account.key(),
+        &accts.base_vault.key(),
+        accts.user.key,
+        &[],
+        token_quantity.m as u64,
+    )?;
+    invoke_signed(
+        &token_transfer_instruction,
+        &[
+            accts.token_program.to_account_info(),
+            accts.user_token_account.to_account_info(),
+            accts.base_vault.to_account_info(),
+            accts.user.to_account_info(),
+        ],
+        &[base_vault_seeds],
+    )?;
+
+    trader_risk_group.activate_if_uninitialized(
+        product_index,
+        &accts.product.key(),
+        outright.cum_funding_per_share,
+        outright.cum_social_loss_per_share,
+        market_product_group.active_combos(),
+    )?;
+    let position_index = trader_risk_group.active_products[product_index] as usize;
+    let trader_position = &mut trader_risk_group.trader_positions[position_index];
+    trader_position.position = trader_position.position.checked_add(quantity)?;
+    Ok(())
+}
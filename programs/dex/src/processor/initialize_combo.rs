@@ -12,9 +12,9 @@ use anchor_lang::{
 use num::Integer;
 
 use crate::{
-    error::{DomainOrProgramResult, UtilError},
+    error::{DexError, DomainOrProgramResult, UtilError},
     state::{
-        constants::MAX_LEGS,
+        constants::{MAX_LEGS, MAX_LEG_RATIO},
         market_product_group::*,
         products::{Combo, Leg, Product, ProductMetadata},
     },
@@ -61,21 +61,29 @@ pub fn process(
 
     let mut market_product_group = accts.market_product_group.load_mut()?;
     let mut legs = [Leg::default(); MAX_LEGS];
-    let mut seeds = Vec::with_capacity(params.ratios.len() * 34);
     for (i, (ratio, product)) in params.ratios.iter().zip(ctx.remaining_accounts).enumerate() {
         let (product_index, _) = market_product_group.find_outright(product.key)?;
+        assert(
+            !market_product_group.is_spot(product_index),
+            DexError::SpotProductNotSupported,
+        )?;
         legs[i] = Leg {
             product_index,
             product_key: *product.key,
             ratio: *ratio as i64,
         };
-        seeds.extend(product.key.to_bytes().iter());
     }
-
-    for ratio in params.ratios.iter() {
-        seeds.extend(ratio.to_le_bytes().iter());
-    }
-    // Format of the seeds is [product_key_1, ..., product_key_N, [ratio_1, ..., ratio_N]]
+    let num_legs = params.ratios.len();
+    let seeds = Combo::get_seeds(
+        &legs[..num_legs]
+            .iter()
+            .map(|leg| leg.product_key)
+            .collect::<Vec<_>>(),
+        &legs[..num_legs]
+            .iter()
+            .map(|leg| leg.ratio)
+            .collect::<Vec<_>>(),
+    );
     let (product_key, _) =
         Pubkey::find_program_address(&seeds.chunks(32).collect::<Vec<&[u8]>>(), ctx.program_id);
     let (market_authority_key, bump) =
@@ -95,7 +103,7 @@ pub fn process(
                 contract_volume: ZERO_FRAC,
                 prices: PriceEwma::default(),
             },
-            num_legs: params.ratios.len(),
+            num_legs,
             legs,
         },
     };
@@ -107,19 +115,27 @@ pub fn process(
     Ok(())
 }
 
-fn assert_valid_ratios(ratios: &Vec<i8>) -> ProgramResult {
+fn assert_valid_ratios(ratios: &[i32]) -> ProgramResult {
     if ratios.len() < 2 {
         msg!("Combo must have at least 2 legs");
         return Err(ProgramError::InvalidAccountData);
     }
-    let mut gcd: i8 = -1;
-    for item in ratios.iter() {
-        if gcd != -1 {
-            gcd = item.gcd(&gcd);
-        } else {
-            gcd = *item;
-        }
+    if ratios.len() > MAX_LEGS {
+        msg!("Combo can have at most {} legs", MAX_LEGS);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // Checked before the gcd, which overflows on i32::MIN
+    if ratios
+        .iter()
+        .any(|ratio| *ratio == 0 || ratio.unsigned_abs() > MAX_LEG_RATIO as u32)
+    {
+        msg!(
+            "Leg ratios must be non-zero and at most {} in absolute value",
+            MAX_LEG_RATIO
+        );
+        return Err(ProgramError::InvalidAccountData);
     }
+    let gcd = ratios.iter().fold(0, |gcd, item| item.gcd(&gcd));
     if gcd != 1 {
         msg!("Leg ratios have not been fully reduced");
         return Err(ProgramError::InvalidAccountData);
@@ -52,7 +52,11 @@ fn validate(
         msg!("MarketProductGroup account is not initialized");
         return Err(UtilError::AccountUninitialized.into());
     }
-    assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+    if market_product_group.product_lister == Pubkey::default()
+        || accts.authority.key() != market_product_group.product_lister
+    {
+        assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+    }
     match market_product_group.find_product_index(&accts.product.key()) {
         Ok(_) => return Err(UtilError::DuplicateProductKey.into()),
         Err(_) => {}
@@ -87,6 +91,10 @@ pub fn process(
         cum_social_loss_per_share: ZERO_FRAC,
         open_long_interest: ZERO_FRAC,
         open_short_interest: ZERO_FRAC,
+        max_open_interest: ZERO_FRAC,
+        max_position: ZERO_FRAC,
+        index_price: ZERO_FRAC,
+        index_price_slot: 0,
         padding: Default::default(),
     };
     market_product.prices.initialize(Clock::get()?.slot);
@@ -12,22 +12,29 @@ use anchor_lang::{
 };
 
 use crate::{
-    error::{DomainOrProgramResult, UtilError},
+    error::{DexError, DomainOrProgramResult, UtilError},
     state::{
         constants::{SLOTS_15_MIN, SLOTS_1_MIN, SLOTS_5_MIN, SLOTS_60_MIN},
         enums::AccountTag,
     },
-    utils::validation::{assert_keys_equal, get_rent},
+    utils::validation::{assert, assert_keys_equal, get_rent},
     InitializeMarketProductGroup, InitializeMarketProductGroupParams,
 };
 
 const TOKEN_ACCOUNT_SIZE: u64 = spl_token::state::Account::LEN as u64;
 
-pub fn validate(ctx: &Context<InitializeMarketProductGroup>) -> DomainOrProgramResult {
+pub fn validate(
+    ctx: &Context<InitializeMarketProductGroup>,
+    params: &InitializeMarketProductGroupParams,
+) -> DomainOrProgramResult {
     assert_keys_equal(
         *ctx.accounts.fee_model_configuration_acct.as_ref().owner,
         ctx.accounts.fee_model_program.key(),
     )?;
+    assert(
+        params.on_post_discriminant_len <= 8 && params.on_cancel_discriminant_len <= 8,
+        DexError::InvalidDiscriminantLength,
+    )?;
     Ok(())
 }
 
@@ -35,7 +42,7 @@ pub fn process(
     ctx: Context<InitializeMarketProductGroup>,
     params: InitializeMarketProductGroupParams,
 ) -> DomainOrProgramResult {
-    validate(&ctx)?;
+    validate(&ctx, &params)?;
     let accts = ctx.accounts;
     let mut market_product_group = accts.market_product_group.load_init().map_err(|e| {
         msg!("Failed to deserialize market product group");
@@ -116,6 +123,11 @@ pub fn process(
         params.create_risk_state_account_discriminant;
     market_product_group.validate_account_liquidation_discriminant =
         params.validate_account_liquidation_discriminant;
+    market_product_group.on_post_discriminant_len = params.on_post_discriminant_len as u16;
+    market_product_group.on_cancel_discriminant_len = params.on_cancel_discriminant_len as u16;
+    market_product_group.on_post_discriminant = params.on_post_discriminant;
+    market_product_group.on_cancel_discriminant = params.on_cancel_discriminant;
+    market_product_group.fee_accounts_len = params.fee_accounts_len as u16;
     // fees
     market_product_group.fee_collector = accts.fee_collector.key();
     market_product_group.fee_model_program_id = accts.fee_model_program.key();
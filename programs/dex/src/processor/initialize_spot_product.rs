@@ -0,0 +1,121 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        msg,
+        program::invoke_signed,
+        program_pack::{IsInitialized, Pack},
+        pubkey::Pubkey,
+        system_instruction,
+        sysvar::{rent::Rent, Sysvar},
+    },
+};
+
+use crate::{
+    error::{DexError, DomainOrProgramResult, UtilError},
+    state::market_product_group::SpotMarket,
+    utils::validation::{assert, assert_keys_equal, assert_with_msg, get_rent},
+    DomainOrProgramError, InitializeSpotProduct,
+};
+
+const TOKEN_ACCOUNT_SIZE: u64 = spl_token::state::Account::LEN as u64;
+
+fn validate(
+    ctx: &Context<InitializeSpotProduct>,
+) -> std::result::Result<usize, DomainOrProgramError> {
+    let accts = &ctx.accounts;
+    let market_product_group = accts.market_product_group.load()?;
+    if !market_product_group.is_initialized() {
+        msg!("MarketProductGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    if market_product_group.product_lister == Pubkey::default()
+        || accts.authority.key() != market_product_group.product_lister
+    {
+        assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+    }
+    let (product_index, outright) = market_product_group.find_outright(&accts.product.key())?;
+    assert(
+        !market_product_group.is_spot(product_index),
+        UtilError::AccountAlreadyInitialized,
+    )?;
+    // Cash settled positions can't be turned into base balances
+    assert_with_msg(
+        outright.is_removable() && outright.num_queue_events == 0,
+        ProgramError::InvalidArgument,
+        "Only products that have not traded yet can become spot products",
+    )?;
+    assert(
+        outright.base_decimals <= accts.base_mint.decimals as u64,
+        DexError::ProductDecimalPrecisionError,
+    )?;
+    assert(
+        !market_product_group.is_expired(&market_product_group.market_products[product_index]),
+        DexError::ContractIsExpired,
+    )?;
+    Ok(product_index)
+}
+
+/// Makes a freshly listed outright physically settled. Positions in the product become
+/// balances of `base_mint` that are deposited into and withdrawn from the base vault.
+pub fn process(ctx: Context<InitializeSpotProduct>) -> DomainOrProgramResult {
+    let product_index = validate(&ctx)?;
+    let accts = ctx.accounts;
+
+    let base_vault_seeds_without_bump: &[&[u8]] = &[
+        b"base_vault",
+        &accts.market_product_group.key().to_bytes(),
+        &accts.product.key().to_bytes(),
+    ];
+    let (base_vault_key, base_vault_bump) =
+        Pubkey::find_program_address(base_vault_seeds_without_bump, ctx.program_id);
+    let base_vault_seeds = &[
+        base_vault_seeds_without_bump[0],
+        base_vault_seeds_without_bump[1],
+        base_vault_seeds_without_bump[2],
+        &[base_vault_bump],
+    ];
+    assert_keys_equal(base_vault_key, *accts.base_vault.key)?;
+    msg!("Creating the base vault");
+    invoke_signed(
+        &system_instruction::create_account(
+            accts.authority.key,
+            accts.base_vault.key,
+            get_rent(&Rent::get()?, TOKEN_ACCOUNT_SIZE, &accts.base_vault),
+            TOKEN_ACCOUNT_SIZE,
+            accts.token_program.key,
+        ),
+        &[
+            accts.authority.to_account_info(),
+            accts.base_vault.clone(),
+            accts.system_program.to_account_info(),
+        ],
+        &[base_vault_seeds],
+    )?;
+
+    msg!("Initializing the base vault");
+    invoke_signed(
+        &spl_token::instruction::initialize_account2(
+            accts.token_program.key,
+            accts.base_vault.key,
+            &accts.base_mint.key(),
+            accts.base_vault.key,
+        )?,
+        &[
+            accts.base_vault.clone(),
+            accts.base_mint.to_account_info(),
+            accts.sysvar_rent.clone(),
+        ],
+        &[base_vault_seeds],
+    )?;
+
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    market_product_group.spot_markets[product_index] = SpotMarket {
+        base_mint: accts.base_mint.key(),
+        base_decimals: accts.base_mint.decimals as u64,
+        base_vault_bump: base_vault_bump as u64,
+    };
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    accts.market_product_group.key().log();
+    Ok(())
+}
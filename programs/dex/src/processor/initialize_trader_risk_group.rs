@@ -46,6 +46,7 @@ pub fn process<'a, 'b, 'c, 'info>(
     trader_risk_group.client_order_id = 0;
     trader_risk_group.open_orders.initialize();
     trader_risk_group.pending_fees = ZERO_FRAC;
+    trader_risk_group.pending_fees_quote_qty = ZERO_FRAC;
 
     let risk_program_id = accts.risk_engine_program.key();
     create_risk_state_account(
@@ -0,0 +1,73 @@
+use std::mem::size_of;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        entrypoint::MAX_PERMITTED_DATA_INCREASE, program::invoke, system_instruction,
+    },
+    Discriminator,
+};
+
+use crate::{
+    error::{DomainOrProgramResult, UtilError},
+    state::{
+        legacy::{
+            authority_offset, legacy_market_product_group_len, migrate_market_product_group_data,
+        },
+        market_product_group::MarketProductGroup,
+        products::Product,
+    },
+    utils::validation::{assert, assert_keys_equal, assert_with_msg},
+    MigrateMarketProductGroup,
+};
+
+/// Grows a market product group created with the legacy layout (4 leg combos and no fields after
+/// `sequence_number`) to the current one. Accounts can only grow by `MAX_PERMITTED_DATA_INCREASE`
+/// per instruction, so this has to be called until the account reaches its full size, at which
+/// point the products and the fields after them are moved to their new offsets. Fields that did
+/// not exist in the legacy layout are left zeroed, which disables them.
+pub fn process(ctx: Context<MigrateMarketProductGroup>) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let account = &accts.market_product_group;
+    let len = account.data_len();
+    let new_len = 8 + size_of::<MarketProductGroup>();
+    {
+        let data = account.try_borrow_data()?;
+        assert_with_msg(
+            len >= legacy_market_product_group_len() && len < new_len,
+            UtilError::AccountAlreadyInitialized,
+            "Market product group does not have the legacy layout or is already migrated",
+        )?;
+        assert(
+            data[..8] == MarketProductGroup::discriminator(),
+            UtilError::AccountUninitialized,
+        )?;
+        let offset = authority_offset();
+        let authority = Pubkey::new(&data[offset..offset + 32]);
+        assert_keys_equal(authority, accts.authority.key())?;
+    }
+
+    let target_len = (len + MAX_PERMITTED_DATA_INCREASE).min(new_len);
+    let rent = Rent::get()?
+        .minimum_balance(target_len)
+        .saturating_sub(account.lamports());
+    if rent > 0 {
+        invoke(
+            &system_instruction::transfer(&accts.payer.key(), &account.key(), rent),
+            &[
+                accts.payer.to_account_info(),
+                account.to_account_info(),
+                accts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+    account.realloc(target_len, true)?;
+    msg!("Market product group size: {} / {}", target_len, new_len);
+
+    if target_len == new_len {
+        let mut data = account.try_borrow_mut_data()?;
+        migrate_market_product_group_data(&mut data, size_of::<Product>());
+        msg!("Migrated market product group");
+    }
+    Ok(())
+}
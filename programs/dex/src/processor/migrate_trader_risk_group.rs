@@ -0,0 +1,91 @@
+use std::mem::size_of;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        entrypoint::MAX_PERMITTED_DATA_INCREASE, program::invoke, system_instruction,
+    },
+    Discriminator,
+};
+
+use crate::{
+    error::{DomainOrProgramResult, UtilError},
+    state::{
+        constants::MAX_TRADER_POSITIONS,
+        legacy::{
+            grow_trader_positions, legacy_trader_risk_group_len, trader_risk_group_num_positions,
+            trader_risk_group_owner_offset, TraderRiskGroupAppendedTail, LEGACY_TRADER_POSITIONS,
+        },
+        trader_risk_group::{TraderPosition, TraderRiskGroup},
+    },
+    utils::validation::{assert, assert_keys_equal, assert_with_msg},
+    MigrateTraderRiskGroup,
+};
+
+/// Grows a trader risk group created with fewer than `MAX_TRADER_POSITIONS` positions. Accounts
+/// can only grow by `MAX_PERMITTED_DATA_INCREASE` per instruction, so each call adds as many
+/// uninitialized positions as fit and moves the fields after them, leaving a valid account with
+/// more positions. The first call on a legacy account also appends the fields it is missing.
+/// This has to be called until the account reaches its full size.
+pub fn process(ctx: Context<MigrateTraderRiskGroup>) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let account = &accts.trader_risk_group;
+    let len = account.data_len();
+    let (num_positions, appended_len) = if len == legacy_trader_risk_group_len() {
+        (
+            LEGACY_TRADER_POSITIONS,
+            size_of::<TraderRiskGroupAppendedTail>(),
+        )
+    } else {
+        let num_positions = trader_risk_group_num_positions(len);
+        assert_with_msg(
+            num_positions.is_some(),
+            ProgramError::InvalidAccountData,
+            "Trader risk group has an unknown layout",
+        )?;
+        (num_positions.unwrap(), 0)
+    };
+    {
+        let data = account.try_borrow_data()?;
+        assert_with_msg(
+            num_positions < MAX_TRADER_POSITIONS,
+            UtilError::AccountAlreadyInitialized,
+            "Trader risk group is already migrated",
+        )?;
+        assert(
+            data[..8] == TraderRiskGroup::discriminator(),
+            UtilError::AccountUninitialized,
+        )?;
+        let offset = trader_risk_group_owner_offset();
+        let owner = Pubkey::new(&data[offset..offset + 32]);
+        assert_keys_equal(owner, accts.owner.key())?;
+    }
+
+    let position_len = size_of::<TraderPosition>();
+    let new_num_positions = (num_positions
+        + (MAX_PERMITTED_DATA_INCREASE - appended_len) / position_len)
+        .min(MAX_TRADER_POSITIONS);
+    let target_len = len + appended_len + (new_num_positions - num_positions) * position_len;
+    let rent = Rent::get()?
+        .minimum_balance(target_len)
+        .saturating_sub(account.lamports());
+    if rent > 0 {
+        invoke(
+            &system_instruction::transfer(&accts.payer.key(), &account.key(), rent),
+            &[
+                accts.payer.to_account_info(),
+                account.to_account_info(),
+                accts.system_program.to_account_info(),
+            ],
+        )?;
+    }
+    account.realloc(target_len, true)?;
+    let mut data = account.try_borrow_mut_data()?;
+    grow_trader_positions(&mut data, num_positions, new_num_positions);
+    msg!(
+        "Trader positions: {} / {}",
+        new_num_positions,
+        MAX_TRADER_POSITIONS
+    );
+    Ok(())
+}
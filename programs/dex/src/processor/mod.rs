@@ -1,16 +1,32 @@
+pub mod add_collateral;
 pub mod cancel_order;
 pub mod change_authority;
 pub mod clear_expired_orderbook;
 pub mod consume_orderbook_events;
+pub mod deposit_collateral;
 pub mod deposit_funds;
+pub mod deposit_spot_funds;
 pub mod initialize_combo;
 pub mod initialize_market_product;
 pub mod initialize_market_product_group;
+pub mod initialize_spot_product;
 pub mod initialize_trader_risk_group;
+pub mod migrate_market_product_group;
+pub mod migrate_trader_risk_group;
 pub mod new_order;
+pub mod remove_all_dependent_combos;
 pub mod remove_market_product;
 pub mod sweep_fees;
 pub mod transfer_full_position;
+pub mod update_collateral;
+pub mod update_index_price;
+pub mod update_market_product;
+pub mod update_market_product_group;
+pub mod update_position_limits;
+pub mod update_price_band;
 pub mod update_product_funding;
+pub mod update_trader_engine_accounts;
 pub mod update_trader_funding;
+pub mod withdraw_collateral;
 pub mod withdraw_funds;
+pub mod withdraw_spot_funds;
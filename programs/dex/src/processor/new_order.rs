@@ -29,16 +29,17 @@ use crate::{
     state::{
         callback_info::CallBackInfo,
         enums::OrderType,
-        fee_model::{TraderFeeParams, TraderFees},
+        fee_model::{OrderEventParams, TraderFeeParams, TraderFees},
+        market_product_group::PriceBand,
         products::Product,
         risk_engine_register::*,
     },
     utils::{
-        cpi::{find_fees, risk_check},
+        cpi::{find_fees, notify_fee_model, risk_check},
         loadable::Loadable,
         logs::DexOrderSummary,
         numeric::{fp32_mul, u64_to_quote, Fractional, ZERO_FRAC},
-        orderbook::{get_bbo, update_prices},
+        orderbook::{get_bbo, load_orderbook, update_prices},
         param::WithAcct,
         validation::{assert, assert_keys_equal},
     },
@@ -100,6 +101,9 @@ fn validate(ctx: &Context<NewOrder>) -> std::result::Result<(), DomainOrProgramE
     Ok(())
 }
 
+// Orderbook, market signer, event queue, bids and asks of a combo leg
+const LEG_ACCOUNTS_LEN: usize = 5;
+
 pub fn process<'info>(
     ctx: Context<'_, '_, '_, 'info, NewOrder<'info>>,
     params: NewOrderParams,
@@ -117,13 +121,16 @@ pub fn process<'info>(
         self_trade_behavior,
         match_limit,
         limit_price,
+        implied_out,
     } = params;
     let orderbook = MarketState::get(&accts.orderbook)?;
-    if max_base_qty < u64_to_quote(orderbook.min_base_order_size as u64)? {
+    let (product_index, _) = market_product_group.find_product_index(&accts.product.key())?;
+    let min_base_order_size = (orderbook.min_base_order_size as u64)
+        .max(market_product_group.min_base_order_sizes[product_index]);
+    if max_base_qty < u64_to_quote(min_base_order_size)? {
         msg!("The base order size is too small.");
         return Err(ProgramError::InvalidArgument.into());
     }
-    let (product_index, _) = market_product_group.find_product_index(&accts.product.key())?;
     let product = market_product_group.market_products[product_index];
 
     // Product validation
@@ -132,6 +139,8 @@ pub fn process<'info>(
         DexError::ContractIsExpired,
     )?;
     assert_keys_equal(product.orderbook, accts.orderbook.key())?;
+    // Before the open orders are read for the callback info and the limits
+    trader_risk_group.clear_stale_open_orders(&market_product_group)?;
 
     let (post_only, post_allowed) = match order_type {
         OrderType::Limit => (false, true),
@@ -156,27 +165,70 @@ pub fn process<'info>(
             accts.system_program.to_account_info(),
         ],
     )?;
+    let limit_price = apply_price_band(
+        &market_product_group.price_bands[product_index],
+        &product,
+        limit_price,
+        side,
+        post_only,
+        Clock::get()?.slot,
+    )?;
     let limit_price_aob =
         get_limit_price_aob(limit_price, product.price_offset, product.tick_size)?;
 
-    let starting_queue_size =
-        EventQueueHeader::deserialize(&mut (&accts.event_queue.data.borrow() as &[u8]))
-            .map_err(ProgramError::from)?
-            .count;
-
-    invoke_signed_unchecked(
-        &agnostic_orderbook::instruction::new_order::Accounts {
-            market: accts.orderbook.key,
-            event_queue: accts.event_queue.key,
-            bids: accts.bids.key,
-            asks: accts.asks.key,
-            authority: accts.market_signer.key,
+    let fee_accounts_len = market_product_group.fee_accounts_len as usize;
+    assert(
+        ctx.remaining_accounts.len() >= fee_accounts_len,
+        DexError::MissingUserAccount,
+    )?;
+    let (fee_accounts, remaining_accounts) = ctx.remaining_accounts.split_at(fee_accounts_len);
+    let (leg_accounts, risk_accounts) = match product {
+        Product::Combo { combo } if implied_out => {
+            let leg_accounts_len = LEG_ACCOUNTS_LEN * combo.num_legs;
+            assert(
+                remaining_accounts.len() >= leg_accounts_len,
+                DexError::MissingUserAccount,
+            )?;
+            remaining_accounts.split_at(leg_accounts_len)
         }
-        .get_instruction(
-            accts.aaob_program.key(),
-            agnostic_orderbook::instruction::AgnosticOrderbookInstruction::NewOrder as u8,
+        _ => (&remaining_accounts[..0], remaining_accounts),
+    };
+    let (implied_base_qty, implied_quote_qty, implied_gross_quote_qty) =
+        if leg_accounts.is_empty() || post_only {
+            (ZERO_FRAC, ZERO_FRAC, ZERO_FRAC)
+        } else {
+            match_implied_out(
+                accts,
+                &mut market_product_group,
+                &product,
+                leg_accounts,
+                &callback_info,
+                side,
+                max_base_qty,
+                limit_price,
+                self_trade_behavior,
+                match_limit,
+                orderbook.callback_info_len as usize,
+            )?
+        };
+    let combo_base_qty = max_base_qty.checked_sub(implied_base_qty)?;
+
+    let OrderSummary {
+        posted_order_id,
+        total_base_qty,
+        total_quote_qty,
+        total_base_qty_posted,
+    } = if combo_base_qty > ZERO_FRAC || implied_base_qty == ZERO_FRAC {
+        let (order_summary, new_events) = invoke_new_order(
+            &accts.aaob_program,
+            &accts.orderbook,
+            &accts.market_signer,
+            &accts.event_queue,
+            &accts.bids,
+            &accts.asks,
+            &[accts.product.key.as_ref(), &[product.bump as u8]],
             agnostic_orderbook::instruction::new_order::Params {
-                max_base_qty: max_base_qty.round(product.base_decimals as u32)?.m as u64,
+                max_base_qty: combo_base_qty.round(product.base_decimals as u32)?.m as u64,
                 max_quote_qty: u64::MAX,
                 limit_price: limit_price_aob,
                 side,
@@ -186,72 +238,35 @@ pub fn process<'info>(
                 post_allowed,
                 self_trade_behavior,
             },
-        ),
-        &[
-            accts.aaob_program.clone(),
-            accts.orderbook.clone(),
-            accts.market_signer.clone(),
-            accts.event_queue.clone(),
-            accts.bids.clone(),
-            accts.asks.clone(),
-        ],
-        &[&[accts.product.key.as_ref(), &[product.bump as u8]]],
-    )?;
-
-    let ending_queue_size =
-        EventQueueHeader::deserialize(&mut (&accts.event_queue.data.borrow() as &[u8]))
-            .map_err(ProgramError::from)?
-            .count;
-
-    let new_events = ending_queue_size.saturating_sub(starting_queue_size);
-
-    update_new_queue_events(
-        &product,
-        product_index,
-        &mut market_product_group,
-        new_events,
-    )?;
-
-    let OrderSummary {
-        posted_order_id,
-        total_base_qty,
-        total_quote_qty,
-        total_base_qty_posted,
-    }: OrderSummary = read_register(&accts.event_queue).unwrap().unwrap();
-
-    emit!(DexOrderSummary::new(
-        posted_order_id,
-        total_base_qty,
-        total_quote_qty,
-        total_base_qty_posted,
-    ));
-
-    {
-        let bids = Slab::new_from_acc_info(&accts.bids, orderbook.callback_info_len as usize);
-        let asks = Slab::new_from_acc_info(&accts.asks, orderbook.callback_info_len as usize);
-        let windows = &market_product_group.ewma_windows.clone();
-        let best_bid = get_bbo(
-            bids.find_max(),
-            &bids,
-            Side::Bid,
-            product.tick_size,
-            product.price_offset,
-        )?;
-        let best_ask = get_bbo(
-            asks.find_min(),
-            &asks,
-            Side::Ask,
-            product.tick_size,
-            product.price_offset,
         )?;
-        update_prices(
-            &Clock::get()?,
-            &mut market_product_group.market_products[product_index].prices,
-            best_bid,
-            best_ask,
-            windows,
+        update_new_queue_events(
+            &product,
+            product_index,
+            &mut market_product_group,
+            new_events,
         )?;
-    }
+        emit!(DexOrderSummary::new(
+            order_summary.posted_order_id,
+            order_summary.total_base_qty,
+            order_summary.total_quote_qty,
+            order_summary.total_base_qty_posted,
+        ));
+        order_summary
+    } else {
+        OrderSummary {
+            posted_order_id: None,
+            total_base_qty: 0,
+            total_quote_qty: 0,
+            total_base_qty_posted: 0,
+        }
+    };
+    update_book_prices(
+        &mut market_product_group,
+        product_index,
+        &accts.bids,
+        &accts.asks,
+        orderbook.callback_info_len as usize,
+    )?;
 
     let [total_base_qty_dex, matched_base_qty_dex, matched_quote_qty_dex] = process_from_aob(
         total_base_qty,
@@ -262,6 +277,14 @@ pub fn process<'info>(
         product.tick_size,
         product.base_decimals,
     )?;
+    // Fills on the combo book are consumed at the combo's net quote, implied fills leg by leg
+    let fill_quote_qty = matched_quote_qty_dex
+        .abs()
+        .checked_add(implied_gross_quote_qty)?;
+    // Implied fills are settled leg by leg on the outright books but count as fills of the combo
+    let total_base_qty_dex = total_base_qty_dex.checked_add(implied_base_qty)?;
+    let matched_base_qty_dex = matched_base_qty_dex.checked_add(implied_base_qty)?;
+    let matched_quote_qty_dex = matched_quote_qty_dex.checked_add(implied_quote_qty)?;
     let is_combo = product.is_combo();
     //// For the snapshot to be sent to risk engine
     let (old_ask_qty_in_book, old_bid_qty_in_book) = (
@@ -275,7 +298,7 @@ pub fn process<'info>(
         side,
     )?;
 
-    let crossed = matched_quote_qty_dex != ZERO_FRAC;
+    let crossed = matched_quote_qty_dex != ZERO_FRAC || implied_base_qty != ZERO_FRAC;
     update_metadata(
         &product,
         &mut trader_risk_group,
@@ -285,6 +308,14 @@ pub fn process<'info>(
         side,
         crossed,
     )?;
+    check_limits(
+        &product,
+        &trader_risk_group,
+        &market_product_group,
+        product_index,
+        matched_base_qty_dex,
+        side,
+    )?;
 
     if crossed || trader_risk_group.valid_until == 0 {
         // Make call into the risk engine if there's a cross or if the trader's fees are uninitialized
@@ -298,9 +329,11 @@ pub fn process<'info>(
             } else {
                 ZERO_FRAC
             },
+            fill_quote_qty,
             matched_base_qty_dex,
             accts.product.key(),
             side,
+            fee_accounts,
         )?;
     }
     if crossed {
@@ -321,7 +354,31 @@ pub fn process<'info>(
         }
     }
     match posted_order_id {
-        Some(order_id) => trader_risk_group.add_open_order(product_index, order_id)?,
+        Some(order_id) => {
+            trader_risk_group.add_open_order(
+                product_index,
+                order_id,
+                market_product_group.product_epochs[product_index],
+            )?;
+            if let Some(discriminant) = market_product_group.get_on_post_discriminant() {
+                notify_fee_model(
+                    &accts.fee_model_program,
+                    accts.market_product_group.as_ref(),
+                    &accts.trader_risk_group,
+                    &accts.trader_fee_state_acct,
+                    &accts.fee_model_configuration_acct,
+                    &accts.risk_and_fee_signer,
+                    discriminant,
+                    &OrderEventParams {
+                        side,
+                        order_id,
+                        base_qty: total_base_qty_dex.checked_sub(matched_base_qty_dex)?,
+                        product: accts.product.key(),
+                    },
+                    market_product_group.risk_and_fee_bump as u8,
+                )?;
+            }
+        }
         None => {}
     }
 
@@ -336,7 +393,7 @@ pub fn process<'info>(
         &accts.trader_risk_state_acct,
         &accts.risk_model_configuration_acct,
         &accts.risk_and_fee_signer,
-        ctx.remaining_accounts,
+        risk_accounts,
         &OrderInfo {
             total_order_qty: total_base_qty_dex,
             matched_order_qty: matched_base_qty_dex,
@@ -367,15 +424,327 @@ pub fn process<'info>(
     Ok(())
 }
 
-fn handle_fees(
-    accts: &NewOrder,
+fn update_book_prices(
+    market_product_group: &mut MarketProductGroup,
+    product_index: usize,
+    bids: &AccountInfo,
+    asks: &AccountInfo,
+    callback_info_len: usize,
+) -> DomainOrProgramResult {
+    let product = market_product_group.market_products[product_index];
+    let bids = Slab::new_from_acc_info(bids, callback_info_len);
+    let asks = Slab::new_from_acc_info(asks, callback_info_len);
+    let windows = &market_product_group.ewma_windows.clone();
+    let best_bid = get_bbo(
+        bids.find_max(),
+        &bids,
+        Side::Bid,
+        product.tick_size,
+        product.price_offset,
+    )?;
+    let best_ask = get_bbo(
+        asks.find_min(),
+        &asks,
+        Side::Ask,
+        product.tick_size,
+        product.price_offset,
+    )?;
+    update_prices(
+        &Clock::get()?,
+        &mut market_product_group.market_products[product_index].prices,
+        best_bid,
+        best_ask,
+        windows,
+    )
+}
+
+/// Sends a new order to the AAOB signed by the product's market signer. Returns the order
+/// summary and the number of events the order pushed to the event queue.
+pub fn invoke_new_order<'info>(
+    aaob_program: &AccountInfo<'info>,
+    orderbook: &AccountInfo<'info>,
+    market_signer: &AccountInfo<'info>,
+    event_queue: &AccountInfo<'info>,
+    bids: &AccountInfo<'info>,
+    asks: &AccountInfo<'info>,
+    market_signer_seeds: &[&[u8]],
+    params: agnostic_orderbook::instruction::new_order::Params,
+) -> DomainOrProgramResult<(OrderSummary, u64)> {
+    let starting_queue_size =
+        EventQueueHeader::deserialize(&mut (&event_queue.data.borrow() as &[u8]))
+            .map_err(ProgramError::from)?
+            .count;
+
+    invoke_signed_unchecked(
+        &agnostic_orderbook::instruction::new_order::Accounts {
+            market: orderbook.key,
+            event_queue: event_queue.key,
+            bids: bids.key,
+            asks: asks.key,
+            authority: market_signer.key,
+        }
+        .get_instruction(
+            aaob_program.key(),
+            agnostic_orderbook::instruction::AgnosticOrderbookInstruction::NewOrder as u8,
+            params,
+        ),
+        &[
+            aaob_program.clone(),
+            orderbook.clone(),
+            market_signer.clone(),
+            event_queue.clone(),
+            bids.clone(),
+            asks.clone(),
+        ],
+        &[market_signer_seeds],
+    )?;
+
+    let ending_queue_size =
+        EventQueueHeader::deserialize(&mut (&event_queue.data.borrow() as &[u8]))
+            .map_err(ProgramError::from)?
+            .count;
+    let order_summary: OrderSummary = read_register(event_queue).unwrap().unwrap();
+    Ok((
+        order_summary,
+        ending_queue_size.saturating_sub(starting_queue_size),
+    ))
+}
+
+/// Fills a combo order against its legs' outright books for as long as the price implied by the
+/// best orders of the legs crosses the order's limit and beats the combo book. Each round takes
+/// the most quantity that the top of every leg book can fill in full, so the legs always fill in
+/// the combo's ratios or the transaction fails. Returns the filled combo quantity and the quote
+/// quantity it was filled at in combo terms. Self trades on the outright books are not matched
+/// around, a leg that can't fill in full because of one fails the order.
+fn match_implied_out<'info>(
+    accts: &NewOrder<'info>,
+    market_product_group: &mut MarketProductGroup,
+    product: &Product,
+    leg_accounts: &[AccountInfo<'info>],
+    callback_info: &CallBackInfo,
+    side: Side,
+    max_base_qty: Fractional,
+    limit_price: Fractional,
+    self_trade_behavior: SelfTradeBehavior,
+    match_limit: u64,
+    callback_info_len: usize,
+) -> DomainOrProgramResult<(Fractional, Fractional, Fractional)> {
+    let combo = product.try_to_combo()?;
+    let legs = combo.legs().to_vec();
+    let mut leg_books = Vec::with_capacity(legs.len());
+    for (leg, accounts) in legs.iter().zip(leg_accounts.chunks(LEG_ACCOUNTS_LEN)) {
+        let outright = market_product_group.market_products[leg.product_index].try_to_outright()?;
+        let (orderbook, market_signer, event_queue, bids, asks) = (
+            &accounts[0],
+            &accounts[1],
+            &accounts[2],
+            &accounts[3],
+            &accounts[4],
+        );
+        assert_keys_equal(outright.orderbook, *orderbook.key)?;
+        assert_keys_equal(
+            Pubkey::create_program_address(
+                &[outright.product_key.as_ref(), &[outright.bump as u8]],
+                &crate::ID,
+            )
+            .map_err(|_| ProgramError::InvalidSeeds)?,
+            *market_signer.key,
+        )?;
+        let market_state = load_orderbook(orderbook, market_signer.key)?;
+        assert_keys_equal(
+            Pubkey::new_from_array(market_state.event_queue),
+            *event_queue.key,
+        )?;
+        assert_keys_equal(Pubkey::new_from_array(market_state.bids), *bids.key)?;
+        assert_keys_equal(Pubkey::new_from_array(market_state.asks), *asks.key)?;
+        leg_books.push((
+            orderbook,
+            market_signer,
+            event_queue,
+            bids,
+            asks,
+            market_state,
+        ));
+    }
+
+    // Implied fills are only taken while they improve on the combo book
+    let combo_best_price = {
+        let slab = match side {
+            Side::Bid => Slab::new_from_acc_info(&accts.asks, callback_info_len),
+            Side::Ask => Slab::new_from_acc_info(&accts.bids, callback_info_len),
+        };
+        let node = match side {
+            Side::Bid => slab.find_min(),
+            Side::Ask => slab.find_max(),
+        };
+        get_bbo(
+            node,
+            &slab,
+            side.opposite(),
+            product.tick_size,
+            product.price_offset,
+        )?
+    };
+    let qty_decimals = legs
+        .iter()
+        .map(|leg| market_product_group.market_products[leg.product_index].base_decimals)
+        .fold(product.base_decimals, u64::min) as u32;
+
+    let mut remaining_qty = max_base_qty;
+    let mut implied_base_qty = ZERO_FRAC;
+    let mut implied_quote_qty = ZERO_FRAC;
+    // Sum of the absolute quote of every leg fill, which is what the fills are consumed at
+    let mut implied_gross_quote_qty = ZERO_FRAC;
+    for _ in 0..match_limit {
+        // Price and size of the best order each leg would trade against
+        let mut tops = Vec::with_capacity(legs.len());
+        let mut implied_price = ZERO_FRAC;
+        let mut qty = remaining_qty;
+        for (leg, (_, _, _, bids, asks, market_state)) in legs.iter().zip(leg_books.iter()) {
+            let outright = &market_product_group.market_products[leg.product_index];
+            let leg_side = get_leg_side(side, leg.ratio);
+            let slab = match leg_side {
+                Side::Bid => Slab::new_from_acc_info(asks, market_state.callback_info_len as usize),
+                Side::Ask => Slab::new_from_acc_info(bids, market_state.callback_info_len as usize),
+            };
+            let node = match leg_side {
+                Side::Bid => slab.find_min(),
+                Side::Ask => slab.find_max(),
+            };
+            let leaf = match node {
+                Some(nh) => slab.get_node(nh).unwrap().as_leaf().unwrap().to_owned(),
+                None => return Ok((implied_base_qty, implied_quote_qty, implied_gross_quote_qty)),
+            };
+            let price = get_bbo(
+                node,
+                &slab,
+                leg_side.opposite(),
+                outright.tick_size,
+                outright.price_offset,
+            )?;
+            implied_price = implied_price.checked_add(price.checked_mul(leg.ratio)?)?;
+            qty = qty.min(
+                Fractional::new(leaf.base_quantity as i64, outright.base_decimals)
+                    .checked_div(Fractional::from(leg.ratio.abs()))?,
+            );
+            tops.push(leaf.price());
+        }
+        let crosses = match side {
+            Side::Bid => implied_price <= limit_price && implied_price < combo_best_price,
+            Side::Ask => implied_price >= limit_price && implied_price > combo_best_price,
+        };
+        let qty = qty.round_unchecked(qty_decimals)?;
+        if !crosses || qty <= ZERO_FRAC {
+            break;
+        }
+        let mut leg_qtys = Vec::with_capacity(legs.len());
+        for (leg, (_, _, _, _, _, market_state)) in legs.iter().zip(leg_books.iter()) {
+            let outright = &market_product_group.market_products[leg.product_index];
+            let leg_qty = qty
+                .checked_mul(Fractional::from(leg.ratio.abs()))?
+                .round(outright.base_decimals as u32)?
+                .m as u64;
+            if leg_qty < market_state.min_base_order_size {
+                return Ok((implied_base_qty, implied_quote_qty, implied_gross_quote_qty));
+            }
+            leg_qtys.push(leg_qty);
+        }
+
+        for (i, leg) in legs.iter().enumerate() {
+            let (orderbook, market_signer, event_queue, bids, asks, market_state) = leg_books[i];
+            let outright = market_product_group.market_products[leg.product_index];
+            let leg_side = get_leg_side(side, leg.ratio);
+            invoke_unchecked(
+                &system_instruction::transfer(
+                    accts.user.key,
+                    orderbook.key,
+                    market_state.cranker_reward,
+                ),
+                &[
+                    accts.user.clone(),
+                    orderbook.clone(),
+                    accts.system_program.to_account_info(),
+                ],
+            )?;
+            let (order_summary, new_events) = invoke_new_order(
+                &accts.aaob_program,
+                orderbook,
+                market_signer,
+                event_queue,
+                bids,
+                asks,
+                &[outright.product_key.as_ref(), &[outright.bump as u8]],
+                agnostic_orderbook::instruction::new_order::Params {
+                    max_base_qty: leg_qtys[i],
+                    max_quote_qty: u64::MAX,
+                    limit_price: tops[i],
+                    side: leg_side,
+                    match_limit,
+                    callback_info: callback_info.to_vec(),
+                    post_only: false,
+                    post_allowed: false,
+                    self_trade_behavior,
+                },
+            )?;
+            let [_, matched_base_qty, matched_quote_qty] = process_from_aob(
+                order_summary.total_base_qty,
+                order_summary.total_base_qty_posted,
+                order_summary.total_quote_qty,
+                tops[i],
+                outright.price_offset,
+                outright.tick_size,
+                outright.base_decimals,
+            )?;
+            assert(
+                matched_base_qty == Fractional::new(leg_qtys[i] as i64, outright.base_decimals),
+                DexError::ImpliedOrderNotFilled,
+            )?;
+            implied_quote_qty = match leg.ratio > 0 {
+                true => implied_quote_qty.checked_add(matched_quote_qty)?,
+                false => implied_quote_qty.checked_sub(matched_quote_qty)?,
+            };
+            implied_gross_quote_qty =
+                implied_gross_quote_qty.checked_add(matched_quote_qty.abs())?;
+            let outright =
+                market_product_group.market_products[leg.product_index].try_to_outright_mut()?;
+            outright.num_queue_events = outright
+                .num_queue_events
+                .saturating_add(new_events as usize);
+            update_book_prices(
+                market_product_group,
+                leg.product_index,
+                bids,
+                asks,
+                market_state.callback_info_len as usize,
+            )?;
+        }
+        implied_base_qty = implied_base_qty.checked_add(qty)?;
+        remaining_qty = remaining_qty.checked_sub(qty)?;
+        msg!("Filled {} at implied price {}", qty, implied_price);
+    }
+    Ok((implied_base_qty, implied_quote_qty, implied_gross_quote_qty))
+}
+
+/// Buying a combo buys its legs with a positive ratio and sells the others
+fn get_leg_side(side: Side, ratio: i64) -> Side {
+    if ratio > 0 {
+        side
+    } else {
+        side.opposite()
+    }
+}
+
+fn handle_fees<'info>(
+    accts: &NewOrder<'info>,
     clock: &Clock,
     market_product_group: &MarketProductGroup,
     trader_risk_group: &mut TraderRiskGroup,
     matched_quote_qty: Fractional,
+    fill_quote_qty: Fractional,
     matched_base_qty: Fractional,
     product: Pubkey,
     side: Side,
+    fee_accounts: &[AccountInfo<'info>],
 ) -> DomainOrProgramResult {
     if trader_risk_group.valid_until <= clock.unix_timestamp {
         let fee_params = TraderFeeParams {
@@ -393,6 +762,7 @@ fn handle_fees(
             &accts.fee_model_configuration_acct,
             &accts.fee_output_register,
             &accts.risk_and_fee_signer,
+            fee_accounts,
             market_product_group.get_find_fees_discriminant(),
             &fee_params,
             market_product_group.risk_and_fee_bump as u8,
@@ -405,6 +775,9 @@ fn handle_fees(
         .checked_mul(matched_quote_qty)?;
 
     trader_risk_group.pending_fees = trader_risk_group.pending_fees.checked_add(taker_fees)?;
+    trader_risk_group.pending_fees_quote_qty = trader_risk_group
+        .pending_fees_quote_qty
+        .checked_add(fill_quote_qty)?;
     trader_risk_group.valid_until = computed_fees.valid_until;
     trader_risk_group.maker_fee_bps = computed_fees.maker_fee_bps;
     trader_risk_group.taker_fee_bps = computed_fees.taker_fee_bps;
@@ -464,6 +837,158 @@ fn update_metadata(
     Ok(())
 }
 
+/// Rejects orders that grow the trader's position in any leg past its position limit, either
+/// through the matched quantity or if every resting order of the trader that grows the leg in
+/// the same direction fills, including resting orders of other products that share the leg.
+/// Capacity for resting orders is reserved when they are posted, so their fills can't exceed
+/// the limit when they are consumed unless the limit was lowered in between.
+///
+/// Matched quantity that grows the trader's position is assumed to add to the open interest:
+/// the open interest only grows when the positions of both the taker and the maker grow.
+fn check_limits(
+    product: &Product,
+    trader_risk_group: &TraderRiskGroup,
+    market_product_group: &MarketProductGroup,
+    product_index: usize,
+    matched_base_qty_dex: Fractional,
+    side: Side,
+) -> DomainOrProgramResult {
+    let open_orders = &trader_risk_group.open_orders.products[product_index];
+    let book_qty = match side {
+        Side::Bid => open_orders.bid_qty_in_book,
+        Side::Ask => open_orders.ask_qty_in_book,
+    };
+    for (ratio, i) in product.get_ratios_and_product_indices(product_index) {
+        let outright = market_product_group.market_products[i].try_to_outright()?;
+        let trader_position =
+            &trader_risk_group.trader_positions[trader_risk_group.active_products[i] as usize];
+        let signed_ratio = match side {
+            Side::Bid => ratio,
+            Side::Ask => -ratio,
+        };
+        let position = trader_position
+            .position
+            .checked_add(trader_position.pending_position)?;
+        let has_position_limit = outright.max_position != ZERO_FRAC;
+        if has_position_limit || outright.max_open_interest != ZERO_FRAC {
+            let old_position = position
+                .checked_sub(matched_base_qty_dex.checked_mul(Fractional::from(signed_ratio))?)?;
+            outright.check_position_limit(old_position, position)?;
+            if position.abs() > old_position.abs() {
+                outright.check_open_interest_limit(
+                    matched_base_qty_dex.checked_mul(Fractional::from(signed_ratio.abs()))?,
+                )?;
+            }
+        }
+        // Walking every product's resting orders is only worth it when there is a limit to check
+        if has_position_limit {
+            let resting_qty = get_resting_qty(
+                trader_risk_group,
+                market_product_group,
+                i,
+                signed_ratio.signum(),
+            )?;
+            outright.check_position_limit(
+                position,
+                position.checked_add(
+                    resting_qty.checked_mul(Fractional::from(signed_ratio.signum()))?,
+                )?,
+            )?;
+        }
+        // Spot sells, including resting asks, must be covered by the trader's base balance
+        if market_product_group.is_spot(i) && side == Side::Ask {
+            assert(
+                position.checked_sub(book_qty)? >= ZERO_FRAC,
+                DexError::InsufficientBaseBalance,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Quantity of outright `outright_index` that the trader's resting orders in every product
+/// would add to their position in `direction` (1 for long, -1 for short) if they all filled
+fn get_resting_qty(
+    trader_risk_group: &TraderRiskGroup,
+    market_product_group: &MarketProductGroup,
+    outright_index: usize,
+    direction: i64,
+) -> DomainOrProgramResult<Fractional> {
+    let mut resting_qty = ZERO_FRAC;
+    for (product_index, product) in market_product_group.active_products() {
+        let open_orders = &trader_risk_group.open_orders.products[product_index];
+        if open_orders.num_open_orders == 0 {
+            continue;
+        }
+        for (ratio, i) in product.get_ratios_and_product_indices(product_index) {
+            if i != outright_index {
+                continue;
+            }
+            let book_qty = if ratio * direction > 0 {
+                open_orders.bid_qty_in_book
+            } else {
+                open_orders.ask_qty_in_book
+            };
+            resting_qty =
+                resting_qty.checked_add(book_qty.checked_mul(Fractional::from(ratio.abs()))?)?;
+        }
+    }
+    Ok(resting_qty)
+}
+
+/// Post only orders outside of the product's price band are rejected, the limit price of any
+/// other order is clamped to the band so that it can't sweep the book past it.
+fn apply_price_band(
+    price_band: &PriceBand,
+    product: &Product,
+    limit_price: Fractional,
+    side: Side,
+    post_only: bool,
+    slot: u64,
+) -> DomainOrProgramResult<Fractional> {
+    let index_price = product
+        .try_to_outright()
+        .ok()
+        .and_then(|outright| outright.get_index_price(slot));
+    let (low, high) = match price_band.get_bounds(&product.prices, index_price)? {
+        Some(bounds) => bounds,
+        None => return Ok(limit_price),
+    };
+    if low <= limit_price && limit_price <= high {
+        return Ok(limit_price);
+    }
+    if post_only {
+        msg!(
+            "Limit price {} is outside of the price band [{}, {}]",
+            limit_price,
+            low,
+            high
+        );
+        return Err(DexError::PriceOutsideBand.into());
+    }
+    let bound = match side {
+        Side::Bid if limit_price > high => high,
+        Side::Ask if limit_price < low => low,
+        // Orders on the passive side of the band can't trade through it
+        _ => return Ok(limit_price),
+    };
+    // Round towards the inside of the band so the clamped price stays within it
+    let (ticks, remainder) = bound
+        .checked_add(product.price_offset)?
+        .checked_div(product.tick_size)?
+        .to_int_with_remainder();
+    let ticks = match side {
+        Side::Ask if remainder > ZERO_FRAC => ticks + 1,
+        Side::Bid if remainder < ZERO_FRAC => ticks - 1,
+        _ => ticks,
+    };
+    let clamped = Fractional::from(ticks)
+        .checked_mul(product.tick_size)?
+        .checked_sub(product.price_offset)?;
+    msg!("Clamping limit price {} to {}", limit_price, clamped);
+    Ok(clamped)
+}
+
 #[inline(always)]
 pub fn get_limit_price_aob(
     price: Fractional,
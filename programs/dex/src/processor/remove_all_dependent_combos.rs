@@ -0,0 +1,134 @@
+use agnostic_orderbook::{critbit::Slab, state::EventQueueHeader};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{msg, program::invoke_signed, program_pack::IsInitialized},
+};
+use bonfida_utils::InstructionsAccount;
+use borsh::BorshDeserialize;
+
+use crate::{
+    error::{DexError, DomainOrProgramResult, UtilError},
+    state::products::Product,
+    utils::{
+        orderbook::load_orderbook,
+        validation::{assert, assert_keys_equal, assert_with_msg},
+    },
+    DomainOrProgramError, RemoveAllDependentCombos,
+};
+
+/// Orderbook, market signer, event queue, bids and asks of each dependent combo
+const COMBO_ACCOUNTS_LEN: usize = 5;
+
+fn validate(
+    ctx: &Context<RemoveAllDependentCombos>,
+) -> std::result::Result<Vec<Product>, DomainOrProgramError> {
+    let accts = &ctx.accounts;
+    let market_product_group = accts.market_product_group.load()?;
+    if !market_product_group.is_initialized() {
+        msg!("MarketProductGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+    let (_, product) = market_product_group.find_product_index(&accts.product.key())?;
+    let outright = product.try_to_outright()?;
+    assert(outright.is_expired(), DexError::ContractIsNotExpired)?;
+
+    let combos = market_product_group
+        .active_products()
+        .filter(|(_, p)| match p {
+            Product::Combo { combo } => combo.has_leg(accts.product.key()),
+            Product::Outright { outright: _ } => false,
+        })
+        .map(|(_, p)| *p)
+        .collect::<Vec<_>>();
+    assert(
+        ctx.remaining_accounts.len() == COMBO_ACCOUNTS_LEN * combos.len(),
+        DexError::MissingUserAccount,
+    )?;
+    Ok(combos)
+}
+
+/// Closes the orderbooks of the combos that have the expired product as a leg and deactivates
+/// them. Their books must have been cleared with clear_expired_orderbook and their event queues
+/// cranked beforehand. Open orders left on a removed combo in a trader's risk group are reset
+/// through the product epochs once the index is reused.
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, RemoveAllDependentCombos<'info>>,
+) -> DomainOrProgramResult {
+    let combos = validate(&ctx)?;
+    let accts = &ctx.accounts;
+
+    for (combo, accounts) in combos
+        .iter()
+        .zip(ctx.remaining_accounts.chunks(COMBO_ACCOUNTS_LEN))
+    {
+        let (orderbook, market_signer, event_queue, bids, asks) = (
+            &accounts[0],
+            &accounts[1],
+            &accounts[2],
+            &accounts[3],
+            &accounts[4],
+        );
+        assert_keys_equal(combo.orderbook, *orderbook.key)?;
+        let market_state = load_orderbook(orderbook, market_signer.key)?;
+        assert_keys_equal(
+            Pubkey::new_from_array(market_state.event_queue),
+            *event_queue.key,
+        )?;
+        assert_keys_equal(Pubkey::new_from_array(market_state.bids), *bids.key)?;
+        assert_keys_equal(Pubkey::new_from_array(market_state.asks), *asks.key)?;
+
+        // Cancelling the resting orders pushes out events that can only be consumed with the
+        // traders' accounts, so clearing the book is left to clear_expired_orderbook and the
+        // crank. The AAOB refuses to close a market whose book or event queue is not empty.
+        let callback_info_len = market_state.callback_info_len as usize;
+        let event_queue_header =
+            EventQueueHeader::deserialize(&mut (&event_queue.data.borrow() as &[u8]))
+                .map_err(ProgramError::from)?;
+        assert_with_msg(
+            event_queue_header.count == 0
+                && Slab::new_from_acc_info(bids, callback_info_len)
+                    .root()
+                    .is_none()
+                && Slab::new_from_acc_info(asks, callback_info_len)
+                    .root()
+                    .is_none(),
+            DexError::OrderbookNotEmpty,
+            "Combos must be cleared with clear_expired_orderbook and cranked before removal",
+        )?;
+        let close_market_instruction = agnostic_orderbook::instruction::close_market::Accounts {
+            market: orderbook.key,
+            event_queue: event_queue.key,
+            bids: bids.key,
+            asks: asks.key,
+            authority: market_signer.key,
+            lamports_target_account: accts.authority.key,
+        }
+        .get_instruction(
+            accts.aaob_program.key(),
+            agnostic_orderbook::instruction::AgnosticOrderbookInstruction::CloseMarket as u8,
+            agnostic_orderbook::instruction::close_market::Params {},
+        );
+        invoke_signed(
+            &close_market_instruction,
+            &[
+                accts.aaob_program.to_account_info(),
+                orderbook.clone(),
+                market_signer.clone(),
+                event_queue.clone(),
+                bids.clone(),
+                asks.clone(),
+                accts.authority.to_account_info(),
+            ],
+            &[&[combo.product_key.as_ref(), &[combo.bump as u8]]],
+        )?;
+        let mut market_product_group = accts.market_product_group.load_mut()?;
+        market_product_group.deactivate_product(combo.product_key)?;
+    }
+
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    accts.market_product_group.key().log();
+    Ok(())
+}
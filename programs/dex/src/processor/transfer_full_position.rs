@@ -118,14 +118,13 @@ pub fn process<'info>(
             return Err(DexError::InvalidAccountHealthError.into());
         }
         msg!("Liquidatee account health is below liquidation threshold");
-        let social_losses = liquidation_info.social_losses;
         let cash_decimals = market_product_group.decimals;
         let mut total_social_loss = ZERO_FRAC;
         // Attempt to transfer over full position
         for (mut liquidatee_position, social_loss) in liquidatee_risk_group
             .trader_positions
             .iter_mut()
-            .zip(social_losses.iter())
+            .zip(liquidation_info.social_losses.iter())
         {
             if !liquidatee_position.is_initialized() {
                 continue;
@@ -217,6 +216,15 @@ pub fn process<'info>(
             .checked_add(liquidatee_risk_group.cash_balance)?
             .checked_sub(liquidation_info.liquidation_price)?;
         liquidatee_risk_group.cash_balance = liquidatee_cash;
+        // Non-cash collateral is part of the portfolio value and goes to the liquidator
+        for (liquidator_balance, liquidatee_balance) in liquidator_risk_group
+            .collateral_balances
+            .iter_mut()
+            .zip(liquidatee_risk_group.collateral_balances.iter_mut())
+        {
+            *liquidator_balance = liquidator_balance.checked_add(*liquidatee_balance)?;
+            *liquidatee_balance = ZERO_FRAC;
+        }
     }
 
     {
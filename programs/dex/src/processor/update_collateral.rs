@@ -0,0 +1,44 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{msg, program_pack::IsInitialized},
+};
+
+use crate::{
+    error::{DomainOrProgramResult, UtilError},
+    utils::validation::{assert, assert_keys_equal, assert_with_msg},
+    UpdateCollateral, UpdateCollateralParams,
+};
+
+pub fn process(
+    ctx: Context<UpdateCollateral>,
+    params: UpdateCollateralParams,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    assert(
+        market_product_group.is_initialized(),
+        UtilError::AccountUninitialized,
+    )?;
+    assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+    assert_with_msg(
+        params.haircut_bps <= 10_000,
+        ProgramError::InvalidArgument,
+        "Haircut can be at most 10000 bps",
+    )?;
+    assert_with_msg(
+        params.oracle_type != 0,
+        ProgramError::InvalidArgument,
+        "Collateral oracle type must be set",
+    )?;
+    let index = market_product_group.find_collateral(&accts.mint.key())?;
+    let collateral = &mut market_product_group.collaterals[index];
+    collateral.oracle = accts.oracle.key();
+    collateral.haircut_bps = params.haircut_bps;
+    collateral.oracle_type = params.oracle_type;
+    collateral.max_staleness_slots = params.max_staleness_slots;
+    collateral.max_confidence_bps = params.max_confidence_bps;
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    accts.market_product_group.key().log();
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{program_pack::IsInitialized, sysvar::clock::Clock},
+};
+
+use crate::{
+    error::{DomainOrProgramResult, UtilError},
+    utils::validation::assert,
+    UpdateIndexPrice, UpdateIndexPriceParams,
+};
+
+/// Records the payoff of the product's oracle index, which price bands fall back to when one
+/// side of the book is empty. Only the product itself can push it, like funding.
+pub fn process(
+    ctx: Context<UpdateIndexPrice>,
+    params: UpdateIndexPriceParams,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    assert(
+        market_product_group.is_initialized(),
+        UtilError::AccountUninitialized,
+    )?;
+
+    let (idx, _) = market_product_group.find_product_index(&accts.product.key())?;
+    let product = market_product_group.market_products[idx].try_to_outright_mut()?;
+    product.index_price = params.price;
+    product.index_price_slot = Clock::get()?.slot;
+    Ok(())
+}
@@ -0,0 +1,388 @@
+use agnostic_orderbook::{
+    critbit::Slab,
+    state::{
+        get_side_from_order_id, read_register, EventQueueHeader, OrderSummary, SelfTradeBehavior,
+        Side,
+    },
+};
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        msg,
+        program::{invoke_signed_unchecked, invoke_unchecked},
+        program_error::ProgramError,
+        program_pack::IsInitialized,
+        system_instruction,
+    },
+};
+use bonfida_utils::InstructionsAccount;
+use borsh::BorshDeserialize;
+
+use crate::{
+    error::{DexError, DomainOrProgramResult, UtilError},
+    processor::new_order::invoke_new_order,
+    state::{
+        callback_info::CallBackInfo, constants::SENTINEL, market_product_group::MarketProductGroup,
+        trader_risk_group::TraderRiskGroup,
+    },
+    utils::{
+        numeric::{Fractional, ZERO_FRAC},
+        orderbook::load_orderbook,
+        validation::{assert, assert_keys_equal, assert_with_msg},
+    },
+    UpdateCombo, UpdateMarketProduct, UpdateMarketProductParams,
+};
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateMarketProduct<'info>>,
+    params: UpdateMarketProductParams,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    update_product(
+        &accts.authority,
+        &accts.market_product_group,
+        &accts.product,
+        &accts.aaob_program,
+        &accts.orderbook,
+        &accts.market_signer,
+        &accts.event_queue,
+        &accts.bids,
+        &accts.asks,
+        &accts.system_program,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        params,
+        false,
+    )
+}
+
+pub fn process_combo<'info>(
+    ctx: Context<'_, '_, '_, 'info, UpdateCombo<'info>>,
+    params: UpdateMarketProductParams,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    update_product(
+        &accts.authority,
+        &accts.market_product_group,
+        &accts.product,
+        &accts.aaob_program,
+        &accts.orderbook,
+        &accts.market_signer,
+        &accts.event_queue,
+        &accts.bids,
+        &accts.asks,
+        &accts.system_program,
+        ctx.remaining_accounts,
+        ctx.program_id,
+        params,
+        true,
+    )
+}
+
+/// Orders rest in the orderbook and fills wait in the event queue as a number of ticks, so a new
+/// tick size would reprice them. The event queue must therefore be empty, and orders can only
+/// rest in the book when the new tick size divides the old one. Those orders are rescaled to the
+/// finer tick by `rescale_resting_orders`.
+///
+/// The AAOB's own `min_base_order_size` is fixed when the orderbook is created, so the minimum
+/// order size can only be raised above it.
+fn update_product<'info>(
+    authority: &Signer<'info>,
+    market_product_group: &AccountLoader<'info, MarketProductGroup>,
+    product: &AccountInfo<'info>,
+    aaob_program: &AccountInfo<'info>,
+    orderbook: &AccountInfo<'info>,
+    market_signer: &AccountInfo<'info>,
+    event_queue: &AccountInfo<'info>,
+    bids: &AccountInfo<'info>,
+    asks: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    trader_risk_groups: &[AccountInfo<'info>],
+    program_id: &Pubkey,
+    params: UpdateMarketProductParams,
+    is_combo: bool,
+) -> DomainOrProgramResult {
+    let market_product_group_key = market_product_group.key();
+    let mut market_product_group = market_product_group.load_mut()?;
+    if !market_product_group.is_initialized() {
+        msg!("MarketProductGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    assert_keys_equal(authority.key(), market_product_group.authority)?;
+    let (product_index, market_product) =
+        market_product_group.find_product_index(&product.key())?;
+    assert(
+        market_product.is_combo() == is_combo,
+        if is_combo {
+            DexError::ProductNotCombo
+        } else {
+            DexError::ProductNotOutright
+        },
+    )?;
+    assert_keys_equal(market_product.orderbook, orderbook.key())?;
+    assert_with_msg(
+        params.tick_size > ZERO_FRAC,
+        ProgramError::InvalidArgument,
+        "Tick size must be positive",
+    )?;
+    assert(
+        market_product_group
+            .active_products()
+            .all(|(i, p)| i == product_index || p.name != params.name),
+        DexError::DuplicateProductNameError,
+    )?;
+
+    let (market_signer_key, _) =
+        Pubkey::find_program_address(&[product.key().as_ref()], program_id);
+    assert_keys_equal(market_signer_key, *market_signer.key)?;
+    let orderbook_state = load_orderbook(orderbook, &market_signer_key)?;
+    assert_with_msg(
+        params.min_base_order_size == 0
+            || params.min_base_order_size >= orderbook_state.min_base_order_size as u64,
+        ProgramError::InvalidArgument,
+        "Minimum order size can only be raised above the orderbook's",
+    )?;
+    let min_base_order_size =
+        (orderbook_state.min_base_order_size as u64).max(params.min_base_order_size);
+    Fractional::from(min_base_order_size as i64)
+        .checked_mul(params.tick_size)?
+        .round(market_product_group.decimals as u32)
+        .map_err(|_| {
+            msg!("Orderbook minimum size and product tick size are incompatible with market decimals");
+            DexError::ProductDecimalPrecisionError
+        })?;
+
+    if params.tick_size != market_product.tick_size {
+        assert_keys_equal(
+            Pubkey::new_from_array(orderbook_state.event_queue),
+            *event_queue.key,
+        )?;
+        assert_keys_equal(Pubkey::new_from_array(orderbook_state.bids), *bids.key)?;
+        assert_keys_equal(Pubkey::new_from_array(orderbook_state.asks), *asks.key)?;
+        let callback_info_len = orderbook_state.callback_info_len as usize;
+        let event_queue_header =
+            EventQueueHeader::deserialize(&mut (&event_queue.data.borrow() as &[u8]))
+                .map_err(ProgramError::from)?;
+        assert_with_msg(
+            event_queue_header.count == 0,
+            DexError::OrderbookNotEmpty,
+            "Tick size can only change while the event queue is empty",
+        )?;
+        let book_is_empty = Slab::new_from_acc_info(bids, callback_info_len)
+            .root()
+            .is_none()
+            && Slab::new_from_acc_info(asks, callback_info_len)
+                .root()
+                .is_none();
+        if !book_is_empty {
+            let tick_ratio = market_product
+                .tick_size
+                .checked_div(params.tick_size)?
+                .round(0)
+                .map_err(|_| {
+                    msg!("Tick size can only change to a divisor of the current one while orders rest in the book");
+                    DexError::OrderbookNotEmpty
+                })?;
+            rescale_resting_orders(
+                &market_product_group,
+                market_product_group_key,
+                product_index,
+                trader_risk_groups,
+                authority,
+                system_program,
+                aaob_program,
+                orderbook,
+                market_signer,
+                event_queue,
+                bids,
+                asks,
+                &[product.key.as_ref(), &[market_product.bump as u8]],
+                callback_info_len,
+                orderbook_state.cranker_reward,
+                tick_ratio.m as u64,
+            )?;
+        }
+    }
+
+    let market_product = &mut market_product_group.market_products[product_index];
+    market_product.name = params.name;
+    market_product.tick_size = params.tick_size;
+    market_product_group.min_base_order_sizes[product_index] = params.min_base_order_size;
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    Ok(())
+}
+
+/// Moves every resting order onto a tick size `tick_ratio` times finer. The AAOB keys orders by
+/// their price in ticks, so each order is cancelled and posted again at the same price with the
+/// same callback info, with `payer` covering their cranker rewards. Orders are posted back in priority order so they keep their place in the
+/// queue, and the open orders of their trader risk groups are pointed at the new order ids.
+fn rescale_resting_orders<'info>(
+    market_product_group: &MarketProductGroup,
+    market_product_group_key: Pubkey,
+    product_index: usize,
+    trader_risk_groups: &[AccountInfo<'info>],
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    aaob_program: &AccountInfo<'info>,
+    orderbook: &AccountInfo<'info>,
+    market_signer: &AccountInfo<'info>,
+    event_queue: &AccountInfo<'info>,
+    bids: &AccountInfo<'info>,
+    asks: &AccountInfo<'info>,
+    market_signer_seeds: &[&[u8]],
+    callback_info_len: usize,
+    cranker_reward: u64,
+    tick_ratio: u64,
+) -> DomainOrProgramResult {
+    // (trader risk group, open orders index, order id, remaining base qty)
+    let mut resting_orders = vec![];
+    for (account_index, account) in trader_risk_groups.iter().enumerate() {
+        let loader = AccountLoader::<TraderRiskGroup>::try_from(account)?;
+        let mut trader_risk_group = loader.load_mut()?;
+        assert_keys_equal(
+            trader_risk_group.market_product_group,
+            market_product_group_key,
+        )?;
+        trader_risk_group.clear_stale_open_orders(market_product_group)?;
+        let mut i = trader_risk_group.open_orders.products[product_index].head_index;
+        while i != SENTINEL {
+            let order_id = trader_risk_group.open_orders.orders[i].id;
+            let order_summary = invoke_cancel_order(
+                aaob_program,
+                orderbook,
+                market_signer,
+                event_queue,
+                bids,
+                asks,
+                market_signer_seeds,
+                order_id,
+            )?;
+            resting_orders.push((account_index, i, order_id, order_summary.total_base_qty));
+            i = trader_risk_group.open_orders.orders[i].next;
+        }
+    }
+    assert_with_msg(
+        Slab::new_from_acc_info(bids, callback_info_len)
+            .root()
+            .is_none()
+            && Slab::new_from_acc_info(asks, callback_info_len)
+                .root()
+                .is_none(),
+        DexError::MissingUserAccount,
+        "The trader risk group of every resting order is needed to change the tick size",
+    )?;
+    // Events would still refer to the cancelled order ids
+    let event_queue_header =
+        EventQueueHeader::deserialize(&mut (&event_queue.data.borrow() as &[u8]))
+            .map_err(ProgramError::from)?;
+    assert(event_queue_header.count == 0, DexError::TransactionAborted)?;
+
+    // The AAOB charges the cranker reward again for every posted order
+    invoke_unchecked(
+        &system_instruction::transfer(
+            payer.key,
+            orderbook.key,
+            cranker_reward
+                .checked_mul(resting_orders.len() as u64)
+                .ok_or(UtilError::NumericalOverflow)?,
+        ),
+        &[
+            payer.to_account_info(),
+            orderbook.clone(),
+            system_program.to_account_info(),
+        ],
+    )?;
+
+    // Bid ids invert the sequence number, so the highest id is the best and oldest bid
+    resting_orders.sort_by_key(
+        |&(_, _, order_id, _)| match get_side_from_order_id(order_id) {
+            Side::Bid => u128::MAX - order_id,
+            Side::Ask => order_id,
+        },
+    );
+    for (account_index, open_orders_idx, order_id, base_qty) in resting_orders {
+        let price_ticks = ((order_id >> 96) as u64)
+            .checked_mul(tick_ratio)
+            .filter(|ticks| *ticks <= u32::MAX as u64)
+            .ok_or(UtilError::NumericalOverflow)?;
+        let account = &trader_risk_groups[account_index];
+        let (order_summary, new_events) = invoke_new_order(
+            aaob_program,
+            orderbook,
+            market_signer,
+            event_queue,
+            bids,
+            asks,
+            market_signer_seeds,
+            agnostic_orderbook::instruction::new_order::Params {
+                max_base_qty: base_qty,
+                max_quote_qty: u64::MAX,
+                limit_price: price_ticks << 32,
+                side: get_side_from_order_id(order_id),
+                match_limit: 1,
+                callback_info: CallBackInfo {
+                    user_account: account.key(),
+                    open_orders_idx: open_orders_idx as u64,
+                }
+                .to_vec(),
+                post_only: true,
+                post_allowed: true,
+                self_trade_behavior: SelfTradeBehavior::AbortTransaction,
+            },
+        )?;
+        let new_order_id = match order_summary.posted_order_id {
+            Some(new_order_id) if new_events == 0 => new_order_id,
+            _ => {
+                msg!(
+                    "Order {} could not be posted at the finer tick size",
+                    order_id
+                );
+                return Err(DexError::TransactionAborted.into());
+            }
+        };
+        let loader = AccountLoader::<TraderRiskGroup>::try_from(account)?;
+        loader.load_mut()?.open_orders.orders[open_orders_idx].id = new_order_id;
+    }
+    Ok(())
+}
+
+/// Cancels an order on the AAOB signed by the product's market signer and returns the summary
+/// of what was left of it
+fn invoke_cancel_order<'info>(
+    aaob_program: &AccountInfo<'info>,
+    orderbook: &AccountInfo<'info>,
+    market_signer: &AccountInfo<'info>,
+    event_queue: &AccountInfo<'info>,
+    bids: &AccountInfo<'info>,
+    asks: &AccountInfo<'info>,
+    market_signer_seeds: &[&[u8]],
+    order_id: u128,
+) -> DomainOrProgramResult<OrderSummary> {
+    invoke_signed_unchecked(
+        &agnostic_orderbook::instruction::cancel_order::Accounts {
+            market: orderbook.key,
+            event_queue: event_queue.key,
+            bids: bids.key,
+            asks: asks.key,
+            authority: market_signer.key,
+        }
+        .get_instruction(
+            aaob_program.key(),
+            agnostic_orderbook::instruction::AgnosticOrderbookInstruction::CancelOrder as u8,
+            agnostic_orderbook::instruction::cancel_order::Params { order_id },
+        ),
+        &[
+            aaob_program.clone(),
+            orderbook.clone(),
+            market_signer.clone(),
+            event_queue.clone(),
+            bids.clone(),
+            asks.clone(),
+        ],
+        &[market_signer_seeds],
+    )?;
+    Ok(read_register(event_queue)
+        .map_err(ProgramError::from)?
+        .ok_or(DexError::OrderNotFound)?)
+}
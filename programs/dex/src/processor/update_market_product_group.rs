@@ -0,0 +1,264 @@
+use std::mem::size_of;
+
+use anchor_lang::{
+    prelude::*,
+    solana_program::{msg, program_error::ProgramError, program_pack::IsInitialized},
+};
+
+use crate::{
+    error::{DexError, DomainOrProgramResult, UtilError},
+    state::{
+        fee_model::TraderFees,
+        market_product_group::{FeeModelConfig, RiskEngineConfig},
+        risk_engine_register::RiskOutputRegister,
+    },
+    utils::{
+        logs::*,
+        validation::{assert, assert_keys_equal, assert_with_msg},
+    },
+    ApplyMarketProductGroupUpdate, UpdateMarketProductGroup, UpdateMarketProductGroupParams,
+};
+
+/// The output register of a new risk engine or fee model is written by that program on every
+/// call, so it has to be owned by it and fit the output
+fn validate_output_register(
+    registers: &[AccountInfo],
+    register: &Pubkey,
+    program_id: &Pubkey,
+    len: usize,
+) -> DomainOrProgramResult {
+    let register = registers
+        .iter()
+        .find(|a| a.key == register)
+        .ok_or_else(|| {
+            msg!("Missing output register {}", register);
+            ProgramError::NotEnoughAccountKeys
+        })?;
+    assert_keys_equal(*register.owner, *program_id)?;
+    assert_with_msg(
+        register.data_len() >= len,
+        ProgramError::AccountDataTooSmall,
+        "Output register is too small",
+    )?;
+    Ok(())
+}
+
+fn validate(
+    params: &UpdateMarketProductGroupParams,
+    registers: &[AccountInfo],
+) -> DomainOrProgramResult {
+    if let Some(risk_engine) = &params.risk_engine {
+        assert(risk_engine.is_valid(), DexError::InvalidDiscriminantLength)?;
+        validate_output_register(
+            registers,
+            &risk_engine.output_register,
+            &risk_engine.program_id,
+            8 + size_of::<RiskOutputRegister>(),
+        )?;
+    }
+    if let Some(fee_model) = &params.fee_model {
+        assert(fee_model.is_valid(), DexError::InvalidDiscriminantLength)?;
+        validate_output_register(
+            registers,
+            &fee_model.output_register,
+            &fee_model.program_id,
+            size_of::<TraderFees>(),
+        )?;
+    }
+    if let Some(ewma_windows) = &params.ewma_windows {
+        assert_with_msg(
+            ewma_windows.iter().all(|w| *w > 0),
+            ProgramError::InvalidArgument,
+            "EWMA windows must be positive",
+        )?;
+    }
+    if let Some(fee_bps_bounds) = &params.fee_bps_bounds {
+        assert_with_msg(
+            fee_bps_bounds.is_valid(),
+            ProgramError::InvalidArgument,
+            "Minimum fee bps must not exceed the maximum",
+        )?;
+    }
+    Ok(())
+}
+
+/// Risk engine and fee model swaps are scheduled rather than applied while
+/// `engine_update_timelock` is set, so traders can exit before the new risk or fee logic applies.
+/// Everything else takes effect immediately. The output registers of the new risk engine and fee
+/// model are passed as remaining accounts. Once a swap applies, each trader moves their risk and
+/// fee state accounts to the new programs with `update_trader_engine_accounts`.
+pub fn process(
+    ctx: Context<UpdateMarketProductGroup>,
+    params: UpdateMarketProductGroupParams,
+) -> DomainOrProgramResult {
+    validate(&params, ctx.remaining_accounts)?;
+    let accts = ctx.accounts;
+    let market_product_group_key = accts.market_product_group.key();
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    if !market_product_group.is_initialized() {
+        msg!("MarketProductGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+
+    if params.cancel_pending_updates {
+        let risk_engine = (market_product_group.pending_risk_engine_effective_at != 0)
+            .then(|| market_product_group.pending_risk_engine);
+        let fee_model = (market_product_group.pending_fee_model_effective_at != 0)
+            .then(|| market_product_group.pending_fee_model);
+        assert(
+            risk_engine.is_some() || fee_model.is_some(),
+            DexError::EngineUpdateNotPending,
+        )?;
+        market_product_group.pending_risk_engine = RiskEngineConfig::default();
+        market_product_group.pending_risk_engine_effective_at = 0;
+        market_product_group.pending_fee_model = FeeModelConfig::default();
+        market_product_group.pending_fee_model_effective_at = 0;
+        emit!(EngineUpdatesCancelled {
+            market_product_group: market_product_group_key,
+            risk_engine,
+            fee_model,
+        });
+    }
+    if let Some(fee_collector) = params.fee_collector {
+        emit!(FeeCollectorUpdated {
+            market_product_group: market_product_group_key,
+            old: market_product_group.fee_collector,
+            new: fee_collector,
+        });
+        market_product_group.fee_collector = fee_collector;
+    }
+    if let Some(ewma_windows) = params.ewma_windows {
+        emit!(EwmaWindowsUpdated {
+            market_product_group: market_product_group_key,
+            old: market_product_group.ewma_windows,
+            new: ewma_windows,
+        });
+        market_product_group.ewma_windows = ewma_windows;
+    }
+    if let Some(fee_bps_bounds) = params.fee_bps_bounds {
+        emit!(FeeBpsBoundsUpdated {
+            market_product_group: market_product_group_key,
+            old: market_product_group.fee_bps_bounds(),
+            new: fee_bps_bounds,
+        });
+        market_product_group.set_fee_bps_bounds(&fee_bps_bounds);
+    }
+    // A lower timelock could be used to rush through a swap that was announced with the old one
+    if let Some(timelock) = params.engine_update_timelock {
+        assert(
+            timelock >= market_product_group.engine_update_timelock,
+            DexError::EngineUpdateTimelockDecreased,
+        )?;
+        emit!(EngineUpdateTimelockUpdated {
+            market_product_group: market_product_group_key,
+            old: market_product_group.engine_update_timelock,
+            new: timelock,
+        });
+        market_product_group.engine_update_timelock = timelock;
+    }
+
+    let timelock = market_product_group.engine_update_timelock;
+    let effective_at = if timelock > 0 {
+        Clock::get()?.unix_timestamp + timelock as i64
+    } else {
+        0
+    };
+    if let Some(risk_engine) = params.risk_engine {
+        let old = market_product_group.risk_engine_config();
+        if timelock > 0 {
+            market_product_group.pending_risk_engine = risk_engine;
+            market_product_group.pending_risk_engine_effective_at = effective_at;
+            emit!(RiskEngineUpdateScheduled {
+                market_product_group: market_product_group_key,
+                old,
+                new: risk_engine,
+                effective_at,
+            });
+        } else {
+            market_product_group.set_risk_engine_config(&risk_engine);
+            emit!(RiskEngineUpdated {
+                market_product_group: market_product_group_key,
+                old,
+                new: risk_engine,
+            });
+        }
+    }
+    if let Some(fee_model) = params.fee_model {
+        let old = market_product_group.fee_model_config();
+        if timelock > 0 {
+            market_product_group.pending_fee_model = fee_model;
+            market_product_group.pending_fee_model_effective_at = effective_at;
+            emit!(FeeModelUpdateScheduled {
+                market_product_group: market_product_group_key,
+                old,
+                new: fee_model,
+                effective_at,
+            });
+        } else {
+            market_product_group.set_fee_model_config(&fee_model);
+            emit!(FeeModelUpdated {
+                market_product_group: market_product_group_key,
+                old,
+                new: fee_model,
+            });
+        }
+    }
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    market_product_group_key.log();
+    Ok(())
+}
+
+/// Permissionless, applies the scheduled swaps whose timelock has passed
+pub fn apply(ctx: Context<ApplyMarketProductGroupUpdate>) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let market_product_group_key = accts.market_product_group.key();
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    if !market_product_group.is_initialized() {
+        msg!("MarketProductGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    let risk_engine_effective_at = market_product_group.pending_risk_engine_effective_at;
+    let fee_model_effective_at = market_product_group.pending_fee_model_effective_at;
+    assert(
+        risk_engine_effective_at != 0 || fee_model_effective_at != 0,
+        DexError::EngineUpdateNotPending,
+    )?;
+    let now = Clock::get()?.unix_timestamp;
+    let apply_risk_engine = risk_engine_effective_at != 0 && risk_engine_effective_at <= now;
+    let apply_fee_model = fee_model_effective_at != 0 && fee_model_effective_at <= now;
+    assert(
+        apply_risk_engine || apply_fee_model,
+        DexError::EngineUpdateTimelocked,
+    )?;
+
+    if apply_risk_engine {
+        let old = market_product_group.risk_engine_config();
+        let new = market_product_group.pending_risk_engine;
+        market_product_group.set_risk_engine_config(&new);
+        market_product_group.pending_risk_engine = RiskEngineConfig::default();
+        market_product_group.pending_risk_engine_effective_at = 0;
+        emit!(RiskEngineUpdated {
+            market_product_group: market_product_group_key,
+            old,
+            new,
+        });
+    }
+    if apply_fee_model {
+        let old = market_product_group.fee_model_config();
+        let new = market_product_group.pending_fee_model;
+        market_product_group.set_fee_model_config(&new);
+        market_product_group.pending_fee_model = FeeModelConfig::default();
+        market_product_group.pending_fee_model_effective_at = 0;
+        emit!(FeeModelUpdated {
+            market_product_group: market_product_group_key,
+            old,
+            new,
+        });
+    }
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    market_product_group_key.log();
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{msg, program_error::ProgramError, program_pack::IsInitialized},
+};
+
+use crate::{
+    error::{DomainOrProgramResult, UtilError},
+    utils::{
+        numeric::ZERO_FRAC,
+        validation::{assert_keys_equal, assert_with_msg},
+    },
+    UpdatePositionLimits, UpdatePositionLimitsParams,
+};
+
+/// Limits only apply to fills after the update, existing positions and open interest above
+/// the new limits can still be reduced.
+pub fn process(
+    ctx: Context<UpdatePositionLimits>,
+    params: UpdatePositionLimitsParams,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    if !market_product_group.is_initialized() {
+        msg!("MarketProductGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+    assert_with_msg(
+        params.max_open_interest >= ZERO_FRAC && params.max_position >= ZERO_FRAC,
+        ProgramError::InvalidArgument,
+        "Position limits must not be negative",
+    )?;
+    let (product_index, _) = market_product_group.find_product_index(&accts.product.key())?;
+    let outright = market_product_group.market_products[product_index].try_to_outright_mut()?;
+    outright.max_open_interest = params.max_open_interest;
+    outright.max_position = params.max_position;
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    Ok(())
+}
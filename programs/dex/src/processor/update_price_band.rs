@@ -0,0 +1,48 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{msg, program_error::ProgramError, program_pack::IsInitialized},
+};
+
+use crate::{
+    error::{DomainOrProgramResult, UtilError},
+    state::market_product_group::PriceBand,
+    utils::{
+        numeric::ZERO_FRAC,
+        validation::{assert_keys_equal, assert_with_msg},
+    },
+    UpdatePriceBand, UpdatePriceBandParams,
+};
+
+/// The band is centered on the product's EWMA midpoint, so it only takes effect once the
+/// product has traded or had its book sampled.
+pub fn process(
+    ctx: Context<UpdatePriceBand>,
+    params: UpdatePriceBandParams,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+    if !market_product_group.is_initialized() {
+        msg!("MarketProductGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    assert_keys_equal(accts.authority.key(), market_product_group.authority)?;
+    assert_with_msg(
+        params.width >= ZERO_FRAC,
+        ProgramError::InvalidArgument,
+        "Price band width must not be negative",
+    )?;
+    let (product_index, product) = market_product_group.find_product_index(&accts.product.key())?;
+    assert_with_msg(
+        (params.ewma_window as usize) < product.prices.ewma_bid.len(),
+        ProgramError::InvalidArgument,
+        "Invalid EWMA window",
+    )?;
+    market_product_group.price_bands[product_index] = PriceBand {
+        bps: params.bps,
+        width: params.width,
+        ewma_window: params.ewma_window,
+    };
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    Ok(())
+}
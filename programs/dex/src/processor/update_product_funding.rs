@@ -22,6 +22,11 @@ pub fn process(
     )?;
 
     let (idx, _) = market_product_group.find_product_index(&accts.product.key())?;
+    // Spot balances are never cash settled or cleared on expiry
+    assert(
+        !market_product_group.is_spot(idx),
+        DexError::SpotProductNotSupported,
+    )?;
     let cash_decimals = market_product_group.decimals;
     let product = market_product_group.market_products[idx].try_to_outright_mut()?;
     product.apply_new_funding(params.amount, cash_decimals)?;
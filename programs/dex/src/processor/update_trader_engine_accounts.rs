@@ -0,0 +1,97 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{msg, program_error::ProgramError, program_pack::IsInitialized},
+};
+
+use crate::{
+    error::{DomainOrProgramResult, UtilError},
+    utils::{
+        cpi::create_risk_state_account,
+        logs::TraderEngineAccountsUpdated,
+        validation::{assert_keys_equal, assert_with_msg},
+    },
+    UpdateTraderEngineAccounts,
+};
+
+/// Moves the trader's risk and fee state accounts to the risk engine and fee model of the group
+/// after either was swapped by `update_market_product_group`. Only the accounts still owned by a
+/// swapped out program are replaced: a new risk state account is created through the risk engine
+/// like in `initialize_trader_risk_group` and the new fee state account must already be
+/// initialized by the fee model.
+pub fn process<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, UpdateTraderEngineAccounts<'info>>,
+) -> DomainOrProgramResult {
+    let accts = ctx.accounts;
+    let mut trader_risk_group = accts.trader_risk_group.load_mut()?;
+    let market_product_group = accts.market_product_group.load()?;
+    if !trader_risk_group.is_initialized() {
+        msg!("TraderRiskGroup account is not initialized");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+    assert_keys_equal(trader_risk_group.owner, accts.owner.key())?;
+    assert_keys_equal(
+        trader_risk_group.market_product_group,
+        accts.market_product_group.key(),
+    )?;
+    assert_keys_equal(
+        trader_risk_group.risk_state_account,
+        accts.old_trader_risk_state_acct.key(),
+    )?;
+    assert_keys_equal(
+        trader_risk_group.fee_state_account,
+        accts.old_trader_fee_state_acct.key(),
+    )?;
+    let risk_engine_swapped =
+        *accts.old_trader_risk_state_acct.owner != market_product_group.risk_engine_program_id;
+    let fee_model_swapped =
+        *accts.old_trader_fee_state_acct.owner != market_product_group.fee_model_program_id;
+    assert_with_msg(
+        risk_engine_swapped || fee_model_swapped,
+        ProgramError::InvalidArgument,
+        "Trader state accounts are owned by the current risk engine and fee model",
+    )?;
+
+    if fee_model_swapped {
+        assert_keys_equal(
+            *accts.trader_fee_state_acct.owner,
+            market_product_group.fee_model_program_id,
+        )?;
+        trader_risk_group.fee_state_account = accts.trader_fee_state_acct.key();
+    }
+    if risk_engine_swapped {
+        assert_keys_equal(
+            accts.risk_engine_program.key(),
+            market_product_group.risk_engine_program_id,
+        )?;
+        // Risk account should not be initialized
+        assert_keys_equal(
+            *accts.trader_risk_state_acct.owner,
+            accts.system_program.key(),
+        )?;
+        create_risk_state_account(
+            &accts.risk_engine_program,
+            &accts.owner,
+            &accts.risk_signer,
+            &accts.trader_risk_state_acct,
+            &accts.market_product_group,
+            &accts.system_program,
+            &ctx.remaining_accounts,
+            market_product_group
+                .create_risk_state_account_discriminant
+                .to_vec(),
+            market_product_group.risk_and_fee_bump as u8,
+        )?;
+        // Risk state account should be initialized and assigned to the risk engine program
+        assert_keys_equal(
+            *accts.trader_risk_state_acct.owner,
+            market_product_group.risk_engine_program_id,
+        )?;
+        trader_risk_group.risk_state_account = accts.trader_risk_state_acct.key();
+    }
+    emit!(TraderEngineAccountsUpdated {
+        trader_risk_group: accts.trader_risk_group.key(),
+        risk_state_account: trader_risk_group.risk_state_account,
+        fee_state_account: trader_risk_group.fee_state_account,
+    });
+    Ok(())
+}
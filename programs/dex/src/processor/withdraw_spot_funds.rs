@@ -0,0 +1,152 @@
+use anchor_lang::{
+    prelude::*,
+    solana_program::{
+        msg, program::invoke_signed_unchecked, program_pack::IsInitialized, pubkey::Pubkey,
+    },
+};
+
+use crate::{
+    error::{DexError, DomainOrProgramResult, UtilError},
+    state::risk_engine_register::*,
+    utils::{
+        cpi::risk_check,
+        numeric::ZERO_FRAC,
+        validation::{assert, assert_keys_equal, assert_valid_token_account_owner},
+    },
+    DomainOrProgramError, WithdrawSpotFunds, WithdrawSpotFundsParams,
+};
+
+fn validate(ctx: &Context<WithdrawSpotFunds>) -> std::result::Result<usize, DomainOrProgramError> {
+    let accts = &ctx.accounts;
+    let trader_risk_group = accts.trader_risk_group.load()?;
+    let market_product_group = accts.market_product_group.load()?;
+
+    if !trader_risk_group.is_initialized() {
+        msg!("TraderRiskGroup account is not initialized yet!");
+        return Err(UtilError::AccountUninitialized.into());
+    }
+
+    assert_keys_equal(
+        market_product_group.risk_engine_program_id,
+        accts.risk_engine_program.key(),
+    )?;
+    assert_keys_equal(
+        trader_risk_group.market_product_group,
+        accts.market_product_group.key(),
+    )?;
+    assert_valid_token_account_owner(accts.user_token_account.as_ref(), &accts.user.key())?;
+    assert_keys_equal(accts.user.key(), trader_risk_group.owner)?;
+    assert_keys_equal(
+        trader_risk_group.risk_state_account,
+        accts.trader_risk_state_acct.key(),
+    )?;
+    assert_keys_equal(
+        market_product_group.risk_model_configuration_acct,
+        accts.risk_model_configuration_acct.key(),
+    )?;
+    let (product_index, _) = market_product_group.find_outright(&accts.product.key())?;
+    assert(
+        market_product_group.is_spot(product_index),
+        DexError::ProductNotSpot,
+    )?;
+    Ok(product_index)
+}
+
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, WithdrawSpotFunds<'info>>,
+    params: WithdrawSpotFundsParams,
+) -> DomainOrProgramResult {
+    let product_index = validate(&ctx)?;
+    let accts = ctx.accounts;
+    let mut trader_risk_group = accts.trader_risk_group.load_mut()?;
+    let mut market_product_group = accts.market_product_group.load_mut()?;
+
+    let WithdrawSpotFundsParams { quantity } = params;
+
+    let spot_market = market_product_group.spot_markets[product_index];
+    let base_decimals = market_product_group.market_products[product_index].base_decimals;
+    let quantity = quantity.round(base_decimals as u32)?;
+    assert(quantity > ZERO_FRAC, DexError::FundsError)?;
+    let token_quantity = quantity.round(spot_market.base_decimals as u32)?;
+
+    let base_vault_seeds = &[
+        b"base_vault",
+        accts.market_product_group.as_ref().key.as_ref(),
+        accts.product.key.as_ref(),
+        &[spot_market.base_vault_bump as u8],
+    ];
+    let base_vault_key = Pubkey::create_program_address(base_vault_seeds, ctx.program_id)?;
+    assert_keys_equal(base_vault_key, accts.base_vault.key())?;
+
+    trader_risk_group.apply_all_funding(&mut market_product_group)?;
+    assert(
+        trader_risk_group.is_active_product(product_index)?,
+        DexError::InsufficientBaseBalance,
+    )?;
+    // Base reserved by resting asks can't be withdrawn
+    let position_index = trader_risk_group.active_products[product_index] as usize;
+    let trader_position = trader_risk_group.trader_positions[position_index];
+    let ask_qty_in_book = trader_risk_group.open_orders.products[product_index].ask_qty_in_book;
+    assert(
+        trader_position
+            .position
+            .checked_add(trader_position.pending_position)?
+            .checked_sub(ask_qty_in_book)?
+            >= quantity,
+        DexError::InsufficientBaseBalance,
+    )?;
+
+    let token_transfer_instruction = spl_token::instruction::transfer(
+        &accts.token_program.key(),
+        &accts.base_vault.key(),
+        &accts.user_token_account.key(),
+        &accts.base_vault.key(),
+        &[],
+        token_quantity.m as u64,
+    )?;
+    invoke_signed_unchecked(
+        &token_transfer_instruction,
+        &[
+            accts.token_program.to_account_info(),
+            accts.base_vault.to_account_info(),
+            accts.user_token_account.to_account_info(),
+        ],
+        &[base_vault_seeds],
+    )?;
+
+    trader_risk_group.trader_positions[position_index].position -= quantity;
+
+    // Base balances count as collateral, so withdrawing them must leave the account healthy
+    let risk_engine_output = risk_check(
+        &accts.risk_engine_program,
+        &accts.market_product_group,
+        &accts.trader_risk_group,
+        &accts.risk_output_register,
+        &accts.trader_risk_state_acct,
+        &accts.risk_model_configuration_acct,
+        &accts.risk_signer,
+        ctx.remaining_accounts,
+        &OrderInfo {
+            operation_type: OperationType::CheckHealth,
+            ..Default::default()
+        },
+        market_product_group.get_validate_account_health_discriminant(),
+        market_product_group.risk_and_fee_bump as u8,
+    )?;
+
+    let health_info = match risk_engine_output {
+        HealthResult::Health { health_info: v } => v,
+        HealthResult::Liquidation {
+            liquidation_info: _,
+        } => return Err(DexError::InvalidAccountHealthError.into()),
+    };
+
+    if health_info.action != ActionStatus::Approved {
+        return Err(DexError::InvalidAccountHealthError.into());
+    }
+
+    market_product_group.sequence_number += 1;
+    msg!("sequence: {}", market_product_group.sequence_number);
+    accts.market_product_group.key().log();
+    Ok(())
+}
@@ -14,8 +14,10 @@ pub const MAX_PRODUCTS: usize = 256;
 #[constant]
 pub const HEALTH_BUFFER_LEN: usize = 32;
 
+// Equal to MAX_OUTRIGHTS so a trader can hold a position in every outright, trader risk groups
+// created with fewer positions are grown with migrate_trader_risk_group
 #[constant]
-pub const MAX_TRADER_POSITIONS: usize = 16;
+pub const MAX_TRADER_POSITIONS: usize = 128;
 
 #[constant]
 pub const MAX_OPEN_ORDERS_PER_POSITION: u64 = 256;
@@ -50,7 +52,14 @@ pub const CALLBACK_ID_LEN: u64 = 32;
 pub const MAX_COMBOS: usize = 128;
 
 #[constant]
-pub const MAX_LEGS: usize = 4;
+pub const MAX_LEGS: usize = 8;
+
+/// Bound on the absolute ratio of each combo leg, which multiplies positions and prices
+#[constant]
+pub const MAX_LEG_RATIO: i32 = 1000;
+
+#[constant]
+pub const MAX_COLLATERALS: usize = 8;
 
 // timing constants
 #[constant]
@@ -64,3 +73,7 @@ pub const SLOTS_15_MIN: u64 = 2250;
 
 #[constant]
 pub const SLOTS_60_MIN: u64 = 9000;
+
+// index prices older than this are not used as the reference of a price band
+#[constant]
+pub const INDEX_PRICE_MAX_AGE_SLOTS: u64 = SLOTS_5_MIN;
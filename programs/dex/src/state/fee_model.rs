@@ -24,6 +24,20 @@ pub struct TraderFeeParams {
     pub product: Pubkey,
 }
 
+/// Passed to the optional `on_post` and `on_cancel` hooks of the fee model.
+/// `base_qty` is the quantity that was added to (or removed from) the book.
+/// `on_cancel` only fires from `cancel_order`, which includes cancels of an unhealthy trader's orders.
+/// Resting size that leaves the book through fills or product expiry is not reported, so fee models
+/// that track resting size should reconcile against the open orders of the trader risk group,
+/// which is passed to every hook.
+#[derive(Copy, Clone, Debug, AnchorDeserialize, AnchorSerialize)]
+pub struct OrderEventParams {
+    pub side: Side,
+    pub order_id: u128,
+    pub base_qty: Fractional,
+    pub product: Pubkey,
+}
+
 // 10_000 bps == 100%
 const MAX_FEE_BPS: i32 = 10_000;
 const MIN_FEE_BPS: i32 = -10_000;
@@ -0,0 +1,368 @@
+//! Layout of market product groups created before combos could have `MAX_LEGS` legs and before
+//! the fields after `sequence_number` were appended, and of trader risk groups created with fewer
+//! than `MAX_TRADER_POSITIONS` positions and before the fields after `open_orders` were appended.
+//! Only used to migrate those accounts.
+
+use std::mem::size_of;
+
+use anchor_lang::{prelude::*, solana_program::clock::UnixTimestamp};
+
+use crate::{
+    state::{
+        constants::{MAX_COLLATERALS, MAX_OUTRIGHTS, MAX_PRODUCTS, MAX_TRADER_POSITIONS, NAME_LEN},
+        enums::AccountTag,
+        products::{Leg, Outright, ProductMetadata},
+        trader_risk_group::{TraderPosition, TraderRiskGroup},
+    },
+    utils::{bitset::Bitset, numeric::Fractional},
+};
+
+pub const LEGACY_MAX_LEGS: usize = 4;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LegacyCombo {
+    pub metadata: ProductMetadata,
+    pub num_legs: usize,
+    pub legs: [Leg; LEGACY_MAX_LEGS],
+}
+
+#[repr(C, u64)]
+#[derive(Clone, Copy)]
+pub enum LegacyProduct {
+    Outright { outright: Outright },
+    Combo { combo: LegacyCombo },
+}
+
+/// Fields before `market_products`, which kept their layout
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct MarketProductGroupHead {
+    pub tag: AccountTag,
+    pub name: [u8; NAME_LEN],
+    pub authority: Pubkey,
+    pub successor: Pubkey,
+    pub vault_mint: Pubkey,
+    pub collected_fees: Fractional,
+    pub fee_collector: Pubkey,
+    pub decimals: u64,
+    pub risk_engine_program_id: Pubkey,
+    pub fee_model_program_id: Pubkey,
+    pub fee_model_configuration_acct: Pubkey,
+    pub risk_model_configuration_acct: Pubkey,
+    pub active_flags_products: Bitset,
+    pub ewma_windows: [u64; 4],
+}
+
+/// Fields between `market_products` and the end of the legacy account, which kept their layout
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LegacyMarketProductGroupTail {
+    pub vault_bump: u16,
+    pub risk_and_fee_bump: u16,
+    pub find_fees_discriminant_len: u16,
+    pub validate_account_discriminant_len: u16,
+    pub find_fees_discriminant: [u8; 8],
+    pub validate_account_health_discriminant: [u8; 8],
+    pub validate_account_liquidation_discriminant: [u8; 8],
+    pub create_risk_state_account_discriminant: [u8; 8],
+    pub max_maker_fee_bps: i16,
+    pub min_maker_fee_bps: i16,
+    pub max_taker_fee_bps: i16,
+    pub min_taker_fee_bps: i16,
+    pub fee_output_register: Pubkey,
+    pub risk_output_register: Pubkey,
+    // u128 is 8 byte aligned on chain
+    pub sequence_number: [u64; 2],
+}
+
+/// Length of a legacy market product group account, including the discriminator
+pub fn legacy_market_product_group_len() -> usize {
+    8 + size_of::<MarketProductGroupHead>()
+        + MAX_PRODUCTS * size_of::<LegacyProduct>()
+        + size_of::<LegacyMarketProductGroupTail>()
+}
+
+/// Offset of the authority in the account data of both layouts
+pub fn authority_offset() -> usize {
+    8 + size_of::<AccountTag>() + NAME_LEN
+}
+
+/// Moves the products and the fields after them from their legacy offsets to the current ones.
+/// `data` is the whole account data at its current length, with the legacy account at its start
+/// and zeroes after it. Products are grown in place: their legacy bytes are a prefix of the
+/// current layout and the legs that were added are zeroed.
+pub fn migrate_market_product_group_data(data: &mut [u8], product_len: usize) {
+    let legacy_product_len = size_of::<LegacyProduct>();
+    let products_offset = 8 + size_of::<MarketProductGroupHead>();
+    let tail_len = size_of::<LegacyMarketProductGroupTail>();
+    let legacy_tail_offset = products_offset + MAX_PRODUCTS * legacy_product_len;
+    let tail_offset = products_offset + MAX_PRODUCTS * product_len;
+    // Everything only moves towards the end, so the last bytes are moved first
+    data.copy_within(
+        legacy_tail_offset..legacy_tail_offset + tail_len,
+        tail_offset,
+    );
+    for i in (0..MAX_PRODUCTS).rev() {
+        let src = products_offset + i * legacy_product_len;
+        let dst = products_offset + i * product_len;
+        data.copy_within(src..src + legacy_product_len, dst);
+        data[dst + legacy_product_len..dst + product_len].fill(0);
+    }
+}
+
+/// Fields of a trader risk group before `trader_positions`, which kept their layout
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TraderRiskGroupHead {
+    pub tag: AccountTag,
+    pub market_product_group: Pubkey,
+    pub owner: Pubkey,
+    pub active_products: [u8; MAX_OUTRIGHTS],
+    pub total_deposited: Fractional,
+    pub total_withdrawn: Fractional,
+    pub cash_balance: Fractional,
+    pub pending_cash_balance: Fractional,
+    pub pending_fees: Fractional,
+    pub valid_until: UnixTimestamp,
+    pub maker_fee_bps: i32,
+    pub taker_fee_bps: i32,
+}
+
+pub const LEGACY_TRADER_POSITIONS: usize = 16;
+
+/// Fields appended after `open_orders`, which legacy trader risk groups don't have
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct TraderRiskGroupAppendedTail {
+    pub collateral_balances: [Fractional; MAX_COLLATERALS],
+    pub pending_fees_quote_qty: Fractional,
+    pub open_orders_epochs: [u64; MAX_PRODUCTS],
+}
+
+/// Length of a legacy trader risk group account, including the discriminator
+pub fn legacy_trader_risk_group_len() -> usize {
+    8 + size_of::<TraderRiskGroup>()
+        - (MAX_TRADER_POSITIONS - LEGACY_TRADER_POSITIONS) * size_of::<TraderPosition>()
+        - size_of::<TraderRiskGroupAppendedTail>()
+}
+
+/// Number of trader positions of a trader risk group account of `len` bytes, including the
+/// discriminator, that already has the appended fields. Such groups that are being migrated only
+/// differ in the length of `trader_positions`, so the count follows from the length.
+pub fn trader_risk_group_num_positions(len: usize) -> Option<usize> {
+    let missing = (8 + size_of::<TraderRiskGroup>()).checked_sub(len)?;
+    let position_len = size_of::<TraderPosition>();
+    if missing % position_len != 0 {
+        return None;
+    }
+    MAX_TRADER_POSITIONS.checked_sub(missing / position_len)
+}
+
+/// Offset of the owner in the account data of trader risk groups with any number of positions
+pub fn trader_risk_group_owner_offset() -> usize {
+    8 + size_of::<AccountTag>() + size_of::<Pubkey>()
+}
+
+/// Grows `trader_positions` from `num_positions` to `new_num_positions` by moving the fields
+/// after them towards the end. `data` is the whole account data at its new length, with the
+/// account of `num_positions` positions at its start and zeroes after it. The added positions are
+/// zeroed, which leaves them uninitialized. A legacy account is grown the same way, its appended
+/// fields are then zeroed as well.
+pub fn grow_trader_positions(data: &mut [u8], num_positions: usize, new_num_positions: usize) {
+    let position_len = size_of::<TraderPosition>();
+    let positions_offset = 8 + size_of::<TraderRiskGroupHead>();
+    let tail_offset = positions_offset + num_positions * position_len;
+    let new_tail_offset = positions_offset + new_num_positions * position_len;
+    let tail_len = data.len() - new_tail_offset;
+    data.copy_within(tail_offset..tail_offset + tail_len, new_tail_offset);
+    data[tail_offset..new_tail_offset].fill(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{
+        market_product_group::MarketProductGroup,
+        products::{Combo, Product},
+    };
+    use std::ptr::addr_of;
+
+    /// Zeroed account data of `len` bytes, aligned so the account fits after the discriminator
+    fn account_data(len: usize) -> Vec<u128> {
+        vec![0u128; len / 16 + 2]
+    }
+
+    fn offset<T>(base: &MarketProductGroup, field: *const T) -> usize {
+        field as usize - base as *const MarketProductGroup as usize
+    }
+
+    #[test]
+    fn test_legacy_layout() {
+        let len = 8 + size_of::<MarketProductGroup>();
+        let words = account_data(len);
+        let data = &bytemuck::cast_slice::<u128, u8>(&words)[8..8 + len];
+        let mpg: &MarketProductGroup = bytemuck::from_bytes(&data[8..]);
+        let market_products = offset(mpg, addr_of!(mpg.market_products));
+        assert_eq!(market_products, size_of::<MarketProductGroupHead>());
+        assert_eq!(offset(mpg, addr_of!(mpg.authority)) + 8, authority_offset());
+        // The tail starts right after the products and ends with the sequence number
+        let vault_bump = offset(mpg, addr_of!(mpg.vault_bump));
+        assert_eq!(
+            vault_bump,
+            market_products + MAX_PRODUCTS * size_of::<Product>()
+        );
+        assert_eq!(
+            offset(mpg, addr_of!(mpg.sequence_number)) + 16 - vault_bump,
+            size_of::<LegacyMarketProductGroupTail>()
+        );
+    }
+
+    #[test]
+    fn test_migrate_market_product_group_data() {
+        let len = 8 + size_of::<MarketProductGroup>();
+        let mut expected_words = account_data(len);
+        let expected = &mut bytemuck::cast_slice_mut::<u128, u8>(&mut expected_words)[8..8 + len];
+        {
+            let market_product_group: &mut MarketProductGroup =
+                bytemuck::from_bytes_mut(&mut expected[8..]);
+            market_product_group.authority = Pubkey::new_unique();
+            market_product_group.sequence_number = 42;
+            market_product_group.fee_output_register = Pubkey::new_unique();
+            let mut outright = Outright::default();
+            outright.product_key = Pubkey::new_unique();
+            outright.max_position = Fractional::from(7);
+            market_product_group.market_products[0] = Product::Outright { outright };
+            let mut combo = Combo::default();
+            combo.product_key = Pubkey::new_unique();
+            combo.num_legs = LEGACY_MAX_LEGS;
+            for (i, leg) in combo.legs.iter_mut().take(LEGACY_MAX_LEGS).enumerate() {
+                leg.product_index = i;
+                leg.ratio = i as i64 + 1;
+            }
+            market_product_group.market_products[MAX_PRODUCTS - 1] = Product::Combo { combo };
+        }
+        expected[..8].copy_from_slice(&[1; 8]);
+
+        // Write the same group in the legacy layout
+        let product_len = size_of::<Product>();
+        let legacy_product_len = size_of::<LegacyProduct>();
+        let products_offset = 8 + size_of::<MarketProductGroupHead>();
+        let tail_len = size_of::<LegacyMarketProductGroupTail>();
+        let mut words = account_data(len);
+        let data = &mut bytemuck::cast_slice_mut::<u128, u8>(&mut words)[8..8 + len];
+        data[..products_offset].copy_from_slice(&expected[..products_offset]);
+        for i in 0..MAX_PRODUCTS {
+            let src = products_offset + i * product_len;
+            let dst = products_offset + i * legacy_product_len;
+            data[dst..dst + legacy_product_len]
+                .copy_from_slice(&expected[src..src + legacy_product_len]);
+        }
+        let tail_offset = products_offset + MAX_PRODUCTS * product_len;
+        let legacy_tail_offset = products_offset + MAX_PRODUCTS * legacy_product_len;
+        data[legacy_tail_offset..legacy_tail_offset + tail_len]
+            .copy_from_slice(&expected[tail_offset..tail_offset + tail_len]);
+        assert_eq!(
+            legacy_tail_offset + tail_len,
+            legacy_market_product_group_len()
+        );
+
+        migrate_market_product_group_data(data, product_len);
+        assert!(data == expected);
+    }
+
+    #[test]
+    fn test_trader_risk_group_layout() {
+        let len = 8 + size_of::<TraderRiskGroup>();
+        let words = account_data(len);
+        let data = &bytemuck::cast_slice::<u128, u8>(&words)[8..8 + len];
+        let trg: &TraderRiskGroup = bytemuck::from_bytes(&data[8..]);
+        let base = trg as *const TraderRiskGroup as usize;
+        assert_eq!(
+            addr_of!(trg.trader_positions) as usize - base,
+            size_of::<TraderRiskGroupHead>()
+        );
+        assert_eq!(
+            addr_of!(trg.owner) as usize - base + 8,
+            trader_risk_group_owner_offset()
+        );
+        assert_eq!(
+            trader_risk_group_num_positions(len),
+            Some(MAX_TRADER_POSITIONS)
+        );
+        assert_eq!(
+            trader_risk_group_num_positions(len - 16 * size_of::<TraderPosition>()),
+            Some(MAX_TRADER_POSITIONS - 16)
+        );
+        assert_eq!(trader_risk_group_num_positions(len - 1), None);
+        assert_eq!(trader_risk_group_num_positions(len + 1), None);
+        // The appended fields end the account and legacy accounts can't be mistaken for ones
+        // that are being migrated
+        let collateral_balances = addr_of!(trg.collateral_balances) as usize - base;
+        assert_eq!(
+            addr_of!(trg.open_orders_epochs) as usize - base + size_of::<[u64; MAX_PRODUCTS]>(),
+            collateral_balances + size_of::<TraderRiskGroupAppendedTail>()
+        );
+        assert_eq!(size_of::<TraderRiskGroupAppendedTail>(), 2192);
+        assert_eq!(
+            trader_risk_group_num_positions(legacy_trader_risk_group_len()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_grow_trader_positions() {
+        let len = 8 + size_of::<TraderRiskGroup>();
+        let mut expected_words = account_data(len);
+        let expected = &mut bytemuck::cast_slice_mut::<u128, u8>(&mut expected_words)[8..8 + len];
+        let collateral_balances_offset;
+        {
+            let trader_risk_group: &mut TraderRiskGroup =
+                bytemuck::from_bytes_mut(&mut expected[8..]);
+            trader_risk_group.tag = AccountTag::TraderRiskGroup;
+            trader_risk_group.owner = Pubkey::new_unique();
+            trader_risk_group.cash_balance = Fractional::from(100);
+            for (i, position) in trader_risk_group
+                .trader_positions
+                .iter_mut()
+                .take(LEGACY_TRADER_POSITIONS)
+                .enumerate()
+            {
+                position.tag = AccountTag::TraderPosition;
+                position.product_key = Pubkey::new_unique();
+                position.product_index = i;
+                position.position = Fractional::from(i as i64 - 8);
+            }
+            trader_risk_group.risk_state_account = Pubkey::new_unique();
+            trader_risk_group.client_order_id = 42;
+            trader_risk_group.open_orders.total_open_orders = 3;
+            collateral_balances_offset = 8 + addr_of!(trader_risk_group.collateral_balances)
+                as usize
+                - trader_risk_group as *const TraderRiskGroup as usize;
+        }
+        expected[..8].copy_from_slice(&[1; 8]);
+
+        // Write the same group in the legacy layout, its appended fields are expected to be zeroed
+        let position_len = size_of::<TraderPosition>();
+        let missing = (MAX_TRADER_POSITIONS - LEGACY_TRADER_POSITIONS) * position_len;
+        let tail_offset =
+            8 + size_of::<TraderRiskGroupHead>() + LEGACY_TRADER_POSITIONS * position_len;
+        let legacy_len = collateral_balances_offset - missing;
+        assert_eq!(legacy_len, legacy_trader_risk_group_len());
+        let mut words = account_data(len);
+        let data = &mut bytemuck::cast_slice_mut::<u128, u8>(&mut words)[8..8 + len];
+        data[..tail_offset].copy_from_slice(&expected[..tail_offset]);
+        data[tail_offset..legacy_len]
+            .copy_from_slice(&expected[tail_offset + missing..collateral_balances_offset]);
+
+        // The first step also appends the missing fields, like migrate_trader_risk_group
+        let step = 50;
+        let step_len = legacy_len
+            + size_of::<TraderRiskGroupAppendedTail>()
+            + (step - LEGACY_TRADER_POSITIONS) * position_len;
+        grow_trader_positions(&mut data[..step_len], LEGACY_TRADER_POSITIONS, step);
+        assert_eq!(trader_risk_group_num_positions(step_len), Some(step));
+        grow_trader_positions(data, step, MAX_TRADER_POSITIONS);
+        assert!(data == expected);
+    }
+}
@@ -22,7 +22,7 @@ use crate::{
     utils::{
         bitset::Bitset,
         loadable::Loadable,
-        numeric::{Fractional, ZERO_FRAC},
+        numeric::{bps, Fractional, ZERO_FRAC},
         validation::assert,
         TwoIterators,
     },
@@ -66,6 +66,39 @@ pub struct MarketProductGroup {
     pub fee_output_register: Pubkey,
     pub risk_output_register: Pubkey,
     pub sequence_number: u128,
+    // optional fee model hooks, a length of 0 means the hook is disabled
+    pub on_post_discriminant_len: u16,
+    pub on_cancel_discriminant_len: u16,
+    pub on_post_discriminant: [u8; 8],
+    pub on_cancel_discriminant: [u8; 8],
+    // number of leading remaining accounts in new_order that are forwarded to the fee model
+    pub fee_accounts_len: u16,
+    // total maker rebates paid out, these are netted out of collected_fees
+    pub rebates_paid: Fractional,
+    // optional account besides the authority that may list new products, e.g. a program PDA
+    pub product_lister: Pubkey,
+    // minimum order size of each product on top of the orderbook's min_base_order_size, 0 if unset
+    #[serde(with = "BigArray")]
+    pub min_base_order_sizes: [u64; MAX_PRODUCTS],
+    // limit prices of new orders are bounded around the EWMA mid of each product, 0 if unset
+    #[serde(with = "BigArray")]
+    pub price_bands: [PriceBand; MAX_PRODUCTS],
+    // base mint and vault of physically settled products, the default pubkey for cash settled ones
+    #[serde(with = "BigArray")]
+    pub spot_markets: [SpotMarket; MAX_PRODUCTS],
+    // accepted collateral mints besides vault_mint, the default mint for unused slots
+    pub collaterals: [Collateral; MAX_COLLATERALS],
+    // delay in seconds of risk engine and fee model swaps, 0 applies them immediately
+    pub engine_update_timelock: u64,
+    // swaps waiting for the timelock, an effective_at of 0 means nothing is pending
+    pub pending_risk_engine: RiskEngineConfig,
+    pub pending_risk_engine_effective_at: i64,
+    pub pending_fee_model: FeeModelConfig,
+    pub pending_fee_model_effective_at: i64,
+    // incremented whenever a product is listed at the index, so open orders of traders on a
+    // removed product are not attributed to the one listed after it
+    #[serde(with = "BigArray")]
+    pub product_epochs: [u64; MAX_PRODUCTS],
 }
 
 impl Default for MarketProductGroup {
@@ -128,16 +161,35 @@ impl MarketProductGroup {
             .filter_map(|(idx, prod)| Some((idx, prod.try_to_outright().ok()?)))
     }
 
+    /// Positions in spot products are base token balances held in the product's base vault
+    pub fn is_spot(&self, product_index: usize) -> bool {
+        self.spot_markets[product_index].is_enabled()
+    }
+
+    pub fn find_collateral(&self, mint: &Pubkey) -> DomainOrProgramResult<usize> {
+        self.collaterals
+            .iter()
+            .position(|c| c.is_enabled() && &c.mint == mint)
+            .ok_or(DexError::CollateralNotAccepted.into())
+    }
+
     pub fn active_combos(&self) -> impl Iterator<Item = (usize, &Combo)> {
         self.active_products()
             .filter_map(|(idx, prod)| Some((idx, prod.try_to_combo().ok()?)))
     }
 
+    /// Outrights can only be deactivated once all combos that reference them are deactivated
     pub fn deactivate_product(&mut self, key: Pubkey) -> DomainOrProgramResult {
-        // todo: handle if Outright has Combos that reference it
-        let (index, _) = self.find_product_index(&key)?;
+        let (index, product) = self.find_product_index(&key)?;
+        assert(
+            product.is_combo() || !self.active_combos().any(|(_, combo)| combo.has_leg(key)),
+            DexError::CombosNotRemoved,
+        )?;
         self.active_flags_products.remove(index)?;
         self.market_products[index] = Default::default();
+        self.min_base_order_sizes[index] = 0;
+        self.price_bands[index] = Default::default();
+        self.spot_markets[index] = Default::default();
         Ok(())
     }
 
@@ -151,9 +203,81 @@ impl MarketProductGroup {
             .find_idx_and_insert()
             .map_err(|_| DexError::FullMarketProductGroup)?;
         self.market_products[idx] = product;
+        self.product_epochs[idx] = self.product_epochs[idx].wrapping_add(1);
+        self.min_base_order_sizes[idx] = 0;
+        self.price_bands[idx] = Default::default();
+        self.spot_markets[idx] = Default::default();
         Ok(())
     }
 
+    pub fn risk_engine_config(&self) -> RiskEngineConfig {
+        RiskEngineConfig {
+            program_id: self.risk_engine_program_id,
+            configuration_acct: self.risk_model_configuration_acct,
+            output_register: self.risk_output_register,
+            validate_account_discriminant_len: self.validate_account_discriminant_len as u64,
+            validate_account_health_discriminant: self.validate_account_health_discriminant,
+            validate_account_liquidation_discriminant: self
+                .validate_account_liquidation_discriminant,
+            create_risk_state_account_discriminant: self.create_risk_state_account_discriminant,
+        }
+    }
+
+    pub fn set_risk_engine_config(&mut self, config: &RiskEngineConfig) {
+        self.risk_engine_program_id = config.program_id;
+        self.risk_model_configuration_acct = config.configuration_acct;
+        self.risk_output_register = config.output_register;
+        self.validate_account_discriminant_len = config.validate_account_discriminant_len as u16;
+        self.validate_account_health_discriminant = config.validate_account_health_discriminant;
+        self.validate_account_liquidation_discriminant =
+            config.validate_account_liquidation_discriminant;
+        self.create_risk_state_account_discriminant = config.create_risk_state_account_discriminant;
+    }
+
+    pub fn fee_model_config(&self) -> FeeModelConfig {
+        FeeModelConfig {
+            program_id: self.fee_model_program_id,
+            configuration_acct: self.fee_model_configuration_acct,
+            output_register: self.fee_output_register,
+            find_fees_discriminant_len: self.find_fees_discriminant_len as u64,
+            find_fees_discriminant: self.find_fees_discriminant,
+            on_post_discriminant_len: self.on_post_discriminant_len as u64,
+            on_cancel_discriminant_len: self.on_cancel_discriminant_len as u64,
+            on_post_discriminant: self.on_post_discriminant,
+            on_cancel_discriminant: self.on_cancel_discriminant,
+            fee_accounts_len: self.fee_accounts_len as u64,
+        }
+    }
+
+    pub fn set_fee_model_config(&mut self, config: &FeeModelConfig) {
+        self.fee_model_program_id = config.program_id;
+        self.fee_model_configuration_acct = config.configuration_acct;
+        self.fee_output_register = config.output_register;
+        self.find_fees_discriminant_len = config.find_fees_discriminant_len as u16;
+        self.find_fees_discriminant = config.find_fees_discriminant;
+        self.on_post_discriminant_len = config.on_post_discriminant_len as u16;
+        self.on_cancel_discriminant_len = config.on_cancel_discriminant_len as u16;
+        self.on_post_discriminant = config.on_post_discriminant;
+        self.on_cancel_discriminant = config.on_cancel_discriminant;
+        self.fee_accounts_len = config.fee_accounts_len as u16;
+    }
+
+    pub fn fee_bps_bounds(&self) -> FeeBpsBounds {
+        FeeBpsBounds {
+            max_maker_fee_bps: self.max_maker_fee_bps,
+            min_maker_fee_bps: self.min_maker_fee_bps,
+            max_taker_fee_bps: self.max_taker_fee_bps,
+            min_taker_fee_bps: self.min_taker_fee_bps,
+        }
+    }
+
+    pub fn set_fee_bps_bounds(&mut self, bounds: &FeeBpsBounds) {
+        self.max_maker_fee_bps = bounds.max_maker_fee_bps;
+        self.min_maker_fee_bps = bounds.min_maker_fee_bps;
+        self.max_taker_fee_bps = bounds.max_taker_fee_bps;
+        self.min_taker_fee_bps = bounds.min_taker_fee_bps;
+    }
+
     pub fn get_prices(&mut self, product_idx: usize) -> &mut PriceEwma {
         &mut self.market_products[product_idx].prices
     }
@@ -172,6 +296,20 @@ impl MarketProductGroup {
             [..self.validate_account_discriminant_len as usize]
             .to_vec()
     }
+
+    pub fn get_on_post_discriminant(&self) -> Option<Vec<u8>> {
+        match self.on_post_discriminant_len {
+            0 => None,
+            len => Some(self.on_post_discriminant[..len as usize].to_vec()),
+        }
+    }
+
+    pub fn get_on_cancel_discriminant(&self) -> Option<Vec<u8>> {
+        match self.on_cancel_discriminant_len {
+            0 => None,
+            len => Some(self.on_cancel_discriminant[..len as usize].to_vec()),
+        }
+    }
 }
 
 #[zero_copy]
@@ -206,6 +344,182 @@ impl PriceEwma {
     }
 }
 
+#[zero_copy]
+#[derive(
+    Pod, Default, Debug, Eq, PartialEq, AnchorSerialize, AnchorDeserialize, Serialize, Deserialize,
+)]
+pub struct PriceBand {
+    // half width of the band in bps of the reference price, takes precedence over width
+    pub bps: u64,
+    // half width of the band as an absolute price
+    pub width: Fractional,
+    // index of the ewma used as the reference price
+    pub ewma_window: u64,
+}
+
+unsafe impl Zeroable for PriceBand {}
+
+impl PriceBand {
+    pub fn is_enabled(&self) -> bool {
+        self.bps != 0 || self.width != ZERO_FRAC
+    }
+
+    /// Returns the lowest and highest limit price allowed for new orders, or None if the band
+    /// is disabled or there is no reference price. The reference is the midpoint of the ewma
+    /// bid and ask. If one side has no ewma price, a single side could be walked anywhere, so
+    /// the product's recent index price is used instead and that side only as a last resort.
+    pub fn get_bounds(
+        &self,
+        prices: &PriceEwma,
+        index_price: Option<Fractional>,
+    ) -> DomainOrProgramResult<Option<(Fractional, Fractional)>> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let window = self.ewma_window as usize;
+        let bid = prices.ewma_bid[window];
+        let ask = prices.ewma_ask[window];
+        let reference = match (bid != NO_BID_PRICE, ask != NO_ASK_PRICE, index_price) {
+            (true, true, _) => bid.checked_add(ask)?.checked_div(Fractional::new(2, 0))?,
+            (_, _, Some(index_price)) => index_price,
+            (true, false, None) => bid,
+            (false, true, None) => ask,
+            (false, false, None) => return Ok(None),
+        };
+        let width = match self.bps {
+            0 => self.width,
+            b => reference.abs().checked_mul(bps(b as i64))?,
+        };
+        Ok(Some((
+            reference.checked_sub(width)?,
+            reference.checked_add(width)?,
+        )))
+    }
+}
+
+#[zero_copy]
+#[derive(
+    Pod, Default, Debug, Eq, PartialEq, AnchorSerialize, AnchorDeserialize, Serialize, Deserialize,
+)]
+pub struct SpotMarket {
+    pub base_mint: Pubkey,
+    // decimals of the base mint, trader positions are rounded to these when withdrawn
+    pub base_decimals: u64,
+    // bump of the base vault PDA, seeds are [b"base_vault", market_product_group, product]
+    pub base_vault_bump: u64,
+}
+
+unsafe impl Zeroable for SpotMarket {}
+
+impl SpotMarket {
+    pub fn is_enabled(&self) -> bool {
+        self.base_mint != Pubkey::default()
+    }
+}
+
+#[zero_copy]
+#[derive(
+    Pod, Default, Debug, Eq, PartialEq, AnchorSerialize, AnchorDeserialize, Serialize, Deserialize,
+)]
+pub struct Collateral {
+    pub mint: Pubkey,
+    // price account that the risk engine values the collateral with
+    pub oracle: Pubkey,
+    pub decimals: u64,
+    // bump of the vault PDA, seeds are [b"collateral_vault", market_product_group, mint]
+    pub vault_bump: u64,
+    // share of the oracle value that does not count towards the trader's portfolio value
+    pub haircut_bps: u64,
+    // instruments OracleType of the oracle and the bounds its price is read with
+    pub oracle_type: u64,
+    pub max_staleness_slots: u64,
+    pub max_confidence_bps: u64,
+}
+
+unsafe impl Zeroable for Collateral {}
+
+impl Collateral {
+    pub fn is_enabled(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+
+    /// Value of `balance` at the oracle `price` after the haircut
+    pub fn get_value(
+        &self,
+        balance: Fractional,
+        price: Fractional,
+    ) -> DomainOrProgramResult<Fractional> {
+        let weight = Fractional::from(1).checked_sub(bps(self.haircut_bps as i64))?;
+        Ok(balance.checked_mul(price)?.checked_mul(weight)?)
+    }
+}
+
+/// Program, accounts and instruction discriminants the dex uses to call the risk engine
+#[zero_copy]
+#[derive(
+    Pod, Default, Debug, Eq, PartialEq, AnchorSerialize, AnchorDeserialize, Serialize, Deserialize,
+)]
+pub struct RiskEngineConfig {
+    pub program_id: Pubkey,
+    pub configuration_acct: Pubkey,
+    pub output_register: Pubkey,
+    pub validate_account_discriminant_len: u64,
+    pub validate_account_health_discriminant: [u8; 8],
+    pub validate_account_liquidation_discriminant: [u8; 8],
+    pub create_risk_state_account_discriminant: [u8; 8],
+}
+
+unsafe impl Zeroable for RiskEngineConfig {}
+
+impl RiskEngineConfig {
+    pub fn is_valid(&self) -> bool {
+        self.validate_account_discriminant_len <= 8
+    }
+}
+
+/// Program, accounts and instruction discriminants the dex uses to call the fee model
+#[zero_copy]
+#[derive(
+    Pod, Default, Debug, Eq, PartialEq, AnchorSerialize, AnchorDeserialize, Serialize, Deserialize,
+)]
+pub struct FeeModelConfig {
+    pub program_id: Pubkey,
+    pub configuration_acct: Pubkey,
+    pub output_register: Pubkey,
+    pub find_fees_discriminant_len: u64,
+    pub find_fees_discriminant: [u8; 8],
+    pub on_post_discriminant_len: u64,
+    pub on_cancel_discriminant_len: u64,
+    pub on_post_discriminant: [u8; 8],
+    pub on_cancel_discriminant: [u8; 8],
+    pub fee_accounts_len: u64,
+}
+
+unsafe impl Zeroable for FeeModelConfig {}
+
+impl FeeModelConfig {
+    pub fn is_valid(&self) -> bool {
+        self.find_fees_discriminant_len <= 8
+            && self.on_post_discriminant_len <= 8
+            && self.on_cancel_discriminant_len <= 8
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, Eq, PartialEq, AnchorSerialize, AnchorDeserialize)]
+pub struct FeeBpsBounds {
+    pub max_maker_fee_bps: i16,
+    pub min_maker_fee_bps: i16,
+    pub max_taker_fee_bps: i16,
+    pub min_taker_fee_bps: i16,
+}
+
+impl FeeBpsBounds {
+    pub fn is_valid(&self) -> bool {
+        self.min_maker_fee_bps <= self.max_maker_fee_bps
+            && self.min_taker_fee_bps <= self.max_taker_fee_bps
+    }
+}
+
 #[account(zero_copy)]
 #[derive(AnchorSerialize, Serialize, Deserialize)]
 #[repr(transparent)]
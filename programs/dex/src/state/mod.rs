@@ -2,6 +2,7 @@ pub mod callback_info;
 pub mod constants;
 pub mod enums;
 pub mod fee_model;
+pub mod legacy;
 pub mod market_product_group;
 pub mod open_orders;
 pub mod products;
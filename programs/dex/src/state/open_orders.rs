@@ -141,6 +141,7 @@ impl OpenOrders {
         Err(ProgramError::InvalidAccountData.into())
     }
 
+    /// Removes all open orders of the product and resets its order counts and book quantities
     pub fn clear(&mut self, index: usize) -> DomainOrProgramResult {
         let head_index = &mut self.products[index].head_index;
         let mut i = *head_index as usize;
@@ -150,6 +151,13 @@ impl OpenOrders {
             self.remove_node(index, i);
             i = next;
         }
+        let product_meta = &mut self.products[index];
+        self.total_open_orders = self
+            .total_open_orders
+            .saturating_sub(product_meta.num_open_orders);
+        product_meta.num_open_orders = 0;
+        product_meta.ask_qty_in_book = ZERO_FRAC;
+        product_meta.bid_qty_in_book = ZERO_FRAC;
         Ok(())
     }
 }
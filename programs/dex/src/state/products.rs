@@ -9,7 +9,11 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     error::{DexError, DomainOrProgramResult},
-    state::{constants::MAX_LEGS, enums::ProductStatus, market_product_group::PriceEwma},
+    state::{
+        constants::{INDEX_PRICE_MAX_AGE_SLOTS, MAX_LEGS},
+        enums::ProductStatus,
+        market_product_group::PriceEwma,
+    },
     utils::{numeric::ZERO_FRAC, TwoIterators},
     DomainOrProgramError, Fractional, NAME_LEN,
 };
@@ -115,7 +119,13 @@ pub struct Outright {
     pub cum_social_loss_per_share: Fractional,
     pub open_long_interest: Fractional,
     pub open_short_interest: Fractional,
-    pub padding: [u64; 14],
+    // Limits on the open interest and on the absolute position of each trader, 0 if unlimited
+    pub max_open_interest: Fractional,
+    pub max_position: Fractional,
+    // Payoff of the oracle index pushed by the product's instrument and the slot it was pushed at
+    pub index_price: Fractional,
+    pub index_price_slot: u64,
+    pub padding: [u64; 7],
 }
 
 impl Outright {
@@ -160,6 +170,41 @@ impl Outright {
         self.product_status == ProductStatus::Expired
     }
 
+    /// The last index price pushed by the product, if it is at most `INDEX_PRICE_MAX_AGE_SLOTS` old
+    pub fn get_index_price(&self, slot: u64) -> Option<Fractional> {
+        (self.index_price_slot != 0
+            && slot.saturating_sub(self.index_price_slot) <= INDEX_PRICE_MAX_AGE_SLOTS)
+            .then(|| self.index_price)
+    }
+
+    /// Errors if a position moving from `old_position` to `new_position` grows past the
+    /// position limit. Reducing a position is always allowed.
+    pub fn check_position_limit(
+        &self,
+        old_position: Fractional,
+        new_position: Fractional,
+    ) -> DomainOrProgramResult {
+        if self.max_position != ZERO_FRAC
+            && new_position.abs() > old_position.abs()
+            && new_position.abs() > self.max_position
+        {
+            return Err(DexError::PositionLimitExceeded.into());
+        }
+        Ok(())
+    }
+
+    /// Errors if growing the open interest by `size` would exceed the open interest limit.
+    /// Every long is matched by a short, so the open long interest is the open interest.
+    /// Fills still waiting in the event queue are only counted once they are consumed.
+    pub fn check_open_interest_limit(&self, size: Fractional) -> DomainOrProgramResult {
+        if self.max_open_interest != ZERO_FRAC
+            && self.open_long_interest.checked_add(size)? > self.max_open_interest
+        {
+            return Err(DexError::OpenInterestLimitExceeded.into());
+        }
+        Ok(())
+    }
+
     pub fn update_open_interest_change(
         &mut self,
         trade_size: Fractional,
@@ -241,12 +286,33 @@ impl Combo {
     }
 
     pub fn get_product_key_seeds(&self) -> Vec<u8> {
-        let mut seeds = Vec::<u8>::with_capacity((size_of::<Pubkey>() + 1) * self.num_legs);
-        for leg in self.legs.iter().take(self.num_legs) {
-            seeds.extend(leg.product_key.to_bytes().iter());
+        let (product_keys, ratios): (Vec<Pubkey>, Vec<i64>) = self
+            .legs()
+            .iter()
+            .map(|leg| (leg.product_key, leg.ratio))
+            .unzip();
+        Combo::get_seeds(&product_keys, &ratios)
+    }
+
+    /// Format of the seeds is [product_key_1, ..., product_key_N, [ratio_1, ..., ratio_N]].
+    /// Ratios used to be limited to i8, so they are encoded as single bytes whenever they all
+    /// fit to keep the product keys of existing combos, and as little endian i32s otherwise.
+    pub fn get_seeds(product_keys: &[Pubkey], ratios: &[i64]) -> Vec<u8> {
+        let fits_i8 = ratios
+            .iter()
+            .all(|r| i8::MIN as i64 <= *r && *r <= i8::MAX as i64);
+        let ratio_len = if fits_i8 { 1 } else { size_of::<i32>() };
+        let mut seeds =
+            Vec::<u8>::with_capacity((size_of::<Pubkey>() + ratio_len) * product_keys.len());
+        for product_key in product_keys.iter() {
+            seeds.extend(product_key.to_bytes().iter());
         }
-        for leg in self.legs.iter().take(self.num_legs) {
-            seeds.extend((leg.ratio as i8).to_le_bytes().iter());
+        for ratio in ratios.iter() {
+            if fits_i8 {
+                seeds.extend((*ratio as i8).to_le_bytes().iter());
+            } else {
+                seeds.extend((*ratio as i32).to_le_bytes().iter());
+            }
         }
         seeds
     }
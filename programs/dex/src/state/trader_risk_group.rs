@@ -11,8 +11,8 @@ use crate::{
     error::{DexError, DomainOrProgramError, DomainOrProgramResult},
     state::{
         constants::{
-            HEALTH_BUFFER_LEN, MAX_COMBOS, MAX_OPEN_ORDERS_PER_POSITION, MAX_OUTRIGHTS,
-            MAX_TRADER_POSITIONS,
+            HEALTH_BUFFER_LEN, MAX_COLLATERALS, MAX_COMBOS, MAX_OPEN_ORDERS_PER_POSITION,
+            MAX_OUTRIGHTS, MAX_PRODUCTS, MAX_TRADER_POSITIONS,
         },
         enums::AccountTag,
         market_product_group::MarketProductGroup,
@@ -36,7 +36,7 @@ pub struct TraderRiskGroup {
     pub active_products: [u8; MAX_OUTRIGHTS],
     pub total_deposited: Fractional,
     pub total_withdrawn: Fractional,
-    // Treat cash separately since it is collateral (unless we eventually support spot)
+    // Treat cash separately since it is collateral, base balances of spot products are positions
     pub cash_balance: Fractional,
     // Keep track of pending fills for risk calculations (only for takers)
     pub pending_cash_balance: Fractional,
@@ -51,6 +51,12 @@ pub struct TraderRiskGroup {
     // Densely packed linked list of open orders
     pub client_order_id: u128,
     pub open_orders: OpenOrders,
+    // balances of the group's non-cash collaterals, indexed like MarketProductGroup::collaterals
+    pub collateral_balances: [Fractional; MAX_COLLATERALS],
+    // Matched quote qty that the pending taker fees were computed on, summed over all orders
+    pub pending_fees_quote_qty: Fractional,
+    // product epoch of the product the open orders at each index were placed on
+    pub open_orders_epochs: [u64; MAX_PRODUCTS],
 }
 
 impl IsInitialized for TraderRiskGroup {
@@ -66,6 +72,28 @@ impl Default for TraderRiskGroup {
 }
 
 impl TraderRiskGroup {
+    /// Settles the pro-rata share of the pending taker fees owed for a fill of `quote_qty`.
+    /// Pending fees are pooled across orders, so a fill is charged the blended rate of every
+    /// order that is still waiting to be cranked rather than the rate of its own order
+    pub fn take_pending_fill_fees(
+        &mut self,
+        quote_qty: Fractional,
+    ) -> DomainOrProgramResult<Fractional> {
+        let fill_fees = if quote_qty < self.pending_fees_quote_qty {
+            self.pending_fees
+                .checked_mul(quote_qty)?
+                .checked_div(self.pending_fees_quote_qty)?
+        } else {
+            self.pending_fees
+        };
+        self.pending_fees = self.pending_fees.checked_sub(fill_fees)?;
+        self.pending_fees_quote_qty = self
+            .pending_fees_quote_qty
+            .checked_sub(quote_qty)?
+            .max(ZERO_FRAC);
+        Ok(fill_fees)
+    }
+
     pub fn find_position_index(&self, position_pk: &Pubkey) -> Option<usize> {
         self.trader_positions
             .iter()
@@ -112,6 +140,8 @@ impl TraderRiskGroup {
                     self.open_orders.clear(combo_index)?;
                 }
             }
+            // Dependent combos may have been removed before the trader's orders on them were
+            self.clear_stale_open_orders(market_product_group)?;
             self.clear(product_key)?;
         }
         Ok(())
@@ -144,6 +174,8 @@ impl TraderRiskGroup {
         &mut self,
         market_product_group: &mut MarketProductGroup,
     ) -> DomainOrProgramResult {
+        // Before the risk engine sees the open orders of removed products
+        self.clear_stale_open_orders(market_product_group)?;
         for trader_index in 0..self.trader_positions.len() {
             if !self.trader_positions[trader_index].is_initialized() {
                 continue;
@@ -153,7 +185,31 @@ impl TraderRiskGroup {
         Ok(())
     }
 
-    pub fn add_open_order(&mut self, index: usize, order_id: u128) -> DomainOrProgramResult {
+    /// Resets the open orders left on products that were removed, including those at indices
+    /// that have since been reused by another product
+    pub fn clear_stale_open_orders(
+        &mut self,
+        market_product_group: &MarketProductGroup,
+    ) -> DomainOrProgramResult {
+        for index in 0..MAX_PRODUCTS {
+            if self.open_orders.products[index].num_open_orders > 0
+                && (!market_product_group.active_flags_products.contains(index)
+                    || self.open_orders_epochs[index] != market_product_group.product_epochs[index])
+            {
+                self.open_orders.clear(index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `epoch` is the product's MarketProductGroup::product_epochs entry, stale open orders at
+    /// the index must have been cleared beforehand
+    pub fn add_open_order(
+        &mut self,
+        index: usize,
+        order_id: u128,
+        epoch: u64,
+    ) -> DomainOrProgramResult {
         // TODO: consider reinstating is_active check at some point
         let num_open_orders = self.open_orders.products[index].num_open_orders;
 
@@ -164,6 +220,7 @@ impl TraderRiskGroup {
 
         self.open_orders.products[index].num_open_orders += 1;
         self.open_orders.total_open_orders += 1;
+        self.open_orders_epochs[index] = epoch;
         self.open_orders
             .add_open_order(index, order_id)
             .map_err(Into::into)
@@ -20,9 +20,9 @@ use std::{
 use crate::{
     create_trader_risk_state_acct_ix,
     error::{DexError, DomainOrProgramResult, UtilError},
-    find_fees_ix,
+    find_fees_ix, order_event_ix,
     state::{
-        fee_model::TraderFeeParams,
+        fee_model::{OrderEventParams, TraderFeeParams},
         risk_engine_register::{HealthResult, OrderInfo, RiskOutputRegister},
     },
     utils::{
@@ -37,7 +37,7 @@ use crate::{
     TraderRiskGroup,
 };
 
-pub fn find_fees<'a>(
+pub fn find_fees<'a, 'c>(
     fee_model_program: &AccountInfo<'a>,
     market_product_group: &AccountInfo<'a>,
     trader_risk_group: &AccountLoader<'a, TraderRiskGroup>,
@@ -45,10 +45,22 @@ pub fn find_fees<'a>(
     fee_model_configuration_acct: &AccountInfo<'a>,
     fee_output_register: &AccountInfo<'a>,
     fee_signer: &AccountInfo<'a>,
+    remaining_fee_accounts: &'c [AccountInfo<'a>],
     discriminant: Vec<u8>,
     fee_params: &TraderFeeParams,
     fee_bump: u8,
 ) -> ProgramResult {
+    let mut fee_accounts = vec![];
+    fee_accounts.extend_from_slice(&[
+        fee_model_program.clone(),
+        market_product_group.to_account_info(),
+        trader_risk_group.to_account_info(),
+        trader_fee_state.clone(),
+        fee_model_configuration_acct.clone(),
+        fee_output_register.clone(),
+        fee_signer.clone(),
+    ]);
+    fee_accounts.extend(remaining_fee_accounts.iter().cloned());
     invoke_signed_unchecked(
         &find_fees_ix(
             fee_model_program.key(),
@@ -58,16 +70,45 @@ pub fn find_fees<'a>(
             fee_model_configuration_acct.key(),
             fee_output_register.key(),
             fee_signer.key(),
+            remaining_fee_accounts.iter().map(Key::key).collect(),
             fee_params,
             discriminant,
         )?,
+        fee_accounts.as_slice(),
+        &[&[market_product_group.key().as_ref(), &[fee_bump]]],
+    )
+}
+
+/// Notifies the fee model that an order was posted to or removed from the book.
+/// The discriminant selects between the `on_post` and `on_cancel` hooks.
+pub fn notify_fee_model<'a>(
+    fee_model_program: &AccountInfo<'a>,
+    market_product_group: &AccountInfo<'a>,
+    trader_risk_group: &AccountLoader<'a, TraderRiskGroup>,
+    trader_fee_state: &AccountInfo<'a>,
+    fee_model_configuration_acct: &AccountInfo<'a>,
+    fee_signer: &AccountInfo<'a>,
+    discriminant: Vec<u8>,
+    params: &OrderEventParams,
+    fee_bump: u8,
+) -> ProgramResult {
+    invoke_signed_unchecked(
+        &order_event_ix(
+            fee_model_program.key(),
+            market_product_group.key(),
+            trader_risk_group.key(),
+            trader_fee_state.key(),
+            fee_model_configuration_acct.key(),
+            fee_signer.key(),
+            params,
+            discriminant,
+        )?,
         &[
             fee_model_program.clone(),
             market_product_group.to_account_info(),
             trader_risk_group.to_account_info(),
             trader_fee_state.clone(),
             fee_model_configuration_acct.clone(),
-            fee_output_register.clone(),
             fee_signer.clone(),
         ],
         &[&[market_product_group.key().as_ref(), &[fee_bump]]],
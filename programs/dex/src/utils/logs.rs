@@ -1,5 +1,8 @@
 use agnostic_orderbook::state::OrderSummary;
 use anchor_lang::prelude::*;
+
+use crate::state::market_product_group::{FeeBpsBounds, FeeModelConfig, RiskEngineConfig};
+
 #[event]
 pub struct DexOrderSummary {
     pub posted_order_id: Option<u128>,
@@ -31,3 +34,79 @@ impl DexOrderSummary {
         )
     }
 }
+
+#[event]
+pub struct FeeCollectorUpdated {
+    pub market_product_group: Pubkey,
+    pub old: Pubkey,
+    pub new: Pubkey,
+}
+
+#[event]
+pub struct RiskEngineUpdated {
+    pub market_product_group: Pubkey,
+    pub old: RiskEngineConfig,
+    pub new: RiskEngineConfig,
+}
+
+/// A timelocked risk engine swap that `apply_market_product_group_update` can make after `effective_at`
+#[event]
+pub struct RiskEngineUpdateScheduled {
+    pub market_product_group: Pubkey,
+    pub old: RiskEngineConfig,
+    pub new: RiskEngineConfig,
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct FeeModelUpdated {
+    pub market_product_group: Pubkey,
+    pub old: FeeModelConfig,
+    pub new: FeeModelConfig,
+}
+
+/// A timelocked fee model swap that `apply_market_product_group_update` can make after `effective_at`
+#[event]
+pub struct FeeModelUpdateScheduled {
+    pub market_product_group: Pubkey,
+    pub old: FeeModelConfig,
+    pub new: FeeModelConfig,
+    pub effective_at: i64,
+}
+
+/// Emitted with the swaps that were dropped before they took effect
+#[event]
+pub struct EngineUpdatesCancelled {
+    pub market_product_group: Pubkey,
+    pub risk_engine: Option<RiskEngineConfig>,
+    pub fee_model: Option<FeeModelConfig>,
+}
+
+#[event]
+pub struct EwmaWindowsUpdated {
+    pub market_product_group: Pubkey,
+    pub old: [u64; 4],
+    pub new: [u64; 4],
+}
+
+#[event]
+pub struct FeeBpsBoundsUpdated {
+    pub market_product_group: Pubkey,
+    pub old: FeeBpsBounds,
+    pub new: FeeBpsBounds,
+}
+
+#[event]
+pub struct EngineUpdateTimelockUpdated {
+    pub market_product_group: Pubkey,
+    pub old: u64,
+    pub new: u64,
+}
+
+/// The trader's state accounts after moving them to a swapped in risk engine or fee model
+#[event]
+pub struct TraderEngineAccountsUpdated {
+    pub trader_risk_group: Pubkey,
+    pub risk_state_account: Pubkey,
+    pub fee_state_account: Pubkey,
+}
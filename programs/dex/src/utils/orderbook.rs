@@ -55,6 +55,19 @@ pub fn get_bbo(
     }
 }
 
+/// Recovers the limit price (in DEX price space) encoded in the upper 64 bits of an AOB order id
+pub fn get_order_price(
+    order_id: u128,
+    tick_size: Fractional,
+    price_offset: Fractional,
+) -> std::result::Result<Fractional, DomainOrProgramError> {
+    let price_aob = (order_id >> 64) as u64;
+    let price_dex = Fractional::new((price_aob >> 32) as i64, 0)
+        .checked_mul(tick_size)?
+        .checked_sub(price_offset)?;
+    Ok(price_dex)
+}
+
 pub fn update_prices(
     clock: &Clock,
     prices: &mut PriceEwma,
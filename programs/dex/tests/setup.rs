@@ -7,6 +7,7 @@ use dexteritysdk::{
     BootstrapConfig, OptionalBootstrapFields, RiskEngines, SDKContext,
     ANCHOR_CREATE_RISK_STATE_ACCOUNT_DISCRIMINANT, ANCHOR_VALIDATE_ACCOUNT_HEALTH_DISCRIMINANT,
     ANCHOR_VALIDATE_ACCOUNT_LIQUIDATION_DISCRIMINANT, FIND_FEES_DISCRIMINANT,
+    ON_CANCEL_DISCRIMINANT, ON_POST_DISCRIMINANT,
 };
 use solana_program::{pubkey::Pubkey, system_program};
 use solana_program_test::ProgramTest;
@@ -109,6 +110,11 @@ pub(crate) fn load_test_config(risk_engine: &str) -> BootstrapConfig {
         liq_disc,
         create_risk_state_disc,
         fees_disc: FIND_FEES_DISCRIMINANT as u64,
+        on_post_disc: Some(ON_POST_DISCRIMINANT as u64),
+        on_cancel_disc: Some(ON_CANCEL_DISCRIMINANT as u64),
+        fee_accounts_len: 0,
+        fee_discount_tiers: None,
+        series: None,
         risk_disc_len: disc_len as u64,
         risk_engine_program_id,
         fees_disc_len: 1,
@@ -128,10 +134,17 @@ pub async fn bootstrap_tests(
 ) -> (DexAdmin, Vec<SDKTrader>) {
     log_disable();
     let mut config = load_test_config(risk_engine);
+    if fee_model == "token_discount_fees" {
+        // traders forward their vault mint wallet to qualify for the discount
+        config.fee_discount_tiers = Some(vec![(1, 5)]);
+        config.fee_accounts_len = 1;
+    }
     let mut program_test = ProgramTest::default();
     program_test.add_program("dex", config.dex_program_id, None);
     program_test.add_program("agnostic_orderbook", config.aaob_program_id, None);
-    program_test.add_program(risk_engine, config.risk_engine_program_id, None);
+    // Both risk engines are loaded so tests can swap between them
+    program_test.add_program("noop_risk_engine", noop_risk_engine::ID, None);
+    program_test.add_program("alpha_risk_engine", alpha_risk_engine::ID, None);
     program_test.add_program(fee_model, config.fee_model_program_id, None);
     program_test.add_program("instruments", config.instruments_program_id, None);
     program_test.add_program("dummy_oracle", config.dummy_oracle_program_id, None);
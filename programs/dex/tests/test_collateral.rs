@@ -0,0 +1,209 @@
+#![allow(non_snake_case)]
+
+use agnostic_orderbook::state::{SelfTradeBehavior, Side};
+use anchor_lang::solana_program::program_pack::Pack;
+use dex::{
+    state::enums::OrderType, utils::numeric::Fractional, AddCollateralParams,
+    UpdateCollateralParams,
+};
+use dexteritysdk::{
+    common::{utils::*, KeypairD},
+    oracle::update_oracle::*,
+    trader::SDKTrader,
+    SDKContext,
+};
+use instruments::state::enums::OracleType;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{account::ReadableAccount, signature::Signer};
+
+use crate::setup::{bootstrap_tests, set_prices};
+
+mod setup;
+
+const COLLATERAL_MINT_DECIMALS: u8 = 9;
+
+fn collateral_params(haircut_bps: u64) -> AddCollateralParams {
+    AddCollateralParams {
+        haircut_bps,
+        oracle_type: OracleType::Dummy as u64,
+        max_staleness_slots: 0,
+        max_confidence_bps: 0,
+    }
+}
+
+/// Creates a collateral mint priced at `price` by the dummy oracle and mints `qty` of it to the
+/// trader, returns the mint, the oracle and the trader's token account
+async fn setup_collateral(
+    ctx: &SDKContext,
+    trader: &SDKTrader,
+    price: i64,
+    qty: u64,
+) -> SDKResult<(KeypairD, Pubkey, Pubkey)> {
+    let (mint, oracle, wallet) = setup_collateral(ctx, trader, 20, 10).await?;
+
+    // haircuts are capped at 100%
+    assert!(ctx
+        .add_collateral(mint.pubkey(), oracle, collateral_params(10_001))
+        .await
+        .is_err());
+    ctx.add_collateral(mint.pubkey(), oracle, collateral_params(2_000))
+        .await?;
+    // neither the same mint nor the vault mint can be added twice
+    assert!(ctx
+        .add_collateral(mint.pubkey(), oracle, collateral_params(2_000))
+        .await
+        .is_err());
+    assert!(ctx
+        .add_collateral(ctx.vault_mint, oracle, collateral_params(0))
+        .await
+        .is_err());
+    // the risk engine needs to know how to read the oracle
+    assert!(ctx
+        .add_collateral(
+            mint.pubkey(),
+            oracle,
+            AddCollateralParams {
+                oracle_type: 0,
+                ..collateral_params(2_000)
+            }
+        )
+        .await
+        .is_err());
+    let market_product_group = ctx.get_market_product_group().await;
+    let collateral = market_product_group.collaterals[0];
+    assert_eq!(collateral.mint, mint.pubkey());
+    assert_eq!(collateral.haircut_bps, 2_000);
+    assert_eq!(collateral.decimals, COLLATERAL_MINT_DECIMALS as u64);
+
+    ctx.update_collateral(
+        mint.pubkey(),
+        oracle,
+        UpdateCollateralParams {
+            haircut_bps: 5_000,
+            oracle_type: OracleType::Dummy as u64,
+            max_staleness_slots: 0,
+            max_confidence_bps: 0,
+        },
+    )
+    .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    assert_eq!(market_product_group.collaterals[0].haircut_bps, 5_000);
+
+    trader
+        .deposit_collateral(ctx, mint.pubkey(), wallet, 10)
+        .await?;
+    // the cash wallet is in a mint that is not accepted as collateral
+    assert!(trader
+        .deposit_collateral(ctx, ctx.vault_mint, trader.wallet, 1)
+        .await
+        .is_err());
+    let trg = trader.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg.collateral_balances[0], 10);
+
+    assert!(trader
+        .withdraw_collateral(ctx, mint.pubkey(), wallet, 11, &[oracle])
+        .await
+        .is_err());
+    trader
+        .withdraw_collateral(ctx, mint.pubkey(), wallet, 4, &[oracle])
+        .await?;
+    let trg = trader.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg.collateral_balances[0], 6);
+    let token_account =
+        spl_token::state::Account::unpack(ctx.client.get_account(wallet).await?.data()).unwrap();
+    assert_eq_frac(
+        Fractional::new(token_account.amount as i64, COLLATERAL_MINT_DECIMALS as u64),
+        4,
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_collateral_alpha_risk_engine() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("alpha_risk_engine", "constant_fees", "test", 3, 1).await;
+    let (maker, market_maker, liquidator) = (&traders[1], &traders[0], &traders[2]);
+
+    // 100 units at 20 with a 20% haircut are worth 1600
+    let (mint, oracle, wallet) = setup_collateral(ctx, maker, 20, 100).await?;
+    ctx.add_collateral(mint.pubkey(), oracle, collateral_params(2_000))
+        .await?;
+    maker
+        .deposit_collateral(ctx, mint.pubkey(), wallet, 100)
+        .await?;
+
+    // The only bid marks the product, so a resting bid at p needs a portfolio value of p / 2.
+    // 1700 is covered by the collateral before the haircut but not after it.
+    assert!(place_bid(ctx, maker, 3400, &[oracle]).await.is_err());
+    // The oracle of a collateral with a balance must be passed in
+    assert!(place_bid(ctx, maker, 3000, &[]).await.is_err());
+    place_bid(ctx, maker, 3000, &[oracle]).await?;
+
+    market_maker.deposit(ctx, 1_000_000).await?;
+    market_maker
+        .place_order(ctx, &ctx.products[0], Side::Ask, 1, 3000)
+        .await?;
+    // Quotes around 3000 keep marking the product
+    set_prices(
+        ctx,
+        market_maker,
+        vec![0],
+        &vec![Fractional::new(3000, 0)],
+        false,
+    )
+    .await?;
+    market_maker.crank(ctx, &ctx.products[0], &[maker]).await?;
+    let trg = maker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(
+        trg.trader_positions[trg.active_products[0] as usize].position,
+        1,
+    );
+
+    // A margin of 3000 needs a portfolio value of 1500, 90 units are only worth 1440
+    assert!(maker
+        .withdraw_collateral(ctx, mint.pubkey(), wallet, 10, &[oracle])
+        .await
+        .is_err());
+    maker
+        .withdraw_collateral(ctx, mint.pubkey(), wallet, 2, &[oracle])
+        .await?;
+
+    // At 5 the remaining 98 units are worth 392, below the liquidation threshold of 600
+    update_oracle_price_account(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        solana_program::system_program::id(),
+        5,
+        0,
+    )
+    .await?;
+    liquidator.deposit(ctx, 1_000_000).await?;
+    assert!(liquidator
+        .transfer_position(
+            ctx,
+            ctx.market_product_group,
+            maker.account,
+            maker.risk_state_account,
+        )
+        .await
+        .is_err());
+    liquidator
+        .transfer_position_with_accts(
+            ctx,
+            ctx.market_product_group,
+            maker.account,
+            maker.risk_state_account,
+            &[oracle],
+        )
+        .await?;
+    let trg = maker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg.collateral_balances[0], 0);
+    let trg = liquidator.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg.collateral_balances[0], 98);
+    assert_eq_frac(
+        trg.trader_positions[trg.active_products[0] as usize].position,
+        1,
+    );
+    Ok(())
+}
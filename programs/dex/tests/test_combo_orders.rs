@@ -1,8 +1,11 @@
 #![allow(non_snake_case)]
 use agnostic_orderbook::state::Side;
 use anchor_lang::Key;
-use dex::utils::numeric::{Fractional, ZERO_FRAC};
-use dexteritysdk::bootstrap::setup_combo;
+use dex::{
+    state::products::Combo,
+    utils::numeric::{Fractional, ZERO_FRAC},
+};
+use dexteritysdk::bootstrap::{setup_combo, setup_combo_with_ratios};
 use itertools::Itertools;
 
 mod setup;
@@ -135,6 +138,7 @@ async fn test_combo_orders() {
         combo.event_queue,
         &trader_1.keypair,
         traders.as_mut_slice(),
+        &[],
         10,
     )
     .await
@@ -164,3 +168,122 @@ async fn test_combo_orders() {
         assert_eq!(position_1.pending_position, ZERO_FRAC);
     }
 }
+
+#[tokio::test]
+async fn test_combo_many_legs() {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 6).await;
+    let products = ctx
+        .products
+        .iter()
+        .map(|p| p.key())
+        .sorted()
+        .collect::<Vec<_>>();
+    // 200 doesn't fit in an i8 so the product key is derived from i32 ratios
+    let ratios = [1, -3, 2, -1, 200, -2];
+    let combo = setup_combo_with_ratios(ctx, &products, &ratios, 0)
+        .await
+        .unwrap();
+    let combo_group_data = ctx.get_market_product_group().await;
+    let (_, combo_0) = combo_group_data.find_combo(&combo.key).unwrap();
+    assert_eq!(combo_0.num_legs, ratios.len());
+    for (leg, (product, ratio)) in combo_0.legs().iter().zip(combo.legs.iter()) {
+        assert_eq!(leg.product_key, *product);
+        assert_eq!(leg.ratio, *ratio as i64);
+    }
+
+    // combos with small ratios keep the original single byte seeds
+    let mut seeds = Vec::new();
+    for product in products[..2].iter() {
+        seeds.extend(product.to_bytes().iter());
+    }
+    seeds.extend([1_i8, -1].iter().flat_map(|r| r.to_le_bytes()));
+    assert_eq!(Combo::get_seeds(&products[..2], &[1, -1]), seeds);
+
+    traders[0]
+        .place_combo_order(ctx, &combo, Side::Ask, Fractional::new(10, 1), 0)
+        .await
+        .unwrap();
+    traders[1]
+        .place_combo_order(ctx, &combo, Side::Bid, Fractional::new(10, 1), 1)
+        .await
+        .unwrap();
+    let trader_risk_group_1 = traders[1].get_trader_risk_group(&ctx.client).await;
+    for leg in combo_0.legs().iter() {
+        let t_i = trader_risk_group_1.active_products[leg.product_index] as usize;
+        let position = trader_risk_group_1.trader_positions[t_i];
+        let expected_pos = Fractional::new(leg.ratio, 0) * Fractional::new(10, 1);
+        assert_eq!(position.pending_position, expected_pos);
+    }
+}
+
+#[tokio::test]
+async fn test_implied_combo_order() {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 3, 2).await;
+    for trader in traders.iter() {
+        trader.deposit(ctx, COLLATERAL).await.unwrap();
+    }
+    let mut products = ctx.products.clone();
+    products.sort_by_key(|p| p.key);
+    let combo = setup_combo(
+        ctx,
+        products
+            .iter()
+            .map(|p| p.key)
+            .collect::<Vec<_>>()
+            .as_slice(),
+        0,
+    )
+    .await
+    .unwrap();
+
+    // buying the combo buys the first leg at 30 and sells the second at 20
+    traders[0]
+        .place_order(ctx, &products[0], Side::Ask, 2, 30)
+        .await
+        .unwrap();
+    traders[1]
+        .place_order(ctx, &products[1], Side::Bid, 2, 20)
+        .await
+        .unwrap();
+    traders[2]
+        .place_implied_combo_order(ctx, &combo, Side::Bid, 3, 12)
+        .await
+        .unwrap();
+
+    let combo_group_data = ctx.get_market_product_group().await;
+    let (combo_index, combo_0) = combo_group_data.find_combo(&combo.key).unwrap();
+    let trader_risk_group = traders[2].get_trader_risk_group(&ctx.client).await;
+    // the implied fill is settled on the outright books, the rest rests on the combo book
+    assert_eq!(
+        trader_risk_group.pending_cash_balance,
+        Fractional::new(-20, 0)
+    );
+    assert_eq!(
+        trader_risk_group.open_orders.products[combo_index].bid_qty_in_book,
+        Fractional::new(1, 0)
+    );
+    for leg in combo_0.legs().iter() {
+        let t_i = trader_risk_group.active_products[leg.product_index] as usize;
+        let position = trader_risk_group.trader_positions[t_i];
+        assert_eq!(position.pending_position, Fractional::new(2 * leg.ratio, 0));
+    }
+
+    traders[0]
+        .crank(ctx, &products[0], &[&traders[2]])
+        .await
+        .unwrap();
+    traders[1]
+        .crank(ctx, &products[1], &[&traders[2]])
+        .await
+        .unwrap();
+    let trader_risk_group = traders[2].get_trader_risk_group(&ctx.client).await;
+    assert_eq!(trader_risk_group.pending_cash_balance, ZERO_FRAC);
+    for leg in combo_0.legs().iter() {
+        let t_i = trader_risk_group.active_products[leg.product_index] as usize;
+        let position = trader_risk_group.trader_positions[t_i];
+        assert_eq!(position.position, Fractional::new(2 * leg.ratio, 0));
+        assert_eq!(position.pending_position, ZERO_FRAC);
+    }
+}
@@ -6,6 +6,7 @@ use dex::{
     utils::numeric::Fractional,
 };
 use dexteritysdk::{
+    admin::DexAdmin,
     common::KeypairD,
     processor::{orderbook::create_orderbook_with_params, update_product_funding},
     state::SDKProduct,
@@ -246,6 +247,7 @@ async fn test_expiration() {
         combo.event_queue,
         &traders[0].keypair,
         trader_keys.as_mut_slice(),
+        &[],
         4,
     )
     .await
@@ -283,3 +285,287 @@ async fn test_expiration() {
         );
     }
 }
+
+async fn add_outright(ctx: &DexAdmin, i: usize) -> (SDKProduct, KeypairD) {
+    let product = KeypairD::new();
+    let (market_signer, _) =
+        Pubkey::find_program_address(&[product.pubkey().as_ref()], &ctx.dex_program_id);
+    let event_size = Event::compute_slot_size(40) as u64;
+    let (orderbook_key, bids_key, asks_key, eq_key) = create_orderbook_with_params(
+        &ctx.client,
+        ctx.aaob_program_id,
+        market_signer,
+        75 + event_size * 5000,
+        10000,
+        10000,
+        1, // min_base_order_size
+        1000,
+    )
+    .await
+    .unwrap();
+    let name_str = format!("product{:width$}", i, width = NAME_LEN - 7);
+    let mut name: [u8; NAME_LEN] = Default::default();
+    name.clone_from_slice(name_str.as_bytes());
+    ctx.initialize_market_product(
+        product.pubkey(),
+        orderbook_key,
+        name,
+        Fractional::new(1, 1),
+        6,
+        0,
+    )
+    .await
+    .unwrap();
+    let sdk_product = SDKProduct {
+        name,
+        key: product.pubkey(),
+        orderbook: orderbook_key,
+        bids: bids_key,
+        asks: asks_key,
+        event_queue: eq_key,
+        market_signer,
+    };
+    (sdk_product, product)
+}
+
+#[tokio::test]
+async fn test_remove_all_dependent_combos() {
+    let n_products = 0;
+    let (ctx, traders) = &mut bootstrap_tests(
+        "noop_risk_engine",
+        "constant_fees",
+        "test_remove_all_dependent_combos",
+        2,
+        n_products,
+    )
+    .await;
+
+    let mut products = vec![];
+    let mut signers = vec![];
+    for i in 0..2 {
+        let (product, signer) = add_outright(ctx, i).await;
+        products.push(product);
+        signers.push(signer);
+    }
+
+    let combo = setup_combo(
+        ctx,
+        products
+            .iter()
+            .map(|p| p.key())
+            .sorted()
+            .collect::<Vec<_>>()
+            .as_slice(),
+        0,
+    )
+    .await
+    .unwrap();
+    let mpg = ctx.get_market_product_group().await;
+    let (combo_index, _) = mpg.find_product_index(&combo.key).unwrap();
+
+    traders[0]
+        .place_combo_order(ctx, &combo, Side::Bid, 5, -Fractional::new(4, 1))
+        .await
+        .unwrap();
+    traders[1]
+        .place_combo_order(ctx, &combo, Side::Ask, 5, -Fractional::new(2, 1))
+        .await
+        .unwrap();
+
+    update_product_funding::update_product_funding(
+        &ctx.client,
+        ctx.market_product_group,
+        &signers[0],
+        Fractional::from(0),
+        true,
+    )
+    .await
+    .unwrap();
+
+    // The outright cannot be removed while the combo still references it
+    assert!(ctx
+        .remove_market_product(
+            ctx.market_product_group,
+            products[0].key,
+            ctx.aaob_program_id,
+            products[0].orderbook,
+            products[0].market_signer,
+            products[0].event_queue,
+            products[0].bids,
+            products[0].asks,
+        )
+        .await
+        .is_err());
+
+    ctx.clear_expired_orderbook(
+        combo.key,
+        combo.market_signer,
+        combo.orderbook,
+        combo.event_queue,
+        combo.bids,
+        combo.asks,
+        Some(10),
+    )
+    .await
+    .unwrap();
+    let mut trader_keys = vec![
+        traders[0].account,
+        traders[0].fee_acct,
+        traders[1].account,
+        traders[1].fee_acct,
+    ];
+    ctx.crank_raw(
+        combo.key,
+        combo.market_signer,
+        combo.orderbook,
+        combo.event_queue,
+        &traders[0].keypair,
+        trader_keys.as_mut_slice(),
+        &[],
+        4,
+    )
+    .await
+    .unwrap();
+
+    ctx.remove_all_dependent_combos(products[0].key, &[combo.clone()])
+        .await
+        .unwrap();
+    let mpg = ctx.get_market_product_group().await;
+    assert!(mpg.find_product_index(&combo.key).is_err());
+    assert_eq!(mpg.active_combos().count(), 0);
+
+    // The combo's index is reused before the traders settle, their orders on the removed combo
+    // must not be attributed to the new product
+    let (new_product, _new_signer) = add_outright(ctx, 2).await;
+    let mpg = ctx.get_market_product_group().await;
+    let (new_index, _) = mpg.find_product_index(&new_product.key).unwrap();
+    assert_eq!(new_index, combo_index);
+    traders[0]
+        .place_order(ctx, &new_product, Side::Bid, 1, Fractional::new(1, 0))
+        .await
+        .unwrap();
+
+    for (i, trader) in traders.iter().enumerate() {
+        trader
+            .apply_funding(ctx, ctx.market_product_group)
+            .await
+            .unwrap();
+        let trg = ctx
+            .client
+            .get_anchor_account::<TraderRiskGroup>(trader.account)
+            .await;
+        let expected_open_orders = if i == 0 { 1 } else { 0 };
+        assert_eq!(
+            trg.open_orders.products[combo_index].num_open_orders,
+            expected_open_orders
+        );
+        assert_eq!(trg.open_orders.total_open_orders, expected_open_orders);
+    }
+
+    ctx.remove_market_product(
+        ctx.market_product_group,
+        products[0].key,
+        ctx.aaob_program_id,
+        products[0].orderbook,
+        products[0].market_signer,
+        products[0].event_queue,
+        products[0].bids,
+        products[0].asks,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_negative_settlement_price() {
+    let (ctx, traders) = &mut bootstrap_tests(
+        "noop_risk_engine",
+        "constant_fees",
+        "test_negative_settlement_price",
+        2,
+        0,
+    )
+    .await;
+
+    let signer = KeypairD::new();
+    let (market_signer, _) =
+        Pubkey::find_program_address(&[signer.pubkey().as_ref()], &ctx.dex_program_id);
+    let event_size = Event::compute_slot_size(40) as u64;
+    let (orderbook_key, bids_key, asks_key, eq_key) = create_orderbook_with_params(
+        &ctx.client,
+        ctx.aaob_program_id,
+        market_signer,
+        75 + event_size * 5000,
+        10000,
+        10000,
+        1, // min_base_order_size
+        1000,
+    )
+    .await
+    .unwrap();
+    let name_str = format!("product{:width$}", 0, width = NAME_LEN - 7);
+    let mut name: [u8; NAME_LEN] = Default::default();
+    name.clone_from_slice(name_str.as_bytes());
+    ctx.initialize_market_product(
+        signer.pubkey(),
+        orderbook_key,
+        name,
+        Fractional::new(1, 1),
+        6,
+        0,
+    )
+    .await
+    .unwrap();
+    let product = SDKProduct {
+        name,
+        key: signer.pubkey(),
+        orderbook: orderbook_key,
+        bids: bids_key,
+        asks: asks_key,
+        event_queue: eq_key,
+        market_signer,
+    };
+
+    traders[0]
+        .place_order(ctx, &product, Side::Bid, 2, 100)
+        .await
+        .unwrap();
+    traders[1]
+        .place_order(ctx, &product, Side::Ask, 2, 100)
+        .await
+        .unwrap();
+    traders[1]
+        .crank(ctx, &product, &[&traders[0]])
+        .await
+        .unwrap();
+
+    // A linear product whose underlying settles below zero
+    update_product_funding::update_product_funding(
+        &ctx.client,
+        ctx.market_product_group,
+        &signer,
+        -Fractional::from(15),
+        true,
+    )
+    .await
+    .unwrap();
+    let mpg = ctx.get_market_product_group().await;
+    let (_, market_product) = mpg.find_outright(&product.key).unwrap();
+    assert_eq!(market_product.cum_funding_per_share, -Fractional::from(15));
+    assert!(market_product.is_expired());
+
+    // The long pays the negative settlement price to the short
+    for (trader, expected_funding) in traders.iter().zip([-30, 30]) {
+        let cash_before = trader.get_trader_risk_group(&ctx.client).await.cash_balance;
+        trader
+            .apply_funding(ctx, ctx.market_product_group)
+            .await
+            .unwrap();
+        let trg = trader.get_trader_risk_group(&ctx.client).await;
+        assert_eq!(
+            trg.cash_balance - cash_before,
+            Fractional::from(expected_funding)
+        );
+        assert_eq!(trg.trader_positions[0].position, Fractional::from(0));
+    }
+}
@@ -0,0 +1,113 @@
+#![allow(non_snake_case)]
+
+use agnostic_orderbook::state::{SelfTradeBehavior, Side};
+use dex::{state::enums::OrderType, utils::numeric::Fractional, UpdateMarketProductGroupParams};
+use dexteritysdk::{
+    common::utils::*, trader::SDKTrader, SDKContext, ON_CANCEL_DISCRIMINANT, ON_POST_DISCRIMINANT,
+};
+use solana_program::instruction::Instruction;
+
+use crate::setup::bootstrap_tests;
+
+mod setup;
+
+/// Number of on_post and on_cancel calls the fee model logs when simulating `ixs`
+async fn num_order_events(ctx: &SDKContext, trader: &SDKTrader, ixs: Vec<Instruction>) -> usize {
+    let logs = ctx
+        .client
+        .simulate_instructions(ixs, vec![&trader.keypair])
+        .await
+        .unwrap();
+    logs.iter()
+        .filter(|log| {
+            log.starts_with("Program log: Fee Ix: OnPost")
+                || log.starts_with("Program log: Fee Ix: OnCancel")
+        })
+        .count()
+}
+
+fn bid_ixs(ctx: &SDKContext, trader: &SDKTrader) -> Vec<Instruction> {
+    trader.order_ixs(
+        ctx,
+        &ctx.products[0],
+        Side::Bid,
+        Fractional::from(10),
+        Fractional::from(10),
+        SelfTradeBehavior::DecrementTake,
+        &[],
+        &[],
+        OrderType::Limit,
+    )
+}
+
+#[tokio::test]
+async fn test_fee_hooks_on_post_and_cancel() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 1, 1).await;
+    let trader = &traders[0].clone();
+    let product = &ctx.products[0].clone();
+
+    let mpg = ctx.get_market_product_group().await;
+    assert_eq!(
+        mpg.get_on_post_discriminant(),
+        Some(vec![ON_POST_DISCRIMINANT])
+    );
+    assert_eq!(
+        mpg.get_on_cancel_discriminant(),
+        Some(vec![ON_CANCEL_DISCRIMINANT])
+    );
+
+    trader.deposit(ctx, 1000).await.unwrap();
+    // resting order invokes the on_post hook
+    assert_eq!(num_order_events(ctx, trader, bid_ixs(ctx, trader)).await, 1);
+    trader
+        .place_order(ctx, product, Side::Bid, 10, 10)
+        .await
+        .unwrap();
+    let trg = trader.get_trader_risk_group(&ctx.client).await;
+    let order_id = trg.open_orders.orders[trg.open_orders.products[0].head_index as usize].id;
+    assert_eq_frac(trg.open_orders.products[0].bid_qty_in_book, 10);
+
+    // cancelling invokes the on_cancel hook
+    let cancel_ixs = trader.cancel_order_ixs(ctx, product, order_id);
+    assert_eq!(num_order_events(ctx, trader, cancel_ixs).await, 1);
+    trader.cancel(ctx, product, order_id).await.unwrap();
+    let trg = trader.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg.open_orders.products[0].bid_qty_in_book, 0);
+    assert_eq!(trg.open_orders.products[0].num_open_orders, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancel_without_on_cancel_hook() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 1, 1).await;
+    let trader = &traders[0].clone();
+    let product = &ctx.products[0].clone();
+
+    let mut fee_model = ctx.get_market_product_group().await.fee_model_config();
+    fee_model.on_cancel_discriminant_len = 0;
+    ctx.update_market_product_group(UpdateMarketProductGroupParams {
+        fee_model: Some(fee_model),
+        ..Default::default()
+    })
+    .await?;
+    // cancels no longer pass the fee model accounts
+    ctx.as_mut().on_cancel_hook = false;
+
+    trader.deposit(ctx, 1000).await.unwrap();
+    assert_eq!(num_order_events(ctx, trader, bid_ixs(ctx, trader)).await, 1);
+    trader
+        .place_order(ctx, product, Side::Bid, 10, 10)
+        .await
+        .unwrap();
+    let trg = trader.get_trader_risk_group(&ctx.client).await;
+    let order_id = trg.open_orders.orders[trg.open_orders.products[0].head_index as usize].id;
+
+    let cancel_ixs = trader.cancel_order_ixs(ctx, product, order_id);
+    assert_eq!(num_order_events(ctx, trader, cancel_ixs).await, 0);
+    trader.cancel(ctx, product, order_id).await.unwrap();
+    let trg = trader.get_trader_risk_group(&ctx.client).await;
+    assert_eq!(trg.open_orders.products[0].num_open_orders, 0);
+    Ok(())
+}
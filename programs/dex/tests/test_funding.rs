@@ -5,14 +5,27 @@ use anchor_lang::solana_program::sysvar::clock::Clock;
 use borsh::BorshDeserialize;
 use dexteritysdk::{
     common::{utils::*, KeypairD},
-    instrument::settle_derivative,
-    oracle::{update_clock::*, update_oracle::*},
-    processor::update_trader_funding,
+    instrument::{initialize_derivative, settle_derivative},
+    oracle::{set_price_schedule::*, update_clock::*, update_oracle::*},
+    processor::{orderbook::create_orderbook, update_trader_funding},
+    SDKContext,
 };
 
-use dex::utils::numeric::{Fractional, ZERO_FRAC};
-use dummy_oracle::state::OraclePrice;
-use instruments::state::derivative_metadata::DerivativeMetadata;
+use dex::{
+    state::constants::NAME_LEN,
+    utils::numeric::{Fractional, ZERO_FRAC},
+};
+use dummy_oracle::state::{OraclePrice, ScheduleClock};
+use instruments::{
+    state::{
+        constants::MIN_SETTLEMENT_SAMPLES,
+        derivative_metadata::DerivativeMetadata,
+        enums::{InstrumentType, OracleType},
+    },
+    SettlementOutcome,
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
 
 use crate::setup::bootstrap_tests;
 
@@ -148,6 +161,189 @@ async fn test_dummy_oracle() {
     assert_eq!(err, ERROR_STATUS);
 }
 
+#[tokio::test]
+async fn test_dummy_oracle_price_schedule() {
+    let n_products = 1;
+    let (ctx, _) = &mut bootstrap_tests(
+        "noop_risk_engine",
+        "constant_fees",
+        "oracle_schedule",
+        2,
+        n_products,
+    )
+    .await;
+    let market_product_group = ctx.get_market_product_group().await;
+    let instrument_pubkey = market_product_group.market_products[0].product_key;
+
+    let derivative_metadata: Box<DerivativeMetadata> = ctx
+        .client
+        .get_anchor_account::<DerivativeMetadata>(instrument_pubkey)
+        .await;
+
+    update_oracle_price_account(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        solana_program::system_program::id(),
+        20,
+        6,
+    )
+    .await
+    .unwrap();
+    set_oracle_price_schedule(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        ScheduleClock::UnixTimestamp,
+        vec![(10, 100), (20, 200), (30, 150)],
+    )
+    .await
+    .unwrap();
+
+    // scheduled price follows the unix timestamp of the dummy clock
+    for (unix_timestamp, expected_price) in [(5, 20), (10, 100), (19, 100), (25, 200), (40, 150)] {
+        update_clock_account(
+            &ctx.client,
+            ctx.dummy_oracle_program_id,
+            &ctx.payer,
+            solana_program::system_program::id(),
+            unix_timestamp as u64,
+            0,
+            0,
+            0,
+            unix_timestamp,
+        )
+        .await
+        .unwrap();
+        let account = ctx
+            .client
+            .get_account(derivative_metadata.clock)
+            .await
+            .unwrap();
+        let clock: Clock = bincode::deserialize(&account.data.clone()).unwrap();
+        let account = ctx
+            .client
+            .get_account(derivative_metadata.price_oracle)
+            .await
+            .unwrap();
+        let oracle_price = OraclePrice::try_from_slice(&account.data.clone()).unwrap();
+        let (price, slot) = oracle_price.price_at(&clock);
+        assert_eq!(price, expected_price);
+        if unix_timestamp >= 10 {
+            assert_eq!(slot, clock.slot);
+        }
+    }
+
+    // times must be increasing
+    let err = match set_oracle_price_schedule(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        ScheduleClock::Slot,
+        vec![(10, 100), (10, 200)],
+    )
+    .await
+    {
+        Ok(_) => OK_STATUS,
+        Err(_) => ERROR_STATUS,
+    };
+    assert_eq!(err, ERROR_STATUS);
+}
+
+#[tokio::test]
+async fn test_settle_derivatives() {
+    let n_products = 2;
+    let (ctx, _) = &mut bootstrap_tests(
+        "noop_risk_engine",
+        "constant_fees",
+        "settle_batch",
+        2,
+        n_products,
+    )
+    .await;
+    let market_product_group = ctx.get_market_product_group().await;
+    let mut derivatives = vec![];
+    for i in 0..n_products {
+        let instrument_pubkey = market_product_group.market_products[i].product_key;
+        let derivative_metadata: Box<DerivativeMetadata> = ctx
+            .client
+            .get_anchor_account::<DerivativeMetadata>(instrument_pubkey)
+            .await;
+        derivatives.push((instrument_pubkey, derivative_metadata.price_oracle));
+    }
+    let derivative_metadata: Box<DerivativeMetadata> = ctx
+        .client
+        .get_anchor_account::<DerivativeMetadata>(derivatives[0].0)
+        .await;
+    let clock = derivative_metadata.clock;
+
+    update_oracle_price_account(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        solana_program::system_program::id(),
+        2025000000,
+        6,
+    )
+    .await
+    .unwrap();
+
+    let mut settled = false;
+    for unix_timestamp in [103, 1000, 1000] {
+        update_clock_account(
+            &ctx.client,
+            ctx.dummy_oracle_program_id,
+            &ctx.payer,
+            solana_program::system_program::id(),
+            unix_timestamp as u64,
+            unix_timestamp,
+            unix_timestamp as u64,
+            unix_timestamp as u64,
+            unix_timestamp,
+        )
+        .await
+        .unwrap();
+        let outcomes = settle_derivative::simulate_settle_derivatives(
+            &ctx.client,
+            ctx.market_product_group,
+            clock,
+            &derivatives,
+            &[],
+        )
+        .await
+        .unwrap();
+        let expected_outcome = if unix_timestamp < 1000 || settled {
+            SettlementOutcome::NotDue
+        } else {
+            SettlementOutcome::Settled
+        };
+        assert_eq!(outcomes, vec![expected_outcome; derivatives.len()]);
+        settled = expected_outcome == SettlementOutcome::Settled;
+        // derivatives that are not due are skipped instead of failing the batch
+        settle_derivative::settle_derivatives(
+            &ctx.client,
+            ctx.market_product_group,
+            clock,
+            &derivatives,
+            &[],
+        )
+        .await
+        .unwrap();
+        for (instrument_pubkey, _) in derivatives.iter() {
+            let derivative_metadata: Box<DerivativeMetadata> = ctx
+                .client
+                .get_anchor_account::<DerivativeMetadata>(*instrument_pubkey)
+                .await;
+            let expected_funding_time = if unix_timestamp < 1000 {
+                derivative_metadata.initialization_time
+            } else {
+                1000
+            };
+            assert_eq!(derivative_metadata.last_funding_time, expected_funding_time);
+        }
+    }
+}
+
 #[tokio::test]
 async fn test_funding() {
     let n_products = 1;
@@ -357,6 +553,7 @@ async fn test_funding() {
             derivative_metadata.price_oracle,
             derivative_metadata.clock,
             instrument_pubkey,
+            &[],
         )
         .await;
         assert!(!err.is_ok());
@@ -393,6 +590,7 @@ async fn test_funding() {
             derivative_metadata.price_oracle,
             derivative_metadata.clock,
             instrument_pubkey,
+            &[],
         )
         .await;
         assert!(res.is_ok());
@@ -442,3 +640,158 @@ async fn test_funding() {
         }
     }
 }
+
+/// Returns the slot the dummy oracle is stamped with, which is the slot of the bank rather
+/// than the one of the dummy clock
+async fn set_oracle_price(ctx: &SDKContext, price: i64) -> u64 {
+    let oracle = update_oracle_price_account(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        solana_program::system_program::id(),
+        price,
+        0,
+    )
+    .await
+    .unwrap();
+    let account = ctx.client.get_account(oracle).await.unwrap();
+    OraclePrice::try_from_slice(&account.data).unwrap().slot
+}
+
+async fn set_dummy_clock(ctx: &SDKContext, slot: u64, unix_timestamp: i64) {
+    update_clock_account(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        solana_program::system_program::id(),
+        slot,
+        0,
+        0,
+        0,
+        unix_timestamp,
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_settlement_window() {
+    let (ctx, _) = &mut bootstrap_tests(
+        "noop_risk_engine",
+        "constant_fees",
+        "test_settlement_window",
+        0,
+        1,
+    )
+    .await;
+    let derivative_metadata = ctx
+        .client
+        .get_anchor_account::<DerivativeMetadata>(ctx.products[0].key)
+        .await;
+    let (clock, oracle) = (derivative_metadata.clock, derivative_metadata.price_oracle);
+    let slot = set_oracle_price(ctx, 150).await;
+    set_dummy_clock(ctx, slot, 1000).await;
+    let args = || {
+        let mut args = initialize_derivative::InitializeDerivativeOptionalArgs::new(
+            InstrumentType::ExpiringCall,
+            1000,
+            100,
+            100,
+            OracleType::Dummy,
+        );
+        args.max_staleness_slots = 10;
+        args.settlement_window = 50;
+        args.min_settlement_samples = MIN_SETTLEMENT_SAMPLES;
+        args
+    };
+    // A single sample cannot set the settlement price
+    let mut too_few_samples = args();
+    too_few_samples.min_settlement_samples = 1;
+    assert!(initialize_derivative::initialize_derivative(
+        &ctx.client,
+        ctx.authority.pubkey(),
+        ctx.market_product_group,
+        oracle,
+        clock,
+        100,
+        too_few_samples,
+    )
+    .await
+    .is_err());
+    let derivative = initialize_derivative::initialize_derivative(
+        &ctx.client,
+        ctx.authority.pubkey(),
+        ctx.market_product_group,
+        oracle,
+        clock,
+        100,
+        args(),
+    )
+    .await
+    .unwrap();
+    let (market_signer, _) = Pubkey::find_program_address(&[derivative.as_ref()], &dex::ID);
+    let (orderbook, _, _, _) = create_orderbook(&ctx.client, ctx.aaob_program_id, market_signer)
+        .await
+        .unwrap();
+    let mut name = [b' '; NAME_LEN];
+    name[..7].copy_from_slice(b"sampled");
+    ctx.initialize_market_product(derivative, orderbook, name, Fractional::new(100, 4), 7, 0)
+        .await
+        .unwrap();
+
+    // Samples are only recorded in the settlement window
+    set_dummy_clock(ctx, slot, 1040).await;
+    assert!(settle_derivative::record_settlement_sample(
+        &ctx.client,
+        oracle,
+        clock,
+        derivative,
+        &[]
+    )
+    .await
+    .is_err());
+    for unix_timestamp in [1060, 1070] {
+        set_dummy_clock(ctx, slot, unix_timestamp).await;
+        settle_derivative::record_settlement_sample(&ctx.client, oracle, clock, derivative, &[])
+            .await
+            .unwrap();
+    }
+    let slot = set_oracle_price(ctx, 160).await;
+    set_dummy_clock(ctx, slot, 1080).await;
+    settle_derivative::record_settlement_sample(&ctx.client, oracle, clock, derivative, &[])
+        .await
+        .unwrap();
+
+    // The oracle goes stale before expiry, settlement uses the samples without reading it
+    set_dummy_clock(ctx, slot + 100, 1090).await;
+    assert!(settle_derivative::record_settlement_sample(
+        &ctx.client,
+        oracle,
+        clock,
+        derivative,
+        &[]
+    )
+    .await
+    .is_err());
+    set_dummy_clock(ctx, slot + 100, 1100).await;
+    settle_derivative::settle_derivative(
+        &ctx.client,
+        ctx.market_product_group,
+        oracle,
+        clock,
+        derivative,
+        &[],
+    )
+    .await
+    .unwrap();
+    let derivative_metadata = ctx
+        .client
+        .get_anchor_account::<DerivativeMetadata>(derivative)
+        .await;
+    assert_eq!(derivative_metadata.num_settlement_samples, 3);
+    assert!(derivative_metadata.expired());
+    // 150 for 20s, then 160 for 20s until expiry
+    let mpg = ctx.get_market_product_group().await;
+    let (_, market_product) = mpg.find_outright(&derivative).unwrap();
+    assert_eq_frac(market_product.cum_funding_per_share, Fractional::new(55, 0));
+}
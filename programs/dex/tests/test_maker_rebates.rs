@@ -0,0 +1,222 @@
+#![allow(non_snake_case)]
+
+use agnostic_orderbook::state::Side;
+use dexteritysdk::common::utils::*;
+
+use dex::{
+    state::market_product_group::FeeBpsBounds, utils::numeric::bps, UpdateMarketProductGroupParams,
+};
+
+use crate::setup::bootstrap_tests;
+
+mod setup;
+
+#[tokio::test]
+async fn test_maker_rebate_paid() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 1).await;
+    let maker = &traders[0].clone();
+    let taker = &traders[1].clone();
+    let product = &ctx.products[0].clone();
+
+    let maker_fee = bps(-50);
+    let taker_fee = bps(200);
+    let deposit = 1000;
+    let size = 10;
+    let fill_price = 10;
+    let quote = size * fill_price; // 100
+
+    ctx.update_fees(-50, 200).await.unwrap();
+
+    maker.deposit(ctx, deposit).await.unwrap();
+    taker.deposit(ctx, deposit).await.unwrap();
+    maker
+        .place_order(ctx, product, Side::Bid, size, fill_price)
+        .await
+        .unwrap();
+    taker
+        .place_order(ctx, product, Side::Ask, size, 1)
+        .await
+        .unwrap();
+    taker.crank(ctx, product, &[maker]).await.unwrap();
+
+    let trg_maker = maker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(
+        trg_maker.cash_balance,
+        deposit - quote + (-(quote * maker_fee)),
+    );
+    let trg_taker = taker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(
+        trg_taker.cash_balance,
+        deposit + quote + (-(quote * taker_fee)),
+    );
+
+    let mpg = ctx.get_market_product_group().await;
+    assert_eq_frac(mpg.rebates_paid, -(quote * maker_fee));
+    assert_eq_frac(mpg.collected_fees, quote * taker_fee + quote * maker_fee);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_maker_rebate_capped_at_taker_fee() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 1).await;
+    let maker = &traders[0].clone();
+    let taker = &traders[1].clone();
+    let product = &ctx.products[0].clone();
+
+    // the maker rebate is larger than the taker fee
+    let taker_fee = bps(20);
+    let deposit = 1000;
+    let size = 10;
+    let fill_price = 10;
+    let quote = size * fill_price; // 100
+
+    ctx.update_fees(-100, 20).await.unwrap();
+
+    maker.deposit(ctx, deposit).await.unwrap();
+    taker.deposit(ctx, deposit).await.unwrap();
+    maker
+        .place_order(ctx, product, Side::Bid, size, fill_price)
+        .await
+        .unwrap();
+    taker
+        .place_order(ctx, product, Side::Ask, size, 1)
+        .await
+        .unwrap();
+    taker.crank(ctx, product, &[maker]).await.unwrap();
+
+    // the maker only receives the taker fee
+    let trg_maker = maker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg_maker.cash_balance, deposit - quote + quote * taker_fee);
+    let trg_taker = taker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(
+        trg_taker.cash_balance,
+        deposit + quote + (-(quote * taker_fee)),
+    );
+
+    let mpg = ctx.get_market_product_group().await;
+    assert_eq_frac(mpg.rebates_paid, quote * taker_fee);
+    assert_eq_frac(mpg.collected_fees, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_taker_rebate_capped_at_maker_fee() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 1).await;
+    let maker = &traders[0].clone();
+    let taker = &traders[1].clone();
+    let product = &ctx.products[0].clone();
+
+    // the taker rebate is larger than the maker fee
+    let maker_fee = bps(10);
+    let deposit = 1000;
+    let size = 10;
+    let fill_price = 10;
+    let quote = size * fill_price; // 100
+
+    ctx.update_fees(10, -50).await.unwrap();
+
+    maker.deposit(ctx, deposit).await.unwrap();
+    taker.deposit(ctx, deposit).await.unwrap();
+    maker
+        .place_order(ctx, product, Side::Bid, size, fill_price)
+        .await
+        .unwrap();
+    taker
+        .place_order(ctx, product, Side::Ask, size, 1)
+        .await
+        .unwrap();
+    taker.crank(ctx, product, &[maker]).await.unwrap();
+
+    // the taker only receives the maker fee
+    let trg_maker = maker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(
+        trg_maker.cash_balance,
+        deposit - quote + (-(quote * maker_fee)),
+    );
+    let trg_taker = taker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg_taker.cash_balance, deposit + quote + quote * maker_fee);
+    assert_eq_frac(trg_taker.pending_fees, 0);
+
+    let mpg = ctx.get_market_product_group().await;
+    assert_eq_frac(mpg.rebates_paid, quote * maker_fee);
+    assert_eq_frac(mpg.collected_fees, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fills_settle_taker_fees_at_the_matched_rate() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 3, 1).await;
+    let maker_0 = &traders[0].clone();
+    let maker_1 = &traders[1].clone();
+    let taker = &traders[2].clone();
+    let product = &ctx.products[0].clone();
+
+    let maker_fee = bps(-50);
+    let taker_fee = bps(200);
+    let deposit = 1000;
+    let size = 5;
+    let fill_price = 10;
+    let quote = size * fill_price; // 50 per fill
+
+    ctx.update_fees(-50, 200).await.unwrap();
+
+    for trader in [maker_0, maker_1, taker] {
+        trader.deposit(ctx, deposit).await.unwrap();
+    }
+    for maker in [maker_0, maker_1] {
+        maker
+            .place_order(ctx, product, Side::Bid, size, fill_price)
+            .await
+            .unwrap();
+    }
+    taker
+        .place_order(ctx, product, Side::Ask, 2 * size, 1)
+        .await
+        .unwrap();
+    let trg_taker = taker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg_taker.pending_fees, 2 * quote * taker_fee);
+
+    // the taker fee rate is out of bounds by the time the fills are consumed
+    ctx.update_market_product_group(UpdateMarketProductGroupParams {
+        fee_bps_bounds: Some(FeeBpsBounds {
+            max_maker_fee_bps: 1000,
+            min_maker_fee_bps: -100,
+            max_taker_fee_bps: 100,
+            min_taker_fee_bps: -100,
+        }),
+        ..Default::default()
+    })
+    .await?;
+    taker
+        .crank(ctx, product, &[maker_0, maker_1])
+        .await
+        .unwrap();
+
+    // every fill settles its share of the fees computed when the order matched,
+    // and funds the rebate of its maker
+    let trg_taker = taker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(
+        trg_taker.cash_balance,
+        deposit + 2 * quote + (-(2 * quote * taker_fee)),
+    );
+    assert_eq_frac(trg_taker.pending_fees, 0);
+    for maker in [maker_0, maker_1] {
+        let trg_maker = maker.get_trader_risk_group(&ctx.client).await;
+        assert_eq_frac(
+            trg_maker.cash_balance,
+            deposit - quote + (-(quote * maker_fee)),
+        );
+    }
+
+    let mpg = ctx.get_market_product_group().await;
+    assert_eq_frac(mpg.rebates_paid, -(2 * quote * maker_fee));
+    assert_eq_frac(
+        mpg.collected_fees,
+        2 * quote * taker_fee + 2 * quote * maker_fee,
+    );
+    Ok(())
+}
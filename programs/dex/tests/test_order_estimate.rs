@@ -0,0 +1,66 @@
+#![allow(non_snake_case)]
+
+use agnostic_orderbook::state::Side;
+use dex::utils::numeric::bps;
+use dexteritysdk::common::utils::*;
+
+use crate::setup::bootstrap_tests;
+
+mod setup;
+
+#[tokio::test]
+async fn test_order_estimate() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 1).await;
+    let maker = &traders[0].clone();
+    let taker = &traders[1].clone();
+    let product = &ctx.products[0].clone();
+
+    let taker_fee = bps(200);
+    let deposit = 1000;
+    ctx.update_fees(10, 200).await.unwrap();
+    maker.deposit(ctx, deposit).await.unwrap();
+    taker.deposit(ctx, deposit).await.unwrap();
+
+    maker
+        .place_order(ctx, product, Side::Ask, 10, 10)
+        .await
+        .unwrap();
+    maker
+        .place_order(ctx, product, Side::Ask, 10, 11)
+        .await
+        .unwrap();
+    // a resting order populates the taker's fee cache without trading
+    taker
+        .place_order(ctx, product, Side::Bid, 1, 1)
+        .await
+        .unwrap();
+
+    let estimate = taker
+        .estimate_order(ctx, product, Side::Bid, 5, 9)
+        .await
+        .unwrap();
+    assert_eq_frac(estimate.filled_base_qty, 0);
+    assert!(estimate.average_price.is_none());
+
+    let estimate = taker
+        .estimate_order(ctx, product, Side::Bid, 15, 11)
+        .await
+        .unwrap();
+    let quote = 10 * 10 + 5 * 11;
+    assert!(estimate.fees_are_cached);
+    assert_eq_frac(estimate.filled_base_qty, 15);
+    assert_eq_frac(estimate.filled_quote_qty, quote);
+    assert_eq_frac(estimate.taker_fee_bps, taker_fee);
+    assert_eq_frac(estimate.taker_fee, quote * taker_fee);
+    assert_eq_frac(estimate.net_cash_impact, -quote + (-(quote * taker_fee)));
+
+    taker
+        .place_order(ctx, product, Side::Bid, 15, 11)
+        .await
+        .unwrap();
+    taker.crank(ctx, product, &[maker]).await.unwrap();
+    let trg_taker = taker.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg_taker.cash_balance, estimate.net_cash_impact + deposit);
+    Ok(())
+}
@@ -1,12 +1,53 @@
 #![allow(non_snake_case)]
-use agnostic_orderbook::state::Side;
+use agnostic_orderbook::state::{Event, Side};
 use anchor_lang::Key;
-use dex::state::{constants::*, enums::*};
-use dexteritysdk::{bootstrap::setup_combo, common::utils::*, state::SDKProduct};
+use dex::{
+    state::{constants::*, enums::*},
+    utils::numeric::Fractional,
+};
+use dexteritysdk::{
+    admin::DexAdmin,
+    bootstrap::setup_combo,
+    common::{utils::*, KeypairD},
+    processor::orderbook::create_orderbook_with_params,
+    state::SDKProduct,
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
 
 mod setup;
 use crate::setup::bootstrap_tests;
 
+async fn add_outright(ctx: &DexAdmin, i: usize) -> SDKResult {
+    let product = KeypairD::new();
+    let (market_signer, _) =
+        Pubkey::find_program_address(&[product.pubkey().as_ref()], &ctx.dex_program_id);
+    let event_size = Event::compute_slot_size(40) as u64;
+    let (orderbook_key, _, _, _) = create_orderbook_with_params(
+        &ctx.client,
+        ctx.aaob_program_id,
+        market_signer,
+        75 + event_size * 5000,
+        10000,
+        10000,
+        1, // min_base_order_size
+        1000,
+    )
+    .await?;
+    let name_str = format!("product{:width$}", i, width = NAME_LEN - 7);
+    let mut name: [u8; NAME_LEN] = Default::default();
+    name.clone_from_slice(name_str.as_bytes());
+    ctx.initialize_market_product(
+        product.pubkey(),
+        orderbook_key,
+        name,
+        Fractional::new(1, 1),
+        6,
+        0,
+    )
+    .await
+}
+
 #[tokio::test]
 async fn test_replace_trader_position() -> SDKResult {
     let (ctx, traders) = &mut bootstrap_tests(
@@ -14,7 +55,7 @@ async fn test_replace_trader_position() -> SDKResult {
         "constant_fees",
         "test",
         1,
-        MAX_TRADER_POSITIONS as u32 + 1,
+        MAX_TRADER_POSITIONS as u32,
     )
     .await;
     let combo = setup_combo(
@@ -49,42 +90,36 @@ async fn test_replace_trader_position() -> SDKResult {
             assert_eq!(pos.tag, AccountTag::TraderPosition);
         }
     }
+    // Every position is occupied, there is one for each outright so no product can be listed
+    // that the trader would have to replace a position for
+    {
+        let res = add_outright(ctx, MAX_TRADER_POSITIONS).await;
+        assert!(res.is_err(), "should fail");
+    }
     // Place a combo that includes the first 2 products.
     trader
         .place_combo_order(ctx, &combo, Side::Ask, 10, 100)
         .await?;
-    // Cancel an order and try to place new order for a MAX_TRADER_POSITIONS+1 product.
-    // This should fail because there exists a combo with open orders where a leg corresponds to the product that was cancelled.
-    // This means the trader position cannot be replaced and therefore there are not enough positions.
-    {
-        let trg = trader.get_trader_risk_group(&ctx.client).await;
-        let first_order = trg.open_orders.orders[trg.open_orders.products[0].head_index].id;
-        trader.cancel(ctx, &products[0], first_order).await?;
-
-        let res = trader
-            .place_order(ctx, &products[MAX_TRADER_POSITIONS], Side::Ask, 40, 100)
-            .await;
-        assert!(res.is_err(), "should fail");
-    }
-    // Cancel an order and place an order for a different product. This should replace the existing position since it is zero'd out (w/ no combos)
-    {
+    // Cancel the orders of the first product, which is a leg of the combo with open orders, and of
+    // the fourth product. Both positions are now empty, but new orders on the products keep them
+    // rather than taking another one.
+    for i in [0, 3] {
         let trg = trader.get_trader_risk_group(&ctx.client).await;
-        let fourth_order = trg.open_orders.orders[trg.open_orders.products[3].head_index].id;
-        let new_status = trader.cancel(ctx, &products[3], fourth_order).await;
-        assert!(new_status.is_ok());
+        let order = trg.open_orders.orders[trg.open_orders.products[i].head_index].id;
+        trader.cancel(ctx, &products[i], order).await?;
         trader
-            .place_order(ctx, &products[MAX_TRADER_POSITIONS], Side::Ask, 41, 100) // second tx must be different from failed one above
-            .await
-            .unwrap();
+            .place_order(ctx, &products[i], Side::Ask, 41, 100) // must be different from the cancelled order
+            .await?;
     }
 
-    // Assert third position was replaced
+    // Assert no position was replaced
     {
         let trg = trader.get_trader_risk_group(&ctx.client).await;
-        assert_eq!(
-            trg.trader_positions[3].product_key,
-            products[MAX_TRADER_POSITIONS].key,
-        );
+        for (i, product) in products.iter().enumerate() {
+            let pos = &trg.trader_positions[trg.active_products[i] as usize];
+            assert_eq!(pos.tag, AccountTag::TraderPosition);
+            assert_eq!(pos.product_key, product.key);
+        }
     }
 
     Ok(())
@@ -0,0 +1,252 @@
+#![allow(non_snake_case)]
+
+use dex::utils::numeric::{Fractional, ZERO_FRAC};
+use dexteritysdk::{
+    bootstrap::bootstrap_series,
+    common::{utils::*, KeypairD},
+    instrument::{series, settle_derivative},
+    oracle::update_clock::*,
+    state::SDKProduct,
+    SDKContext, SeriesConfig,
+};
+use instruments::{
+    state::{derivative_metadata::DerivativeMetadata, enums::InstrumentType, series::Series},
+    InitializeSeriesParams,
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use crate::setup::bootstrap_tests;
+
+mod setup;
+
+async fn set_time(ctx: &SDKContext, unix_timestamp: i64) {
+    update_clock_account(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        solana_program::system_program::id(),
+        unix_timestamp as u64,
+        unix_timestamp,
+        unix_timestamp as u64,
+        unix_timestamp as u64,
+        unix_timestamp,
+    )
+    .await
+    .unwrap();
+}
+
+async fn settle_all(ctx: &SDKContext, products: &[SDKProduct]) {
+    for product in products {
+        let derivative_metadata = ctx
+            .client
+            .get_anchor_account::<DerivativeMetadata>(product.key)
+            .await;
+        settle_derivative::settle_derivative(
+            &ctx.client,
+            ctx.market_product_group,
+            derivative_metadata.price_oracle,
+            derivative_metadata.clock,
+            product.key,
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+}
+
+async fn initialize_series(
+    ctx: &SDKContext,
+    authority: &Keypair,
+    derivative_metadata: &DerivativeMetadata,
+    params: InitializeSeriesParams,
+) -> SDKResult<Pubkey> {
+    series::initialize_series(
+        &ctx.client,
+        authority,
+        derivative_metadata.price_oracle,
+        ctx.market_product_group,
+        derivative_metadata.clock,
+        params,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_roll_series() {
+    let (ctx, _) = &mut bootstrap_tests(
+        "noop_risk_engine",
+        "constant_fees",
+        "test_roll_series",
+        0,
+        1,
+    )
+    .await;
+    let derivative_metadata = ctx
+        .client
+        .get_anchor_account::<DerivativeMetadata>(ctx.products[0].key)
+        .await;
+    let (clock, oracle) = (derivative_metadata.clock, derivative_metadata.price_oracle);
+    set_time(ctx, 1000).await;
+
+    let (series_key, products) = bootstrap_series(
+        ctx,
+        oracle,
+        clock,
+        &SeriesConfig {
+            strikes: vec![100, 200],
+            expiry_interval: 100,
+            puts: false,
+        },
+        ctx.aaob_program_id,
+    )
+    .await
+    .unwrap();
+    let series_account = ctx.client.get_anchor_account::<Series>(series_key).await;
+    assert_eq!(series_account.num_rolls, 1);
+    assert_eq!(products.len(), 2);
+    let mpg = ctx.get_market_product_group().await;
+    let mut names = vec![];
+    for (product, strike) in products.iter().zip([100, 200]) {
+        let (_, market_product) = mpg.find_outright(&product.key).unwrap();
+        names.push(market_product.name);
+        let derivative_metadata = ctx
+            .client
+            .get_anchor_account::<DerivativeMetadata>(product.key)
+            .await;
+        assert_eq!(
+            derivative_metadata.instrument_type,
+            InstrumentType::ExpiringCall
+        );
+        assert_eq!(derivative_metadata.strike, Fractional::from(strike));
+        assert_eq!(derivative_metadata.initialization_time, 1000);
+        assert_eq!(derivative_metadata.minimum_funding_period, 100);
+    }
+
+    // The next expiry is only listed once the current one has settled
+    assert!(
+        series::roll_series(&ctx.client, ctx.aaob_program_id, series_key)
+            .await
+            .is_err()
+    );
+    set_time(ctx, 1100).await;
+    settle_all(ctx, &products).await;
+
+    // Nobody rolled the series for two intervals, so their expiries are skipped
+    set_time(ctx, 1350).await;
+    let rolled_products = series::roll_series(&ctx.client, ctx.aaob_program_id, series_key)
+        .await
+        .unwrap();
+    let series_account = ctx.client.get_anchor_account::<Series>(series_key).await;
+    assert_eq!(series_account.num_rolls, 4);
+    let mpg = ctx.get_market_product_group().await;
+    for product in rolled_products.iter() {
+        let (_, market_product) = mpg.find_outright(&product.key).unwrap();
+        names.push(market_product.name);
+        let derivative_metadata = ctx
+            .client
+            .get_anchor_account::<DerivativeMetadata>(product.key)
+            .await;
+        assert_eq!(derivative_metadata.initialization_time, 1300);
+        assert_eq!(derivative_metadata.last_funding_time, 1300);
+    }
+    // Every listed product has its own name
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), 4);
+}
+
+#[tokio::test]
+async fn test_initialize_series() {
+    let (ctx, _) = &mut bootstrap_tests(
+        "noop_risk_engine",
+        "constant_fees",
+        "test_initialize_series",
+        0,
+        1,
+    )
+    .await;
+    let derivative_metadata = ctx
+        .client
+        .get_anchor_account::<DerivativeMetadata>(ctx.products[0].key)
+        .await;
+    let params = InitializeSeriesParams {
+        instrument_type: InstrumentType::ExpiringCall,
+        oracle_type: derivative_metadata.oracle_type,
+        strikes: vec![Fractional::from(100)],
+        start_time: 1000,
+        expiry_interval: 100,
+        close_authority: ctx.authority.pubkey(),
+        name: Default::default(),
+        tick_size: Fractional::new(100, 4),
+        base_decimals: 7,
+        price_offset: ZERO_FRAC,
+        max_staleness_slots: 0,
+        max_confidence_bps: 0,
+        cap: ZERO_FRAC,
+        digital_payout: ZERO_FRAC,
+        settlement_window: 10,
+        min_settlement_samples: 3,
+    };
+    // Series can only hold expiring instruments
+    assert!(initialize_series(
+        ctx,
+        &ctx.authority,
+        &derivative_metadata,
+        InitializeSeriesParams {
+            instrument_type: InstrumentType::RecurringCall,
+            ..params.clone()
+        }
+    )
+    .await
+    .is_err());
+    assert!(initialize_series(
+        ctx,
+        &ctx.authority,
+        &derivative_metadata,
+        InitializeSeriesParams {
+            strikes: vec![],
+            ..params.clone()
+        }
+    )
+    .await
+    .is_err());
+    // Every expiry needs time to trade before its settlement window opens
+    assert!(initialize_series(
+        ctx,
+        &ctx.authority,
+        &derivative_metadata,
+        InitializeSeriesParams {
+            settlement_window: 100,
+            ..params.clone()
+        }
+    )
+    .await
+    .is_err());
+    // Only the market product group authority can create a series
+    let not_authority = KeypairD::new();
+    assert!(
+        initialize_series(ctx, &not_authority, &derivative_metadata, params.clone())
+            .await
+            .is_err()
+    );
+
+    let series_key = initialize_series(ctx, &ctx.authority, &derivative_metadata, params)
+        .await
+        .unwrap();
+    let series_account = ctx.client.get_anchor_account::<Series>(series_key).await;
+    assert!(series_account.is_initialized());
+    assert_eq!(series_account.strikes(), &[Fractional::from(100)]);
+    assert_eq!(series_account.num_rolls, 0);
+    assert_eq!(series_account.settlement_window, 10);
+    assert_eq!(series_account.min_settlement_samples, 3);
+    assert_eq!(
+        series_account.market_product_group,
+        ctx.market_product_group
+    );
+    assert_eq!(
+        series_account.price_oracle,
+        derivative_metadata.price_oracle
+    );
+    assert_ne!(series_account.clock, Pubkey::default());
+}
@@ -0,0 +1,89 @@
+#![allow(non_snake_case)]
+
+use agnostic_orderbook::state::Side;
+use anchor_lang::solana_program::program_pack::Pack;
+use dex::utils::numeric::Fractional;
+use dexteritysdk::common::{utils::*, KeypairD};
+use solana_sdk::{account::ReadableAccount, signature::Signer};
+
+use crate::setup::bootstrap_tests;
+
+mod setup;
+
+const BASE_MINT_DECIMALS: u8 = 9;
+
+#[tokio::test]
+async fn test_spot_product() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 1).await;
+    let seller = &traders[0].clone();
+    let buyer = &traders[1].clone();
+    let product = &ctx.products[0].clone();
+
+    let base_mint = KeypairD::new();
+    create_mint2(&ctx.client, &base_mint, &ctx.payer, BASE_MINT_DECIMALS).await?;
+    ctx.initialize_spot_product(product.key, base_mint.pubkey())
+        .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    assert!(market_product_group.is_spot(0));
+
+    let seller_base_wallet =
+        create_token_account(&ctx.client, &base_mint.pubkey(), &seller.keypair.pubkey()).await?;
+    let buyer_base_wallet =
+        create_token_account(&ctx.client, &base_mint.pubkey(), &buyer.keypair.pubkey()).await?;
+    mint_to(
+        &ctx.client,
+        &base_mint.pubkey(),
+        &seller_base_wallet,
+        &ctx.payer,
+        10 * 10_u64.pow(BASE_MINT_DECIMALS as u32),
+    )
+    .await?;
+
+    seller
+        .deposit_spot(ctx, product, seller_base_wallet, 10)
+        .await?;
+    buyer.deposit(ctx, 1000).await?;
+    let trg = seller.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(trg.trader_positions[0].position, 10);
+
+    // sells can't exceed the base balance, including what is already resting in the book
+    assert!(seller
+        .place_order(ctx, product, Side::Ask, 11, 25)
+        .await
+        .is_err());
+    seller.place_order(ctx, product, Side::Ask, 4, 25).await?;
+    assert!(seller
+        .place_order(ctx, product, Side::Ask, 7, 25)
+        .await
+        .is_err());
+    assert!(seller
+        .withdraw_spot(ctx, product, seller_base_wallet, 7)
+        .await
+        .is_err());
+
+    buyer.place_order(ctx, product, Side::Bid, 4, 25).await?;
+    buyer.crank(ctx, product, &[seller]).await?;
+
+    let seller_trg = seller.get_trader_risk_group(&ctx.client).await;
+    let buyer_trg = buyer.get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(seller_trg.trader_positions[0].position, 6);
+    assert_eq_frac(buyer_trg.trader_positions[0].position, 4);
+
+    // the bought base tokens can be withdrawn but not sold short afterwards
+    buyer
+        .withdraw_spot(ctx, product, buyer_base_wallet, 4)
+        .await?;
+    let buyer_wallet =
+        spl_token::state::Account::unpack(ctx.client.get_account(buyer_base_wallet).await?.data())
+            .unwrap();
+    assert_eq_frac(
+        Fractional::new(buyer_wallet.amount as i64, BASE_MINT_DECIMALS as u64),
+        4,
+    );
+    assert!(buyer
+        .place_order(ctx, product, Side::Ask, 1, 25)
+        .await
+        .is_err());
+    Ok(())
+}
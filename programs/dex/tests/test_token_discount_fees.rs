@@ -0,0 +1,145 @@
+#![allow(non_snake_case)]
+
+use agnostic_orderbook::state::Side;
+use dexteritysdk::{
+    common::{utils::*, KeypairD},
+    SDKContext,
+};
+use solana_program::{pubkey::Pubkey, system_program};
+use solana_sdk::signature::Signer;
+use token_discount_fees::{update_fees_ix, DiscountTier, UpdateFeesParams};
+
+use dex::utils::numeric::Fractional;
+
+use crate::setup::bootstrap_tests;
+
+mod setup;
+
+fn update_fees(ctx: &SDKContext, authority: Pubkey) -> solana_program::instruction::Instruction {
+    update_fees_ix(
+        ctx.fee_model_program_id,
+        authority,
+        ctx.fee_model_config_acct,
+        ctx.market_product_group,
+        system_program::id(),
+        UpdateFeesParams {
+            maker_fee_bps: 10,
+            taker_fee_bps: 20,
+            discount_mint: ctx.vault_mint,
+            tiers: vec![DiscountTier {
+                min_balance: 1,
+                discount_bps: 5,
+            }],
+        },
+    )
+}
+
+#[tokio::test]
+async fn test_update_fees_requires_authority() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "token_discount_fees", "test", 1, 1).await;
+    let trader = &traders[0];
+
+    let ix = update_fees(ctx, trader.keypair.pubkey());
+    assert!(ctx
+        .client
+        .sign_send_instructions(vec![ix], vec![&trader.keypair])
+        .await
+        .is_err());
+
+    // the config was created by the payer during bootstrap
+    let ix = update_fees(ctx, ctx.payer.pubkey());
+    ctx.client.sign_send_instructions(vec![ix], vec![]).await
+}
+
+#[tokio::test]
+async fn test_new_config_requires_group_signature() -> SDKResult {
+    let (ctx, _) =
+        &mut bootstrap_tests("noop_risk_engine", "token_discount_fees", "test", 1, 1).await;
+    let market_product_group = KeypairD::new();
+    let (fee_model_config_acct, _) = Pubkey::find_program_address(
+        &[
+            b"fee_model_config_acct",
+            market_product_group.pubkey().as_ref(),
+        ],
+        &ctx.fee_model_program_id,
+    );
+    let mut ix = update_fees(ctx, ctx.payer.pubkey());
+    ix.accounts[1].pubkey = fee_model_config_acct;
+    ix.accounts[2].pubkey = market_product_group.pubkey();
+
+    // nobody can claim the config of a group they do not control
+    assert!(ctx
+        .client
+        .sign_send_instructions(vec![ix.clone()], vec![])
+        .await
+        .is_err());
+    ix.accounts[2].is_signer = true;
+    ctx.client
+        .sign_send_instructions(vec![ix], vec![&market_product_group])
+        .await
+}
+
+#[tokio::test]
+async fn test_fee_accounts_forwarded_to_fee_model() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "token_discount_fees", "test", 3, 1).await;
+    let product = &ctx.products[0].clone();
+    let ix = update_fees(ctx, ctx.payer.pubkey());
+    ctx.client.sign_send_instructions(vec![ix], vec![]).await?;
+    for trader in traders.iter() {
+        trader.deposit(ctx, 1000).await?;
+    }
+    let (taker, maker_0, maker_1) = (&traders[0], &traders[1], &traders[2]);
+
+    // only a wallet of the trader holding the discount mint qualifies
+    maker_0
+        .place_order_with_fee_accts(ctx, product, Side::Ask, 1, 100, &[maker_0.wallet])
+        .await?;
+    maker_1
+        .place_order_with_fee_accts(ctx, product, Side::Ask, 1, 100, &[maker_1.account])
+        .await?;
+    let maker_0_risk_group = maker_0.get_trader_risk_group(&ctx.client).await;
+    let maker_1_risk_group = maker_1.get_trader_risk_group(&ctx.client).await;
+    assert_eq!(maker_0_risk_group.taker_fee_bps, 15);
+    assert_eq!(maker_1_risk_group.taker_fee_bps, 20);
+
+    // fee accounts are required once the fee model takes them
+    assert!(taker
+        .place_order(ctx, product, Side::Bid, 2, 100)
+        .await
+        .is_err());
+    taker
+        .place_order_with_fee_accts(ctx, product, Side::Bid, 2, 100, &[taker.wallet])
+        .await?;
+    let taker_risk_group = taker.get_trader_risk_group(&ctx.client).await;
+    assert_eq!(taker_risk_group.taker_fee_bps, 15);
+
+    // each maker's fee accounts trail the sorted trader accounts
+    taker
+        .crank_with_fee_accts(
+            ctx,
+            product,
+            &[maker_0, maker_1],
+            &[
+                (maker_0.account, &[maker_0.wallet]),
+                (maker_1.account, &[maker_1.account]),
+            ],
+        )
+        .await?;
+    let taker_risk_group = taker.get_trader_risk_group(&ctx.client).await;
+    let t_i = taker_risk_group.active_products[0] as usize;
+    assert_eq!(
+        taker_risk_group.trader_positions[t_i].position,
+        Fractional::from(2)
+    );
+    for maker in [maker_0, maker_1] {
+        let maker_risk_group = maker.get_trader_risk_group(&ctx.client).await;
+        let t_i = maker_risk_group.active_products[0] as usize;
+        assert_eq!(
+            maker_risk_group.trader_positions[t_i].position,
+            Fractional::from(-1)
+        );
+    }
+    Ok(())
+}
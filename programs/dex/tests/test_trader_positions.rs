@@ -0,0 +1,58 @@
+#![allow(non_snake_case)]
+use agnostic_orderbook::state::Side;
+use dex::state::{constants::*, enums::*};
+use dexteritysdk::common::utils::*;
+
+mod setup;
+use crate::setup::bootstrap_tests;
+
+// Default compute budget of an instruction
+const MAX_COMPUTE_UNITS: u64 = 200_000;
+
+#[tokio::test]
+async fn test_trader_positions() -> SDKResult {
+    let (ctx, traders) = &mut bootstrap_tests(
+        "alpha_risk_engine",
+        "constant_fees",
+        "test",
+        2,
+        MAX_OUTRIGHTS as u32,
+    )
+    .await;
+    let (maker, taker) = (&traders[0], &traders[1]);
+    let products = ctx.products.clone();
+    maker.deposit(ctx, 1_000_000).await?;
+    taker.deposit(ctx, 1_000_000).await?;
+
+    // Assert positions Uninitialized to start
+    {
+        let trg = taker.get_trader_risk_group(&ctx.client).await;
+        for pos in &trg.trader_positions {
+            assert_eq!(pos.tag, AccountTag::Uninitialized);
+        }
+    }
+    // Take a position in every outright, the risk engine and the dex go through every
+    // position on each order so its cost is measured along the way
+    for (i, product) in products.iter().enumerate() {
+        maker.place_order(ctx, product, Side::Ask, 1, 100).await?;
+        taker.place_order(ctx, product, Side::Bid, 1, 100).await?;
+        taker.crank(ctx, product, &[maker]).await?;
+        if i + 1 == 64 || i + 1 == MAX_TRADER_POSITIONS {
+            let units = taker
+                .order_compute_units(ctx, &products[0], Side::Bid, 1, 99)
+                .await?;
+            assert!(units < MAX_COMPUTE_UNITS);
+        }
+    }
+    // Assert all positions have now been initialized
+    {
+        let trg = taker.get_trader_risk_group(&ctx.client).await;
+        for (i, product) in products.iter().enumerate() {
+            let pos = &trg.trader_positions[trg.active_products[i] as usize];
+            assert_eq!(pos.tag, AccountTag::TraderPosition);
+            assert_eq!(pos.product_key, product.key);
+            assert_eq_frac(pos.position, 1);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,370 @@
+#![allow(non_snake_case)]
+
+use agnostic_orderbook::state::{SelfTradeBehavior, Side};
+
+use borsh::BorshDeserialize;
+use dex::{
+    state::{constants::NAME_LEN, enums::OrderType},
+    utils::{numeric::Fractional, orderbook::get_order_price},
+};
+use dexteritysdk::{
+    bootstrap::setup_combo_with_ratios,
+    common::utils::*,
+    instrument::settle_derivative,
+    oracle::{update_clock::*, update_oracle::*},
+    state::{SDKCombo, SDKProduct},
+    trader::SDKTrader,
+    SDKContext,
+};
+use dummy_oracle::state::OraclePrice;
+use instruments::state::derivative_metadata::DerivativeMetadata;
+
+use crate::setup::bootstrap_tests;
+
+mod setup;
+
+#[tokio::test]
+async fn test_update_market_product() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 1).await;
+    let trader = &traders[0].clone();
+    let product = &ctx.products[0].clone();
+    for trader in traders.iter() {
+        trader.deposit(ctx, 1_000_000).await?;
+    }
+
+    let mut name: [u8; NAME_LEN] = Default::default();
+    name.clone_from_slice(format!("renamed{:width$}", 0, width = NAME_LEN - 7).as_bytes());
+    let tick_size = Fractional::new(100, 4);
+    ctx.update_market_product(product, &[], name, tick_size, 0)
+        .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    let (_, market_product) = market_product_group.find_outright(&product.key).unwrap();
+    assert_eq!(market_product.name, name);
+    assert_eq_frac(market_product.tick_size, tick_size);
+
+    // resting orders are stored in ticks, so they can only move to a finer tick size
+    trader.place_order(ctx, product, Side::Bid, 1, 25).await?;
+    let new_tick_size = Fractional::new(500, 4);
+    assert!(ctx
+        .update_market_product(product, &[], name, new_tick_size, 0)
+        .await
+        .is_err());
+    trader.cancel_all_orders(ctx, &[0]).await?;
+    ctx.update_market_product(product, &[], name, new_tick_size, 0)
+        .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    let (_, market_product) = market_product_group.find_outright(&product.key).unwrap();
+    assert_eq_frac(market_product.tick_size, new_tick_size);
+
+    // raise the minimum order size
+    ctx.update_market_product(product, &[], name, new_tick_size, 5)
+        .await?;
+    assert!(trader
+        .place_order(ctx, product, Side::Bid, 1, 25)
+        .await
+        .is_err());
+    trader.place_order(ctx, product, Side::Bid, 5, 25).await?;
+
+    // fills waiting in the event queue are priced in ticks as well
+    traders[1]
+        .place_order(ctx, product, Side::Ask, 5, 25)
+        .await?;
+    let finer_tick_size = Fractional::new(100, 4);
+    assert!(ctx
+        .update_market_product(product, &[], name, finer_tick_size, 5)
+        .await
+        .is_err());
+    trader
+        .crank(ctx, product, &[&traders[0], &traders[1]])
+        .await?;
+
+    // every trader with a resting order is needed to rescale the book
+    trader.place_order(ctx, product, Side::Bid, 5, 20).await?;
+    traders[1]
+        .place_order(ctx, product, Side::Ask, 5, 30)
+        .await?;
+    assert!(ctx
+        .update_market_product(product, &[trader], name, finer_tick_size, 5)
+        .await
+        .is_err());
+    ctx.update_market_product(product, &[trader, &traders[1]], name, finer_tick_size, 5)
+        .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    let (product_index, market_product) = market_product_group.find_outright(&product.key).unwrap();
+    assert_eq_frac(market_product.tick_size, finer_tick_size);
+    for (trader, price) in [(trader, 20), (&traders[1], 30)] {
+        let trader_risk_group = trader.get_trader_risk_group(&ctx.client).await;
+        let open_orders = &trader_risk_group.open_orders;
+        let order_id = open_orders.orders[open_orders.products[product_index].head_index].id;
+        assert_eq_frac(
+            get_order_price(
+                order_id,
+                market_product.tick_size,
+                market_product.price_offset,
+            )
+            .unwrap(),
+            price,
+        );
+    }
+    // the rescaled orders still fill at their price
+    trader.place_order(ctx, product, Side::Bid, 5, 30).await?;
+    trader
+        .crank(ctx, product, &[&traders[0], &traders[1]])
+        .await?;
+    let trader_risk_group = traders[1].get_trader_risk_group(&ctx.client).await;
+    assert_eq!(
+        trader_risk_group.open_orders.products[product_index].num_open_orders,
+        0
+    );
+    let tpi = trader_risk_group.active_products[product_index] as usize;
+    assert_eq_frac(trader_risk_group.trader_positions[tpi].position, -10);
+
+    // combos are updated through update_combo
+    let not_a_combo = SDKCombo {
+        key: product.key,
+        name: product.name,
+        orderbook: product.orderbook,
+        bids: product.bids,
+        asks: product.asks,
+        market_signer: product.market_signer,
+        event_queue: product.event_queue,
+        legs: vec![],
+    };
+    assert!(ctx
+        .update_combo(&not_a_combo, &[], name, finer_tick_size, 0)
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_position_limits() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 1).await;
+    let product = &ctx.products[0].clone();
+    for trader in traders.iter() {
+        trader.deposit(ctx, 1_000_000).await?;
+    }
+
+    ctx.update_position_limits(product.key, 0, 10).await?;
+    // resting orders count towards the position limit as if they filled
+    assert!(traders[0]
+        .place_order(ctx, product, Side::Bid, 20, 25)
+        .await
+        .is_err());
+    traders[0]
+        .place_order(ctx, product, Side::Bid, 10, 25)
+        .await?;
+    traders[1]
+        .place_order(ctx, product, Side::Ask, 10, 25)
+        .await?;
+    traders[0]
+        .crank(ctx, product, &[&traders[0], &traders[1]])
+        .await?;
+    assert!(traders[1]
+        .place_order(ctx, product, Side::Ask, 1, 26)
+        .await
+        .is_err());
+    // reducing a position at the limit is allowed
+    traders[1]
+        .place_order(ctx, product, Side::Bid, 5, 24)
+        .await?;
+    traders[1].cancel_all_orders(ctx, &[0]).await?;
+
+    ctx.update_position_limits(product.key, 10, 0).await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    let (_, market_product) = market_product_group.find_outright(&product.key).unwrap();
+    assert_eq_frac(market_product.open_long_interest, 10);
+    traders[0]
+        .place_order(ctx, product, Side::Bid, 5, 25)
+        .await?;
+    assert!(traders[1]
+        .place_order(ctx, product, Side::Ask, 5, 25)
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_combo_position_limits() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 1, 2).await;
+    let trader = &traders[0].clone();
+    let product = &ctx.products[0].clone();
+    trader.deposit(ctx, 1_000_000).await?;
+    let products = ctx.products.iter().map(|p| p.key).collect::<Vec<_>>();
+    let combo = setup_combo_with_ratios(ctx, &products, &[1, 1], 0).await?;
+
+    ctx.update_position_limits(product.key, 0, 10).await?;
+    trader.place_order(ctx, product, Side::Bid, 6, 25).await?;
+    // resting combo orders reserve room in each leg next to the outright's resting orders
+    assert!(trader
+        .place_combo_order(ctx, &combo, Side::Bid, 6, 50)
+        .await
+        .is_err());
+    trader
+        .place_combo_order(ctx, &combo, Side::Bid, 4, 50)
+        .await?;
+    assert!(trader
+        .place_order(ctx, product, Side::Bid, 1, 25)
+        .await
+        .is_err());
+    // orders in the other direction don't use up the room
+    trader
+        .place_combo_order(ctx, &combo, Side::Ask, 10, 60)
+        .await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_price_band() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 3, 1).await;
+    let product = &ctx.products[0].clone();
+    for trader in traders.iter() {
+        trader.deposit(ctx, 1_000_000).await?;
+    }
+    traders[0]
+        .place_order(ctx, product, Side::Bid, 1, 100)
+        .await?;
+    traders[1]
+        .place_order(ctx, product, Side::Ask, 1, 102)
+        .await?;
+
+    ctx.update_price_band(product.key, 500, 0, 0).await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    let (product_index, market_product) = market_product_group.find_outright(&product.key).unwrap();
+    assert!(market_product_group.price_bands[product_index]
+        .get_bounds(&market_product.prices, None)
+        .unwrap()
+        .is_some());
+
+    // post only orders far outside of the band are rejected
+    assert!(traders[2]
+        .place_order_with_self_trade_behavior(
+            ctx,
+            product,
+            Side::Ask,
+            1,
+            200,
+            SelfTradeBehavior::DecrementTake,
+            &[],
+            OrderType::PostOnly,
+        )
+        .await
+        .is_err());
+
+    // aggressive orders are clamped to the band instead of resting at their limit
+    traders[2]
+        .place_order(ctx, product, Side::Bid, 2, 200)
+        .await?;
+    traders[2]
+        .crank(ctx, product, &[&traders[1], &traders[2]])
+        .await?;
+    let trader_risk_group = traders[2].get_trader_risk_group(&ctx.client).await;
+    assert_eq_frac(
+        trader_risk_group.open_orders.products[product_index].bid_qty_in_book,
+        1,
+    );
+    let tpi = trader_risk_group.active_products[product_index] as usize;
+    assert_eq_frac(trader_risk_group.trader_positions[tpi].position, 1);
+
+    // removing the band allows any price again
+    ctx.update_price_band(product.key, 0, 0, 0).await?;
+    traders[2]
+        .place_order_with_self_trade_behavior(
+            ctx,
+            product,
+            Side::Ask,
+            1,
+            200,
+            SelfTradeBehavior::DecrementTake,
+            &[],
+            OrderType::PostOnly,
+        )
+        .await?;
+    Ok(())
+}
+
+async fn post_only_ask(
+    ctx: &SDKContext,
+    trader: &SDKTrader,
+    product: &SDKProduct,
+    price: i64,
+) -> SDKResult {
+    trader
+        .place_order_with_self_trade_behavior(
+            ctx,
+            product,
+            Side::Ask,
+            1,
+            price,
+            SelfTradeBehavior::DecrementTake,
+            &[],
+            OrderType::PostOnly,
+        )
+        .await
+}
+
+#[tokio::test]
+async fn test_one_sided_price_band() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 2, 1).await;
+    let product = &ctx.products[0].clone();
+    for trader in traders.iter() {
+        trader.deposit(ctx, 1_000_000).await?;
+    }
+    traders[0]
+        .place_order(ctx, product, Side::Ask, 1, 102)
+        .await?;
+    ctx.update_price_band(product.key, 500, 0, 0).await?;
+    // without an index price the band is centered on the only side of the book
+    assert!(post_only_ask(ctx, &traders[1], product, 205).await.is_err());
+
+    // the product's instrument pushes the index (the payoff of a call struck at 0) to the dex
+    let derivative_metadata = ctx
+        .client
+        .get_anchor_account::<DerivativeMetadata>(product.key)
+        .await;
+    let oracle = update_oracle_price_account(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        solana_program::system_program::id(),
+        200,
+        0,
+    )
+    .await?;
+    let account = ctx.client.get_account(oracle).await?;
+    let slot = OraclePrice::try_from_slice(&account.data)?.slot;
+    update_clock_account(
+        &ctx.client,
+        ctx.dummy_oracle_program_id,
+        &ctx.payer,
+        solana_program::system_program::id(),
+        slot,
+        0,
+        0,
+        0,
+        1000,
+    )
+    .await?;
+    settle_derivative::update_premium_index(
+        &ctx.client,
+        ctx.market_product_group,
+        derivative_metadata.price_oracle,
+        derivative_metadata.clock,
+        product.key,
+        &[],
+    )
+    .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    let (_, market_product) = market_product_group.find_outright(&product.key).unwrap();
+    assert_eq_frac(market_product.index_price, 200);
+
+    // a one sided book is banded around the index instead
+    post_only_ask(ctx, &traders[1], product, 205).await?;
+    assert!(post_only_ask(ctx, &traders[1], product, 220).await.is_err());
+    Ok(())
+}
@@ -0,0 +1,188 @@
+#![allow(non_snake_case)]
+
+use agnostic_orderbook::state::Side;
+use dex::{
+    state::{market_product_group::FeeBpsBounds, risk_engine_register::RiskOutputRegister},
+    UpdateMarketProductGroupParams,
+};
+use dexteritysdk::{
+    common::{utils::*, KeypairD},
+    processor::market_product_group::update_market_product_group_ix,
+};
+use solana_sdk::signature::Signer;
+
+use crate::setup::bootstrap_tests;
+
+mod setup;
+
+#[tokio::test]
+async fn test_update_market_product_group() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 1, 1).await;
+    let trader = &traders[0].clone();
+    let new_fee_collector = KeypairD::new().pubkey();
+
+    // only the authority can update the group
+    let ix = update_market_product_group_ix(
+        trader.keypair.pubkey(),
+        ctx.market_product_group,
+        UpdateMarketProductGroupParams {
+            fee_collector: Some(trader.keypair.pubkey()),
+            ..Default::default()
+        },
+    );
+    assert!(ctx
+        .client
+        .sign_send_instructions(vec![ix], vec![&trader.keypair])
+        .await
+        .is_err());
+
+    let fee_bps_bounds = FeeBpsBounds {
+        max_maker_fee_bps: 10,
+        min_maker_fee_bps: -5,
+        max_taker_fee_bps: 20,
+        min_taker_fee_bps: 0,
+    };
+    ctx.update_market_product_group(UpdateMarketProductGroupParams {
+        fee_collector: Some(new_fee_collector),
+        ewma_windows: Some([10, 20, 30, 40]),
+        fee_bps_bounds: Some(fee_bps_bounds),
+        ..Default::default()
+    })
+    .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    assert_eq!(market_product_group.fee_collector, new_fee_collector);
+    assert_eq!(market_product_group.ewma_windows, [10, 20, 30, 40]);
+    assert_eq!(market_product_group.fee_bps_bounds(), fee_bps_bounds);
+
+    assert!(ctx
+        .update_market_product_group(UpdateMarketProductGroupParams {
+            ewma_windows: Some([0, 20, 30, 40]),
+            ..Default::default()
+        })
+        .await
+        .is_err());
+    assert!(ctx
+        .update_market_product_group(UpdateMarketProductGroupParams {
+            fee_bps_bounds: Some(FeeBpsBounds {
+                min_maker_fee_bps: 11,
+                ..fee_bps_bounds
+            }),
+            ..Default::default()
+        })
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_timelocked_engine_update() -> SDKResult {
+    let (ctx, _) = &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 1, 1).await;
+    let market_product_group = ctx.get_market_product_group().await;
+    let risk_engine = market_product_group.risk_engine_config();
+    let mut fee_model = market_product_group.fee_model_config();
+
+    // without a timelock swaps apply immediately
+    fee_model.fee_accounts_len = 1;
+    ctx.update_market_product_group(UpdateMarketProductGroupParams {
+        fee_model: Some(fee_model),
+        ..Default::default()
+    })
+    .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    assert_eq!(market_product_group.fee_model_config(), fee_model);
+    assert!(ctx.apply_market_product_group_update().await.is_err());
+
+    let mut new_risk_engine = risk_engine;
+    new_risk_engine.configuration_acct = KeypairD::new().pubkey();
+    ctx.update_market_product_group(UpdateMarketProductGroupParams {
+        engine_update_timelock: Some(3600),
+        risk_engine: Some(new_risk_engine),
+        ..Default::default()
+    })
+    .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    assert_eq!(market_product_group.risk_engine_config(), risk_engine);
+    assert_eq!(market_product_group.pending_risk_engine, new_risk_engine);
+    assert!(market_product_group.pending_risk_engine_effective_at > 0);
+
+    // the swap can't be rushed through
+    assert!(ctx.apply_market_product_group_update().await.is_err());
+    assert!(ctx
+        .update_market_product_group(UpdateMarketProductGroupParams {
+            engine_update_timelock: Some(0),
+            ..Default::default()
+        })
+        .await
+        .is_err());
+
+    ctx.update_market_product_group(UpdateMarketProductGroupParams {
+        cancel_pending_updates: true,
+        ..Default::default()
+    })
+    .await?;
+    let market_product_group = ctx.get_market_product_group().await;
+    assert_eq!(market_product_group.risk_engine_config(), risk_engine);
+    assert_eq!(market_product_group.pending_risk_engine_effective_at, 0);
+    assert!(ctx.apply_market_product_group_update().await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_risk_engine_swap() -> SDKResult {
+    let (ctx, traders) =
+        &mut bootstrap_tests("noop_risk_engine", "constant_fees", "test", 1, 1).await;
+    let mut trader = traders[0].clone();
+    let market_product_group = ctx.get_market_product_group().await;
+    let mut risk_engine = market_product_group.risk_engine_config();
+    risk_engine.program_id = alpha_risk_engine::ID;
+
+    // the output register has to be owned by the new risk engine and fit its output
+    let update = |risk_engine| UpdateMarketProductGroupParams {
+        risk_engine: Some(risk_engine),
+        ..Default::default()
+    };
+    assert!(ctx
+        .update_market_product_group(update(risk_engine))
+        .await
+        .is_err());
+    let small_register = KeypairD::new();
+    ctx.create_account(&small_register, &alpha_risk_engine::ID, 8)
+        .await?;
+    risk_engine.output_register = small_register.pubkey();
+    assert!(ctx
+        .update_market_product_group(update(risk_engine))
+        .await
+        .is_err());
+    let register = KeypairD::new();
+    ctx.create_anchor_account::<RiskOutputRegister>(&register, &alpha_risk_engine::ID)
+        .await?;
+    risk_engine.output_register = register.pubkey();
+    ctx.update_market_product_group(update(risk_engine)).await?;
+
+    // the trader's risk state account is still owned by the old risk engine
+    let old_risk_state_account = trader.risk_state_account;
+    let old_fee_acct = trader.fee_acct;
+    trader.update_engine_accounts(ctx).await?;
+    assert_ne!(trader.risk_state_account, old_risk_state_account);
+    assert_eq!(trader.fee_acct, old_fee_acct);
+    let risk_state_account = ctx.client.get_account(trader.risk_state_account).await?;
+    assert_eq!(risk_state_account.owner, alpha_risk_engine::ID);
+    let trg = trader.get_trader_risk_group(&ctx.client).await;
+    assert_eq!(trg.risk_state_account, trader.risk_state_account);
+    assert_eq!(trg.fee_state_account, old_fee_acct);
+    // nothing is left to move
+    assert!(trader.update_engine_accounts(ctx).await.is_err());
+
+    // orders now go through the new risk engine
+    {
+        let ctx = ctx.as_mut();
+        ctx.risk_engine_program_id = alpha_risk_engine::ID;
+        ctx.out_register_risk_info = register.pubkey();
+    }
+    trader.deposit(ctx, 1_000).await?;
+    trader
+        .place_order(ctx, &ctx.products[0], Side::Bid, 1, 10)
+        .await?;
+    Ok(())
+}
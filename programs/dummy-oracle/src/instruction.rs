@@ -1,4 +1,6 @@
-pub use crate::processor::{initialize_clock, initialize_oracle, update_clock, update_price};
+pub use crate::processor::{
+    initialize_clock, initialize_oracle, set_price_schedule, update_clock, update_price,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 #[derive(BorshSerialize, BorshDeserialize, Clone)]
@@ -10,4 +12,6 @@ pub enum DummyInstruction {
     UpdateClock(update_clock::Params),
 
     UpdatePrice(update_price::Params),
+
+    SetPriceSchedule(set_price_schedule::Params),
 }
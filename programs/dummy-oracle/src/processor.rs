@@ -15,6 +15,9 @@ pub mod initialize_clock;
 #[allow(missing_docs)]
 pub mod initialize_oracle;
 
+#[allow(missing_docs)]
+pub mod set_price_schedule;
+
 #[allow(missing_docs)]
 pub mod update_clock;
 
@@ -46,6 +49,10 @@ impl Processor {
                 msg!("Instruction: UpdatePrice");
                 update_price::process(program_id, accounts, params)
             }
+            DummyInstruction::SetPriceSchedule(params) => {
+                msg!("Instruction: SetPriceSchedule");
+                set_price_schedule::process(program_id, accounts, params)
+            }
         }
     }
 }
@@ -119,14 +126,38 @@ pub fn update_price_ix(
     oracle_price: Pubkey,
     update_authority: Pubkey,
     system_program: Pubkey,
+    clock: Option<Pubkey>,
     params: update_price::Params,
 ) -> Instruction {
     let data = DummyInstruction::UpdatePrice(params).try_to_vec().unwrap();
-    let accounts = vec![
+    let mut accounts = vec![
         AccountMeta::new(oracle_price, false),
         AccountMeta::new(update_authority, true),
         AccountMeta::new_readonly(system_program, false),
     ];
+    if let Some(clock) = clock {
+        accounts.push(AccountMeta::new_readonly(clock, false));
+    }
+    Instruction {
+        program_id,
+        accounts,
+        data,
+    }
+}
+
+pub fn set_price_schedule_ix(
+    program_id: Pubkey,
+    oracle_price: Pubkey,
+    update_authority: Pubkey,
+    params: set_price_schedule::Params,
+) -> Instruction {
+    let data = DummyInstruction::SetPriceSchedule(params)
+        .try_to_vec()
+        .unwrap();
+    let accounts = vec![
+        AccountMeta::new(oracle_price, false),
+        AccountMeta::new(update_authority, true),
+    ];
     Instruction {
         program_id,
         accounts,
@@ -0,0 +1,74 @@
+use crate::{
+    state::{OraclePrice, PricePoint, ScheduleClock, MAX_PRICE_SCHEDULE_LEN},
+    utils::assert_signer,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Times must be strictly increasing. An empty schedule clears the current one.
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Params {
+    pub schedule_clock: ScheduleClock,
+    pub schedule: Vec<PricePoint>,
+}
+
+struct Context<'a, 'b: 'a> {
+    oracle_price: &'a AccountInfo<'b>,
+    update_authority: &'a AccountInfo<'b>,
+}
+
+impl<'a, 'b: 'a> Context<'a, 'b> {
+    pub fn parse(
+        _program_id: &Pubkey,
+        accounts: &'a [AccountInfo<'b>],
+    ) -> std::result::Result<Self, ProgramError> {
+        let accounts_iter = &mut accounts.iter();
+        let a = Self {
+            oracle_price: next_account_info(accounts_iter)?,
+            update_authority: next_account_info(accounts_iter)?,
+        };
+        assert_signer(a.update_authority)?;
+        Ok(a)
+    }
+}
+
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], params: Params) -> ProgramResult {
+    let ctx = Context::parse(program_id, accounts)?;
+    let mut oracle_price = OraclePrice::try_from_slice(&ctx.oracle_price.data.borrow_mut())?;
+    if !oracle_price.is_initialized() {
+        msg!("Oracle Price account is not initialized");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if *ctx.update_authority.key != oracle_price.update_authority {
+        msg!("Update Authorities do not match");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    if params.schedule.len() > MAX_PRICE_SCHEDULE_LEN {
+        msg!(
+            "Price schedule can have at most {} points",
+            MAX_PRICE_SCHEDULE_LEN
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+    if params.schedule.windows(2).any(|w| w[0].time >= w[1].time) {
+        msg!("Price schedule times must be strictly increasing");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    oracle_price.schedule_clock = params.schedule_clock;
+    oracle_price.schedule_len = params.schedule.len() as u64;
+    oracle_price.schedule = [PricePoint::default(); MAX_PRICE_SCHEDULE_LEN];
+    oracle_price.schedule[..params.schedule.len()].copy_from_slice(&params.schedule);
+
+    oracle_price.serialize(&mut *ctx.oracle_price.data.borrow_mut())?;
+    Ok(())
+}
@@ -1,4 +1,7 @@
-use crate::{state::OraclePrice, utils::assert_signer};
+use crate::{
+    state::OraclePrice,
+    utils::{assert_signer, get_clock},
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
@@ -6,7 +9,7 @@ use solana_program::{
     msg,
     program_error::ProgramError,
     pubkey::Pubkey,
-    sysvar::{clock::Clock, Sysvar},
+    sysvar::clock::Clock,
 };
 
 #[repr(C)]
@@ -24,14 +27,19 @@ struct Context<'a, 'b: 'a> {
 
 impl<'a, 'b: 'a> Context<'a, 'b> {
     pub fn parse(
-        _program_id: &Pubkey,
+        program_id: &Pubkey,
         accounts: &'a [AccountInfo<'b>],
     ) -> std::result::Result<Self, ProgramError> {
         let accounts_iter = &mut accounts.iter();
+        let oracle_price = next_account_info(accounts_iter)?;
+        let update_authority = next_account_info(accounts_iter)?;
+        let _system_program = next_account_info(accounts_iter).ok();
+        // The dummy clock overrides the clock sysvar when passed in
+        let clock = get_clock(program_id, next_account_info(accounts_iter).ok())?;
         let a = Self {
-            oracle_price: next_account_info(accounts_iter)?,
-            update_authority: next_account_info(accounts_iter)?,
-            clock: Clock::get()?,
+            oracle_price,
+            update_authority,
+            clock,
         };
         assert_signer(a.update_authority)?;
         Ok(a)
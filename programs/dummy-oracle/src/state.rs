@@ -1,6 +1,8 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 
-use solana_program::pubkey::Pubkey;
+use solana_program::{clock::Clock, pubkey::Pubkey};
+
+pub const MAX_PRICE_SCHEDULE_LEN: usize = 32;
 
 #[derive(BorshDeserialize, BorshSerialize, Copy, Clone, Debug, PartialEq)]
 #[repr(u8)]
@@ -9,6 +11,27 @@ pub enum AccountTag {
     OraclePrice,
 }
 
+/// Clock field that the times of a price schedule are compared against
+#[derive(BorshDeserialize, BorshSerialize, Copy, Clone, Debug, PartialEq)]
+#[repr(u8)]
+pub enum ScheduleClock {
+    Slot,
+    UnixTimestamp,
+}
+
+impl Default for ScheduleClock {
+    fn default() -> Self {
+        ScheduleClock::Slot
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Default, Copy, Clone, Debug, PartialEq)]
+pub struct PricePoint {
+    pub time: i64,
+    pub price: i64,
+}
+
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct OraclePrice {
@@ -17,6 +40,9 @@ pub struct OraclePrice {
     pub decimals: u64,
     pub slot: u64,
     pub update_authority: Pubkey,
+    pub schedule_clock: ScheduleClock,
+    pub schedule_len: u64,
+    pub schedule: [PricePoint; MAX_PRICE_SCHEDULE_LEN],
 }
 
 impl OraclePrice {
@@ -25,9 +51,30 @@ impl OraclePrice {
   + 8  // decimals
   + 8  // slot
   + 32 // update_authority
+  + 1  // schedule_clock
+  + 8  // schedule_len
+  + 16 * MAX_PRICE_SCHEDULE_LEN as u64 // schedule
   ;
 
     pub fn is_initialized(&self) -> bool {
         self.tag == AccountTag::OraclePrice
     }
+
+    /// Returns the price and publish slot as of `clock`. Once a schedule is loaded the
+    /// latest point at or before the clock is published at the current slot, before the
+    /// first point the last updated price is returned.
+    pub fn price_at(&self, clock: &Clock) -> (i64, u64) {
+        let now = match self.schedule_clock {
+            ScheduleClock::Slot => clock.slot as i64,
+            ScheduleClock::UnixTimestamp => clock.unix_timestamp,
+        };
+        match self.schedule[..self.schedule_len as usize]
+            .iter()
+            .rev()
+            .find(|point| point.time <= now)
+        {
+            Some(point) => (point.price, clock.slot),
+            None => (self.price, self.slot),
+        }
+    }
 }
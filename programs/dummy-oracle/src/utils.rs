@@ -7,7 +7,7 @@ use solana_program::{
     program_option::COption,
     program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    sysvar::rent::Rent,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
 };
 use spl_associated_token_account::get_associated_token_address;
 use spl_token::state::Account;
@@ -122,3 +122,23 @@ pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
         Ok(())
     }
 }
+
+/// Reads the dummy clock created by `InitializeClock` when it is passed in, otherwise the
+/// clock sysvar
+pub fn get_clock(
+    program_id: &Pubkey,
+    clock_info: Option<&AccountInfo>,
+) -> std::result::Result<Clock, ProgramError> {
+    match clock_info {
+        Some(clock_info) => {
+            let (clock_key, _) = Pubkey::find_program_address(&[b"clock"], program_id);
+            assert_keys_equal(clock_key, *clock_info.key)?;
+            assert_owned_by(clock_info, program_id)?;
+            bincode::deserialize(&clock_info.data.borrow()).map_err(|e| {
+                msg!("Failed to deserialize clock {}", e);
+                ProgramError::InvalidAccountData
+            })
+        }
+        None => Clock::get(),
+    }
+}
@@ -13,7 +13,7 @@ use solana_program::{
 use bytemuck::{Pod, Zeroable};
 use dex::{
     error::DomainOrProgramResult,
-    state::fee_model::{TraderFeeParams, TraderFees},
+    state::fee_model::{OrderEventParams, TraderFeeParams, TraderFees},
     utils::{
         param::{WithAcct, WithKey},
         validation::{assert_keys_equal, assert_signer, get_rent},
@@ -42,6 +42,9 @@ enum ConstantFeeModelInstruction {
     // These instructions are not exposed to the DEX
     InitializeTraderAcct,
     UpdateFees(UpdateFeesParams),
+    // Optional hooks invoked by the DEX when an order rests on or leaves the book
+    OnPost { params: OrderEventParams },
+    OnCancel { params: OrderEventParams },
 }
 
 #[repr(C)]
@@ -89,6 +92,10 @@ fn process(
         ConstantFeeModelInstruction::InitializeTraderAcct => {
             process_initialize_trader_acct(program_id, accounts)
         }
+        ConstantFeeModelInstruction::OnPost { params }
+        | ConstantFeeModelInstruction::OnCancel { params } => {
+            process_order_event(program_id, accounts, &params)
+        }
     }
     .map_err(|e| {
         msg!("Error: {}", &e);
@@ -136,6 +143,39 @@ fn process_find_fees(
     Ok(())
 }
 
+fn process_order_event(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _params: &OrderEventParams,
+) -> DomainOrProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let market_product_group = next_account_info(accounts_iter)?;
+    let trader_risk_group = next_account_info(accounts_iter)?;
+    let trader_fee_state = WithKey::<TraderFeeState>::load(next_account_info(accounts_iter)?)?;
+    let _fee_model_configuration_acct =
+        WithKey::<FeeConfig>::load(next_account_info(accounts_iter)?)?;
+    let fee_signer = next_account_info(accounts_iter)?;
+
+    let (fee_signer_key, _) =
+        Pubkey::find_program_address(&[market_product_group.key.as_ref()], &dex::ID);
+    assert_keys_equal(fee_signer_key, *fee_signer.key)?;
+    assert_signer(fee_signer)?;
+
+    let trader_state_key = Pubkey::create_program_address(
+        &[
+            b"trader_fee_acct",
+            &trader_risk_group.key().to_bytes(),
+            market_product_group.key.as_ref(),
+            &[trader_fee_state.bump as u8],
+        ],
+        program_id,
+    )?;
+    assert_keys_equal(trader_state_key, *trader_fee_state.key)?;
+
+    // Constant fees do not depend on resting liquidity, so there is nothing to record
+    Ok(())
+}
+
 fn process_update_fees(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
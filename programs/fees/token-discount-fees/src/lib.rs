@@ -0,0 +1,453 @@
+use anchor_lang::prelude::*;
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+use bytemuck::{Pod, Zeroable};
+use dex::{
+    error::DomainOrProgramResult,
+    state::{
+        fee_model::{OrderEventParams, TraderFeeParams, TraderFees},
+        market_product_group::MarketProductGroup,
+        trader_risk_group::TraderRiskGroup,
+    },
+    utils::{
+        param::{WithAcct, WithKey},
+        validation::{assert, assert_keys_equal, assert_signer, get_rent},
+    },
+};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke_signed,
+    rent::Rent,
+};
+
+pub const MAX_DISCOUNT_TIERS: usize = 8;
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    process(program_id, accounts, instruction_data)
+}
+
+/// Same instruction layout as constant-fees so that the DEX discriminants are interchangeable
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[repr(u8)]
+enum TokenDiscountFeeModelInstruction {
+    // This instruction is invoked by the DEX contract
+    FindFees { params: TraderFeeParams },
+    // These instructions are not exposed to the DEX
+    InitializeTraderAcct,
+    UpdateFees(UpdateFeesParams),
+    // Optional hooks invoked by the DEX when an order rests on or leaves the book
+    OnPost { params: OrderEventParams },
+    OnCancel { params: OrderEventParams },
+}
+
+#[repr(C)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, PartialEq, Clone, Copy, Default)]
+pub struct DiscountTier {
+    /// Minimum balance (in native units of the discount mint) needed to qualify for the tier
+    pub min_balance: u64,
+    /// Number of bps taken off the maker and taker fees
+    pub discount_bps: i32,
+}
+
+#[repr(C)]
+#[derive(Debug, BorshSerialize, BorshDeserialize, PartialEq, Clone)]
+pub struct UpdateFeesParams {
+    pub maker_fee_bps: i32,
+    pub taker_fee_bps: i32,
+    pub discount_mint: Pubkey,
+    pub tiers: Vec<DiscountTier>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+pub struct TraderFeeState {
+    pub bump: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+struct Tier {
+    min_balance: u64,
+    discount_bps: i32,
+    padding: u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+struct FeeConfig {
+    maker_fee_bps: i32,
+    taker_fee_bps: i32,
+    // Signer allowed to update the fees, set when the config is created
+    authority: Pubkey,
+    discount_mint: Pubkey,
+    num_tiers: u64,
+    // Sorted by increasing min_balance
+    tiers: [Tier; MAX_DISCOUNT_TIERS],
+}
+
+impl FeeConfig {
+    /// Returns the discount of the highest tier the balance qualifies for
+    fn get_discount_bps(&self, balance: u64) -> i32 {
+        self.tiers[..self.num_tiers as usize]
+            .iter()
+            .filter(|t| balance >= t.min_balance)
+            .last()
+            .map_or(0, |t| t.discount_bps)
+    }
+}
+
+/// Discounts only ever reduce a fee towards zero, rebates are left untouched
+fn apply_discount(fee_bps: i32, discount_bps: i32) -> i32 {
+    if fee_bps <= 0 {
+        fee_bps
+    } else {
+        (fee_bps - discount_bps).max(0)
+    }
+}
+
+fn print_ix_name(ix: impl std::fmt::Debug) {
+    msg!("Fee Ix: {:?}", ix);
+}
+
+fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let ix = TokenDiscountFeeModelInstruction::try_from_slice(instruction_data).map_err(|e| {
+        msg!("Error: {}", e);
+        ProgramError::InvalidInstructionData
+    })?;
+
+    print_ix_name(&ix);
+    match ix {
+        TokenDiscountFeeModelInstruction::FindFees { params } => {
+            process_find_fees(program_id, accounts, &params)
+        }
+        TokenDiscountFeeModelInstruction::UpdateFees(params) => {
+            process_update_fees(program_id, accounts, params)
+        }
+        TokenDiscountFeeModelInstruction::InitializeTraderAcct => {
+            process_initialize_trader_acct(program_id, accounts)
+        }
+        TokenDiscountFeeModelInstruction::OnPost { .. }
+        | TokenDiscountFeeModelInstruction::OnCancel { .. } => Ok(()),
+    }
+    .map_err(|e| {
+        msg!("Error: {}", &e);
+        e.into()
+    })
+}
+
+fn process_find_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _params: &TraderFeeParams,
+) -> DomainOrProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let market_product_group = next_account_info(accounts_iter)?;
+    let trader_risk_group = next_account_info(accounts_iter)?;
+    let trader_fee_state = WithKey::<TraderFeeState>::load(next_account_info(accounts_iter)?)?;
+    let fee_model_configuration_acct =
+        WithKey::<FeeConfig>::load(next_account_info(accounts_iter)?)?;
+    let mut fee_output_register =
+        WithKey::<TraderFees>::load_mut(next_account_info(accounts_iter)?)?;
+
+    let fee_signer = next_account_info(accounts_iter)?;
+
+    let (fee_signer_key, _) =
+        Pubkey::find_program_address(&[market_product_group.key.as_ref()], &dex::ID);
+    assert_keys_equal(fee_signer_key, *fee_signer.key)?;
+    assert_signer(fee_signer)?;
+
+    let trader_state_key = Pubkey::create_program_address(
+        &[
+            b"trader_fee_acct",
+            &trader_risk_group.key().to_bytes(),
+            market_product_group.key.as_ref(),
+            &[trader_fee_state.bump as u8],
+        ],
+        program_id,
+    )?;
+    assert_keys_equal(trader_state_key, *trader_fee_state.key)?;
+
+    let owner = AccountLoader::<TraderRiskGroup>::try_from(trader_risk_group)?
+        .load()?
+        .owner;
+    // The remaining accounts may contain anything the DEX forwards, so unrelated accounts are skipped
+    let balance = accounts_iter
+        .filter(|a| a.owner == &spl_token::ID && a.data_len() == spl_token::state::Account::LEN)
+        .filter_map(|a| spl_token::state::Account::unpack(&a.data.borrow()).ok())
+        .filter(|t| t.owner == owner && t.mint == fee_model_configuration_acct.discount_mint)
+        .map(|t| t.amount)
+        .max()
+        .unwrap_or(0);
+    let discount_bps = fee_model_configuration_acct.get_discount_bps(balance);
+
+    fee_output_register.valid_until = solana_program::clock::Clock::get()?.unix_timestamp + 1; // add an offset to allow skipping fee model calculations
+    fee_output_register.set_taker_fee_bps(apply_discount(
+        fee_model_configuration_acct.taker_fee_bps,
+        discount_bps,
+    ));
+    fee_output_register.set_maker_fee_bps(apply_discount(
+        fee_model_configuration_acct.maker_fee_bps,
+        discount_bps,
+    ));
+
+    Ok(())
+}
+
+fn process_update_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    params: UpdateFeesParams,
+) -> DomainOrProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let authority = next_account_info(accounts_iter)?;
+    let fee_model_config_acct = next_account_info(accounts_iter)?;
+    let market_product_group = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    assert_signer(authority)?;
+    assert(
+        params.tiers.len() <= MAX_DISCOUNT_TIERS,
+        ProgramError::InvalidArgument,
+    )?;
+    assert(
+        params
+            .tiers
+            .windows(2)
+            .all(|w| w[0].min_balance < w[1].min_balance),
+        ProgramError::InvalidArgument,
+    )?;
+    assert(
+        params.tiers.iter().all(|t| t.discount_bps >= 0),
+        ProgramError::InvalidArgument,
+    )?;
+
+    let is_new = fee_model_config_acct.data_is_empty();
+    if is_new {
+        // The DEX requires the config to exist before the group is initialized, so a group that
+        // does not exist yet has to sign for itself. Otherwise only its authority may create it.
+        if market_product_group.data_is_empty() {
+            assert_signer(market_product_group)?;
+        } else {
+            let group_authority =
+                AccountLoader::<MarketProductGroup>::try_from(market_product_group)?
+                    .load()?
+                    .authority;
+            assert_keys_equal(*authority.key, group_authority)?;
+        }
+        let label_seed = b"fee_model_config_acct";
+        let (config_acct, bump_seed) = Pubkey::find_program_address(
+            &[label_seed, market_product_group.key.as_ref()],
+            program_id,
+        );
+        let seeds = &[label_seed, market_product_group.key.as_ref(), &[bump_seed]];
+        assert_keys_equal(config_acct, *fee_model_config_acct.key)?;
+        let size = std::mem::size_of::<FeeConfig>();
+        invoke_signed(
+            &solana_program::system_instruction::create_account(
+                authority.key,
+                fee_model_config_acct.key,
+                get_rent(&Rent::get()?, size as u64, fee_model_config_acct),
+                size as u64,
+                program_id,
+            ),
+            &[
+                authority.clone(),
+                fee_model_config_acct.clone(),
+                system_program.clone(),
+            ],
+            &[seeds],
+        )?;
+    }
+    let mut fee_model_configuration_acct = WithAcct::<FeeConfig>::load_mut(fee_model_config_acct)?;
+    if is_new {
+        fee_model_configuration_acct.authority = *authority.key;
+    } else {
+        assert_keys_equal(*authority.key, fee_model_configuration_acct.authority)?;
+    }
+    fee_model_configuration_acct.maker_fee_bps = params.maker_fee_bps;
+    fee_model_configuration_acct.taker_fee_bps = params.taker_fee_bps;
+    fee_model_configuration_acct.discount_mint = params.discount_mint;
+    fee_model_configuration_acct.num_tiers = params.tiers.len() as u64;
+    fee_model_configuration_acct.tiers = [Tier::zeroed(); MAX_DISCOUNT_TIERS];
+    for (tier, params) in fee_model_configuration_acct
+        .tiers
+        .iter_mut()
+        .zip(params.tiers.iter())
+    {
+        tier.min_balance = params.min_balance;
+        tier.discount_bps = params.discount_bps;
+    }
+
+    Ok(())
+}
+
+fn process_initialize_trader_acct(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> DomainOrProgramResult {
+    let accounts_iter = &mut accounts.iter();
+    let payer = next_account_info(accounts_iter)?;
+    let _fee_model_config_acct = WithAcct::<FeeConfig>::load(next_account_info(accounts_iter)?)?;
+    let trader_fee_acct = next_account_info(accounts_iter)?;
+    let market_product_group = next_account_info(accounts_iter)?;
+    let trader_risk_group = next_account_info(accounts_iter)?;
+    let system_program = next_account_info(accounts_iter)?;
+
+    if !trader_fee_acct.data_is_empty() {
+        msg!("TraderFeeAcct already initialized");
+        return Err(ProgramError::InvalidArgument.into());
+    }
+
+    let label_seed = b"trader_fee_acct";
+    let (trader_fee_acct_key, bump) = Pubkey::find_program_address(
+        &[
+            label_seed,
+            &trader_risk_group.key.to_bytes(),
+            &market_product_group.key.to_bytes(),
+        ],
+        program_id,
+    );
+    assert_keys_equal(*trader_fee_acct.key, trader_fee_acct_key)?;
+    let size = std::mem::size_of::<TraderFeeState>();
+    invoke_signed(
+        &solana_program::system_instruction::create_account(
+            payer.key,
+            &trader_fee_acct_key,
+            get_rent(&Rent::get()?, size as u64, trader_fee_acct),
+            size as u64,
+            program_id,
+        ),
+        &[
+            payer.clone(),
+            trader_fee_acct.clone(),
+            system_program.clone(),
+        ],
+        &[&[
+            label_seed,
+            &trader_risk_group.key.to_bytes(),
+            &market_product_group.key.to_bytes(),
+            &[bump],
+        ]],
+    )?;
+    let mut trader_fee_state = WithKey::<TraderFeeState>::load_mut(trader_fee_acct)?;
+    trader_fee_state.bump = bump as u64;
+    Ok(())
+}
+
+pub fn initialize_trader_fee_acct_ix(
+    program_id: Pubkey,
+    payer: Pubkey,
+    fee_model_config_acct: Pubkey,
+    trader_fee_acct: Pubkey,
+    market_product_group: Pubkey,
+    trader_risk_group: Pubkey,
+    system_program: Pubkey,
+) -> Instruction {
+    let data = TokenDiscountFeeModelInstruction::InitializeTraderAcct
+        .try_to_vec()
+        .unwrap();
+    let accounts = vec![
+        AccountMeta::new_readonly(payer, true),
+        AccountMeta::new_readonly(fee_model_config_acct, false),
+        AccountMeta::new(trader_fee_acct, false),
+        AccountMeta::new_readonly(market_product_group, false),
+        AccountMeta::new_readonly(trader_risk_group, false),
+        AccountMeta::new_readonly(system_program, false),
+    ];
+    Instruction {
+        program_id,
+        accounts,
+        data,
+    }
+}
+
+/// `authority` pays for the config on the first update and must sign every later one.
+/// The first update must be signed by the authority of `market_product_group`, or by the group
+/// itself if it has not been created yet (see [`update_fees_ix_for_new_group`])
+pub fn update_fees_ix(
+    program_id: Pubkey,
+    authority: Pubkey,
+    fee_model_config_acct: Pubkey,
+    market_product_group: Pubkey,
+    system_program: Pubkey,
+    params: UpdateFeesParams,
+) -> Instruction {
+    let data = TokenDiscountFeeModelInstruction::UpdateFees(params)
+        .try_to_vec()
+        .unwrap();
+
+    let accounts = vec![
+        AccountMeta::new(authority, true),
+        AccountMeta::new(fee_model_config_acct, false),
+        AccountMeta::new_readonly(market_product_group, false),
+        AccountMeta::new_readonly(system_program, false),
+    ];
+    Instruction {
+        program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates the config of a group that is initialized afterwards, so the group key has to sign
+pub fn update_fees_ix_for_new_group(
+    program_id: Pubkey,
+    authority: Pubkey,
+    fee_model_config_acct: Pubkey,
+    market_product_group: Pubkey,
+    system_program: Pubkey,
+    params: UpdateFeesParams,
+) -> Instruction {
+    let mut ix = update_fees_ix(
+        program_id,
+        authority,
+        fee_model_config_acct,
+        market_product_group,
+        system_program,
+        params,
+    );
+    ix.accounts[2].is_signer = true;
+    ix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discounts() {
+        let mut config = FeeConfig::zeroed();
+        config.num_tiers = 2;
+        config.tiers[0].min_balance = 100;
+        config.tiers[0].discount_bps = 2;
+        config.tiers[1].min_balance = 1000;
+        config.tiers[1].discount_bps = 5;
+        assert_eq!(config.get_discount_bps(99), 0);
+        assert_eq!(config.get_discount_bps(100), 2);
+        assert_eq!(config.get_discount_bps(5000), 5);
+
+        assert_eq!(apply_discount(10, 5), 5);
+        assert_eq!(apply_discount(3, 5), 0);
+        assert_eq!(apply_discount(-2, 5), -2);
+    }
+}
@@ -29,6 +29,16 @@ pub enum DerivativeError {
     InvalidDate,
     #[error("InvalidAccount")]
     InvalidAccount,
+    #[error("StaleOraclePrice")]
+    StaleOraclePrice,
+    #[error("OracleConfidenceTooWide")]
+    OracleConfidenceTooWide,
+    #[error("InsufficientValidOracles")]
+    InsufficientValidOracles,
+    #[error("OutsideSettlementWindow")]
+    OutsideSettlementWindow,
+    #[error("InsufficientSettlementSamples")]
+    InsufficientSettlementSamples,
 }
 
 impl From<DerivativeError> for ProgramError {
@@ -4,12 +4,15 @@ pub mod processor;
 pub mod state;
 
 use crate::state::{
+    composite_oracle::CompositeOracle,
     derivative_metadata::DerivativeMetadata,
-    enums::{InstrumentType, OracleType},
+    enums::{FixedIncomeType, InstrumentType, OracleType},
+    fixed_income_metadata::FixedIncomeMetadata,
+    series::Series,
 };
 use anchor_lang::prelude::*;
 use bytemuck::{Pod, Zeroable};
-use dex::utils::numeric::Fractional;
+use dex::{state::constants::NAME_LEN, utils::numeric::Fractional};
 use solana_program::{
     account_info::AccountInfo,
     clock::UnixTimestamp,
@@ -34,9 +37,57 @@ pub mod instruments {
         processor::settle_derivative::process(ctx)
     }
 
+    pub fn settle_derivatives<'info>(
+        ctx: Context<'_, '_, '_, 'info, SettleDerivatives<'info>>,
+        params: SettleDerivativesParams,
+    ) -> ProgramResult {
+        processor::settle_derivatives::process(ctx, params)
+    }
+
     pub fn close_derivative_account(ctx: Context<CloseDerivativeAccount>) -> ProgramResult {
         processor::close_derivative_account::process(ctx)
     }
+
+    pub fn initialize_composite_oracle(
+        ctx: Context<InitializeCompositeOracle>,
+        params: InitializeCompositeOracleParams,
+    ) -> ProgramResult {
+        processor::initialize_composite_oracle::process(ctx, params)
+    }
+
+    pub fn update_premium_index(ctx: Context<UpdatePremiumIndex>) -> ProgramResult {
+        processor::update_premium_index::process(ctx)
+    }
+
+    pub fn record_settlement_sample(ctx: Context<RecordSettlementSample>) -> ProgramResult {
+        processor::record_settlement_sample::process(ctx)
+    }
+
+    pub fn initialize_fixed_income(
+        ctx: Context<InitializeFixedIncome>,
+        params: InitializeFixedIncomeParams,
+    ) -> ProgramResult {
+        processor::initialize_fixed_income::process(ctx, params)
+    }
+
+    pub fn settle_fixed_income(ctx: Context<SettleFixedIncome>) -> ProgramResult {
+        processor::settle_fixed_income::process(ctx)
+    }
+
+    pub fn close_fixed_income_account(ctx: Context<CloseFixedIncomeAccount>) -> ProgramResult {
+        processor::close_fixed_income_account::process(ctx)
+    }
+
+    pub fn initialize_series(
+        ctx: Context<InitializeSeries>,
+        params: InitializeSeriesParams,
+    ) -> ProgramResult {
+        processor::initialize_series::process(ctx, params)
+    }
+
+    pub fn roll_series<'info>(ctx: Context<'_, '_, '_, 'info, RollSeries<'info>>) -> ProgramResult {
+        processor::roll_series::process(ctx)
+    }
 }
 
 #[derive(Accounts)]
@@ -64,10 +115,34 @@ pub struct SettleDerivative<'info> {
     pub clock: AccountInfo<'info>,
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone)]
+pub struct SettleDerivativesParams {
+    /// Number of (derivative_metadata, price_oracle) pairs at the start of the remaining
+    /// accounts, the accounts after them are the underlying oracles of composite oracles
+    pub num_derivatives: u64,
+}
+
+/// Per-derivative result of `settle_derivatives`, returned in order as return data
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone, Copy)]
+pub enum SettlementOutcome {
+    Settled,
+    NotDue,
+    Expired,
+    AwaitingSettlementSamples,
+}
+
+#[derive(Accounts)]
+pub struct SettleDerivatives<'info> {
+    #[account(mut)]
+    pub market_product_group: AccountInfo<'info>,
+    pub dex_program: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+}
+
 #[repr(C)]
 #[derive(AnchorSerialize, AnchorDeserialize, Pod, Zeroable, PartialEq, Debug, Clone, Copy)]
 pub struct InitializeDerivativeParams {
-    /// CALL or PUT (perpetuals are just calls with 0 strike price)
+    /// Payoff type, see InstrumentType. Linear perps can settle to negative prices
     pub instrument_type: InstrumentType,
     /// Strike price of an option, 0 for for perpetual swaps and futures
     pub strike: Fractional,
@@ -79,6 +154,26 @@ pub struct InitializeDerivativeParams {
     pub close_authority: Pubkey,
     // Oracle type
     pub oracle_type: OracleType,
+    /// Maximum number of slots since the last oracle update, 0 to disable
+    pub max_staleness_slots: u64,
+    /// Maximum oracle confidence interval as a fraction of the price, 0 to use the default of 10%
+    pub max_confidence_bps: u64,
+    /// Maximum funding paid per full funding period as a fraction of the index price, 0 to disable
+    pub max_funding_rate_bps: u64,
+    /// No funding is paid while the premium is within this fraction of the index price
+    pub funding_dampening_bps: u64,
+    /// Maximum payoff of capped instruments, 0 otherwise
+    pub cap: Fractional,
+    /// Payout of binary instruments when in the money, 0 otherwise
+    pub digital_payout: Fractional,
+    /// EWMA window of the market product group used for the mark price of a one-sided book
+    pub mark_price_ewma_window: u64,
+    /// Number of seconds before expiry in which settlement samples are recorded, 0 to settle
+    /// expiring instruments on a single oracle read
+    pub settlement_window: UnixTimestamp,
+    /// Minimum number of settlement samples required to settle, at least
+    /// MIN_SETTLEMENT_SAMPLES and at most one per second of the settlement window
+    pub min_settlement_samples: u64,
 }
 
 #[derive(Accounts)]
@@ -131,3 +226,245 @@ pub struct CloseDerivativeAccount<'info> {
     close_authority: Signer<'info>,
     destination: AccountInfo<'info>,
 }
+
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeCompositeOracleParams {
+    /// Oracle type of each remaining account, in the same order
+    pub oracle_types: Vec<OracleType>,
+    /// Minimum number of oracles that must report a valid price at settlement
+    pub min_valid_oracles: u64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeCompositeOracle<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<CompositeOracle>()
+    )]
+    pub composite_oracle: AccountLoader<'info, CompositeOracle>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePremiumIndex<'info> {
+    #[account(mut)]
+    pub market_product_group: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds=[
+            b"derivative",
+            price_oracle.key.to_bytes().as_ref(),
+            market_product_group.key.to_bytes().as_ref(),
+            (derivative_metadata.load()?.instrument_type as u64).to_le_bytes().as_ref(),
+            derivative_metadata.load()?.strike.m.to_le_bytes().as_ref(),
+            derivative_metadata.load()?.strike.exp.to_le_bytes().as_ref(),
+            derivative_metadata.load()?.initialization_time.to_le_bytes().as_ref(),
+            derivative_metadata.load()?.full_funding_period.to_le_bytes().as_ref(),
+            derivative_metadata.load()?.minimum_funding_period.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub derivative_metadata: AccountLoader<'info, DerivativeMetadata>,
+    pub price_oracle: AccountInfo<'info>,
+    #[account(address = dex::ID)]
+    pub dex_program: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RecordSettlementSample<'info> {
+    #[account(
+        mut,
+        seeds=[
+            b"derivative",
+            price_oracle.key.to_bytes().as_ref(),
+            derivative_metadata.load()?.market_product_group.to_bytes().as_ref(),
+            (derivative_metadata.load()?.instrument_type as u64).to_le_bytes().as_ref(),
+            derivative_metadata.load()?.strike.m.to_le_bytes().as_ref(),
+            derivative_metadata.load()?.strike.exp.to_le_bytes().as_ref(),
+            derivative_metadata.load()?.initialization_time.to_le_bytes().as_ref(),
+            derivative_metadata.load()?.full_funding_period.to_le_bytes().as_ref(),
+            derivative_metadata.load()?.minimum_funding_period.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub derivative_metadata: AccountLoader<'info, DerivativeMetadata>,
+    pub price_oracle: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, Pod, Zeroable, PartialEq, Debug, Clone, Copy)]
+pub struct InitializeFixedIncomeParams {
+    pub fixed_income_type: FixedIncomeType,
+    /// Face value of one contract
+    pub notional: Fractional,
+    /// Annualized fixed rate paid by swap longs, 0 for bond futures
+    pub fixed_rate: Fractional,
+    /// Number of seconds to maturity of the bond delivered by a bond future, 0 for swaps
+    pub tenor: UnixTimestamp,
+    pub initialization_time: UnixTimestamp,
+    pub maturity_time: UnixTimestamp,
+    /// Number of seconds between swap payments, 0 for bond futures
+    pub coupon_period: UnixTimestamp,
+    pub close_authority: Pubkey,
+    pub oracle_type: OracleType,
+    /// Maximum number of slots since the last oracle update, 0 to disable
+    pub max_staleness_slots: u64,
+    /// Maximum oracle confidence interval as a fraction of the rate, 0 to use the default of 10%
+    pub max_confidence_bps: u64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: InitializeFixedIncomeParams)]
+pub struct InitializeFixedIncome<'info> {
+    #[account(
+        init,
+        seeds=[
+            b"fixed_income",
+            rate_oracle.key.to_bytes().as_ref(),
+            market_product_group.key.to_bytes().as_ref(),
+            (params.fixed_income_type as u64).to_le_bytes().as_ref(),
+            params.notional.m.to_le_bytes().as_ref(),
+            params.notional.exp.to_le_bytes().as_ref(),
+            params.fixed_rate.m.to_le_bytes().as_ref(),
+            params.fixed_rate.exp.to_le_bytes().as_ref(),
+            params.tenor.to_le_bytes().as_ref(),
+            params.initialization_time.to_le_bytes().as_ref(),
+            params.maturity_time.to_le_bytes().as_ref(),
+            params.coupon_period.to_le_bytes().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<FixedIncomeMetadata>()
+    )]
+    pub fixed_income_metadata: AccountLoader<'info, FixedIncomeMetadata>,
+    pub rate_oracle: AccountInfo<'info>,
+    pub market_product_group: AccountInfo<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SettleFixedIncome<'info> {
+    #[account(mut)]
+    pub market_product_group: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds=[
+            b"fixed_income",
+            rate_oracle.key.to_bytes().as_ref(),
+            market_product_group.key.to_bytes().as_ref(),
+            (fixed_income_metadata.load()?.fixed_income_type as u64).to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.notional.m.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.notional.exp.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.fixed_rate.m.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.fixed_rate.exp.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.tenor.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.initialization_time.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.maturity_time.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.coupon_period.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub fixed_income_metadata: AccountLoader<'info, FixedIncomeMetadata>,
+    pub rate_oracle: AccountInfo<'info>,
+    #[account(address = dex::ID)]
+    pub dex_program: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseFixedIncomeAccount<'info> {
+    #[account(
+        mut,
+        seeds=[
+            b"fixed_income",
+            fixed_income_metadata.load()?.rate_oracle.to_bytes().as_ref(),
+            fixed_income_metadata.load()?.market_product_group.to_bytes().as_ref(),
+            (fixed_income_metadata.load()?.fixed_income_type as u64).to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.notional.m.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.notional.exp.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.fixed_rate.m.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.fixed_rate.exp.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.tenor.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.initialization_time.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.maturity_time.to_le_bytes().as_ref(),
+            fixed_income_metadata.load()?.coupon_period.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    fixed_income_metadata: AccountLoader<'info, FixedIncomeMetadata>,
+    close_authority: Signer<'info>,
+    #[account(mut)]
+    destination: AccountInfo<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone)]
+pub struct InitializeSeriesParams {
+    /// Must be an expiring instrument type
+    pub instrument_type: InstrumentType,
+    pub oracle_type: OracleType,
+    /// One derivative is listed per strike on every roll
+    pub strikes: Vec<Fractional>,
+    /// Initialization time of the first expiry
+    pub start_time: UnixTimestamp,
+    /// Number of seconds between expiries
+    pub expiry_interval: UnixTimestamp,
+    pub close_authority: Pubkey,
+    pub name: [u8; NAME_LEN],
+    pub tick_size: Fractional,
+    pub base_decimals: u64,
+    pub price_offset: Fractional,
+    pub max_staleness_slots: u64,
+    pub max_confidence_bps: u64,
+    pub cap: Fractional,
+    pub digital_payout: Fractional,
+    /// Number of seconds before each expiry in which settlement samples are recorded,
+    /// 0 to settle on a single oracle read. Must be shorter than `expiry_interval`
+    pub settlement_window: UnixTimestamp,
+    /// Minimum number of settlement samples required to settle every listed product
+    pub min_settlement_samples: u64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSeries<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + std::mem::size_of::<Series>()
+    )]
+    pub series: AccountLoader<'info, Series>,
+    pub price_oracle: AccountInfo<'info>,
+    pub market_product_group: AccountInfo<'info>,
+    /// Authority of the market product group
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub clock: AccountInfo<'info>,
+}
+
+/// Remaining accounts are a (derivative metadata, orderbook) pair per strike of the series
+#[derive(Accounts)]
+pub struct RollSeries<'info> {
+    #[account(mut)]
+    pub series: AccountLoader<'info, Series>,
+    #[account(mut)]
+    pub market_product_group: AccountInfo<'info>,
+    #[account(
+        seeds=[b"product_lister", market_product_group.key.to_bytes().as_ref()],
+        bump,
+    )]
+    pub product_lister: AccountInfo<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub dex_program: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+}
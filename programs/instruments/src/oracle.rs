@@ -1,6 +1,17 @@
-use crate::{error::DerivativeError, state::enums::OracleType};
+use crate::{
+    error::DerivativeError,
+    state::{
+        composite_oracle::CompositeOracle, constants::MAX_ORACLE_DECIMALS,
+        derivative_metadata::DerivativeMetadata, enums::OracleType,
+    },
+};
+use anchor_lang::prelude::AccountLoader;
+use arrayref::array_ref;
 use borsh::BorshDeserialize;
-use dex::{error::UtilError, utils::numeric::Fractional};
+use dex::{
+    error::UtilError,
+    utils::numeric::{bps, Fractional, ZERO_FRAC},
+};
 use dummy_oracle::state::OraclePrice;
 use solana_program::{
     account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult, msg,
@@ -37,23 +48,25 @@ pub fn validate_pyth_accounts(
     Ok(())
 }
 
+/// Default upper bound on the oracle confidence interval as a fraction of the price
+pub const DEFAULT_MAX_CONFIDENCE_BPS: u64 = 1000;
+
 pub fn get_pyth_price(
     pyth_price_info: &AccountInfo,
-    _clock: &Clock,
+    clock: &Clock,
+    max_staleness_slots: u64,
+    max_confidence_bps: u64,
 ) -> std::result::Result<Fractional, ProgramError> {
     let pyth_price_data = &pyth_price_info.try_borrow_data()?;
     let pyth_price = pyth_client::cast::<pyth_client::Price>(pyth_price_data);
-    if pyth_price.agg.price < 0 {
-        msg!("Oracle price cannot be negative");
-        return Err(DerivativeError::InvalidOracleConfig.into());
-    }
+    check_staleness(pyth_price.agg.pub_slot, clock, max_staleness_slots)?;
     let price = pyth_price.agg.price;
     let conf = pyth_price.agg.conf;
-    if price > 0 {
-        let pct = Fractional::new(conf as i64, 0) / Fractional::new(price, 0);
-        if pct > Fractional::new(1, 1) {
+    if price != 0 {
+        let pct = Fractional::new(conf as i64, 0) / Fractional::new(price.abs(), 0);
+        if pct > bps(max_confidence_bps as i64) {
             msg!("Market is too wide");
-            return Err(DerivativeError::InvalidOracleConfig.into());
+            return Err(DerivativeError::OracleConfidenceTooWide.into());
         }
     }
     Ok(Fractional::new(price, pyth_price.expo.abs() as u64))
@@ -61,20 +74,598 @@ pub fn get_pyth_price(
 
 pub fn get_dummy_price(
     price_info: &AccountInfo,
-    _clock: &Clock,
+    clock: &Clock,
+    max_staleness_slots: u64,
 ) -> std::result::Result<Fractional, ProgramError> {
     let price_data = OraclePrice::try_from_slice(&price_info.data.borrow_mut())?;
-    Ok(Fractional::new(price_data.price, price_data.decimals))
+    let (price, slot) = price_data.price_at(clock);
+    check_staleness(slot, clock, max_staleness_slots)?;
+    Ok(Fractional::new(price, price_data.decimals))
+}
+
+pub mod switchboard_v2 {
+    solana_program::declare_id!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+}
+
+pub mod pyth_mainnet {
+    solana_program::declare_id!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+}
+
+pub mod pyth_devnet {
+    solana_program::declare_id!("gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s");
 }
 
-pub fn get_oracle_price(
+/// Checks that an oracle account is owned by the program of its oracle type.
+/// The dummy oracle is deployed per test environment so it has no fixed owner.
+pub fn check_oracle_owner(oracle_type: OracleType, oracle_info: &AccountInfo) -> ProgramResult {
+    let is_valid_owner = match oracle_type {
+        OracleType::Pyth => {
+            oracle_info.owner == &pyth_mainnet::ID || oracle_info.owner == &pyth_devnet::ID
+        }
+        OracleType::Switchboard => oracle_info.owner == &switchboard_v2::ID,
+        OracleType::Dummy => true,
+        _ => false,
+    };
+    if !is_valid_owner {
+        msg!(
+            "Oracle {} is not owned by the {:?} program",
+            oracle_info.key,
+            oracle_type
+        );
+        return Err(DerivativeError::InvalidOracleConfig.into());
+    }
+    Ok(())
+}
+
+// Anchor discriminator of the Switchboard V2 AggregatorAccountData account
+const SWITCHBOARD_AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+// Offsets into the packed AggregatorAccountData (including the discriminator) of the
+// latest_confirmed_round fields that we read
+const SWITCHBOARD_ROUND_OPEN_SLOT_OFFSET: usize = 350;
+const SWITCHBOARD_RESULT_OFFSET: usize = 366;
+const SWITCHBOARD_STD_DEVIATION_OFFSET: usize = 386;
+// A SwitchboardDecimal is an i128 mantissa followed by a u32 scale
+const SWITCHBOARD_DECIMAL_LEN: usize = 20;
+
+fn get_switchboard_price(
+    aggregator_info: &AccountInfo,
+    clock: &Clock,
+    max_staleness_slots: u64,
+    max_confidence_bps: u64,
+) -> std::result::Result<Fractional, ProgramError> {
+    let data = aggregator_info.try_borrow_data()?;
+    if data.len() < SWITCHBOARD_STD_DEVIATION_OFFSET + SWITCHBOARD_DECIMAL_LEN
+        || data[..8] != SWITCHBOARD_AGGREGATOR_DISCRIMINATOR
+    {
+        msg!("Account provided is not a valid Switchboard aggregator");
+        return Err(DerivativeError::InvalidOracleConfig.into());
+    }
+    let round_open_slot =
+        u64::from_le_bytes(*array_ref![data, SWITCHBOARD_ROUND_OPEN_SLOT_OFFSET, 8]);
+    let price = read_switchboard_decimal(&data, SWITCHBOARD_RESULT_OFFSET)?;
+    let std_deviation = read_switchboard_decimal(&data, SWITCHBOARD_STD_DEVIATION_OFFSET)?;
+    check_staleness(round_open_slot, clock, max_staleness_slots)?;
+    if price != ZERO_FRAC && std_deviation / price.abs() > bps(max_confidence_bps as i64) {
+        msg!("Market is too wide");
+        return Err(DerivativeError::OracleConfidenceTooWide.into());
+    }
+    Ok(price)
+}
+
+fn read_switchboard_decimal(
+    data: &[u8],
+    offset: usize,
+) -> std::result::Result<Fractional, ProgramError> {
+    let mut mantissa = i128::from_le_bytes(*array_ref![data, offset, 16]);
+    let mut scale = u32::from_le_bytes(*array_ref![data, offset + 16, 4]);
+    // Drop precision until the value fits into a Fractional
+    while scale > MAX_ORACLE_DECIMALS || mantissa > i64::MAX as i128 || mantissa < i64::MIN as i128
+    {
+        if scale == 0 {
+            msg!("Switchboard value is out of range");
+            return Err(DerivativeError::NumericalOverflow.into());
+        }
+        mantissa /= 10;
+        scale -= 1;
+    }
+    Ok(Fractional::new(mantissa as i64, scale as u64))
+}
+
+/// Returns the median price of the oracles of a CompositeOracle. The underlying oracle
+/// accounts must all be passed in, oracles that fail validation are left out of the median.
+pub fn get_composite_price<'info>(
+    composite_oracle_info: &AccountInfo<'info>,
+    oracle_accounts: &[AccountInfo<'info>],
+    clock: &Clock,
+    max_staleness_slots: u64,
+    max_confidence_bps: u64,
+) -> std::result::Result<Fractional, ProgramError> {
+    let loader = AccountLoader::<CompositeOracle>::try_from(composite_oracle_info)?;
+    let composite_oracle = loader.load()?;
+    if !composite_oracle.is_initialized() {
+        return Err(DerivativeError::UninitializedAccount.into());
+    }
+    let mut prices = Vec::with_capacity(composite_oracle.num_oracles as usize);
+    for (oracle_type, oracle_key) in composite_oracle.oracles() {
+        let oracle_info = oracle_accounts
+            .iter()
+            .find(|a| a.key == &oracle_key)
+            .ok_or_else(|| {
+                msg!("Missing oracle account {}", oracle_key);
+                ProgramError::from(DerivativeError::InvalidAccount)
+            })?;
+        match get_single_oracle_price(
+            oracle_type,
+            oracle_info,
+            clock,
+            max_staleness_slots,
+            max_confidence_bps,
+        ) {
+            Ok(price) => prices.push(price),
+            Err(e) => msg!("Skipping oracle {}: {}", oracle_key, e),
+        }
+    }
+    if prices.is_empty() || (prices.len() as u64) < composite_oracle.min_valid_oracles {
+        msg!(
+            "Only {} of {} oracles are valid",
+            prices.len(),
+            composite_oracle.num_oracles
+        );
+        return Err(DerivativeError::InsufficientValidOracles.into());
+    }
+    prices.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 1 {
+        Ok(prices[mid])
+    } else {
+        Ok(prices[mid - 1]
+            .checked_add(prices[mid])?
+            .checked_div(Fractional::new(2, 0))?)
+    }
+}
+
+fn get_single_oracle_price(
     oracle_type: OracleType,
     price_info: &AccountInfo,
     clock: &Clock,
+    max_staleness_slots: u64,
+    max_confidence_bps: u64,
 ) -> std::result::Result<Fractional, ProgramError> {
+    check_oracle_owner(oracle_type, price_info)?;
     match oracle_type {
-        OracleType::Pyth => get_pyth_price(price_info, clock),
-        OracleType::Dummy => get_dummy_price(price_info, clock),
+        OracleType::Pyth => {
+            get_pyth_price(price_info, clock, max_staleness_slots, max_confidence_bps)
+        }
+        // The dummy oracle does not publish a confidence interval
+        OracleType::Dummy => get_dummy_price(price_info, clock, max_staleness_slots),
+        OracleType::Switchboard => {
+            get_switchboard_price(price_info, clock, max_staleness_slots, max_confidence_bps)
+        }
         _ => Err(UtilError::AccountUninitialized.into()),
     }
 }
+
+pub fn get_oracle_price<'info>(
+    derivative_metadata: &DerivativeMetadata,
+    price_info: &AccountInfo<'info>,
+    oracle_accounts: &[AccountInfo<'info>],
+    clock: &Clock,
+) -> std::result::Result<Fractional, ProgramError> {
+    let price = get_price(
+        derivative_metadata.oracle_type,
+        price_info,
+        oracle_accounts,
+        clock,
+        derivative_metadata.max_staleness_slots,
+        derivative_metadata.max_confidence_bps,
+    )?;
+    // Only linear instruments are defined for a negative underlying
+    if price.is_negative() && !derivative_metadata.instrument_type.is_linear() {
+        msg!("Oracle price cannot be negative");
+        return Err(DerivativeError::InvalidOracleConfig.into());
+    }
+    Ok(price)
+}
+
+/// Reads the price of any supported oracle type. A `max_confidence_bps` of 0 uses the
+/// default limit and a `max_staleness_slots` of 0 disables the staleness check.
+pub fn get_price<'info>(
+    oracle_type: OracleType,
+    price_info: &AccountInfo<'info>,
+    oracle_accounts: &[AccountInfo<'info>],
+    clock: &Clock,
+    max_staleness_slots: u64,
+    max_confidence_bps: u64,
+) -> std::result::Result<Fractional, ProgramError> {
+    let max_confidence_bps = match max_confidence_bps {
+        0 => DEFAULT_MAX_CONFIDENCE_BPS,
+        limit => limit,
+    };
+    match oracle_type {
+        OracleType::Composite => get_composite_price(
+            price_info,
+            oracle_accounts,
+            clock,
+            max_staleness_slots,
+            max_confidence_bps,
+        ),
+        oracle_type => get_single_oracle_price(
+            oracle_type,
+            price_info,
+            clock,
+            max_staleness_slots,
+            max_confidence_bps,
+        ),
+    }
+}
+
+fn check_staleness(
+    last_update_slot: u64,
+    clock: &Clock,
+    max_staleness_slots: u64,
+) -> std::result::Result<(), ProgramError> {
+    if max_staleness_slots > 0 && clock.slot.saturating_sub(last_update_slot) > max_staleness_slots
+    {
+        msg!(
+            "Oracle price is stale, last update slot: {} current slot: {}",
+            last_update_slot,
+            clock.slot
+        );
+        return Err(DerivativeError::StaleOraclePrice.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::enums::InstrumentType;
+    use anchor_lang::Discriminator;
+    use borsh::BorshSerialize;
+    use bytemuck::Zeroable;
+    use dummy_oracle::state::{AccountTag, PricePoint, ScheduleClock, MAX_PRICE_SCHEDULE_LEN};
+
+    fn clock_at_slot(slot: u64) -> Clock {
+        Clock {
+            slot,
+            ..Clock::default()
+        }
+    }
+
+    /// Backs account data with u64s so it is aligned like the data of an on-chain account,
+    /// callers slice it back to the length of the account
+    fn aligned_data(bytes: &[u8]) -> Vec<u64> {
+        let mut data = vec![0_u64; (bytes.len() + 7) / 8];
+        bytemuck::cast_slice_mut::<u64, u8>(&mut data)[..bytes.len()].copy_from_slice(bytes);
+        data
+    }
+
+    fn with_account<T>(bytes: &[u8], owner: &Pubkey, f: impl FnOnce(&AccountInfo) -> T) -> T {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = aligned_data(bytes);
+        let account_info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut bytemuck::cast_slice_mut(&mut data)[..bytes.len()],
+            owner,
+            false,
+            0,
+        );
+        f(&account_info)
+    }
+
+    fn pyth_price_data(price: i64, conf: u64, expo: i32, pub_slot: u64) -> Vec<u8> {
+        let mut pyth_price: pyth_client::Price = unsafe { std::mem::zeroed() };
+        pyth_price.expo = expo;
+        pyth_price.agg.price = price;
+        pyth_price.agg.conf = conf;
+        pyth_price.agg.pub_slot = pub_slot;
+        unsafe {
+            std::slice::from_raw_parts(
+                &pyth_price as *const pyth_client::Price as *const u8,
+                std::mem::size_of::<pyth_client::Price>(),
+            )
+        }
+        .to_vec()
+    }
+
+    fn dummy_price_data(price: i64, decimals: u64, slot: u64) -> Vec<u8> {
+        let oracle_price = OraclePrice {
+            tag: AccountTag::OraclePrice,
+            price,
+            decimals,
+            slot,
+            update_authority: Pubkey::default(),
+            schedule_clock: ScheduleClock::Slot,
+            schedule_len: 0,
+            schedule: [PricePoint::default(); MAX_PRICE_SCHEDULE_LEN],
+        };
+        oracle_price.try_to_vec().unwrap()
+    }
+
+    fn pyth_price(
+        data: &[u8],
+        slot: u64,
+        max_staleness_slots: u64,
+        max_confidence_bps: u64,
+    ) -> std::result::Result<Fractional, ProgramError> {
+        with_account(data, &pyth_mainnet::ID, |price_info| {
+            get_price(
+                OracleType::Pyth,
+                price_info,
+                &[],
+                &clock_at_slot(slot),
+                max_staleness_slots,
+                max_confidence_bps,
+            )
+        })
+    }
+
+    #[test]
+    fn test_stale_oracle_price() {
+        // published at slot 100
+        let data = pyth_price_data(10_000, 0, -2, 100);
+        assert_eq!(
+            pyth_price(&data, 110, 10, 0),
+            Ok(Fractional::new(10_000, 2))
+        );
+        assert_eq!(
+            pyth_price(&data, 111, 10, 0),
+            Err(DerivativeError::StaleOraclePrice.into())
+        );
+        // 0 disables the staleness check
+        assert_eq!(
+            pyth_price(&data, 10_000, 0, 0),
+            Ok(Fractional::new(10_000, 2))
+        );
+
+        let data = dummy_price_data(5, 0, 100);
+        let dummy_price = |data: &[u8], slot| {
+            with_account(data, &Pubkey::new_unique(), |price_info| {
+                get_price(
+                    OracleType::Dummy,
+                    price_info,
+                    &[],
+                    &clock_at_slot(slot),
+                    10,
+                    0,
+                )
+            })
+        };
+        assert_eq!(dummy_price(&data, 110), Ok(Fractional::new(5, 0)));
+        assert_eq!(
+            dummy_price(&data, 111),
+            Err(DerivativeError::StaleOraclePrice.into())
+        );
+    }
+
+    #[test]
+    fn test_oracle_confidence_too_wide() {
+        // confidence interval of 1% of the price
+        let data = pyth_price_data(10_000, 100, -2, 100);
+        assert_eq!(
+            pyth_price(&data, 100, 0, 100),
+            Ok(Fractional::new(10_000, 2))
+        );
+        assert_eq!(
+            pyth_price(&data, 100, 0, 99),
+            Err(DerivativeError::OracleConfidenceTooWide.into())
+        );
+
+        // 0 falls back to the default limit of 10% instead of disabling the check
+        let data = pyth_price_data(10_000, 1_000, -2, 100);
+        assert_eq!(pyth_price(&data, 100, 0, 0), Ok(Fractional::new(10_000, 2)));
+        let data = pyth_price_data(10_000, 1_001, -2, 100);
+        assert_eq!(
+            pyth_price(&data, 100, 0, 0),
+            Err(DerivativeError::OracleConfidenceTooWide.into())
+        );
+    }
+
+    fn switchboard_data(result: (i128, u32), std_deviation: (i128, u32), slot: u64) -> Vec<u8> {
+        let mut bytes = vec![0_u8; SWITCHBOARD_STD_DEVIATION_OFFSET + SWITCHBOARD_DECIMAL_LEN];
+        bytes[..8].copy_from_slice(&SWITCHBOARD_AGGREGATOR_DISCRIMINATOR);
+        bytes[SWITCHBOARD_ROUND_OPEN_SLOT_OFFSET..SWITCHBOARD_ROUND_OPEN_SLOT_OFFSET + 8]
+            .copy_from_slice(&slot.to_le_bytes());
+        for (offset, (mantissa, scale)) in [
+            (SWITCHBOARD_RESULT_OFFSET, result),
+            (SWITCHBOARD_STD_DEVIATION_OFFSET, std_deviation),
+        ] {
+            bytes[offset..offset + 16].copy_from_slice(&mantissa.to_le_bytes());
+            bytes[offset + 16..offset + 20].copy_from_slice(&scale.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn switchboard_price(
+        data: &[u8],
+        owner: &Pubkey,
+        max_confidence_bps: u64,
+    ) -> std::result::Result<Fractional, ProgramError> {
+        with_account(data, owner, |price_info| {
+            get_price(
+                OracleType::Switchboard,
+                price_info,
+                &[],
+                &clock_at_slot(100),
+                10,
+                max_confidence_bps,
+            )
+        })
+    }
+
+    #[test]
+    fn test_switchboard_price() {
+        let data = switchboard_data((12_345, 2), (1, 0), 100);
+        assert_eq!(
+            switchboard_price(&data, &switchboard_v2::ID, 0),
+            Ok(Fractional::new(12_345, 2))
+        );
+        // a standard deviation of 1 is 0.81% of the price
+        assert_eq!(
+            switchboard_price(&data, &switchboard_v2::ID, 80),
+            Err(DerivativeError::OracleConfidenceTooWide.into())
+        );
+        assert_eq!(
+            switchboard_price(&data, &Pubkey::new_unique(), 0),
+            Err(DerivativeError::InvalidOracleConfig.into())
+        );
+
+        // precision beyond what a Fractional holds is dropped
+        let data = switchboard_data((123_456_789_000_000_000_000, 20), (0, 0), 100);
+        assert_eq!(
+            switchboard_price(&data, &switchboard_v2::ID, 0),
+            Ok(Fractional::new(123_456_789, 8))
+        );
+
+        let data = switchboard_data((12_345, 2), (0, 0), 89);
+        assert_eq!(
+            switchboard_price(&data, &switchboard_v2::ID, 0),
+            Err(DerivativeError::StaleOraclePrice.into())
+        );
+        let data = [0; 8];
+        assert_eq!(
+            switchboard_price(&data, &switchboard_v2::ID, 0),
+            Err(DerivativeError::InvalidOracleConfig.into())
+        );
+    }
+
+    #[test]
+    fn test_negative_oracle_price() {
+        let data = dummy_price_data(-5, 0, 100);
+        let mut derivative_metadata = DerivativeMetadata::zeroed();
+        derivative_metadata.oracle_type = OracleType::Dummy;
+        let mut oracle_price = |instrument_type| {
+            derivative_metadata.instrument_type = instrument_type;
+            with_account(&data, &Pubkey::new_unique(), |price_info| {
+                get_oracle_price(&derivative_metadata, price_info, &[], &clock_at_slot(100))
+            })
+        };
+        assert_eq!(
+            oracle_price(InstrumentType::LinearPerp),
+            Ok(Fractional::new(-5, 0))
+        );
+        assert_eq!(
+            oracle_price(InstrumentType::LinearFuture),
+            Ok(Fractional::new(-5, 0))
+        );
+        assert_eq!(
+            oracle_price(InstrumentType::ExpiringCall),
+            Err(DerivativeError::InvalidOracleConfig.into())
+        );
+    }
+
+    #[test]
+    fn test_pyth_owner() {
+        let data = pyth_price_data(10_000, 0, -2, 100);
+        let price = with_account(&data, &Pubkey::new_unique(), |price_info| {
+            get_price(OracleType::Pyth, price_info, &[], &clock_at_slot(100), 0, 0)
+        });
+        assert_eq!(price, Err(DerivativeError::InvalidOracleConfig.into()));
+    }
+
+    fn composite_price(
+        oracles: &[(OracleType, Pubkey, Vec<u8>)],
+        min_valid_oracles: u64,
+    ) -> std::result::Result<Fractional, ProgramError> {
+        let keys = oracles
+            .iter()
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        let mut composite_oracle = CompositeOracle::zeroed();
+        composite_oracle.tag = crate::state::enums::AccountTag::CompositeOracle;
+        composite_oracle.num_oracles = oracles.len() as u64;
+        composite_oracle.min_valid_oracles = min_valid_oracles;
+        for (i, (oracle_type, _, _)) in oracles.iter().enumerate() {
+            composite_oracle.oracle_types[i] = *oracle_type;
+            composite_oracle.oracles[i] = keys[i];
+        }
+        let composite_data = [
+            &CompositeOracle::discriminator()[..],
+            bytemuck::bytes_of(&composite_oracle),
+        ]
+        .concat();
+
+        let mut oracle_data = oracles
+            .iter()
+            .map(|(_, _, bytes)| aligned_data(bytes))
+            .collect::<Vec<_>>();
+        let mut lamports = vec![0; oracles.len()];
+        let oracle_accounts = oracles
+            .iter()
+            .zip(keys.iter())
+            .zip(oracle_data.iter_mut().zip(lamports.iter_mut()))
+            .map(|(((_, owner, bytes), key), (data, lamports))| {
+                AccountInfo::new(
+                    key,
+                    false,
+                    false,
+                    lamports,
+                    &mut bytemuck::cast_slice_mut(data)[..bytes.len()],
+                    owner,
+                    false,
+                    0,
+                )
+            })
+            .collect::<Vec<_>>();
+        let composite_key = Pubkey::new_unique();
+        let mut composite_lamports = 0;
+        let mut composite_aligned = aligned_data(&composite_data);
+        let composite_info = AccountInfo::new(
+            &composite_key,
+            false,
+            false,
+            &mut composite_lamports,
+            &mut bytemuck::cast_slice_mut(&mut composite_aligned)[..composite_data.len()],
+            &crate::ID,
+            false,
+            0,
+        );
+        get_price(
+            OracleType::Composite,
+            &composite_info,
+            &oracle_accounts,
+            &clock_at_slot(100),
+            10,
+            0,
+        )
+    }
+
+    #[test]
+    fn test_composite_median() {
+        let dummy_owner = Pubkey::new_unique();
+        let mut oracles = vec![
+            (
+                OracleType::Dummy,
+                dummy_owner,
+                dummy_price_data(101, 0, 100),
+            ),
+            (
+                OracleType::Pyth,
+                pyth_mainnet::ID,
+                pyth_price_data(9_900, 0, -2, 100),
+            ),
+            (
+                OracleType::Switchboard,
+                switchboard_v2::ID,
+                switchboard_data((100, 0), (0, 0), 100),
+            ),
+        ];
+        assert_eq!(composite_price(&oracles, 3), Ok(Fractional::new(100, 0)));
+
+        // a stale oracle is left out of the median
+        oracles[0].2 = dummy_price_data(101, 0, 0);
+        assert_eq!(composite_price(&oracles, 2), Ok(Fractional::new(995, 1)));
+        assert_eq!(
+            composite_price(&oracles, 3),
+            Err(DerivativeError::InsufficientValidOracles.into())
+        );
+
+        // and so is one that is not owned by its oracle program
+        oracles[1].1 = dummy_owner;
+        assert_eq!(composite_price(&oracles, 1), Ok(Fractional::new(100, 0)));
+    }
+}
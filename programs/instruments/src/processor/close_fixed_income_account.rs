@@ -0,0 +1,36 @@
+use crate::{error::DerivativeError, state::enums::AccountTag, CloseFixedIncomeAccount};
+use anchor_lang::prelude::*;
+use dex::{
+    error::UtilError,
+    utils::validation::{assert, assert_keys_equal},
+};
+use solana_program::entrypoint::ProgramResult;
+
+pub fn process(ctx: Context<CloseFixedIncomeAccount>) -> ProgramResult {
+    let accts = ctx.accounts;
+    let mut fixed_income_metadata = accts.fixed_income_metadata.load_mut()?;
+    assert_keys_equal(*accts.fixed_income_metadata.as_ref().owner, *ctx.program_id)?;
+    assert(
+        fixed_income_metadata.is_initialized(),
+        UtilError::AccountUninitialized,
+    )?;
+    assert_keys_equal(
+        *accts.close_authority.key,
+        fixed_income_metadata.close_authority,
+    )?;
+    assert_keys_equal(
+        fixed_income_metadata.get_key(ctx.program_id)?,
+        accts.fixed_income_metadata.key(),
+    )?;
+    assert(
+        fixed_income_metadata.expired(),
+        DerivativeError::CannotBeDeleted,
+    )?;
+    let dest_starting_lamports = accts.destination.lamports();
+    **accts.destination.lamports.borrow_mut() = dest_starting_lamports
+        .checked_add(accts.fixed_income_metadata.as_ref().lamports())
+        .ok_or(DerivativeError::NumericalOverflow)?;
+    **accts.fixed_income_metadata.as_ref().lamports.borrow_mut() = 0;
+    fixed_income_metadata.tag = AccountTag::Uninitialized;
+    Ok(())
+}
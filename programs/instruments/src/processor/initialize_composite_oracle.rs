@@ -0,0 +1,69 @@
+use crate::{
+    error::DerivativeError,
+    oracle::check_oracle_owner,
+    state::{
+        constants::MAX_COMPOSITE_ORACLES,
+        enums::{AccountTag, OracleType},
+    },
+    InitializeCompositeOracle, InitializeCompositeOracleParams,
+};
+use anchor_lang::prelude::*;
+use dex::utils::validation::assert;
+use solana_program::entrypoint::ProgramResult;
+
+pub fn process(
+    ctx: Context<InitializeCompositeOracle>,
+    params: InitializeCompositeOracleParams,
+) -> ProgramResult {
+    let oracle_accounts = ctx.remaining_accounts;
+    let num_oracles = params.oracle_types.len();
+    assert(
+        num_oracles > 0 && num_oracles <= MAX_COMPOSITE_ORACLES,
+        DerivativeError::InvalidOracleConfig,
+    )?;
+    assert(
+        oracle_accounts.len() == num_oracles,
+        DerivativeError::InvalidOracleConfig,
+    )?;
+    assert(
+        params.min_valid_oracles as usize <= num_oracles,
+        DerivativeError::InvalidOracleConfig,
+    )?;
+    // Composite oracles cannot be nested
+    assert(
+        params.oracle_types.iter().all(|t| {
+            matches!(
+                t,
+                OracleType::Pyth | OracleType::Dummy | OracleType::Switchboard
+            )
+        }),
+        DerivativeError::InvalidOracleConfig,
+    )?;
+    for (i, (oracle_type, oracle)) in params
+        .oracle_types
+        .iter()
+        .zip(oracle_accounts.iter())
+        .enumerate()
+    {
+        assert(
+            oracle_accounts[..i].iter().all(|o| o.key != oracle.key),
+            DerivativeError::InvalidOracleConfig,
+        )?;
+        check_oracle_owner(*oracle_type, oracle)?;
+    }
+
+    let mut composite_oracle = ctx.accounts.composite_oracle.load_init()?;
+    composite_oracle.tag = AccountTag::CompositeOracle;
+    composite_oracle.num_oracles = num_oracles as u64;
+    composite_oracle.min_valid_oracles = params.min_valid_oracles;
+    for (i, (oracle_type, oracle)) in params
+        .oracle_types
+        .iter()
+        .zip(oracle_accounts.iter())
+        .enumerate()
+    {
+        composite_oracle.oracle_types[i] = *oracle_type;
+        composite_oracle.oracles[i] = *oracle.key;
+    }
+    Ok(())
+}
@@ -1,10 +1,14 @@
 use crate::{
     error::DerivativeError,
-    state::enums::{AccountTag, ExpirationStatus, InstrumentType},
+    state::{
+        composite_oracle::CompositeOracle,
+        constants::{MIN_SETTLEMENT_SAMPLES, NUM_EWMA_WINDOWS},
+        enums::{AccountTag, ExpirationStatus, OracleType},
+    },
     InitializeDerivative, InitializeDerivativeParams,
 };
 use anchor_lang::prelude::*;
-use dex::utils::validation::assert;
+use dex::utils::{numeric::ZERO_FRAC, validation::assert};
 use solana_program::{
     entrypoint::ProgramResult, program_error::ProgramError, sysvar::clock::Clock,
 };
@@ -27,14 +31,50 @@ pub fn process(
     )?;
     derivative_metadata.tag = AccountTag::DerivativeMetadata;
 
-    match params.instrument_type {
-        InstrumentType::ExpiringCall | InstrumentType::ExpiringPut => {
+    if params.instrument_type.is_recurring()? {
+        assert(
+            params.settlement_window == 0,
+            DerivativeError::InvalidSettlementTime,
+        )?;
+    } else {
+        assert(
+            params.full_funding_period == params.minimum_funding_period,
+            DerivativeError::InvalidSettlementTime,
+        )?;
+        assert(
+            params.settlement_window >= 0
+                && params.settlement_window < params.minimum_funding_period,
+            DerivativeError::InvalidSettlementTime,
+        )?;
+        // Samples taken in the same second replace each other
+        if params.settlement_window > 0 {
             assert(
-                params.full_funding_period == params.minimum_funding_period,
+                params.min_settlement_samples >= MIN_SETTLEMENT_SAMPLES
+                    && params.min_settlement_samples <= params.settlement_window as u64,
                 DerivativeError::InvalidSettlementTime,
             )?;
         }
-        _ => {}
+    }
+    assert(
+        (params.mark_price_ewma_window as usize) < NUM_EWMA_WINDOWS,
+        ProgramError::InvalidArgument,
+    )?;
+    if params.instrument_type.is_capped() {
+        assert(params.cap > ZERO_FRAC, ProgramError::InvalidArgument)?;
+    }
+    if params.instrument_type.is_binary() {
+        assert(
+            params.digital_payout > ZERO_FRAC,
+            ProgramError::InvalidArgument,
+        )?;
+    }
+
+    if params.oracle_type == OracleType::Composite {
+        let composite_oracle = AccountLoader::<CompositeOracle>::try_from(&accts.price_oracle)?;
+        assert(
+            composite_oracle.load()?.is_initialized(),
+            DerivativeError::InvalidOracleConfig,
+        )?;
     }
 
     // Immutable fields
@@ -49,8 +89,18 @@ pub fn process(
     derivative_metadata.price_oracle = *accts.price_oracle.key;
     derivative_metadata.clock = *accts.clock.key;
     derivative_metadata.oracle_type = params.oracle_type;
+    derivative_metadata.max_staleness_slots = params.max_staleness_slots;
+    derivative_metadata.max_confidence_bps = params.max_confidence_bps;
+    derivative_metadata.max_funding_rate_bps = params.max_funding_rate_bps;
+    derivative_metadata.funding_dampening_bps = params.funding_dampening_bps;
+    derivative_metadata.cap = params.cap;
+    derivative_metadata.digital_payout = params.digital_payout;
+    derivative_metadata.mark_price_ewma_window = params.mark_price_ewma_window;
+    derivative_metadata.settlement_window = params.settlement_window;
+    derivative_metadata.min_settlement_samples = params.min_settlement_samples;
     // Mutable fields
     derivative_metadata.expired = ExpirationStatus::Active;
     derivative_metadata.last_funding_time = params.initialization_time;
+    derivative_metadata.last_premium_time = params.initialization_time;
     Ok(())
 }
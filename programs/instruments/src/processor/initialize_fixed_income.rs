@@ -0,0 +1,68 @@
+use crate::{
+    error::DerivativeError,
+    state::enums::{AccountTag, ExpirationStatus, FixedIncomeType},
+    InitializeFixedIncome, InitializeFixedIncomeParams,
+};
+use anchor_lang::prelude::*;
+use dex::utils::{numeric::ZERO_FRAC, validation::assert};
+use solana_program::{
+    entrypoint::ProgramResult, program_error::ProgramError, sysvar::clock::Clock,
+};
+
+pub fn process(
+    context: Context<InitializeFixedIncome>,
+    params: InitializeFixedIncomeParams,
+) -> ProgramResult {
+    let accts = context.accounts;
+    let mut fixed_income_metadata = accts.fixed_income_metadata.load_init()?;
+    assert(
+        !fixed_income_metadata.is_initialized(),
+        DerivativeError::AccountAlreadyInitialized,
+    )?;
+    let clock: Clock = bincode::deserialize(&accts.clock.data.borrow())
+        .map_err(|_| ProgramError::InvalidArgument)?;
+    assert(
+        params.initialization_time >= clock.unix_timestamp,
+        DerivativeError::InvalidCreationTime,
+    )?;
+    assert(
+        params.maturity_time > params.initialization_time,
+        DerivativeError::InvalidDate,
+    )?;
+    assert(params.notional > ZERO_FRAC, ProgramError::InvalidArgument)?;
+
+    match params.fixed_income_type {
+        FixedIncomeType::ZeroCouponBondFuture => {
+            assert(params.tenor > 0, ProgramError::InvalidArgument)?;
+        }
+        FixedIncomeType::InterestRateSwap => {
+            assert(
+                params.coupon_period > 0,
+                DerivativeError::InvalidSettlementTime,
+            )?;
+        }
+        FixedIncomeType::Uninitialized => return Err(ProgramError::InvalidArgument),
+    }
+    fixed_income_metadata.tag = AccountTag::FixedIncomeMetadata;
+
+    // Immutable fields
+    fixed_income_metadata.bump = context.bumps["fixed_income_metadata"] as u64;
+    fixed_income_metadata.fixed_income_type = params.fixed_income_type;
+    fixed_income_metadata.notional = params.notional;
+    fixed_income_metadata.fixed_rate = params.fixed_rate;
+    fixed_income_metadata.tenor = params.tenor;
+    fixed_income_metadata.initialization_time = params.initialization_time;
+    fixed_income_metadata.maturity_time = params.maturity_time;
+    fixed_income_metadata.coupon_period = params.coupon_period;
+    fixed_income_metadata.close_authority = params.close_authority;
+    fixed_income_metadata.market_product_group = *accts.market_product_group.key;
+    fixed_income_metadata.rate_oracle = *accts.rate_oracle.key;
+    fixed_income_metadata.clock = *accts.clock.key;
+    fixed_income_metadata.oracle_type = params.oracle_type;
+    fixed_income_metadata.max_staleness_slots = params.max_staleness_slots;
+    fixed_income_metadata.max_confidence_bps = params.max_confidence_bps;
+    // Mutable fields
+    fixed_income_metadata.expired = ExpirationStatus::Active;
+    fixed_income_metadata.last_coupon_time = params.initialization_time;
+    Ok(())
+}
@@ -0,0 +1,82 @@
+use crate::{
+    error::DerivativeError,
+    state::{
+        constants::{MAX_SERIES_STRIKES, MIN_SETTLEMENT_SAMPLES},
+        enums::AccountTag,
+    },
+    InitializeSeries, InitializeSeriesParams,
+};
+use anchor_lang::prelude::*;
+use dex::{
+    state::market_product_group::MarketProductGroup,
+    utils::{
+        numeric::ZERO_FRAC,
+        validation::{assert, assert_keys_equal},
+    },
+};
+use solana_program::{entrypoint::ProgramResult, program_error::ProgramError};
+
+pub fn process(ctx: Context<InitializeSeries>, params: InitializeSeriesParams) -> ProgramResult {
+    let accts = ctx.accounts;
+    {
+        let loader = AccountLoader::<MarketProductGroup>::try_from(&accts.market_product_group)?;
+        let market_product_group = loader.load()?;
+        // Series list products through a shared lister, so only the group authority can add them
+        assert_keys_equal(market_product_group.authority, *accts.authority.key)?;
+    }
+    assert(
+        !params.instrument_type.is_recurring()?,
+        DerivativeError::InvalidSettlementTime,
+    )?;
+    assert(
+        !params.strikes.is_empty() && params.strikes.len() <= MAX_SERIES_STRIKES,
+        ProgramError::InvalidArgument,
+    )?;
+    assert(params.expiry_interval > 0, DerivativeError::InvalidDate)?;
+    assert(
+        params.settlement_window >= 0 && params.settlement_window < params.expiry_interval,
+        DerivativeError::InvalidSettlementTime,
+    )?;
+    // Samples taken in the same second replace each other
+    if params.settlement_window > 0 {
+        assert(
+            params.min_settlement_samples >= MIN_SETTLEMENT_SAMPLES
+                && params.min_settlement_samples <= params.settlement_window as u64,
+            DerivativeError::InvalidSettlementTime,
+        )?;
+    }
+    if params.instrument_type.is_capped() {
+        assert(params.cap > ZERO_FRAC, ProgramError::InvalidArgument)?;
+    }
+    if params.instrument_type.is_binary() {
+        assert(
+            params.digital_payout > ZERO_FRAC,
+            ProgramError::InvalidArgument,
+        )?;
+    }
+
+    let mut series = accts.series.load_init()?;
+    series.tag = AccountTag::Series;
+    series.instrument_type = params.instrument_type;
+    series.oracle_type = params.oracle_type;
+    series.num_strikes = params.strikes.len() as u64;
+    series.strikes[..params.strikes.len()].copy_from_slice(&params.strikes);
+    series.start_time = params.start_time;
+    series.expiry_interval = params.expiry_interval;
+    series.num_rolls = 0;
+    series.market_product_group = *accts.market_product_group.key;
+    series.price_oracle = *accts.price_oracle.key;
+    series.clock = *accts.clock.key;
+    series.close_authority = params.close_authority;
+    series.name = params.name;
+    series.tick_size = params.tick_size;
+    series.base_decimals = params.base_decimals;
+    series.price_offset = params.price_offset;
+    series.max_staleness_slots = params.max_staleness_slots;
+    series.max_confidence_bps = params.max_confidence_bps;
+    series.cap = params.cap;
+    series.digital_payout = params.digital_payout;
+    series.settlement_window = params.settlement_window;
+    series.min_settlement_samples = params.min_settlement_samples;
+    Ok(())
+}
@@ -1,3 +1,12 @@
 pub mod close_derivative_account;
+pub mod close_fixed_income_account;
+pub mod initialize_composite_oracle;
 pub mod initialize_derivative;
+pub mod initialize_fixed_income;
+pub mod initialize_series;
+pub mod record_settlement_sample;
+pub mod roll_series;
 pub mod settle_derivative;
+pub mod settle_derivatives;
+pub mod settle_fixed_income;
+pub mod update_premium_index;
@@ -0,0 +1,53 @@
+use crate::{
+    error::DerivativeError, oracle::get_oracle_price, state::enums::OracleType,
+    RecordSettlementSample,
+};
+use anchor_lang::prelude::*;
+use dex::utils::validation::{assert, assert_keys_equal};
+use solana_program::{
+    entrypoint::ProgramResult, program_error::ProgramError, sysvar, sysvar::clock::Clock,
+};
+
+/// Records an oracle sample for the settlement price of an expiring instrument.
+/// Anyone can call this during the settlement window before expiry.
+pub fn process(ctx: Context<RecordSettlementSample>) -> ProgramResult {
+    let accts = &ctx.accounts;
+    let mut derivative_metadata = accts.derivative_metadata.load_mut()?;
+    assert(
+        derivative_metadata.is_initialized(),
+        DerivativeError::UninitializedAccount,
+    )?;
+    assert(
+        !derivative_metadata.instrument_type.is_recurring()?
+            && derivative_metadata.settlement_window > 0,
+        DerivativeError::InvalidAccount,
+    )?;
+    assert_keys_equal(derivative_metadata.clock, *accts.clock.key)?;
+    assert_keys_equal(derivative_metadata.price_oracle, *accts.price_oracle.key)?;
+    match derivative_metadata.oracle_type {
+        OracleType::Pyth => assert_keys_equal(accts.clock.key(), sysvar::clock::ID)?,
+        _ => {}
+    }
+    let clock: Clock = bincode::deserialize(&accts.clock.data.borrow()).map_err(|e| {
+        msg!("Failed to deserialize clock {}", e);
+        ProgramError::InvalidArgument
+    })?;
+    assert(
+        derivative_metadata.in_settlement_window(clock.unix_timestamp),
+        DerivativeError::OutsideSettlementWindow,
+    )?;
+
+    let index_price = get_oracle_price(
+        &derivative_metadata,
+        &accts.price_oracle,
+        ctx.remaining_accounts,
+        &clock,
+    )?;
+    derivative_metadata.record_settlement_sample(index_price, clock.unix_timestamp)?;
+    msg!(
+        "settlement sample {}: {}",
+        derivative_metadata.num_settlement_samples,
+        index_price
+    );
+    Ok(())
+}
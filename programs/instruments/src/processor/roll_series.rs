@@ -0,0 +1,166 @@
+use crate::{
+    error::DerivativeError,
+    state::{
+        derivative_metadata::DerivativeMetadata,
+        enums::{AccountTag, ExpirationStatus},
+    },
+    RollSeries,
+};
+use anchor_lang::{prelude::*, Discriminator};
+use dex::{
+    state::market_product_group::MarketProductGroup,
+    utils::validation::{assert, assert_keys_equal},
+};
+use solana_program::{
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    system_instruction,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+};
+
+/// Lists the current expiry of a series once every product of the previous expiry has
+/// expired. Anyone can roll a series, the caller pays the rent of the new accounts.
+pub fn process<'info>(ctx: Context<'_, '_, '_, 'info, RollSeries<'info>>) -> ProgramResult {
+    let accts = ctx.accounts;
+    let mut series = accts.series.load_mut()?;
+    assert(
+        series.is_initialized(),
+        DerivativeError::UninitializedAccount,
+    )?;
+    assert_keys_equal(series.market_product_group, *accts.market_product_group.key)?;
+    assert_keys_equal(series.clock, *accts.clock.key)?;
+    assert_keys_equal(dex::ID, *accts.dex_program.key)?;
+    let num_strikes = series.num_strikes as usize;
+    assert(
+        ctx.remaining_accounts.len() == 2 * num_strikes,
+        DerivativeError::InvalidAccount,
+    )?;
+    let clock: Clock = bincode::deserialize(&accts.clock.data.borrow())
+        .map_err(|_| ProgramError::InvalidArgument)?;
+
+    {
+        let loader = AccountLoader::<MarketProductGroup>::try_from(&accts.market_product_group)?;
+        let market_product_group = loader.load()?;
+        // Products that were already removed from the group have been settled
+        for product_key in series.live_products[..num_strikes]
+            .iter()
+            .filter(|key| **key != Pubkey::default())
+        {
+            if let Ok((_, product)) = market_product_group.find_product_index(product_key) {
+                assert(
+                    market_product_group.is_expired(product),
+                    DerivativeError::UnsettledAccounts,
+                )?;
+            }
+        }
+    }
+
+    let roll = series.next_roll(clock.unix_timestamp);
+    if roll > series.num_rolls {
+        msg!("Skipping {} missed expiries", roll - series.num_rolls);
+    }
+    let initialization_time = series.initialization_time(roll);
+    let funding_period = series.expiry_interval;
+    let lister_seeds: &[&[u8]] = &[
+        b"product_lister",
+        &accts.market_product_group.key.to_bytes(),
+        &[ctx.bumps["product_lister"]],
+    ];
+    let rent = Rent::get()?;
+    let space = 8 + std::mem::size_of::<DerivativeMetadata>();
+
+    for (i, accounts) in ctx.remaining_accounts.chunks(2).enumerate() {
+        let (derivative_info, orderbook) = (&accounts[0], &accounts[1]);
+        let strike = series.strikes[i];
+        let seeds: &[&[u8]] = &[
+            b"derivative",
+            &series.price_oracle.to_bytes(),
+            &series.market_product_group.to_bytes(),
+            &(series.instrument_type as u64).to_le_bytes(),
+            &strike.m.to_le_bytes(),
+            &strike.exp.to_le_bytes(),
+            &initialization_time.to_le_bytes(),
+            &funding_period.to_le_bytes(),
+            &funding_period.to_le_bytes(),
+        ];
+        let (derivative_key, bump) = Pubkey::find_program_address(seeds, ctx.program_id);
+        assert_keys_equal(derivative_key, *derivative_info.key)?;
+        let bump_seed = [bump];
+        let mut signer_seeds = seeds.to_vec();
+        signer_seeds.push(&bump_seed);
+        invoke_signed(
+            &system_instruction::create_account(
+                accts.payer.key,
+                derivative_info.key,
+                rent.minimum_balance(space).max(1),
+                space as u64,
+                ctx.program_id,
+            ),
+            &[
+                accts.payer.to_account_info(),
+                derivative_info.clone(),
+                accts.system_program.to_account_info(),
+            ],
+            &[&signer_seeds],
+        )?;
+
+        let loader = AccountLoader::<DerivativeMetadata>::try_from_unchecked(
+            ctx.program_id,
+            derivative_info,
+        )?;
+        {
+            let mut derivative_metadata = loader.load_init()?;
+            derivative_metadata.tag = AccountTag::DerivativeMetadata;
+            derivative_metadata.bump = bump as u64;
+            derivative_metadata.instrument_type = series.instrument_type;
+            derivative_metadata.strike = strike;
+            derivative_metadata.initialization_time = initialization_time;
+            derivative_metadata.full_funding_period = funding_period;
+            derivative_metadata.minimum_funding_period = funding_period;
+            derivative_metadata.close_authority = series.close_authority;
+            derivative_metadata.market_product_group = series.market_product_group;
+            derivative_metadata.price_oracle = series.price_oracle;
+            derivative_metadata.clock = series.clock;
+            derivative_metadata.oracle_type = series.oracle_type;
+            derivative_metadata.max_staleness_slots = series.max_staleness_slots;
+            derivative_metadata.max_confidence_bps = series.max_confidence_bps;
+            derivative_metadata.cap = series.cap;
+            derivative_metadata.digital_payout = series.digital_payout;
+            derivative_metadata.settlement_window = series.settlement_window;
+            derivative_metadata.min_settlement_samples = series.min_settlement_samples;
+            derivative_metadata.expired = ExpirationStatus::Active;
+            derivative_metadata.last_funding_time = initialization_time;
+            derivative_metadata.last_premium_time = initialization_time;
+        }
+        derivative_info.try_borrow_mut_data()?[..8]
+            .copy_from_slice(&DerivativeMetadata::discriminator());
+
+        dex::cpi::initialize_market_product(
+            CpiContext::new_with_signer(
+                accts.dex_program.clone(),
+                dex::cpi::accounts::InitializeMarketProduct {
+                    authority: accts.product_lister.clone(),
+                    market_product_group: accts.market_product_group.clone(),
+                    product: derivative_info.clone(),
+                    orderbook: orderbook.clone(),
+                },
+                &[lister_seeds],
+            ),
+            dex::InitializeMarketProductParams {
+                name: series.product_name(roll, i),
+                tick_size: series.tick_size,
+                base_decimals: series.base_decimals,
+                price_offset: series.price_offset,
+            },
+        )?;
+        series.live_products[i] = derivative_key;
+    }
+    series.num_rolls = roll + 1;
+    msg!(
+        "Listed expiry {} of the series, settles at {}",
+        series.num_rolls,
+        initialization_time + funding_period
+    );
+    Ok(())
+}
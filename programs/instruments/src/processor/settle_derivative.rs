@@ -3,7 +3,7 @@ use crate::{
     oracle::get_oracle_price,
     state::{
         derivative_metadata::DerivativeMetadata,
-        enums::{ExpirationStatus, InstrumentType, OracleType},
+        enums::{ExpirationStatus, InstrumentType, MarkPriceMode, OracleType},
     },
     SettleDerivative,
 };
@@ -16,7 +16,7 @@ use dex::{
         products::Product,
     },
     utils::{
-        numeric::{Fractional, ZERO_FRAC},
+        numeric::{bps, Fractional, ZERO_FRAC},
         validation::{assert, assert_keys_equal},
     },
 };
@@ -26,15 +26,20 @@ use solana_program::{
 use std::cell::Ref;
 
 pub fn process(ctx: Context<SettleDerivative>) -> ProgramResult {
-    validate(&ctx)?;
-    let accts = ctx.accounts;
-    let funding_amount = get_funding_amount(accts)?;
+    validate(ctx.accounts, ctx.program_id)?;
+    settle(ctx.accounts, ctx.remaining_accounts)
+}
+
+pub(crate) fn settle<'info>(
+    accts: &mut SettleDerivative<'info>,
+    oracle_accounts: &[AccountInfo<'info>],
+) -> ProgramResult {
+    let funding_amount = get_funding_amount(accts, oracle_accounts)?;
     msg!("About to update dex funding");
     update_product_funding_in_dex(accts, funding_amount)
 }
 
-fn validate(ctx: &Context<SettleDerivative>) -> DomainOrProgramResult {
-    let accts = &ctx.accounts;
+pub(crate) fn validate(accts: &SettleDerivative, program_id: &Pubkey) -> DomainOrProgramResult {
     let derivative_metadata = accts.derivative_metadata.load_mut()?;
     assert(
         derivative_metadata.is_initialized(),
@@ -47,15 +52,16 @@ fn validate(ctx: &Context<SettleDerivative>) -> DomainOrProgramResult {
         DerivativeError::ContractIsExpired,
     )?;
     assert_keys_equal(
-        derivative_metadata.get_key(ctx.program_id)?,
+        derivative_metadata.get_key(program_id)?,
         accts.derivative_metadata.key(),
     )?;
     assert_keys_equal(derivative_metadata.price_oracle, *accts.price_oracle.key)?;
     Ok(())
 }
 
-fn get_funding_amount(
-    accts: &mut SettleDerivative,
+fn get_funding_amount<'info>(
+    accts: &mut SettleDerivative<'info>,
+    oracle_accounts: &[AccountInfo<'info>],
 ) -> std::result::Result<Fractional, ProgramError> {
     let clock: Clock = bincode::deserialize(&accts.clock.data.borrow()).map_err(|e| {
         msg!("Failed to deserialize clock {}", e);
@@ -69,10 +75,6 @@ fn get_funding_amount(
     let loader = AccountLoader::try_from(&accts.market_product_group)?;
     let market_product_group: Ref<MarketProductGroup> = loader.load()?;
 
-    let index_price =
-        get_oracle_price(derivative_metadata.oracle_type, &accts.price_oracle, &clock)?;
-    let payoff = get_payoff(&derivative_metadata, index_price)?;
-
     assert(
         clock.unix_timestamp > derivative_metadata.initialization_time,
         ProgramError::from(DerivativeError::InvalidSettlementTime),
@@ -99,8 +101,27 @@ fn get_funding_amount(
     let funding_amount = if derivative_metadata.instrument_type.is_recurring()? {
         // Handle Everlasting Options and Perpetual Swaps
         // If mark price dips below payoff, longs get paid
-        let mark_price = get_mark_price(market_product, &clock)?;
-        let offset = payoff - mark_price;
+        let index_price = get_oracle_price(
+            &derivative_metadata,
+            &accts.price_oracle,
+            oracle_accounts,
+            &clock,
+        )?;
+        let payoff = get_payoff(&derivative_metadata, index_price)?;
+        let (mark_price, mark_price_mode) = get_mark_price(
+            market_product,
+            &clock,
+            payoff,
+            derivative_metadata.mark_price_ewma_window as usize,
+        )?;
+        derivative_metadata.mark_price_mode = mark_price_mode;
+        derivative_metadata.sample_premium(mark_price - payoff, clock.unix_timestamp)?;
+        let premium = derivative_metadata.take_average_premium()?;
+        let offset = apply_dampening_band(
+            -premium,
+            index_price,
+            derivative_metadata.funding_dampening_bps,
+        )?;
         // Compute fraction of offset you should be paying
         let num = Fractional::from(elapsed);
         let denom = Fractional::from(derivative_metadata.full_funding_period);
@@ -109,11 +130,18 @@ fn get_funding_amount(
         if pct.exp > 2 {
             pct = pct.round_sf(2);
         }
-        let res = (offset * pct).round_sf(market_product_group.decimals as u32);
+        let res = clamp_funding(
+            offset * pct,
+            index_price,
+            pct,
+            derivative_metadata.max_funding_rate_bps,
+        )?
+        .round_sf(market_product_group.decimals as u32);
         msg!(
-            "mark_price: {} index: {} offset: {} num: {} denom: {} pct: {} std::result::Result: {}",
+            "mark_price: {} index: {} premium_twap: {} offset: {} num: {} denom: {} pct: {} std::result::Result: {}",
             mark_price,
             payoff,
+            premium,
             offset,
             num,
             denom,
@@ -123,6 +151,29 @@ fn get_funding_amount(
         res
     } else {
         // Handle Vanilla Options and Futures
+        // The oracle is not read at expiry when the settlement samples are used, so a stale
+        // or wide oracle at expiry cannot block settlement
+        let payoff = if derivative_metadata.settlement_window > 0 {
+            assert(
+                derivative_metadata.has_settlement_samples(),
+                DerivativeError::InsufficientSettlementSamples,
+            )?;
+            let settlement_price = derivative_metadata.settlement_price()?;
+            msg!(
+                "settlement price: {} from {} samples",
+                settlement_price,
+                derivative_metadata.num_settlement_samples
+            );
+            get_payoff(&derivative_metadata, settlement_price)?
+        } else {
+            let index_price = get_oracle_price(
+                &derivative_metadata,
+                &accts.price_oracle,
+                oracle_accounts,
+                &clock,
+            )?;
+            get_payoff(&derivative_metadata, index_price)?
+        };
         derivative_metadata.expired = ExpirationStatus::Expired;
         msg!("payoff: {}", payoff);
         payoff.round_sf(market_product_group.decimals as u32)
@@ -160,42 +211,346 @@ fn update_product_funding_in_dex(accts: &SettleDerivative, amount: Fractional) -
     Ok(())
 }
 
-fn get_payoff(
+/// Offsets within the band around zero pay no funding, larger offsets are shrunk by the
+/// width of the band so that the funding payment stays continuous.
+fn apply_dampening_band(
+    offset: Fractional,
+    index_price: Fractional,
+    dampening_bps: u64,
+) -> std::result::Result<Fractional, DomainOrProgramError> {
+    if dampening_bps == 0 {
+        return Ok(offset);
+    }
+    let band = index_price.abs().checked_mul(bps(dampening_bps as i64))?;
+    Ok(if offset.abs() <= band {
+        ZERO_FRAC
+    } else if offset.is_negative() {
+        offset.checked_add(band)?
+    } else {
+        offset.checked_sub(band)?
+    })
+}
+
+/// Limits the funding paid over `pct` of a full funding period to `max_rate_bps` of the index
+fn clamp_funding(
+    funding: Fractional,
+    index_price: Fractional,
+    pct: Fractional,
+    max_rate_bps: u64,
+) -> std::result::Result<Fractional, DomainOrProgramError> {
+    if max_rate_bps == 0 {
+        return Ok(funding);
+    }
+    let cap = index_price
+        .abs()
+        .checked_mul(bps(max_rate_bps as i64))?
+        .checked_mul(pct)?;
+    Ok(funding.max(-cap).min(cap))
+}
+
+pub(crate) fn get_payoff(
     derivative_metadata: &DerivativeMetadata,
     index_price: Fractional,
 ) -> std::result::Result<Fractional, DomainOrProgramError> {
-    let raw_payoff = match derivative_metadata.instrument_type {
-        InstrumentType::RecurringCall | InstrumentType::ExpiringCall => {
-            index_price - derivative_metadata.strike
+    let strike = derivative_metadata.strike;
+    let call_payoff = (index_price - strike).max(ZERO_FRAC);
+    let put_payoff = (strike - index_price).max(ZERO_FRAC);
+    Ok(match derivative_metadata.instrument_type {
+        InstrumentType::RecurringCall | InstrumentType::ExpiringCall => call_payoff,
+        InstrumentType::RecurringPut | InstrumentType::ExpiringPut => put_payoff,
+        InstrumentType::RecurringBinaryCall | InstrumentType::ExpiringBinaryCall => {
+            if index_price > strike {
+                derivative_metadata.digital_payout
+            } else {
+                ZERO_FRAC
+            }
+        }
+        InstrumentType::RecurringBinaryPut | InstrumentType::ExpiringBinaryPut => {
+            if index_price < strike {
+                derivative_metadata.digital_payout
+            } else {
+                ZERO_FRAC
+            }
         }
-        InstrumentType::RecurringPut | InstrumentType::ExpiringPut => {
-            derivative_metadata.strike - index_price
+        InstrumentType::RecurringCappedCall | InstrumentType::ExpiringCappedCall => {
+            call_payoff.min(derivative_metadata.cap)
         }
-        _ => {
+        InstrumentType::RecurringCappedPut | InstrumentType::ExpiringCappedPut => {
+            put_payoff.min(derivative_metadata.cap)
+        }
+        InstrumentType::LinearPerp | InstrumentType::LinearFuture => index_price - strike,
+        InstrumentType::Uninitialized => {
             return Err(UtilError::AccountUninitialized.into());
         }
-    };
-    Ok(if raw_payoff.is_negative() {
-        ZERO_FRAC
-    } else {
-        raw_payoff
     })
 }
 
-fn get_mark_price(
+/// Returns the mark price and where it came from. Falls back from the book midpoint to the
+/// EWMA midpoint, then to the one quoted side clamped to `index`, and finally to `index`.
+pub(crate) fn get_mark_price(
     market_product: &Product,
     clock: &Clock,
-) -> std::result::Result<Fractional, DomainOrProgramError> {
-    // TODO: This calculation can improved to be more robust
+    index: Fractional,
+    ewma_window: usize,
+) -> std::result::Result<(Fractional, MarkPriceMode), DomainOrProgramError> {
     let best_bid = market_product.get_prev_best_bid(clock.slot);
     let best_ask = market_product.get_prev_best_ask(clock.slot);
-    if best_ask == NO_ASK_PRICE || best_bid == NO_BID_PRICE {
-        msg!("best_bid: {}", best_bid);
-        msg!("best_ask: {}", best_ask);
-        msg!("Bid or ask is empty");
-        return Err(ProgramError::InvalidAccountData.into());
-    }
-    (best_bid + best_ask)
-        .checked_div(Fractional::new(2, 0))
-        .map_err(DomainOrProgramError::from)
+    msg!("best_bid: {}", best_bid);
+    msg!("best_ask: {}", best_ask);
+    let prices = &market_product.prices;
+    let ewma_bid = prices.ewma_bid[ewma_window];
+    let ewma_ask = prices.ewma_ask[ewma_window];
+    let (mark_price, mode) = if best_bid != NO_BID_PRICE && best_ask != NO_ASK_PRICE {
+        (
+            (best_bid + best_ask).checked_div(Fractional::new(2, 0))?,
+            MarkPriceMode::BookMidpoint,
+        )
+    } else if ewma_bid != NO_BID_PRICE && ewma_ask != NO_ASK_PRICE {
+        (
+            (ewma_bid + ewma_ask).checked_div(Fractional::new(2, 0))?,
+            MarkPriceMode::EwmaMidpoint,
+        )
+    } else if best_bid != NO_BID_PRICE {
+        // Resting bids mean the contract is worth at least the bid
+        (best_bid.max(index), MarkPriceMode::OneSidedBook)
+    } else if best_ask != NO_ASK_PRICE {
+        (best_ask.min(index), MarkPriceMode::OneSidedBook)
+    } else {
+        (index, MarkPriceMode::IndexPrice)
+    };
+    msg!("mark price mode: {:?}", mode);
+    Ok((mark_price, mode))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn payoffs(instrument_type: InstrumentType, index_prices: &[i64]) -> Vec<Fractional> {
+        let mut derivative_metadata = DerivativeMetadata::zeroed();
+        derivative_metadata.instrument_type = instrument_type;
+        derivative_metadata.strike = Fractional::new(100, 0);
+        derivative_metadata.cap = Fractional::new(20, 0);
+        derivative_metadata.digital_payout = Fractional::new(1, 0);
+        index_prices
+            .iter()
+            .map(|p| get_payoff(&derivative_metadata, Fractional::from(*p)).unwrap())
+            .collect()
+    }
+
+    fn fracs(values: &[i64]) -> Vec<Fractional> {
+        values.iter().map(|v| Fractional::from(*v)).collect()
+    }
+
+    #[test]
+    fn test_binary_payoff() {
+        // nothing is paid at the strike
+        for instrument_type in [
+            InstrumentType::RecurringBinaryCall,
+            InstrumentType::ExpiringBinaryCall,
+        ] {
+            assert_eq!(payoffs(instrument_type, &[99, 100, 101]), fracs(&[0, 0, 1]));
+        }
+        for instrument_type in [
+            InstrumentType::RecurringBinaryPut,
+            InstrumentType::ExpiringBinaryPut,
+        ] {
+            assert_eq!(payoffs(instrument_type, &[99, 100, 101]), fracs(&[1, 0, 0]));
+        }
+    }
+
+    #[test]
+    fn test_capped_payoff() {
+        for instrument_type in [
+            InstrumentType::RecurringCappedCall,
+            InstrumentType::ExpiringCappedCall,
+        ] {
+            assert_eq!(
+                payoffs(instrument_type, &[90, 100, 110, 119, 120, 121, 200]),
+                fracs(&[0, 0, 10, 19, 20, 20, 20])
+            );
+        }
+        for instrument_type in [
+            InstrumentType::RecurringCappedPut,
+            InstrumentType::ExpiringCappedPut,
+        ] {
+            assert_eq!(
+                payoffs(instrument_type, &[110, 100, 90, 81, 80, 79, 0]),
+                fracs(&[0, 0, 10, 19, 20, 20, 20])
+            );
+        }
+    }
+
+    #[test]
+    fn test_linear_payoff() {
+        // linear instruments pay the full move in both directions, including below zero
+        for instrument_type in [InstrumentType::LinearPerp, InstrumentType::LinearFuture] {
+            assert_eq!(
+                payoffs(instrument_type, &[-20, 0, 99, 100, 101]),
+                fracs(&[-120, -100, -1, 0, 1])
+            );
+        }
+        let derivative_metadata = DerivativeMetadata::zeroed();
+        assert!(get_payoff(&derivative_metadata, Fractional::from(100)).is_err());
+    }
+
+    #[test]
+    fn test_dampening_band() {
+        let index_price = Fractional::new(100, 0);
+        // a band of 0.5 around zero
+        assert_eq!(
+            apply_dampening_band(Fractional::new(3, 1), index_price, 50).unwrap(),
+            ZERO_FRAC
+        );
+        assert_eq!(
+            apply_dampening_band(Fractional::new(-5, 1), index_price, 50).unwrap(),
+            ZERO_FRAC
+        );
+        // only the part outside of the band is paid
+        assert_eq!(
+            apply_dampening_band(Fractional::new(2, 0), index_price, 50).unwrap(),
+            Fractional::new(15, 1)
+        );
+        assert_eq!(
+            apply_dampening_band(Fractional::new(-2, 0), Fractional::new(-100, 0), 50).unwrap(),
+            Fractional::new(-15, 1)
+        );
+        assert_eq!(
+            apply_dampening_band(Fractional::new(3, 1), index_price, 0).unwrap(),
+            Fractional::new(3, 1)
+        );
+    }
+
+    #[test]
+    fn test_funding_rate_cap() {
+        let index_price = Fractional::new(100, 0);
+        let half_period = Fractional::new(5, 1);
+        // 1% of the index per full period is 0.5 over half of one
+        assert_eq!(
+            clamp_funding(Fractional::new(2, 0), index_price, half_period, 100).unwrap(),
+            Fractional::new(5, 1)
+        );
+        assert_eq!(
+            clamp_funding(Fractional::new(-2, 0), index_price, half_period, 100).unwrap(),
+            Fractional::new(-5, 1)
+        );
+        assert_eq!(
+            clamp_funding(Fractional::new(2, 1), index_price, half_period, 100).unwrap(),
+            Fractional::new(2, 1)
+        );
+        assert_eq!(
+            clamp_funding(
+                Fractional::new(2, 0),
+                Fractional::new(-100, 0),
+                1.into(),
+                100
+            )
+            .unwrap(),
+            Fractional::new(1, 0)
+        );
+        assert_eq!(
+            clamp_funding(Fractional::new(2, 0), index_price, half_period, 0).unwrap(),
+            Fractional::new(2, 0)
+        );
+    }
+
+    fn product_with_prices(
+        bid: Fractional,
+        ask: Fractional,
+        ewma_bid: Fractional,
+        ewma_ask: Fractional,
+    ) -> Product {
+        let mut product = Product::default();
+        product.prices.initialize(5);
+        product.prices.bid = bid;
+        product.prices.ask = ask;
+        product.prices.ewma_bid[1] = ewma_bid;
+        product.prices.ewma_ask[1] = ewma_ask;
+        product
+    }
+
+    fn mark_price(product: &Product) -> (Fractional, MarkPriceMode) {
+        let clock = Clock {
+            slot: 10,
+            ..Clock::default()
+        };
+        get_mark_price(product, &clock, Fractional::new(100, 0), 1).unwrap()
+    }
+
+    #[test]
+    fn test_book_midpoint_mark_price() {
+        let product = product_with_prices(
+            Fractional::new(98, 0),
+            Fractional::new(104, 0),
+            Fractional::new(90, 0),
+            Fractional::new(92, 0),
+        );
+        assert_eq!(
+            mark_price(&product),
+            (Fractional::new(101, 0), MarkPriceMode::BookMidpoint)
+        );
+        // prices of the current slot are not used until the next one
+        let clock = Clock {
+            slot: 5,
+            ..Clock::default()
+        };
+        assert_eq!(
+            get_mark_price(&product, &clock, Fractional::new(100, 0), 1).unwrap(),
+            (Fractional::new(91, 0), MarkPriceMode::EwmaMidpoint)
+        );
+    }
+
+    #[test]
+    fn test_ewma_mark_price() {
+        let product = product_with_prices(
+            Fractional::new(98, 0),
+            NO_ASK_PRICE,
+            Fractional::new(90, 0),
+            Fractional::new(92, 0),
+        );
+        assert_eq!(
+            mark_price(&product),
+            (Fractional::new(91, 0), MarkPriceMode::EwmaMidpoint)
+        );
+    }
+
+    #[test]
+    fn test_one_sided_mark_price() {
+        // a bid is a floor and an ask a ceiling on the index
+        for (bid, ask, expected) in [
+            (105, None, 105),
+            (95, None, 100),
+            (0, Some(95), 95),
+            (0, Some(105), 100),
+        ] {
+            let product = match ask {
+                None => product_with_prices(
+                    Fractional::new(bid, 0),
+                    NO_ASK_PRICE,
+                    NO_BID_PRICE,
+                    Fractional::new(92, 0),
+                ),
+                Some(ask) => product_with_prices(
+                    NO_BID_PRICE,
+                    Fractional::new(ask, 0),
+                    Fractional::new(90, 0),
+                    NO_ASK_PRICE,
+                ),
+            };
+            assert_eq!(
+                mark_price(&product),
+                (Fractional::new(expected, 0), MarkPriceMode::OneSidedBook)
+            );
+        }
+    }
+
+    #[test]
+    fn test_index_mark_price() {
+        let product = product_with_prices(NO_BID_PRICE, NO_ASK_PRICE, NO_BID_PRICE, NO_ASK_PRICE);
+        assert_eq!(
+            mark_price(&product),
+            (Fractional::new(100, 0), MarkPriceMode::IndexPrice)
+        );
+    }
 }
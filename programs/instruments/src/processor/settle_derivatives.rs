@@ -0,0 +1,130 @@
+use crate::{
+    error::DerivativeError,
+    processor::settle_derivative::{settle, validate},
+    state::{derivative_metadata::DerivativeMetadata, enums::AccountTag},
+    SettleDerivative, SettleDerivatives, SettleDerivativesParams, SettlementOutcome,
+};
+use anchor_lang::prelude::*;
+use dex::utils::validation::assert;
+use solana_program::{
+    clock::UnixTimestamp, entrypoint::ProgramResult, program::set_return_data,
+    program_error::ProgramError, sysvar::clock::Clock,
+};
+
+/// Settles every derivative passed in that is due, skipping the ones that are not.
+/// The outcome of each derivative is returned in order as return data.
+pub fn process<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleDerivatives<'info>>,
+    params: SettleDerivativesParams,
+) -> ProgramResult {
+    let accts = &ctx.accounts;
+    let num_derivative_accounts = 2 * params.num_derivatives as usize;
+    assert(
+        ctx.remaining_accounts.len() >= num_derivative_accounts,
+        DerivativeError::InvalidAccount,
+    )?;
+    let (derivative_accounts, oracle_accounts) =
+        ctx.remaining_accounts.split_at(num_derivative_accounts);
+    let clock: Clock = bincode::deserialize(&accts.clock.data.borrow()).map_err(|e| {
+        msg!("Failed to deserialize clock {}", e);
+        ProgramError::InvalidArgument
+    })?;
+
+    let mut outcomes = Vec::with_capacity(params.num_derivatives as usize);
+    for pair in derivative_accounts.chunks(2) {
+        let mut settle_accts = SettleDerivative {
+            market_product_group: accts.market_product_group.clone(),
+            derivative_metadata: AccountLoader::try_from(&pair[0])?,
+            price_oracle: pair[1].clone(),
+            dex_program: accts.dex_program.clone(),
+            clock: accts.clock.clone(),
+        };
+        let outcome = get_outcome(
+            &*settle_accts.derivative_metadata.load()?,
+            clock.unix_timestamp,
+        )?;
+        if outcome == SettlementOutcome::Settled {
+            validate(&settle_accts, ctx.program_id)?;
+            settle(&mut settle_accts, oracle_accounts)?;
+        } else {
+            msg!("Skipping {}: {:?}", pair[0].key, outcome);
+        }
+        outcomes.push(outcome);
+    }
+    set_return_data(&outcomes.try_to_vec()?);
+    Ok(())
+}
+
+fn get_outcome(
+    derivative_metadata: &DerivativeMetadata,
+    now: UnixTimestamp,
+) -> std::result::Result<SettlementOutcome, ProgramError> {
+    assert(
+        derivative_metadata.tag == AccountTag::DerivativeMetadata,
+        DerivativeError::UninitializedAccount,
+    )?;
+    if derivative_metadata.expired() {
+        return Ok(SettlementOutcome::Expired);
+    }
+    // settle_derivative refuses to settle before initialization no matter the funding period
+    if now <= derivative_metadata.initialization_time {
+        return Ok(SettlementOutcome::NotDue);
+    }
+    if now.saturating_sub(derivative_metadata.last_funding_time)
+        < derivative_metadata.minimum_funding_period
+    {
+        return Ok(SettlementOutcome::NotDue);
+    }
+    if !derivative_metadata.instrument_type.is_recurring()?
+        && derivative_metadata.settlement_window > 0
+        && !derivative_metadata.has_settlement_samples()
+    {
+        return Ok(SettlementOutcome::AwaitingSettlementSamples);
+    }
+    Ok(SettlementOutcome::Settled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::enums::InstrumentType;
+    use bytemuck::Zeroable;
+
+    fn derivative_metadata() -> DerivativeMetadata {
+        let mut derivative_metadata = DerivativeMetadata::zeroed();
+        derivative_metadata.tag = AccountTag::DerivativeMetadata;
+        derivative_metadata.instrument_type = InstrumentType::RecurringCall;
+        derivative_metadata.initialization_time = 1000;
+        derivative_metadata.last_funding_time = 1000;
+        derivative_metadata
+    }
+
+    #[test]
+    fn test_outcome_before_initialization() {
+        let derivative_metadata = derivative_metadata();
+        for now in [0, 999, 1000] {
+            assert_eq!(
+                get_outcome(&derivative_metadata, now).unwrap(),
+                SettlementOutcome::NotDue
+            );
+        }
+        assert_eq!(
+            get_outcome(&derivative_metadata, 1001).unwrap(),
+            SettlementOutcome::Settled
+        );
+    }
+
+    #[test]
+    fn test_outcome_funding_period() {
+        let mut derivative_metadata = derivative_metadata();
+        derivative_metadata.minimum_funding_period = 100;
+        assert_eq!(
+            get_outcome(&derivative_metadata, 1099).unwrap(),
+            SettlementOutcome::NotDue
+        );
+        assert_eq!(
+            get_outcome(&derivative_metadata, 1100).unwrap(),
+            SettlementOutcome::Settled
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use crate::{
+    error::DerivativeError,
+    oracle::get_price,
+    state::{
+        constants::SECONDS_PER_YEAR,
+        enums::{ExpirationStatus, FixedIncomeType, OracleType},
+        fixed_income_metadata::FixedIncomeMetadata,
+    },
+    SettleFixedIncome,
+};
+use anchor_lang::prelude::*;
+use dex::{
+    error::DomainOrProgramResult,
+    state::market_product_group::MarketProductGroup,
+    utils::{
+        numeric::{Fractional, ZERO_FRAC},
+        validation::{assert, assert_keys_equal},
+    },
+};
+use solana_program::{
+    clock::UnixTimestamp, entrypoint::ProgramResult, program_error::ProgramError, sysvar,
+    sysvar::clock::Clock,
+};
+use std::cell::Ref;
+
+pub fn process(ctx: Context<SettleFixedIncome>) -> ProgramResult {
+    validate(&ctx)?;
+    let accts = ctx.accounts;
+    let funding_amount = get_funding_amount(accts, ctx.remaining_accounts)?;
+    update_product_funding_in_dex(accts, funding_amount)
+}
+
+fn validate(ctx: &Context<SettleFixedIncome>) -> DomainOrProgramResult {
+    let accts = &ctx.accounts;
+    let fixed_income_metadata = accts.fixed_income_metadata.load()?;
+    assert(
+        fixed_income_metadata.is_initialized(),
+        DerivativeError::UninitializedAccount,
+    )?;
+    assert(
+        !fixed_income_metadata.expired(),
+        DerivativeError::ContractIsExpired,
+    )?;
+    assert_keys_equal(fixed_income_metadata.clock, *accts.clock.key)?;
+    assert_keys_equal(
+        fixed_income_metadata.get_key(ctx.program_id)?,
+        accts.fixed_income_metadata.key(),
+    )?;
+    assert_keys_equal(fixed_income_metadata.rate_oracle, *accts.rate_oracle.key)?;
+    match fixed_income_metadata.oracle_type {
+        OracleType::Pyth => assert_keys_equal(accts.clock.key(), sysvar::clock::ID)?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn get_funding_amount<'info>(
+    accts: &mut SettleFixedIncome<'info>,
+    oracle_accounts: &[AccountInfo<'info>],
+) -> std::result::Result<Fractional, ProgramError> {
+    let clock: Clock = bincode::deserialize(&accts.clock.data.borrow()).map_err(|e| {
+        msg!("Failed to deserialize clock {}", e);
+        ProgramError::InvalidArgument
+    })?;
+    let mut fixed_income_metadata = accts.fixed_income_metadata.load_mut()?;
+    let loader = AccountLoader::try_from(&accts.market_product_group)?;
+    let market_product_group: Ref<MarketProductGroup> = loader.load()?;
+    let rate = get_price(
+        fixed_income_metadata.oracle_type,
+        &accts.rate_oracle,
+        oracle_accounts,
+        &clock,
+        fixed_income_metadata.max_staleness_slots,
+        fixed_income_metadata.max_confidence_bps,
+    )?;
+    let amount = get_settlement_amount(&mut fixed_income_metadata, rate, clock.unix_timestamp)?;
+    Ok(amount.round_sf(market_product_group.decimals as u32))
+}
+
+/// Bond futures pay the discounted notional once at maturity. Swaps pay the coupon accrued
+/// since the last payment, so a late settlement covers all of the missed periods.
+fn get_settlement_amount(
+    fixed_income_metadata: &mut FixedIncomeMetadata,
+    rate: Fractional,
+    now: UnixTimestamp,
+) -> std::result::Result<Fractional, ProgramError> {
+    let year = Fractional::from(SECONDS_PER_YEAR);
+    let notional = fixed_income_metadata.notional;
+
+    let amount = match fixed_income_metadata.fixed_income_type {
+        FixedIncomeType::ZeroCouponBondFuture => {
+            assert(
+                now >= fixed_income_metadata.maturity_time,
+                DerivativeError::InvalidSettlementTime,
+            )?;
+            // Discount the notional at the oracle rate over the tenor of the bond
+            let year_fraction = Fractional::from(fixed_income_metadata.tenor).checked_div(year)?;
+            let discount = rate.checked_mul(year_fraction)?;
+            let price = notional
+                .checked_mul(Fractional::new(1, 0).checked_sub(discount)?)
+                .map_err(ProgramError::from)?
+                .max(ZERO_FRAC);
+            fixed_income_metadata.expired = ExpirationStatus::Expired;
+            msg!("rate: {} bond price: {}", rate, price);
+            price
+        }
+        FixedIncomeType::InterestRateSwap => {
+            let next_coupon_time =
+                fixed_income_metadata.last_coupon_time + fixed_income_metadata.coupon_period;
+            // The final payment only covers the stub period up to maturity
+            let accrual_end = now.min(fixed_income_metadata.maturity_time);
+            if now < next_coupon_time.min(fixed_income_metadata.maturity_time) {
+                msg!(
+                    "Next coupon is at {}, current time: {}",
+                    next_coupon_time,
+                    now
+                );
+                return Err(DerivativeError::InvalidSettlementTime.into());
+            }
+            let elapsed = accrual_end - fixed_income_metadata.last_coupon_time;
+            // The floating rate is fixed in arrears at the time of payment
+            let year_fraction = Fractional::from(elapsed).checked_div(year)?;
+            let coupon = notional
+                .checked_mul(rate.checked_sub(fixed_income_metadata.fixed_rate)?)
+                .and_then(|c| c.checked_mul(year_fraction))
+                .map_err(ProgramError::from)?;
+            fixed_income_metadata.last_coupon_time = accrual_end;
+            if accrual_end >= fixed_income_metadata.maturity_time {
+                fixed_income_metadata.expired = ExpirationStatus::Expired;
+            }
+            msg!(
+                "floating rate: {} fixed rate: {} elapsed: {} coupon: {}",
+                rate,
+                fixed_income_metadata.fixed_rate,
+                elapsed,
+                coupon
+            );
+            coupon
+        }
+        FixedIncomeType::Uninitialized => {
+            return Err(DerivativeError::UninitializedAccount.into());
+        }
+    };
+    Ok(amount)
+}
+
+fn update_product_funding_in_dex(accts: &SettleFixedIncome, amount: Fractional) -> ProgramResult {
+    let fixed_income_metadata = accts.fixed_income_metadata.load()?;
+    let seeds: &[&[u8]] = &[
+        b"fixed_income",
+        &accts.rate_oracle.key.to_bytes(),
+        &accts.market_product_group.key.to_bytes(),
+        &(fixed_income_metadata.fixed_income_type as u64).to_le_bytes(),
+        &fixed_income_metadata.notional.m.to_le_bytes(),
+        &fixed_income_metadata.notional.exp.to_le_bytes(),
+        &fixed_income_metadata.fixed_rate.m.to_le_bytes(),
+        &fixed_income_metadata.fixed_rate.exp.to_le_bytes(),
+        &fixed_income_metadata.tenor.to_le_bytes(),
+        &fixed_income_metadata.initialization_time.to_le_bytes(),
+        &fixed_income_metadata.maturity_time.to_le_bytes(),
+        &fixed_income_metadata.coupon_period.to_le_bytes(),
+        &[fixed_income_metadata.bump as u8],
+    ];
+    let expired = fixed_income_metadata.expired();
+    let cpi_program = accts.dex_program.clone();
+    let cpi_accounts = dex::cpi::accounts::UpdateProductFunding {
+        market_product_group: accts.market_product_group.clone(),
+        product: accts.fixed_income_metadata.to_account_info(),
+    };
+    dex::cpi::update_product_funding(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, &[seeds]),
+        dex::UpdateProductFundingParams { amount, expired },
+    )?;
+    msg!("Updated funding");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    const QUARTER: UnixTimestamp = SECONDS_PER_YEAR / 4;
+
+    fn interest_rate_swap() -> FixedIncomeMetadata {
+        let mut fixed_income_metadata = FixedIncomeMetadata::zeroed();
+        fixed_income_metadata.fixed_income_type = FixedIncomeType::InterestRateSwap;
+        fixed_income_metadata.notional = Fractional::new(1_000_000, 0);
+        fixed_income_metadata.fixed_rate = Fractional::new(5, 2);
+        fixed_income_metadata.initialization_time = 1_000;
+        fixed_income_metadata.last_coupon_time = 1_000;
+        fixed_income_metadata.maturity_time = 1_000 + SECONDS_PER_YEAR;
+        fixed_income_metadata.coupon_period = QUARTER;
+        fixed_income_metadata
+    }
+
+    #[test]
+    fn test_coupon_accrual() {
+        let mut swap = interest_rate_swap();
+        let rate = Fractional::new(7, 2);
+        assert!(get_settlement_amount(&mut swap, rate, 1_000 + QUARTER - 1).is_err());
+        // 2% over the fixed rate on 1M for a quarter of a year
+        assert_eq!(
+            get_settlement_amount(&mut swap, rate, 1_000 + QUARTER).unwrap(),
+            Fractional::new(5_000, 0)
+        );
+        assert_eq!(swap.last_coupon_time, 1_000 + QUARTER);
+        assert!(!swap.expired());
+        // the fixed leg pays when the floating rate is below the fixed rate
+        assert_eq!(
+            get_settlement_amount(&mut swap, Fractional::new(3, 2), 1_000 + 2 * QUARTER).unwrap(),
+            Fractional::new(-5_000, 0)
+        );
+    }
+
+    #[test]
+    fn test_late_coupon_settlement() {
+        let mut swap = interest_rate_swap();
+        let rate = Fractional::new(7, 2);
+        // a missed payment is accrued into the next one
+        assert_eq!(
+            get_settlement_amount(&mut swap, rate, 1_000 + 2 * QUARTER).unwrap(),
+            Fractional::new(10_000, 0)
+        );
+        assert_eq!(swap.last_coupon_time, 1_000 + 2 * QUARTER);
+        assert!(!swap.expired());
+
+        // the final payment stops accruing at maturity
+        swap.last_coupon_time = 1_000 + 3 * QUARTER;
+        let maturity_time = swap.maturity_time;
+        assert_eq!(
+            get_settlement_amount(&mut swap, rate, maturity_time + 1_000).unwrap(),
+            Fractional::new(5_000, 0)
+        );
+        assert_eq!(swap.last_coupon_time, swap.maturity_time);
+        assert!(swap.expired());
+    }
+
+    #[test]
+    fn test_bond_future_expiry() {
+        let mut bond = FixedIncomeMetadata::zeroed();
+        bond.fixed_income_type = FixedIncomeType::ZeroCouponBondFuture;
+        bond.notional = Fractional::new(100, 0);
+        bond.tenor = SECONDS_PER_YEAR / 2;
+        bond.maturity_time = 1_000;
+        let rate = Fractional::new(1, 1);
+        assert!(get_settlement_amount(&mut bond, rate, 999).is_err());
+        assert!(!bond.expired());
+        assert_eq!(
+            get_settlement_amount(&mut bond, rate, 1_000).unwrap(),
+            Fractional::new(95, 0)
+        );
+        assert!(bond.expired());
+        // the price of the bond is floored at zero
+        bond.expired = ExpirationStatus::Active;
+        assert_eq!(
+            get_settlement_amount(&mut bond, Fractional::new(3, 0), 1_000).unwrap(),
+            ZERO_FRAC
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    error::DerivativeError,
+    oracle::get_oracle_price,
+    processor::settle_derivative::{get_mark_price, get_payoff},
+    state::enums::OracleType,
+    UpdatePremiumIndex,
+};
+use anchor_lang::prelude::*;
+use dex::{
+    state::market_product_group::MarketProductGroup,
+    utils::{
+        numeric::Fractional,
+        validation::{assert, assert_keys_equal},
+    },
+};
+use solana_program::{
+    entrypoint::ProgramResult, program_error::ProgramError, sysvar, sysvar::clock::Clock,
+};
+use std::cell::Ref;
+
+/// Samples the premium of the mark price over the index so that funding can be paid on
+/// the time weighted premium, and pushes the index to the dex. Anyone can call this between
+/// settlements.
+pub fn process(ctx: Context<UpdatePremiumIndex>) -> ProgramResult {
+    let accts = &ctx.accounts;
+    let mut derivative_metadata = accts.derivative_metadata.load_mut()?;
+    assert(
+        derivative_metadata.is_initialized(),
+        DerivativeError::UninitializedAccount,
+    )?;
+    assert(
+        derivative_metadata.instrument_type.is_recurring()?,
+        DerivativeError::InvalidAccount,
+    )?;
+    assert_keys_equal(derivative_metadata.clock, *accts.clock.key)?;
+    assert_keys_equal(derivative_metadata.price_oracle, *accts.price_oracle.key)?;
+    assert_keys_equal(
+        derivative_metadata.market_product_group,
+        *accts.market_product_group.key,
+    )?;
+    match derivative_metadata.oracle_type {
+        OracleType::Pyth => assert_keys_equal(accts.clock.key(), sysvar::clock::ID)?,
+        _ => {}
+    }
+    let clock: Clock = bincode::deserialize(&accts.clock.data.borrow()).map_err(|e| {
+        msg!("Failed to deserialize clock {}", e);
+        ProgramError::InvalidArgument
+    })?;
+    let payoff = {
+        let loader = AccountLoader::try_from(&accts.market_product_group)?;
+        let market_product_group: Ref<MarketProductGroup> = loader.load()?;
+        let (_, market_product) =
+            market_product_group.find_product_index(&accts.derivative_metadata.key())?;
+
+        let index_price = get_oracle_price(
+            &derivative_metadata,
+            &accts.price_oracle,
+            ctx.remaining_accounts,
+            &clock,
+        )?;
+        let payoff = get_payoff(&derivative_metadata, index_price)?;
+        let (mark_price, mark_price_mode) = get_mark_price(
+            market_product,
+            &clock,
+            payoff,
+            derivative_metadata.mark_price_ewma_window as usize,
+        )?;
+        derivative_metadata.mark_price_mode = mark_price_mode;
+        derivative_metadata.sample_premium(mark_price - payoff, clock.unix_timestamp)?;
+        msg!(
+            "premium: {} accumulated over {}s",
+            derivative_metadata.last_premium,
+            derivative_metadata.premium_index_time
+        );
+        payoff
+    };
+    drop(derivative_metadata);
+    update_index_price_in_dex(accts, payoff)
+}
+
+/// Keeps the dex's copy of the index fresh, price bands fall back to it on one sided books
+fn update_index_price_in_dex(accts: &UpdatePremiumIndex, price: Fractional) -> ProgramResult {
+    let derivative_metadata = accts.derivative_metadata.load()?;
+    let seeds: &[&[u8]] = &[
+        b"derivative",
+        &accts.price_oracle.key.to_bytes(),
+        &accts.market_product_group.key.to_bytes(),
+        &(derivative_metadata.instrument_type as u64).to_le_bytes(),
+        &derivative_metadata.strike.m.to_le_bytes(),
+        &derivative_metadata.strike.exp.to_le_bytes(),
+        &derivative_metadata.initialization_time.to_le_bytes(),
+        &derivative_metadata.full_funding_period.to_le_bytes(),
+        &derivative_metadata.minimum_funding_period.to_le_bytes(),
+        &[derivative_metadata.bump as u8],
+    ];
+    let cpi_accounts = dex::cpi::accounts::UpdateIndexPrice {
+        market_product_group: accts.market_product_group.clone(),
+        product: accts.derivative_metadata.to_account_info(),
+    };
+    dex::cpi::update_index_price(
+        CpiContext::new_with_signer(accts.dex_program.clone(), cpi_accounts, &[seeds]),
+        dex::UpdateIndexPriceParams { price },
+    )
+}
@@ -0,0 +1,31 @@
+use crate::state::{
+    constants::MAX_COMPOSITE_ORACLES,
+    enums::{AccountTag, OracleType},
+};
+use anchor_lang::prelude::*;
+
+/// Price source that reports the median of several underlying oracles.
+/// A derivative using it stores the key of this account as its `price_oracle`.
+#[account(zero_copy)]
+pub struct CompositeOracle {
+    pub tag: AccountTag,
+    pub num_oracles: u64,
+    // Settlement fails if fewer than this many oracles report a valid price
+    pub min_valid_oracles: u64,
+    pub oracle_types: [OracleType; MAX_COMPOSITE_ORACLES],
+    pub oracles: [Pubkey; MAX_COMPOSITE_ORACLES],
+}
+
+impl CompositeOracle {
+    pub fn is_initialized(&self) -> bool {
+        self.tag == AccountTag::CompositeOracle
+    }
+
+    pub fn oracles(&self) -> impl Iterator<Item = (OracleType, Pubkey)> + '_ {
+        self.oracle_types
+            .iter()
+            .copied()
+            .zip(self.oracles.iter().copied())
+            .take(self.num_oracles as usize)
+    }
+}
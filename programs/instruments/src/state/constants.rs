@@ -1 +1,11 @@
 pub const MAX_DATES: usize = 32;
+// Number of EWMA windows tracked per product by the dex
+pub const NUM_EWMA_WINDOWS: usize = 4;
+pub const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+pub const MAX_COMPOSITE_ORACLES: usize = 8;
+// Each strike needs two accounts in roll_series
+pub const MAX_SERIES_STRIKES: usize = 8;
+// Oracle prices with more decimals than this are truncated
+pub const MAX_ORACLE_DECIMALS: u32 = 12;
+// Expiring instruments that settle on a settlement window need at least this many samples
+pub const MIN_SETTLEMENT_SAMPLES: u64 = 3;
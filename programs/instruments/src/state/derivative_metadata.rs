@@ -1,6 +1,12 @@
-use crate::state::enums::{AccountTag, ExpirationStatus, InstrumentType, OracleType};
+use crate::state::{
+    constants::MIN_SETTLEMENT_SAMPLES,
+    enums::{AccountTag, ExpirationStatus, InstrumentType, MarkPriceMode, OracleType},
+};
 use anchor_lang::prelude::*;
-use dex::utils::numeric::Fractional;
+use dex::{
+    error::{DomainOrProgramError, UtilError},
+    utils::numeric::{Fractional, ZERO_FRAC},
+};
 use solana_program::{clock::UnixTimestamp, program_error::ProgramError, pubkey::Pubkey};
 
 #[account(zero_copy)]
@@ -19,6 +25,37 @@ pub struct DerivativeMetadata {
     pub close_authority: Pubkey,
     pub clock: Pubkey,
     pub last_funding_time: UnixTimestamp,
+    // Oracle guards, a staleness of 0 disables the check and a confidence of 0 uses
+    // DEFAULT_MAX_CONFIDENCE_BPS
+    pub max_staleness_slots: u64,
+    pub max_confidence_bps: u64,
+    // Funding limits for recurring instruments as a fraction of the index price, 0 disables
+    pub max_funding_rate_bps: u64,
+    pub funding_dampening_bps: u64,
+    // Time weighted sum of (mark - index) since the last funding payment
+    pub premium_index_sum: Fractional,
+    pub premium_index_time: UnixTimestamp,
+    pub last_premium: Fractional,
+    // Time of the last premium sample, starts at the initialization time
+    pub last_premium_time: UnixTimestamp,
+    // Maximum payoff of capped instruments
+    pub cap: Fractional,
+    // Amount paid by binary instruments when in the money
+    pub digital_payout: Fractional,
+    // Index into the market product group's EWMA windows used when the book is one-sided
+    pub mark_price_ewma_window: u64,
+    // Source of the mark price at the last funding settlement or premium sample
+    pub mark_price_mode: MarkPriceMode,
+    // Expiring instruments settle on the time weighted index over this many seconds
+    // before expiry, 0 settles on a single oracle read
+    pub settlement_window: UnixTimestamp,
+    pub min_settlement_samples: u64,
+    pub num_settlement_samples: u64,
+    // Time weighted sum of the index price samples in the settlement window
+    pub settlement_price_sum: Fractional,
+    pub settlement_sample_time: UnixTimestamp,
+    pub last_settlement_sample: Fractional,
+    pub last_settlement_sample_time: UnixTimestamp,
 }
 
 impl DerivativeMetadata {
@@ -45,4 +82,191 @@ impl DerivativeMetadata {
     pub fn expired(&self) -> bool {
         self.expired == ExpirationStatus::Expired
     }
+
+    /// Adds a premium sample to the running sum, weighted by the time since the previous
+    /// sample or funding settlement. A sample taken at settlement therefore covers the part
+    /// of the period that was not sampled, instead of an older sample being held over it.
+    pub fn sample_premium(
+        &mut self,
+        premium: Fractional,
+        now: UnixTimestamp,
+    ) -> std::result::Result<(), DomainOrProgramError> {
+        if now > self.last_premium_time {
+            let elapsed = now - self.last_premium_time;
+            self.premium_index_sum = self
+                .premium_index_sum
+                .checked_add(premium.checked_mul(Fractional::from(elapsed))?)?;
+            self.premium_index_time += elapsed;
+            self.last_premium_time = now;
+        }
+        self.last_premium = premium;
+        Ok(())
+    }
+
+    /// Expiry of an expiring instrument
+    pub fn expiry_time(&self) -> UnixTimestamp {
+        self.initialization_time + self.minimum_funding_period
+    }
+
+    pub fn in_settlement_window(&self, now: UnixTimestamp) -> bool {
+        let expiry = self.expiry_time();
+        now >= expiry - self.settlement_window && now < expiry
+    }
+
+    /// Records an index price sample in the settlement window. Samples taken at the same
+    /// time replace each other.
+    pub fn record_settlement_sample(
+        &mut self,
+        index_price: Fractional,
+        now: UnixTimestamp,
+    ) -> std::result::Result<(), DomainOrProgramError> {
+        if now != self.last_settlement_sample_time {
+            accumulate_sample(
+                &mut self.settlement_price_sum,
+                &mut self.settlement_sample_time,
+                self.last_settlement_sample,
+                self.last_settlement_sample_time,
+                now,
+            )?;
+            self.num_settlement_samples += 1;
+        }
+        self.last_settlement_sample = index_price;
+        self.last_settlement_sample_time = now;
+        Ok(())
+    }
+
+    /// Whether enough samples were recorded in the settlement window to settle on them.
+    /// Accounts created with a lower `min_settlement_samples` still need MIN_SETTLEMENT_SAMPLES.
+    pub fn has_settlement_samples(&self) -> bool {
+        self.num_settlement_samples >= self.min_settlement_samples.max(MIN_SETTLEMENT_SAMPLES)
+    }
+
+    /// Time weighted index price over the settlement window, the last sample is weighted
+    /// until expiry
+    pub fn settlement_price(&self) -> std::result::Result<Fractional, DomainOrProgramError> {
+        let mut sum = self.settlement_price_sum;
+        let mut time = self.settlement_sample_time;
+        accumulate_sample(
+            &mut sum,
+            &mut time,
+            self.last_settlement_sample,
+            self.last_settlement_sample_time,
+            self.expiry_time(),
+        )?;
+        Ok(if time > 0 {
+            sum.checked_div(Fractional::from(time))?
+        } else {
+            self.last_settlement_sample
+        })
+    }
+
+    /// Returns the time weighted premium since the last call and resets the running sum, so
+    /// each funding payment only uses the samples of its own period. Falls back to the
+    /// latest sample if no time has been accumulated.
+    pub fn take_average_premium(&mut self) -> std::result::Result<Fractional, UtilError> {
+        let average = if self.premium_index_time > 0 {
+            self.premium_index_sum
+                .checked_div(Fractional::from(self.premium_index_time))?
+        } else {
+            self.last_premium
+        };
+        self.premium_index_sum = ZERO_FRAC;
+        self.premium_index_time = 0;
+        Ok(average)
+    }
+}
+
+/// Adds `last_sample` weighted by the seconds between `last_sample_time` and `now`.
+/// A `last_sample_time` of 0 means there is no previous sample.
+fn accumulate_sample(
+    sum: &mut Fractional,
+    time: &mut UnixTimestamp,
+    last_sample: Fractional,
+    last_sample_time: UnixTimestamp,
+    now: UnixTimestamp,
+) -> std::result::Result<(), DomainOrProgramError> {
+    if last_sample_time > 0 && now > last_sample_time {
+        let elapsed = now - last_sample_time;
+        *sum = sum.checked_add(last_sample.checked_mul(Fractional::from(elapsed))?)?;
+        *time += elapsed;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn test_premium_twap() {
+        let mut derivative_metadata = DerivativeMetadata::zeroed();
+        derivative_metadata.last_premium_time = 1_000;
+
+        // 10 for 100s, then 40 for 300s
+        derivative_metadata
+            .sample_premium(Fractional::new(10, 0), 1_100)
+            .unwrap();
+        derivative_metadata
+            .sample_premium(Fractional::new(40, 0), 1_400)
+            .unwrap();
+        assert_eq!(derivative_metadata.premium_index_time, 400);
+        // a second sample at the same time replaces the latest premium without weight
+        derivative_metadata
+            .sample_premium(Fractional::new(-5, 0), 1_400)
+            .unwrap();
+        assert_eq!(
+            derivative_metadata.take_average_premium().unwrap(),
+            Fractional::new(325, 1)
+        );
+        assert_eq!(derivative_metadata.premium_index_sum, ZERO_FRAC);
+        assert_eq!(derivative_metadata.premium_index_time, 0);
+
+        // the next period does not carry over any of the samples of the previous one
+        derivative_metadata
+            .sample_premium(Fractional::new(2, 0), 2_400)
+            .unwrap();
+        assert_eq!(
+            derivative_metadata.take_average_premium().unwrap(),
+            Fractional::new(2, 0)
+        );
+        // without any elapsed time the latest sample is used
+        derivative_metadata
+            .sample_premium(Fractional::new(3, 0), 2_400)
+            .unwrap();
+        assert_eq!(
+            derivative_metadata.take_average_premium().unwrap(),
+            Fractional::new(3, 0)
+        );
+    }
+
+    #[test]
+    fn test_settlement_samples() {
+        let mut derivative_metadata = DerivativeMetadata::zeroed();
+        derivative_metadata.initialization_time = 1_000;
+        derivative_metadata.minimum_funding_period = 100;
+        derivative_metadata.settlement_window = 50;
+        // a lower minimum on the account does not allow settling on fewer samples
+        derivative_metadata.min_settlement_samples = 1;
+        for (price, time) in [(150, 1_060), (150, 1_070)] {
+            derivative_metadata
+                .record_settlement_sample(Fractional::new(price, 0), time)
+                .unwrap();
+            assert!(!derivative_metadata.has_settlement_samples());
+        }
+        // replaces the sample at the same time
+        derivative_metadata
+            .record_settlement_sample(Fractional::new(140, 0), 1_070)
+            .unwrap();
+        assert!(!derivative_metadata.has_settlement_samples());
+        derivative_metadata
+            .record_settlement_sample(Fractional::new(160, 0), 1_080)
+            .unwrap();
+        assert!(derivative_metadata.has_settlement_samples());
+        // 150 for 10s, 140 for 10s and 160 for 20s until expiry
+        assert_eq!(
+            derivative_metadata.settlement_price().unwrap(),
+            Fractional::new(1525, 1)
+        );
+    }
 }
@@ -1,6 +1,9 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use bytemuck::{Pod, Zeroable};
 use dex::error::UtilError;
+use std::convert::TryFrom;
+
+use crate::error::DerivativeError;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(u64)]
@@ -8,6 +11,8 @@ pub enum AccountTag {
     Uninitialized,
     DerivativeMetadata,
     FixedIncomeMetadata,
+    CompositeOracle,
+    Series,
 }
 impl Default for AccountTag {
     fn default() -> Self {
@@ -25,6 +30,19 @@ pub enum InstrumentType {
     RecurringPut,
     ExpiringCall,
     ExpiringPut,
+    // Pay a fixed amount when in the money
+    RecurringBinaryCall,
+    RecurringBinaryPut,
+    ExpiringBinaryCall,
+    ExpiringBinaryPut,
+    // Vanilla payoff limited to a maximum, e.g. a call spread
+    RecurringCappedCall,
+    RecurringCappedPut,
+    ExpiringCappedCall,
+    ExpiringCappedPut,
+    // Index minus strike with no floor, so the payoff can be negative
+    LinearPerp,
+    LinearFuture,
 }
 impl Default for InstrumentType {
     fn default() -> Self {
@@ -37,12 +55,68 @@ unsafe impl Pod for InstrumentType {}
 impl InstrumentType {
     pub fn is_recurring(&self) -> std::result::Result<bool, UtilError> {
         match self {
-            InstrumentType::RecurringCall | InstrumentType::RecurringPut => Ok(true),
-            InstrumentType::ExpiringCall | InstrumentType::ExpiringPut => Ok(false),
+            InstrumentType::RecurringCall
+            | InstrumentType::RecurringPut
+            | InstrumentType::RecurringBinaryCall
+            | InstrumentType::RecurringBinaryPut
+            | InstrumentType::RecurringCappedCall
+            | InstrumentType::RecurringCappedPut
+            | InstrumentType::LinearPerp => Ok(true),
+            InstrumentType::ExpiringCall
+            | InstrumentType::ExpiringPut
+            | InstrumentType::ExpiringBinaryCall
+            | InstrumentType::ExpiringBinaryPut
+            | InstrumentType::ExpiringCappedCall
+            | InstrumentType::ExpiringCappedPut
+            | InstrumentType::LinearFuture => Ok(false),
             InstrumentType::Uninitialized => Err(UtilError::AccountUninitialized),
         }
     }
+
+    pub fn is_binary(&self) -> bool {
+        matches!(
+            self,
+            InstrumentType::RecurringBinaryCall
+                | InstrumentType::RecurringBinaryPut
+                | InstrumentType::ExpiringBinaryCall
+                | InstrumentType::ExpiringBinaryPut
+        )
+    }
+
+    pub fn is_linear(&self) -> bool {
+        matches!(
+            self,
+            InstrumentType::LinearPerp | InstrumentType::LinearFuture
+        )
+    }
+
+    pub fn is_capped(&self) -> bool {
+        matches!(
+            self,
+            InstrumentType::RecurringCappedCall
+                | InstrumentType::RecurringCappedPut
+                | InstrumentType::ExpiringCappedCall
+                | InstrumentType::ExpiringCappedPut
+        )
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Copy, Debug, Clone, PartialEq)]
+#[repr(u64)]
+pub enum FixedIncomeType {
+    Uninitialized,
+    // Settles once at maturity to the price of a zero-coupon bond at the oracle rate
+    ZeroCouponBondFuture,
+    // Pays (floating - fixed) * notional to longs every coupon period until maturity
+    InterestRateSwap,
+}
+impl Default for FixedIncomeType {
+    fn default() -> Self {
+        FixedIncomeType::Uninitialized
+    }
 }
+unsafe impl Zeroable for FixedIncomeType {}
+unsafe impl Pod for FixedIncomeType {}
 
 #[derive(BorshSerialize, BorshDeserialize, Copy, Debug, Clone, PartialEq)]
 #[repr(u64)]
@@ -50,6 +124,9 @@ pub enum OracleType {
     Uninitialized,
     Pyth,
     Dummy,
+    Switchboard,
+    // Median of several oracles, see CompositeOracle
+    Composite,
 }
 impl Default for OracleType {
     fn default() -> Self {
@@ -59,6 +136,43 @@ impl Default for OracleType {
 unsafe impl Zeroable for OracleType {}
 unsafe impl Pod for OracleType {}
 
+/// Oracle types stored as a u64 outside of this program, e.g. by dex collaterals
+impl TryFrom<u64> for OracleType {
+    type Error = DerivativeError;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(OracleType::Pyth),
+            2 => Ok(OracleType::Dummy),
+            3 => Ok(OracleType::Switchboard),
+            4 => Ok(OracleType::Composite),
+            _ => Err(DerivativeError::InvalidOracleConfig),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u64)]
+pub enum MarkPriceMode {
+    // Funding has not been settled yet
+    Uninitialized,
+    // Midpoint of the best bid and ask
+    BookMidpoint,
+    // Midpoint of the bid and ask EWMAs
+    EwmaMidpoint,
+    // The only quoted side, clamped to the index
+    OneSidedBook,
+    // No usable prices, funding is zero
+    IndexPrice,
+}
+impl Default for MarkPriceMode {
+    fn default() -> Self {
+        MarkPriceMode::Uninitialized
+    }
+}
+unsafe impl Zeroable for MarkPriceMode {}
+unsafe impl Pod for MarkPriceMode {}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(u64)]
 pub enum ExpirationStatus {
@@ -0,0 +1,62 @@
+use crate::state::enums::{AccountTag, ExpirationStatus, FixedIncomeType, OracleType};
+use anchor_lang::prelude::*;
+use dex::utils::numeric::Fractional;
+use solana_program::{clock::UnixTimestamp, program_error::ProgramError, pubkey::Pubkey};
+
+/// Interest rate product settled against a rate oracle. Rates are annualized and
+/// expressed as a fraction, e.g. 0.05 for 5%.
+#[account(zero_copy)]
+pub struct FixedIncomeMetadata {
+    pub tag: AccountTag,
+    pub expired: ExpirationStatus,
+    pub oracle_type: OracleType,
+    pub fixed_income_type: FixedIncomeType,
+    pub bump: u64,
+    pub notional: Fractional,
+    // Fixed leg of a swap, unused for bond futures
+    pub fixed_rate: Fractional,
+    // Time to maturity of the bond delivered by a bond future, unused for swaps
+    pub tenor: UnixTimestamp,
+    pub initialization_time: UnixTimestamp,
+    pub maturity_time: UnixTimestamp,
+    // Seconds between swap payments, unused for bond futures
+    pub coupon_period: UnixTimestamp,
+    pub rate_oracle: Pubkey,
+    pub market_product_group: Pubkey,
+    pub close_authority: Pubkey,
+    pub clock: Pubkey,
+    pub last_coupon_time: UnixTimestamp,
+    // Oracle guards, a staleness of 0 disables the check and a confidence of 0 uses
+    // DEFAULT_MAX_CONFIDENCE_BPS
+    pub max_staleness_slots: u64,
+    pub max_confidence_bps: u64,
+}
+
+impl FixedIncomeMetadata {
+    pub fn get_key(&self, program_id: &Pubkey) -> std::result::Result<Pubkey, ProgramError> {
+        let seeds = &[
+            b"fixed_income",
+            self.rate_oracle.as_ref(),
+            self.market_product_group.as_ref(),
+            &(self.fixed_income_type as u64).to_le_bytes(),
+            &self.notional.m.to_le_bytes(),
+            &self.notional.exp.to_le_bytes(),
+            &self.fixed_rate.m.to_le_bytes(),
+            &self.fixed_rate.exp.to_le_bytes(),
+            &self.tenor.to_le_bytes(),
+            &self.initialization_time.to_le_bytes(),
+            &self.maturity_time.to_le_bytes(),
+            &self.coupon_period.to_le_bytes(),
+            &[self.bump as u8],
+        ];
+        Ok(Pubkey::create_program_address(seeds, program_id)?)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.tag == AccountTag::FixedIncomeMetadata
+    }
+
+    pub fn expired(&self) -> bool {
+        self.expired == ExpirationStatus::Expired
+    }
+}
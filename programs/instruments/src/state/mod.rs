@@ -1,3 +1,6 @@
+pub mod composite_oracle;
 pub mod constants;
 pub mod derivative_metadata;
 pub mod enums;
+pub mod fixed_income_metadata;
+pub mod series;
@@ -0,0 +1,76 @@
+use crate::state::{
+    constants::MAX_SERIES_STRIKES,
+    enums::{AccountTag, InstrumentType, OracleType},
+};
+use anchor_lang::prelude::*;
+use dex::{state::constants::NAME_LEN, utils::numeric::Fractional};
+use solana_program::{clock::UnixTimestamp, pubkey::Pubkey};
+
+/// Grid of expiring instruments that is relisted every `expiry_interval` seconds.
+/// Roll `n` lists one derivative per strike that starts at `start_time + n * expiry_interval`
+/// and expires one interval later.
+#[account(zero_copy)]
+pub struct Series {
+    pub tag: AccountTag,
+    pub instrument_type: InstrumentType,
+    pub oracle_type: OracleType,
+    pub num_strikes: u64,
+    pub strikes: [Fractional; MAX_SERIES_STRIKES],
+    pub start_time: UnixTimestamp,
+    pub expiry_interval: UnixTimestamp,
+    pub num_rolls: u64,
+    pub market_product_group: Pubkey,
+    pub price_oracle: Pubkey,
+    pub clock: Pubkey,
+    pub close_authority: Pubkey,
+    // Products of the latest roll, these must expire before the next roll
+    pub live_products: [Pubkey; MAX_SERIES_STRIKES],
+    // Market product parameters
+    pub name: [u8; NAME_LEN],
+    pub tick_size: Fractional,
+    pub base_decimals: u64,
+    pub price_offset: Fractional,
+    // Derivative parameters
+    pub max_staleness_slots: u64,
+    pub max_confidence_bps: u64,
+    pub cap: Fractional,
+    pub digital_payout: Fractional,
+    pub settlement_window: UnixTimestamp,
+    pub min_settlement_samples: u64,
+}
+
+impl Series {
+    pub fn is_initialized(&self) -> bool {
+        self.tag == AccountTag::Series
+    }
+
+    pub fn strikes(&self) -> &[Fractional] {
+        &self.strikes[..self.num_strikes as usize]
+    }
+
+    /// Roll that lists the expiry running at `now`. Expiries that passed without being
+    /// rolled are skipped rather than listed after the fact.
+    pub fn next_roll(&self, now: UnixTimestamp) -> u64 {
+        let current_roll = if now > self.start_time {
+            ((now - self.start_time) / self.expiry_interval) as u64
+        } else {
+            0
+        };
+        self.num_rolls.max(current_roll)
+    }
+
+    /// Initialization time of the derivatives listed by `roll`
+    pub fn initialization_time(&self, roll: u64) -> UnixTimestamp {
+        self.start_time + roll as i64 * self.expiry_interval
+    }
+
+    /// The series name with the roll and strike index appended, so that every listed
+    /// product has its own name
+    pub fn product_name(&self, roll: u64, strike_index: usize) -> [u8; NAME_LEN] {
+        let mut name = self.name;
+        let suffix = format!(" {}-{}", roll, strike_index);
+        let suffix = &suffix.as_bytes()[suffix.len().saturating_sub(NAME_LEN)..];
+        name[NAME_LEN - suffix.len()..].copy_from_slice(suffix);
+        name
+    }
+}
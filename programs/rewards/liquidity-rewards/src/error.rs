@@ -0,0 +1,45 @@
+use dex::error::DomainOrProgramError;
+use num_derive::FromPrimitive;
+use solana_program::{decode_error::DecodeError, program_error::ProgramError};
+use thiserror::Error;
+
+#[derive(Error, Debug, Copy, Clone, FromPrimitive, PartialEq)]
+pub enum RewardsError {
+    #[error("AccountAlreadyInitialized")]
+    AccountAlreadyInitialized,
+    #[error("UninitializedAccount")]
+    UninitializedAccount,
+    #[error("InvalidEpochLength")]
+    InvalidEpochLength,
+    #[error("InvalidDistance")]
+    InvalidDistance,
+    #[error("InvalidAuthority")]
+    InvalidAuthority,
+    #[error("NothingToClaim")]
+    NothingToClaim,
+    #[error("NumericalOverflow")]
+    NumericalOverflow,
+    #[error("MissingBookAccounts")]
+    MissingBookAccounts,
+}
+
+impl From<RewardsError> for ProgramError {
+    fn from(e: RewardsError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for RewardsError {
+    fn type_of() -> &'static str {
+        "RewardsError"
+    }
+}
+
+impl From<RewardsError> for DomainOrProgramError {
+    fn from(e: RewardsError) -> Self {
+        DomainOrProgramError::Other {
+            code: e as u32,
+            msg: format!("{}", e),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+pub mod error;
+pub mod processor;
+pub mod state;
+
+use crate::state::{rewards_config::RewardsConfig, trader_rewards::TraderRewards};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use dex::state::{market_product_group::MarketProductGroup, trader_risk_group::TraderRiskGroup};
+use solana_program::{
+    account_info::AccountInfo, clock::UnixTimestamp, entrypoint::ProgramResult, pubkey::Pubkey,
+};
+
+declare_id!("Rewards111111111111111111111111111111111111");
+
+#[program]
+pub mod liquidity_rewards {
+    use super::*;
+
+    pub fn initialize_rewards_config(
+        ctx: Context<InitializeRewardsConfig>,
+        params: InitializeRewardsConfigParams,
+    ) -> ProgramResult {
+        processor::initialize_rewards_config::process(ctx, params)
+    }
+
+    pub fn initialize_trader_rewards(ctx: Context<InitializeTraderRewards>) -> ProgramResult {
+        processor::initialize_trader_rewards::process(ctx)
+    }
+
+    pub fn snapshot_trader<'info>(
+        ctx: Context<'_, '_, '_, 'info, SnapshotTrader<'info>>,
+    ) -> ProgramResult {
+        processor::snapshot_trader::process(ctx)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> ProgramResult {
+        processor::claim_rewards::process(ctx)
+    }
+}
+
+#[repr(C)]
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Debug, Clone, Copy)]
+pub struct InitializeRewardsConfigParams {
+    /// Number of seconds in a rewards epoch
+    pub epoch_length: UnixTimestamp,
+    /// Number of reward tokens (in native units) distributed per epoch
+    pub rewards_per_epoch: u64,
+    /// Orders further than this from the top of book do not earn points
+    pub max_distance_bps: i64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRewardsConfig<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub market_product_group: AccountLoader<'info, MarketProductGroup>,
+    #[account(
+        init,
+        seeds=[b"rewards_config", market_product_group.key().as_ref()],
+        bump,
+        payer = authority,
+        space = 8 + std::mem::size_of::<RewardsConfig>()
+    )]
+    pub rewards_config: AccountLoader<'info, RewardsConfig>,
+    pub reward_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        seeds=[b"rewards_vault", rewards_config.key().as_ref()],
+        bump,
+        payer = authority,
+        token::mint = reward_mint,
+        token::authority = rewards_config,
+    )]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTraderRewards<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub rewards_config: AccountLoader<'info, RewardsConfig>,
+    pub trader_risk_group: AccountLoader<'info, TraderRiskGroup>,
+    #[account(
+        init,
+        seeds=[
+            b"trader_rewards",
+            rewards_config.key().as_ref(),
+            trader_risk_group.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = 8 + std::mem::size_of::<TraderRewards>()
+    )]
+    pub trader_rewards: AccountLoader<'info, TraderRewards>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SnapshotTrader<'info> {
+    #[account(mut)]
+    pub rewards_config: AccountLoader<'info, RewardsConfig>,
+    #[account(
+        mut,
+        seeds=[
+            b"trader_rewards",
+            rewards_config.key().as_ref(),
+            trader_risk_group.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub trader_rewards: AccountLoader<'info, TraderRewards>,
+    pub trader_risk_group: AccountLoader<'info, TraderRiskGroup>,
+    pub market_product_group: AccountLoader<'info, MarketProductGroup>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub rewards_config: AccountLoader<'info, RewardsConfig>,
+    #[account(
+        mut,
+        seeds=[
+            b"trader_rewards",
+            rewards_config.key().as_ref(),
+            trader_risk_group.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub trader_rewards: AccountLoader<'info, TraderRewards>,
+    pub trader_risk_group: AccountLoader<'info, TraderRiskGroup>,
+    #[account(mut)]
+    pub rewards_vault: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
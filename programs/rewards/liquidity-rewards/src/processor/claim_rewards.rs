@@ -0,0 +1,54 @@
+use crate::{error::RewardsError, ClaimRewards};
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+use dex::utils::validation::{assert, assert_keys_equal};
+use solana_program::{entrypoint::ProgramResult, msg, sysvar::clock::Clock};
+
+/// Transfers all rewards from completed epochs to the trader
+pub fn process(ctx: Context<ClaimRewards>) -> ProgramResult {
+    let accts = ctx.accounts;
+    let trader_risk_group = accts.trader_risk_group.load()?;
+    assert_keys_equal(trader_risk_group.owner, accts.owner.key())?;
+
+    let (amount, market_product_group, bump) = {
+        let mut rewards_config = accts.rewards_config.load_mut()?;
+        let mut trader_rewards = accts.trader_rewards.load_mut()?;
+        assert(
+            rewards_config.is_initialized() && trader_rewards.is_initialized(),
+            RewardsError::UninitializedAccount,
+        )?;
+        assert_keys_equal(rewards_config.rewards_vault, accts.rewards_vault.key())?;
+        assert_keys_equal(trader_rewards.rewards_config, accts.rewards_config.key())?;
+        rewards_config.advance_epochs(Clock::get()?.unix_timestamp);
+        trader_rewards.settle_epoch(&rewards_config)?;
+        let amount = trader_rewards
+            .unclaimed_rewards
+            .min(accts.rewards_vault.amount);
+        assert(amount > 0, RewardsError::NothingToClaim)?;
+        trader_rewards.unclaimed_rewards -= amount;
+        trader_rewards.total_claimed = trader_rewards
+            .total_claimed
+            .checked_add(amount)
+            .ok_or(RewardsError::NumericalOverflow)?;
+        (
+            amount,
+            rewards_config.market_product_group,
+            rewards_config.bump as u8,
+        )
+    };
+
+    msg!("Claiming {} reward tokens", amount);
+    token::transfer(
+        CpiContext::new_with_signer(
+            accts.token_program.to_account_info(),
+            token::Transfer {
+                from: accts.rewards_vault.to_account_info(),
+                to: accts.destination.to_account_info(),
+                authority: accts.rewards_config.to_account_info(),
+            },
+            &[&[b"rewards_config", market_product_group.as_ref(), &[bump]]],
+        ),
+        amount,
+    )?;
+    Ok(())
+}
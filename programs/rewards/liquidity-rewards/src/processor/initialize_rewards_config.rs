@@ -0,0 +1,38 @@
+use crate::{
+    error::RewardsError, state::enums::AccountTag, InitializeRewardsConfig,
+    InitializeRewardsConfigParams,
+};
+use anchor_lang::prelude::*;
+use dex::utils::validation::{assert, assert_keys_equal};
+use solana_program::{entrypoint::ProgramResult, sysvar::clock::Clock};
+
+pub fn process(
+    ctx: Context<InitializeRewardsConfig>,
+    params: InitializeRewardsConfigParams,
+) -> ProgramResult {
+    let accts = ctx.accounts;
+    let market_product_group = accts.market_product_group.load()?;
+    // Only the market product group authority can set up rewards for it
+    assert_keys_equal(market_product_group.authority, accts.authority.key())?;
+    assert(params.epoch_length > 0, RewardsError::InvalidEpochLength)?;
+    assert(params.max_distance_bps > 0, RewardsError::InvalidDistance)?;
+
+    let mut rewards_config = accts.rewards_config.load_init()?;
+    assert(
+        !rewards_config.is_initialized(),
+        RewardsError::AccountAlreadyInitialized,
+    )?;
+    rewards_config.tag = AccountTag::RewardsConfig;
+    rewards_config.bump = ctx.bumps["rewards_config"] as u64;
+    rewards_config.vault_bump = ctx.bumps["rewards_vault"] as u64;
+    rewards_config.market_product_group = accts.market_product_group.key();
+    rewards_config.authority = accts.authority.key();
+    rewards_config.reward_mint = accts.reward_mint.key();
+    rewards_config.rewards_vault = accts.rewards_vault.key();
+    rewards_config.epoch_length = params.epoch_length;
+    rewards_config.rewards_per_epoch = params.rewards_per_epoch;
+    rewards_config.max_distance_bps = params.max_distance_bps;
+    rewards_config.current_epoch = 0;
+    rewards_config.epoch_start = Clock::get()?.unix_timestamp;
+    Ok(())
+}
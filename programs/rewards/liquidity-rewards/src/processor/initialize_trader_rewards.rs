@@ -0,0 +1,31 @@
+use crate::{error::RewardsError, state::enums::AccountTag, InitializeTraderRewards};
+use anchor_lang::prelude::*;
+use dex::utils::validation::{assert, assert_keys_equal};
+use solana_program::{entrypoint::ProgramResult, sysvar::clock::Clock};
+
+pub fn process(ctx: Context<InitializeTraderRewards>) -> ProgramResult {
+    let accts = ctx.accounts;
+    let rewards_config = accts.rewards_config.load()?;
+    let trader_risk_group = accts.trader_risk_group.load()?;
+    assert(
+        rewards_config.is_initialized(),
+        RewardsError::UninitializedAccount,
+    )?;
+    assert_keys_equal(
+        trader_risk_group.market_product_group,
+        rewards_config.market_product_group,
+    )?;
+
+    let mut trader_rewards = accts.trader_rewards.load_init()?;
+    assert(
+        !trader_rewards.is_initialized(),
+        RewardsError::AccountAlreadyInitialized,
+    )?;
+    trader_rewards.tag = AccountTag::TraderRewards;
+    trader_rewards.bump = ctx.bumps["trader_rewards"] as u64;
+    trader_rewards.rewards_config = accts.rewards_config.key();
+    trader_rewards.trader_risk_group = accts.trader_risk_group.key();
+    trader_rewards.epoch = rewards_config.current_epoch;
+    trader_rewards.last_snapshot_time = Clock::get()?.unix_timestamp;
+    Ok(())
+}
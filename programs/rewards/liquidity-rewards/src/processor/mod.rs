@@ -0,0 +1,4 @@
+pub mod claim_rewards;
+pub mod initialize_rewards_config;
+pub mod initialize_trader_rewards;
+pub mod snapshot_trader;
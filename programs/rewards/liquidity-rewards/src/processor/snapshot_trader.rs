@@ -0,0 +1,46 @@
+use crate::{error::RewardsError, state::trader_rewards::get_weighted_book_qty, SnapshotTrader};
+use anchor_lang::prelude::*;
+use dex::utils::validation::{assert, assert_keys_equal};
+use solana_program::{entrypoint::ProgramResult, msg, sysvar::clock::Clock};
+
+/// Permissionless crank that accrues points for a trader's resting orders.
+/// The remaining accounts are the orderbook, bids and asks of every product the trader has
+/// open orders on, in product index order.
+pub fn process<'info>(ctx: Context<'_, '_, '_, 'info, SnapshotTrader<'info>>) -> ProgramResult {
+    let accts = ctx.accounts;
+    let mut rewards_config = accts.rewards_config.load_mut()?;
+    let mut trader_rewards = accts.trader_rewards.load_mut()?;
+    let trader_risk_group = accts.trader_risk_group.load()?;
+    let market_product_group = accts.market_product_group.load()?;
+    assert(
+        rewards_config.is_initialized() && trader_rewards.is_initialized(),
+        RewardsError::UninitializedAccount,
+    )?;
+    assert_keys_equal(
+        rewards_config.market_product_group,
+        accts.market_product_group.key(),
+    )?;
+    assert_keys_equal(trader_rewards.rewards_config, accts.rewards_config.key())?;
+    assert_keys_equal(
+        trader_risk_group.market_product_group,
+        accts.market_product_group.key(),
+    )?;
+
+    let now = Clock::get()?.unix_timestamp;
+    rewards_config.advance_epochs(now);
+    trader_rewards.settle_epoch(&rewards_config)?;
+    let weighted_qty = get_weighted_book_qty(
+        &trader_risk_group,
+        &market_product_group,
+        rewards_config.max_distance_bps,
+        ctx.remaining_accounts,
+    )?;
+    trader_rewards.accrue(&mut rewards_config, now, weighted_qty)?;
+    msg!(
+        "epoch: {} points: {} weighted qty: {}",
+        trader_rewards.epoch,
+        trader_rewards.points,
+        weighted_qty
+    );
+    Ok(())
+}
@@ -0,0 +1,5 @@
+/// Number of completed epochs whose point totals are kept on the config.
+/// Points accrued in an epoch older than this can no longer be converted into rewards.
+pub const MAX_EPOCH_HISTORY: usize = 32;
+/// Distance weights and weighted book sizes are rounded to this many decimals
+pub const WEIGHT_DECIMALS: u32 = 4;
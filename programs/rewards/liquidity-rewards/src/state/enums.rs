@@ -0,0 +1,16 @@
+use bytemuck::{Pod, Zeroable};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[repr(u64)]
+pub enum AccountTag {
+    Uninitialized,
+    RewardsConfig,
+    TraderRewards,
+}
+impl Default for AccountTag {
+    fn default() -> Self {
+        AccountTag::Uninitialized
+    }
+}
+unsafe impl Zeroable for AccountTag {}
+unsafe impl Pod for AccountTag {}
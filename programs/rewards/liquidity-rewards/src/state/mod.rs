@@ -0,0 +1,4 @@
+pub mod constants;
+pub mod enums;
+pub mod rewards_config;
+pub mod trader_rewards;
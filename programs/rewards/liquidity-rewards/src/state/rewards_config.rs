@@ -0,0 +1,75 @@
+use crate::state::{constants::MAX_EPOCH_HISTORY, enums::AccountTag};
+use anchor_lang::prelude::*;
+use dex::{
+    error::DomainOrProgramResult,
+    utils::numeric::{Fractional, ZERO_FRAC},
+};
+use solana_program::clock::UnixTimestamp;
+
+/// Rewards configuration for a single market product group.
+/// Each epoch pays out `rewards_per_epoch` tokens from the rewards vault,
+/// split pro rata between traders by the points they accrued during the epoch.
+#[account(zero_copy)]
+pub struct RewardsConfig {
+    pub tag: AccountTag,
+    pub bump: u64,
+    pub vault_bump: u64,
+    pub market_product_group: Pubkey,
+    pub authority: Pubkey,
+    pub reward_mint: Pubkey,
+    pub rewards_vault: Pubkey,
+    pub epoch_length: UnixTimestamp,
+    pub rewards_per_epoch: u64,
+    // Resting orders further than this from the top of book earn no points
+    pub max_distance_bps: i64,
+    pub current_epoch: u64,
+    pub epoch_start: UnixTimestamp,
+    pub current_epoch_points: Fractional,
+    // Point totals of completed epochs, indexed by epoch % MAX_EPOCH_HISTORY
+    pub epoch_points: [Fractional; MAX_EPOCH_HISTORY],
+}
+
+impl RewardsConfig {
+    pub fn is_initialized(&self) -> bool {
+        self.tag == AccountTag::RewardsConfig
+    }
+
+    /// Closes out every epoch that has ended by `now`
+    pub fn advance_epochs(&mut self, now: UnixTimestamp) {
+        if now < self.epoch_start + self.epoch_length {
+            return;
+        }
+        let elapsed_epochs = ((now - self.epoch_start) / self.epoch_length) as u64;
+        // Only the epoch that was in progress has points, any later ones were empty
+        for i in 0..elapsed_epochs.min(MAX_EPOCH_HISTORY as u64) {
+            self.epoch_points[(self.current_epoch + i) as usize % MAX_EPOCH_HISTORY] = if i == 0 {
+                self.current_epoch_points
+            } else {
+                ZERO_FRAC
+            };
+        }
+        self.current_epoch_points = ZERO_FRAC;
+        self.current_epoch += elapsed_epochs;
+        self.epoch_start += elapsed_epochs as i64 * self.epoch_length;
+    }
+
+    /// Returns the point total of a completed epoch, if it is still in the history window
+    pub fn get_epoch_points(&self, epoch: u64) -> Option<Fractional> {
+        if epoch >= self.current_epoch || self.current_epoch - epoch > MAX_EPOCH_HISTORY as u64 {
+            return None;
+        }
+        Some(self.epoch_points[epoch as usize % MAX_EPOCH_HISTORY])
+    }
+
+    /// Converts a trader's points for a completed epoch into reward tokens
+    pub fn get_reward_amount(&self, epoch: u64, points: Fractional) -> DomainOrProgramResult<u64> {
+        let total_points = match self.get_epoch_points(epoch) {
+            Some(p) if p > ZERO_FRAC => p,
+            _ => return Ok(0),
+        };
+        let reward = points
+            .checked_div(total_points)?
+            .checked_mul(Fractional::from(self.rewards_per_epoch as i64))?;
+        Ok(reward.to_int().max(0) as u64)
+    }
+}
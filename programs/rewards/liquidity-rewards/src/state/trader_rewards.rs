@@ -0,0 +1,245 @@
+use crate::{
+    error::RewardsError,
+    state::{constants::WEIGHT_DECIMALS, enums::AccountTag, rewards_config::RewardsConfig},
+};
+use agnostic_orderbook::{
+    critbit::Slab,
+    state::{get_side_from_order_id, Side},
+};
+use anchor_lang::prelude::*;
+use dex::{
+    error::DomainOrProgramResult,
+    state::{
+        constants::{NO_ASK_PRICE, NO_BID_PRICE, SENTINEL},
+        market_product_group::MarketProductGroup,
+        products::Product,
+        trader_risk_group::TraderRiskGroup,
+    },
+    utils::{
+        numeric::{bps, Fractional, ZERO_FRAC},
+        orderbook::{get_order_price, load_orderbook},
+        validation::{assert, assert_keys_equal},
+    },
+};
+use solana_program::clock::UnixTimestamp;
+
+/// Per-trader accounting of liquidity mining points
+#[account(zero_copy)]
+pub struct TraderRewards {
+    pub tag: AccountTag,
+    pub bump: u64,
+    pub rewards_config: Pubkey,
+    pub trader_risk_group: Pubkey,
+    pub epoch: u64,
+    pub last_snapshot_time: UnixTimestamp,
+    // Distance weighted size that was resting on the books at the last snapshot
+    pub weighted_qty: Fractional,
+    pub points: Fractional,
+    pub unclaimed_rewards: u64,
+    pub total_claimed: u64,
+}
+
+impl TraderRewards {
+    pub fn is_initialized(&self) -> bool {
+        self.tag == AccountTag::TraderRewards
+    }
+
+    /// Converts the points of a completed epoch into claimable rewards
+    pub fn settle_epoch(&mut self, rewards_config: &RewardsConfig) -> DomainOrProgramResult {
+        if self.epoch < rewards_config.current_epoch {
+            let reward = rewards_config.get_reward_amount(self.epoch, self.points)?;
+            self.unclaimed_rewards = self
+                .unclaimed_rewards
+                .checked_add(reward)
+                .ok_or(RewardsError::NumericalOverflow)?;
+            self.points = ZERO_FRAC;
+            self.epoch = rewards_config.current_epoch;
+        }
+        Ok(())
+    }
+
+    /// Accrues points for the time since the last snapshot on the smaller of the size resting
+    /// then and now, so size that was only briefly on the books cannot earn for the whole
+    /// interval. Time elapsed before the start of the current epoch is not counted, so each
+    /// trader should be snapshotted at least once per epoch.
+    pub fn accrue(
+        &mut self,
+        rewards_config: &mut RewardsConfig,
+        now: UnixTimestamp,
+        weighted_qty: Fractional,
+    ) -> DomainOrProgramResult {
+        let start = self.last_snapshot_time.max(rewards_config.epoch_start);
+        let elapsed = (now - start).max(0);
+        let held_qty = self.weighted_qty.min(weighted_qty);
+        let new_points = held_qty.checked_mul(Fractional::from(elapsed))?;
+        self.points = self.points.checked_add(new_points)?;
+        rewards_config.current_epoch_points = rewards_config
+            .current_epoch_points
+            .checked_add(new_points)?;
+        self.weighted_qty = weighted_qty;
+        self.last_snapshot_time = now;
+        Ok(())
+    }
+}
+
+/// Number of accounts per product in `book_accounts`: orderbook, bids and asks
+pub const BOOK_ACCOUNTS_LEN: usize = 3;
+
+/// Sums the trader's resting size over all products, weighting each order by how close it is
+/// to the top of book. `book_accounts` holds the orderbook, bids and asks of every product the
+/// trader has open orders on, in product index order.
+pub fn get_weighted_book_qty(
+    trader_risk_group: &TraderRiskGroup,
+    market_product_group: &MarketProductGroup,
+    max_distance_bps: i64,
+    book_accounts: &[AccountInfo],
+) -> DomainOrProgramResult<Fractional> {
+    let mut weighted_qty = ZERO_FRAC;
+    let mut book_accounts = book_accounts.chunks(BOOK_ACCOUNTS_LEN);
+    for (product_index, product) in market_product_group.active_products() {
+        if trader_risk_group.open_orders.products[product_index].num_open_orders == 0 {
+            continue;
+        }
+        let accounts = book_accounts
+            .next()
+            .filter(|a| a.len() == BOOK_ACCOUNTS_LEN)
+            .ok_or(RewardsError::MissingBookAccounts)?;
+        weighted_qty = weighted_qty.checked_add(get_product_weighted_qty(
+            trader_risk_group,
+            product_index,
+            product,
+            max_distance_bps,
+            accounts,
+        )?)?;
+    }
+    // Bound the precision so that multiplying by elapsed time cannot overflow
+    Ok(weighted_qty.round_sf(WEIGHT_DECIMALS))
+}
+
+fn get_product_weighted_qty(
+    trader_risk_group: &TraderRiskGroup,
+    product_index: usize,
+    product: &Product,
+    max_distance_bps: i64,
+    accounts: &[AccountInfo],
+) -> DomainOrProgramResult<Fractional> {
+    let (orderbook, bids, asks) = (&accounts[0], &accounts[1], &accounts[2]);
+    assert_keys_equal(product.orderbook, orderbook.key())?;
+    let (market_signer, _) =
+        Pubkey::find_program_address(&[product.product_key.as_ref()], &dex::ID);
+    let orderbook_state = load_orderbook(orderbook, &market_signer)?;
+    assert(
+        orderbook_state.bids == bids.key.to_bytes() && orderbook_state.asks == asks.key.to_bytes(),
+        RewardsError::MissingBookAccounts,
+    )?;
+    let callback_info_len = orderbook_state.callback_info_len as usize;
+    let bids = Slab::new_from_acc_info(bids, callback_info_len);
+    let asks = Slab::new_from_acc_info(asks, callback_info_len);
+
+    let open_orders = &trader_risk_group.open_orders;
+    let mut weighted_qty = ZERO_FRAC;
+    let mut ptr = open_orders.products[product_index].head_index;
+    while ptr != SENTINEL {
+        let order = open_orders.orders[ptr];
+        ptr = order.next;
+        let (book, top_of_book) = match get_side_from_order_id(order.id) {
+            Side::Bid => (&bids, product.prices.bid),
+            Side::Ask => (&asks, product.prices.ask),
+        };
+        // Orders that were filled but not consumed yet are no longer in the book
+        let leaf = match book.find_by_key(order.id) {
+            Some(handle) => book.get_node(handle).unwrap().as_leaf().unwrap().to_owned(),
+            None => continue,
+        };
+        let price = get_order_price(order.id, product.tick_size, product.price_offset)?;
+        let weight = get_distance_weight(price, top_of_book, max_distance_bps)?;
+        let qty = Fractional::new(leaf.base_quantity as i64, product.base_decimals);
+        weighted_qty = weighted_qty.checked_add(qty.checked_mul(weight)?)?;
+    }
+    Ok(weighted_qty)
+}
+
+/// Linear weight in [0, 1]: 1 at the top of book, 0 at `max_distance_bps` away or further
+fn get_distance_weight(
+    price: Fractional,
+    top_of_book: Fractional,
+    max_distance_bps: i64,
+) -> DomainOrProgramResult<Fractional> {
+    if top_of_book == NO_BID_PRICE || top_of_book == NO_ASK_PRICE || top_of_book == ZERO_FRAC {
+        return Ok(ZERO_FRAC);
+    }
+    let distance = price
+        .checked_sub(top_of_book)?
+        .abs()
+        .checked_div(top_of_book.abs())?;
+    let max_distance = bps(max_distance_bps);
+    if distance >= max_distance {
+        return Ok(ZERO_FRAC);
+    }
+    Ok(Fractional::from(1)
+        .checked_sub(distance.checked_div(max_distance)?)?
+        .round_sf(WEIGHT_DECIMALS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn setup() -> (TraderRewards, RewardsConfig) {
+        let mut rewards_config = RewardsConfig::zeroed();
+        rewards_config.epoch_length = 1000;
+        (TraderRewards::zeroed(), rewards_config)
+    }
+
+    #[test]
+    fn test_accrue_credits_size_held_over_the_interval() {
+        let (mut trader_rewards, mut rewards_config) = setup();
+        trader_rewards
+            .accrue(&mut rewards_config, 100, Fractional::from(10))
+            .unwrap();
+        assert_eq!(trader_rewards.points, ZERO_FRAC);
+        trader_rewards
+            .accrue(&mut rewards_config, 200, Fractional::from(10))
+            .unwrap();
+        assert_eq!(trader_rewards.points, Fractional::from(1000));
+        // size posted right before a snapshot only earns from that snapshot on
+        trader_rewards
+            .accrue(&mut rewards_config, 300, Fractional::from(50))
+            .unwrap();
+        assert_eq!(trader_rewards.points, Fractional::from(2000));
+        // and nothing once it was cancelled before the next one
+        trader_rewards
+            .accrue(&mut rewards_config, 400, ZERO_FRAC)
+            .unwrap();
+        assert_eq!(trader_rewards.points, Fractional::from(2000));
+        assert_eq!(rewards_config.current_epoch_points, Fractional::from(2000));
+    }
+
+    #[test]
+    fn test_accrue_skips_time_before_the_epoch() {
+        let (mut trader_rewards, mut rewards_config) = setup();
+        trader_rewards
+            .accrue(&mut rewards_config, 100, Fractional::from(10))
+            .unwrap();
+        rewards_config.epoch_start = 150;
+        trader_rewards
+            .accrue(&mut rewards_config, 200, Fractional::from(10))
+            .unwrap();
+        assert_eq!(trader_rewards.points, Fractional::from(500));
+    }
+
+    #[test]
+    fn test_distance_weight() {
+        let top_of_book = Fractional::from(100);
+        let weight = |price: i64| get_distance_weight(Fractional::from(price), top_of_book, 1000);
+        assert_eq!(weight(100).unwrap(), Fractional::from(1));
+        assert_eq!(weight(95).unwrap(), Fractional::new(5, 1));
+        assert_eq!(weight(105).unwrap(), Fractional::new(5, 1));
+        assert_eq!(weight(90).unwrap(), ZERO_FRAC);
+        assert_eq!(
+            get_distance_weight(top_of_book, NO_BID_PRICE, 1000).unwrap(),
+            ZERO_FRAC
+        );
+    }
+}
@@ -1,10 +1,11 @@
-use std::ops::Deref;
+use std::{convert::TryFrom, ops::Deref};
 
 use anchor_lang::prelude::*;
 use borsh::{BorshDeserialize, BorshSerialize};
+use instruments::{oracle::get_price, state::enums::OracleType};
 use solana_program::{
-    account_info::AccountInfo, declare_id, entrypoint::ProgramResult, program_error::ProgramError,
-    program_pack::IsInitialized, pubkey::Pubkey,
+    account_info::AccountInfo, clock::Clock, declare_id, entrypoint::ProgramResult,
+    program_error::ProgramError, program_pack::IsInitialized, pubkey::Pubkey, sysvar::Sysvar,
 };
 
 use dex::{
@@ -43,6 +44,7 @@ pub mod risk {
         let account_health = compute_health(
             ctx.accounts.trader_risk_group.load()?.deref(),
             ctx.accounts.market_product_group.load()?.deref(),
+            ctx.remaining_accounts,
         )?;
         let margin_req = account_health.margin_req;
         let portfolio_value = account_health.portfolio_value;
@@ -84,8 +86,11 @@ pub mod risk {
         assert_keys_equal(risk_signer_key, ctx.accounts.risk_signer.key())?;
         let trader_risk_group = ctx.accounts.trader_risk_group.load()?;
         let market_product_group = ctx.accounts.market_product_group.load()?;
-        let account_health =
-            compute_health(trader_risk_group.deref(), market_product_group.deref())?;
+        let account_health = compute_health(
+            trader_risk_group.deref(),
+            market_product_group.deref(),
+            ctx.remaining_accounts,
+        )?;
         let margin_req = account_health.margin_req;
         let portfolio_value = account_health.portfolio_value;
         msg!("Portfolio value: {}", portfolio_value);
@@ -107,19 +112,32 @@ pub mod risk {
             return Err(ProgramError::InvalidAccountData);
         }
 
-        let liquidation_info = get_liquidation_status(
-            trader_risk_group.deref(),
-            portfolio_value,
-            liquidation_price,
-            liq_threshold,
-            health_threshold,
-            social_loss,
-            &account_health,
-        )?;
         let mut out_register = RiskOutputRegister::load_mut(&ctx.accounts.out_register_risk_info)?;
+        // social_losses has a slot per trader position, so the liquidation info is filled in place
         out_register.risk_engine_output = HealthResult::Liquidation {
-            liquidation_info: liquidation_info,
+            liquidation_info: LiquidationInfo {
+                health: HealthStatus::Healthy,
+                action: ActionStatus::NotApproved,
+                total_social_loss: social_loss,
+                liquidation_price,
+                social_losses: [SocialLoss {
+                    product_index: MAX_OUTRIGHTS,
+                    amount: ZERO_FRAC,
+                }; MAX_TRADER_POSITIONS],
+            },
         };
+        if let HealthResult::Liquidation { liquidation_info } = &mut out_register.risk_engine_output
+        {
+            set_liquidation_status(
+                liquidation_info,
+                trader_risk_group.deref(),
+                portfolio_value,
+                liq_threshold,
+                health_threshold,
+                social_loss,
+                &account_health,
+            )?;
+        }
         Ok(())
     }
 
@@ -133,27 +151,18 @@ pub mod risk {
     }
 }
 
-fn get_liquidation_status(
+fn set_liquidation_status(
+    liquidation_info: &mut LiquidationInfo,
     trader_risk_group: &TraderRiskGroup,
     portfolio_value: Fractional,
-    liquidation_price: Fractional,
     liq_threshold: Fractional,
     health_threshold: Fractional,
     social_loss: Fractional,
     account_health: &Health,
-) -> DomainOrProgramResult<LiquidationInfo> {
-    let zero_social = SocialLoss {
-        product_index: MAX_OUTRIGHTS,
-        amount: ZERO_FRAC,
-    };
+) -> DomainOrProgramResult {
     if portfolio_value <= liq_threshold {
-        let mut liquidation_info = LiquidationInfo {
-            health: HealthStatus::Liquidatable,
-            action: ActionStatus::Approved,
-            total_social_loss: social_loss,
-            liquidation_price,
-            social_losses: [zero_social; MAX_TRADER_POSITIONS],
-        };
+        liquidation_info.health = HealthStatus::Liquidatable;
+        liquidation_info.action = ActionStatus::Approved;
         for (i, position) in trader_risk_group.trader_positions.iter().enumerate() {
             if !position.is_initialized() {
                 continue;
@@ -165,24 +174,10 @@ fn get_liquidation_status(
                     .checked_div(account_health.total_abs_dollar_position)?,
             };
         }
-        Ok(liquidation_info)
     } else if (portfolio_value > liq_threshold) && (portfolio_value <= health_threshold) {
-        Ok(LiquidationInfo {
-            health: HealthStatus::Unhealthy,
-            action: ActionStatus::NotApproved,
-            total_social_loss: social_loss,
-            liquidation_price,
-            social_losses: [zero_social; MAX_TRADER_POSITIONS],
-        })
-    } else {
-        Ok(LiquidationInfo {
-            health: HealthStatus::Healthy,
-            action: ActionStatus::NotApproved,
-            total_social_loss: social_loss,
-            liquidation_price,
-            social_losses: [zero_social; MAX_TRADER_POSITIONS],
-        })
+        liquidation_info.health = HealthStatus::Unhealthy;
     }
+    Ok(())
 }
 
 fn fetch_price(
@@ -216,9 +211,55 @@ fn fetch_price(
     Ok(mark_price)
 }
 
+/// Values the trader's non-cash collateral at its oracle price less the haircut. The oracle of
+/// every collateral with a balance must be passed in, along with the oracles of composite ones.
+fn fetch_collateral_value(
+    trader_risk_group: &TraderRiskGroup,
+    market_product_group: &MarketProductGroup,
+    oracles: &[AccountInfo],
+) -> std::result::Result<Fractional, ProgramError> {
+    let mut value = ZERO_FRAC;
+    let clock = Clock::get()?;
+    for (collateral, balance) in market_product_group
+        .collaterals
+        .iter()
+        .zip(trader_risk_group.collateral_balances.iter())
+    {
+        if !collateral.is_enabled() || *balance == ZERO_FRAC {
+            continue;
+        }
+        let oracle = oracles
+            .iter()
+            .find(|a| a.key == &collateral.oracle)
+            .ok_or_else(|| {
+                msg!(
+                    "Missing oracle {} of collateral {}",
+                    collateral.oracle,
+                    collateral.mint
+                );
+                ProgramError::NotEnoughAccountKeys
+            })?;
+        let price = get_price(
+            OracleType::try_from(collateral.oracle_type)?,
+            oracle,
+            oracles,
+            &clock,
+            collateral.max_staleness_slots,
+            collateral.max_confidence_bps,
+        )?;
+        if price.is_negative() {
+            msg!("Oracle price of collateral {} is negative", collateral.mint);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        value = value.checked_add(collateral.get_value(*balance, price)?)?;
+    }
+    Ok(value)
+}
+
 fn compute_health(
     trader_risk_group: &TraderRiskGroup,
     market_product_group: &MarketProductGroup,
+    oracles: &[AccountInfo],
 ) -> std::result::Result<Health, ProgramError> {
     let mut margin_req = ZERO_FRAC;
     let mut open_combos: Vec<usize> = vec![];
@@ -238,7 +279,12 @@ fn compute_health(
 
     let mut trader_portfolio_value = trader_risk_group
         .cash_balance
-        .checked_add(trader_risk_group.pending_cash_balance)?;
+        .checked_add(trader_risk_group.pending_cash_balance)?
+        .checked_add(fetch_collateral_value(
+            trader_risk_group,
+            market_product_group,
+            oracles,
+        )?)?;
 
     let mut total_abs_dollar_position = ZERO_FRAC;
 
@@ -252,12 +298,15 @@ fn compute_health(
             .position
             .checked_add(trader_position.pending_position)?;
         let trader_position_value = price_i.checked_mul(size)?;
-        abs_dollar_position[idx] = trader_position_value.abs();
-
         trader_portfolio_value = trader_portfolio_value.checked_add(trader_position_value)?;
-        margin_req = margin_req.checked_add(abs_dollar_position[idx])?;
-        total_abs_dollar_position =
-            total_abs_dollar_position.checked_add(abs_dollar_position[idx])?;
+
+        // Spot base balances are collateral, they need no margin and take no social loss
+        if !market_product_group.is_spot(idx) || size < ZERO_FRAC {
+            abs_dollar_position[idx] = trader_position_value.abs();
+            margin_req = margin_req.checked_add(abs_dollar_position[idx])?;
+            total_abs_dollar_position =
+                total_abs_dollar_position.checked_add(abs_dollar_position[idx])?;
+        }
 
         let outright_qty = trader_risk_group.open_orders.products[idx]
             .ask_qty_in_book